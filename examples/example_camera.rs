@@ -113,10 +113,10 @@ impl ApplicationHandler for App {
 
                 let zoom_speed = 0.02;
                 if self.zoom_in {
-                    self.camera.zoom = (self.camera.zoom - zoom_speed).max(0.05);
+                    self.camera.zoom += zoom_speed;
                 }
                 if self.zoom_out {
-                    self.camera.zoom += zoom_speed;
+                    self.camera.zoom = (self.camera.zoom - zoom_speed).max(0.05);
                 }
 
                 // Draw
@@ -184,7 +184,7 @@ fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
     let mut app = App {
         window: None,
         ctx: None,
-        camera: Camera2D::new(),
+        camera: Camera2D::new().with_standard_zoom(),
         left: false,
         right: false,
         up: false,
@@ -66,7 +66,6 @@ impl ApplicationHandler for App {
 
         match &event {
             WindowEvent::CloseRequested => event_loop.exit(),
-            WindowEvent::Resized(size) => ctx.resize(size.width, size.height),
             WindowEvent::KeyboardInput { .. } => {
                 //  react to edge events in your update loop, not here
             }
@@ -42,7 +42,7 @@ impl ApplicationHandler for App {
             });
         }
 
-        self.anim = Some(SpriteAnimation { frames, fps: 12.0 });
+        self.anim = Some(SpriteAnimation::new(frames, 12.0));
         self.ctx = Some(ctx);
 
         window.request_redraw();
@@ -0,0 +1,112 @@
+//! Mobile-ready app skeleton: tap to drop circles.
+//!
+//! Desktop: `cargo run --example mobile`
+//! Android: `cargo apk run --example mobile` (cargo-apk builds the example as a cdylib and
+//! calls `android_main`). iOS uses `main` unchanged through an Xcode project or cargo-mobile.
+//!
+//! Android destroys the native window whenever the app goes to the background, so the
+//! context is created in the first `resumed`, suspended in `suspended`, and handed the new
+//! window in every later `resumed`.
+
+use libforge::{Color, LibContext, TouchGesture};
+use std::sync::Arc;
+use winit::{
+    application::ApplicationHandler,
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
+    window::{Window, WindowId},
+};
+
+#[derive(Default)]
+struct App {
+    window: Option<Arc<Window>>,
+    ctx: Option<LibContext<Arc<Window>>>,
+    circles: Vec<(f32, f32)>,
+}
+
+impl ApplicationHandler for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        let window_attributes = Window::default_attributes().with_title("libforge - mobile");
+        let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
+        self.window = Some(window.clone());
+
+        let size = window.inner_size();
+        match &mut self.ctx {
+            // Textures and pipelines survived the suspend; only the surface is new.
+            Some(ctx) => ctx.resume(window).expect("failed to recreate surface"),
+            None => self.ctx = Some(LibContext::new_from_window(window).unwrap()),
+        }
+        if let Some(ctx) = &mut self.ctx {
+            ctx.resize(size.width, size.height);
+        }
+    }
+
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        // The surface must go before the platform destroys the native window.
+        if let Some(ctx) = &mut self.ctx {
+            ctx.suspend();
+        }
+        self.window = None;
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        _window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(ctx) = &mut self.ctx else {
+            return;
+        };
+        ctx.handle_window_event(&event);
+
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::RedrawRequested if !ctx.is_suspended() => {
+                ctx.begin_drawing();
+                for gesture in ctx.touch_gestures() {
+                    if let TouchGesture::Tap(pos) = gesture {
+                        self.circles.push(*pos);
+                    }
+                }
+                if ctx.is_mouse_button_pressed(libforge::MouseButton::Left) {
+                    self.circles.push(ctx.mouse_position());
+                }
+
+                ctx.clear_background(Color([0.1, 0.12, 0.16, 1.0]));
+                for &(x, y) in &self.circles {
+                    ctx.draw_circle(x, y, 40.0, 32, Color([0.95, 0.55, 0.2, 1.0]));
+                }
+                ctx.end_drawing().expect("end_drawing failed");
+            }
+            _ => {}
+        }
+    }
+
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+    }
+}
+
+fn run(event_loop: EventLoop<()>) -> Result<(), Box<dyn std::error::Error + 'static>> {
+    event_loop.set_control_flow(ControlFlow::Poll);
+    event_loop.run_app(&mut App::default())?;
+    Ok(())
+}
+
+#[cfg(target_os = "android")]
+#[unsafe(no_mangle)]
+fn android_main(app: winit::platform::android::activity::AndroidApp) {
+    use winit::platform::android::EventLoopBuilderExtAndroid;
+
+    let event_loop = EventLoop::builder().with_android_app(app).build().unwrap();
+    run(event_loop).unwrap();
+}
+
+// Unused when Android builds the example as a library around `android_main`.
+#[allow(dead_code)]
+fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
+    run(EventLoop::new()?)
+}
@@ -0,0 +1,283 @@
+use crate::Rect;
+use crate::error::RendererError;
+use crate::renderer::TextureId;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A named region of an atlas texture, ready for `draw_subtexture(sub.texture, sub.rect, ..)`.
+#[derive(Clone, Copy, Debug)]
+pub struct SubTexture {
+    pub texture: TextureId,
+    /// Region inside `texture`, in pixels.
+    pub rect: Rect,
+}
+
+/// One GPU texture holding many packed images, looked up by name.
+#[derive(Clone, Debug)]
+pub struct TextureAtlas {
+    pub texture: TextureId,
+    pub width: u32,
+    pub height: u32,
+    regions: HashMap<String, SubTexture>,
+}
+
+impl TextureAtlas {
+    /// Region of the image added under `name`.
+    pub fn get(&self, name: &str) -> Option<SubTexture> {
+        self.regions.get(name).copied()
+    }
+
+    /// Names of all packed images.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.regions.keys().map(String::as_str)
+    }
+}
+
+struct Image {
+    name: String,
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+/// Packs many small images into a single texture at runtime.
+///
+/// Drawing from one atlas keeps sprites in one texture batch instead of switching
+/// textures per sprite. Add images, then call `ctx.build_atlas(builder)`.
+pub struct AtlasBuilder {
+    images: Vec<Image>,
+    /// Transparent gap between packed images, in pixels (avoids filtering bleed).
+    pub padding: u32,
+    /// Largest atlas side, in pixels.
+    pub max_size: u32,
+}
+
+impl Default for AtlasBuilder {
+    fn default() -> Self {
+        Self {
+            images: Vec::new(),
+            padding: 1,
+            max_size: 4096,
+        }
+    }
+}
+
+/// Packed atlas pixels (RGBA8) plus the pixel region of each named image.
+pub(crate) struct PackedAtlas {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+    pub regions: Vec<(String, Rect)>,
+}
+
+/// Placement of each image (by index) in a `width x height` atlas.
+pub(crate) struct Packing {
+    pub width: u32,
+    pub height: u32,
+    pub positions: Vec<(u32, u32)>,
+}
+
+/// Shelf packing: tallest images first, filling rows left to right.
+///
+/// Tries power-of-two sizes from the smallest that could hold the total area up to
+/// `max_size`, returning `None` if nothing fits.
+pub(crate) fn pack(sizes: &[(u32, u32)], padding: u32, max_size: u32) -> Option<Packing> {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(sizes[i].1));
+
+    let area: u64 = sizes
+        .iter()
+        .map(|&(w, h)| (w + padding) as u64 * (h + padding) as u64)
+        .sum();
+    let widest = sizes.iter().map(|s| s.0 + padding).max().unwrap_or(1);
+    let mut side = ((area as f64).sqrt().ceil() as u32)
+        .max(widest)
+        .max(1)
+        .next_power_of_two();
+
+    while side <= max_size {
+        let mut positions = vec![(0, 0); sizes.len()];
+        let (mut x, mut y, mut shelf_h) = (0u32, 0u32, 0u32);
+        let mut fits = true;
+        for &i in &order {
+            let (w, h) = sizes[i];
+            if x + w > side {
+                x = 0;
+                y += shelf_h;
+                shelf_h = 0;
+            }
+            if x + w > side || y + h > side {
+                fits = false;
+                break;
+            }
+            positions[i] = (x, y);
+            x += w + padding;
+            shelf_h = shelf_h.max(h + padding);
+        }
+        if fits {
+            let height = (y + shelf_h).max(1).next_power_of_two().min(side);
+            return Some(Packing {
+                width: side,
+                height,
+                positions,
+            });
+        }
+        side *= 2;
+    }
+    None
+}
+
+impl AtlasBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an image from PNG/JPEG bytes.
+    pub fn add_image_from_bytes(&mut self, name: &str, bytes: &[u8]) -> Result<(), RendererError> {
+        let img = image::load_from_memory(bytes)
+            .map_err(|e| RendererError::Internal(format!("{:?}", e)))?
+            .to_rgba8();
+        self.add_rgba(name, img.width(), img.height(), img.into_raw())
+    }
+
+    /// Add an image file (PNG/JPEG).
+    pub fn add_image_file(
+        &mut self,
+        name: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<(), RendererError> {
+        let bytes = std::fs::read(path.as_ref())
+            .map_err(|e| RendererError::Internal(format!("{}: {e}", path.as_ref().display())))?;
+        self.add_image_from_bytes(name, &bytes)
+    }
+
+    /// Add raw RGBA8 pixels.
+    pub fn add_rgba(
+        &mut self,
+        name: &str,
+        width: u32,
+        height: u32,
+        rgba: Vec<u8>,
+    ) -> Result<(), RendererError> {
+        if rgba.len() != (width as usize) * (height as usize) * 4 {
+            return Err(RendererError::Internal(format!(
+                "atlas image '{name}': expected {width}x{height}x4 bytes, got {}",
+                rgba.len()
+            )));
+        }
+        self.images.push(Image {
+            name: name.to_string(),
+            width,
+            height,
+            rgba,
+        });
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// Pack all images into one RGBA8 pixel buffer.
+    pub(crate) fn build_pixels(&self) -> Result<PackedAtlas, RendererError> {
+        let sizes: Vec<(u32, u32)> = self.images.iter().map(|i| (i.width, i.height)).collect();
+        let packing = pack(&sizes, self.padding, self.max_size).ok_or_else(|| {
+            RendererError::Internal(format!("atlas images do not fit in {0}x{0}", self.max_size))
+        })?;
+
+        let stride = packing.width as usize * 4;
+        let mut pixels = vec![0u8; stride * packing.height as usize];
+        let mut regions = Vec::with_capacity(self.images.len());
+        for (img, &(x, y)) in self.images.iter().zip(&packing.positions) {
+            let row_len = img.width as usize * 4;
+            for row in 0..img.height as usize {
+                let dst = (y as usize + row) * stride + x as usize * 4;
+                pixels[dst..dst + row_len]
+                    .copy_from_slice(&img.rgba[row * row_len..(row + 1) * row_len]);
+            }
+            regions.push((
+                img.name.clone(),
+                Rect {
+                    x: x as f32,
+                    y: y as f32,
+                    w: img.width as f32,
+                    h: img.height as f32,
+                },
+            ));
+        }
+        Ok(PackedAtlas {
+            width: packing.width,
+            height: packing.height,
+            pixels,
+            regions,
+        })
+    }
+}
+
+impl TextureAtlas {
+    pub(crate) fn new(texture: TextureId, packed: PackedAtlas) -> Self {
+        let regions = packed
+            .regions
+            .into_iter()
+            .map(|(name, rect)| (name, SubTexture { texture, rect }))
+            .collect();
+        Self {
+            texture,
+            width: packed.width,
+            height: packed.height,
+            regions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packed_images_do_not_overlap() {
+        let sizes = [(30, 10), (16, 16), (64, 8), (10, 40), (16, 16)];
+        let packing = pack(&sizes, 1, 256).unwrap();
+        let rects: Vec<_> = sizes
+            .iter()
+            .zip(&packing.positions)
+            .map(|(&(w, h), &(x, y))| (x, y, x + w, y + h))
+            .collect();
+        for (i, a) in rects.iter().enumerate() {
+            assert!(a.2 <= packing.width && a.3 <= packing.height);
+            for b in &rects[i + 1..] {
+                let overlap = a.0 < b.2 && b.0 < a.2 && a.1 < b.3 && b.1 < a.3;
+                assert!(!overlap, "{a:?} overlaps {b:?}");
+            }
+        }
+
+        assert!(pack(&[(300, 10)], 0, 256).is_none());
+    }
+
+    #[test]
+    fn build_pixels_copies_images_into_their_regions() {
+        let mut builder = AtlasBuilder::new();
+        builder
+            .add_rgba("red", 2, 1, vec![255, 0, 0, 255, 255, 0, 0, 255])
+            .unwrap();
+        builder
+            .add_rgba("blue", 1, 2, vec![0, 0, 255, 255, 0, 0, 255, 255])
+            .unwrap();
+        assert!(builder.add_rgba("bad", 2, 2, vec![0; 4]).is_err());
+
+        let packed = builder.build_pixels().unwrap();
+        for (name, rect) in &packed.regions {
+            let i = (rect.y as usize * packed.width as usize + rect.x as usize) * 4;
+            let expected = if name == "red" {
+                [255, 0, 0, 255]
+            } else {
+                [0, 0, 255, 255]
+            };
+            assert_eq!(packed.pixels[i..i + 4], expected);
+        }
+    }
+}
@@ -0,0 +1,914 @@
+//! Software mixer fed from gameplay code through a non-blocking command channel.
+//!
+//! libforge does not open an audio device itself. `ctx.create_mixer(sample_rate)` returns a
+//! `Mixer` to move into your output callback (cpal, SDL, a web AudioWorklet, ...), which calls
+//! `mixer.mix(&mut buffer)`. Gameplay code queues sounds with `ctx.play_sound`; the queue is
+//! sent to the mixer as one batch per frame at `end_drawing()`, so firing hundreds of sound
+//! events never waits on the device thread.
+//!
+//! Every voice plays on a [`Bus`]: `Music` and `Sfx` have their own volume and mute (for
+//! options-menu sliders) and both feed `Master`. Volume changes can be faded, and so can
+//! voices (`PlayParams::fade_in`, `AudioQueue::fade_out`), which is how music tracks
+//! crossfade (`AudioQueue::crossfade_music`).
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, Sender, channel};
+
+// WAVE_FORMAT_EXTENSIBLE stores the real format tag in the first two bytes of its
+// SubFormat GUID; the remaining bytes are this fixed KSDATAFORMAT suffix.
+const SUBFORMAT_GUID_SUFFIX: [u8; 14] = [
+    0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71,
+];
+
+/// Decoded audio: interleaved `f32` samples.
+#[derive(Clone, Debug)]
+pub struct Sound {
+    samples: Arc<[f32]>,
+    sample_rate: u32,
+    channels: u16,
+}
+
+/// Error from decoding a sound.
+#[derive(Debug, thiserror::Error)]
+pub enum AudioError {
+    #[error("unsupported or malformed audio data: {0}")]
+    Decode(String),
+}
+
+impl Sound {
+    /// Build a sound from interleaved samples in `[-1, 1]`. Only mono and stereo are supported.
+    pub fn from_samples(
+        sample_rate: u32,
+        channels: u16,
+        samples: Vec<f32>,
+    ) -> Result<Self, AudioError> {
+        if !(1..=2).contains(&channels) || sample_rate == 0 {
+            return Err(AudioError::Decode(format!(
+                "{channels} channels at {sample_rate} Hz"
+            )));
+        }
+        Ok(Self {
+            samples: samples.into(),
+            sample_rate,
+            channels,
+        })
+    }
+
+    /// Decode a RIFF/WAVE file with 8/16/24-bit integer or 32-bit float PCM samples.
+    pub fn from_wav_bytes(bytes: &[u8]) -> Result<Self, AudioError> {
+        let err = |msg: &str| AudioError::Decode(msg.to_string());
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err(err("not a WAVE file"));
+        }
+
+        let mut format = None;
+        let mut data = None;
+        let mut pos = 12;
+        while pos + 8 <= bytes.len() {
+            let id = &bytes[pos..pos + 4];
+            let len = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+            let body = bytes
+                .get(pos + 8..pos + 8 + len)
+                .ok_or_else(|| err("truncated chunk"))?;
+            match id {
+                b"fmt " if len >= 16 => {
+                    let u16_at = |i: usize| u16::from_le_bytes([body[i], body[i + 1]]);
+                    let rate = u32::from_le_bytes(body[4..8].try_into().unwrap());
+                    let tag = match u16_at(0) {
+                        0xFFFE if len >= 40 && body[26..40] == SUBFORMAT_GUID_SUFFIX => u16_at(24),
+                        0xFFFE => return Err(err("unsupported extensible subformat")),
+                        tag => tag,
+                    };
+                    format = Some((tag, u16_at(2), rate, u16_at(14)));
+                }
+                b"data" => data = Some(body),
+                _ => {}
+            }
+            // Chunks are padded to even sizes.
+            pos += 8 + len + (len & 1);
+        }
+
+        let (tag, channels, rate, bits) = format.ok_or_else(|| err("missing fmt chunk"))?;
+        let data = data.ok_or_else(|| err("missing data chunk"))?;
+        let samples: Vec<f32> = match (tag, bits) {
+            (1, 8) => data.iter().map(|&b| (b as f32 - 128.0) / 128.0).collect(),
+            (1, 16) => data
+                .chunks_exact(2)
+                .map(|c| i16::from_le_bytes([c[0], c[1]]) as f32 / 32768.0)
+                .collect(),
+            (1, 24) => data
+                .chunks_exact(3)
+                .map(|c| (i32::from_le_bytes([0, c[0], c[1], c[2]]) >> 8) as f32 / 8_388_608.0)
+                .collect(),
+            (1, 32) => data
+                .chunks_exact(4)
+                .map(|c| i32::from_le_bytes([c[0], c[1], c[2], c[3]]) as f32 / 2_147_483_648.0)
+                .collect(),
+            (3, 32) => data
+                .chunks_exact(4)
+                .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                .collect(),
+            _ => return Err(err(&format!("format {tag} with {bits}-bit samples"))),
+        };
+        Self::from_samples(rate, channels, samples)
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Number of sample frames (samples per channel).
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels as usize
+    }
+
+    /// Duration in seconds.
+    pub fn duration(&self) -> f32 {
+        self.frames() as f32 / self.sample_rate as f32
+    }
+
+    /// Left/right sample at a fractional frame position (linear interpolation).
+    fn frame_at(&self, pos: f64) -> (f32, f32) {
+        let frames = self.frames();
+        let i = pos as usize;
+        if i >= frames {
+            return (0.0, 0.0);
+        }
+        let t = (pos - i as f64) as f32;
+        let j = (i + 1).min(frames - 1);
+        let ch = self.channels as usize;
+        let get = |f: usize, c: usize| self.samples[f * ch + c.min(ch - 1)];
+        let lerp = |c: usize| get(i, c) + (get(j, c) - get(i, c)) * t;
+        (lerp(0), lerp(1))
+    }
+}
+
+/// Handle to a playing sound.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct VoiceId(pub u64);
+
+/// Mixer bus a voice plays on. `Music` and `Sfx` are mixed into `Master`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Bus {
+    /// Final output; voices routed here skip the group volumes.
+    Master,
+    Music,
+    #[default]
+    Sfx,
+}
+
+impl Bus {
+    pub const ALL: [Bus; 3] = [Bus::Master, Bus::Music, Bus::Sfx];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// How to play a sound.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlayParams {
+    /// Linear gain.
+    pub volume: f32,
+    /// Stereo pan in `[-1.0, 1.0]` (see `Camera2D::spatial_sound`).
+    pub pan: f32,
+    /// Playback speed multiplier (also shifts pitch).
+    pub speed: f32,
+    pub looping: bool,
+    /// Seconds into the sound where a loop restarts, so an intro plays once before the
+    /// looping section.
+    pub loop_start: f32,
+    /// Seconds into the sound where a loop wraps back to `loop_start`; `None` for the end.
+    pub loop_end: Option<f32>,
+    /// Bus the voice is mixed into; music started with `play_music` always uses `Bus::Music`.
+    pub bus: Bus,
+    /// Seconds to fade in from silence (0 starts at full volume).
+    pub fade_in: f32,
+}
+
+impl Default for PlayParams {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            pan: 0.0,
+            speed: 1.0,
+            looping: false,
+            loop_start: 0.0,
+            loop_end: None,
+            bus: Bus::Sfx,
+            fade_in: 0.0,
+        }
+    }
+}
+
+/// Message from gameplay code to the mixer.
+#[derive(Clone, Debug)]
+pub enum AudioCommand {
+    Play {
+        voice: VoiceId,
+        sound: Sound,
+        params: PlayParams,
+    },
+    Stop(VoiceId),
+    SetVolume(VoiceId, f32),
+    SetPan(VoiceId, f32),
+    StopAll,
+    /// Make this voice the music track whose position drives the beat clock.
+    SetMusic(Option<VoiceId>),
+    /// Ramp a voice's fade gain (on top of its volume) to `gain` over `seconds`, then stop
+    /// it if `stop` is set.
+    Fade {
+        voice: VoiceId,
+        gain: f32,
+        seconds: f32,
+        stop: bool,
+    },
+    /// Ramp a bus's volume to `volume` over `seconds` (0 for an immediate change).
+    SetBusVolume {
+        bus: Bus,
+        volume: f32,
+        seconds: f32,
+    },
+    SetBusMuted(Bus, bool),
+}
+
+/// Per-frame callback registered with `ctx.set_audio_update`.
+pub type AudioUpdate = Box<dyn FnMut(&mut AudioQueue)>;
+
+/// Gameplay side of the mixer channel: queues commands and sends them in per-frame batches.
+pub struct AudioQueue {
+    sender: Sender<Vec<AudioCommand>>,
+    pending: Vec<AudioCommand>,
+    next_voice: u64,
+    music_position: Arc<AtomicU64>,
+    // The voice last started with `play_music`.
+    music: Option<VoiceId>,
+    // Volume and mute per bus as last queued, so menus can read them back.
+    bus_volumes: [f32; 3],
+    bus_muted: [bool; 3],
+}
+
+impl AudioQueue {
+    /// Queue a sound; it starts on the mixer after the next `flush`.
+    pub fn play(&mut self, sound: &Sound, params: PlayParams) -> VoiceId {
+        let voice = VoiceId(self.next_voice);
+        self.next_voice += 1;
+        self.pending.push(AudioCommand::Play {
+            voice,
+            sound: sound.clone(),
+            params,
+        });
+        voice
+    }
+
+    /// Queue a sound on `Bus::Music` as the music track reported by `music_position`.
+    pub fn play_music(&mut self, sound: &Sound, params: PlayParams) -> VoiceId {
+        let params = PlayParams {
+            bus: Bus::Music,
+            ..params
+        };
+        let voice = self.play(sound, params);
+        self.music = Some(voice);
+        self.pending.push(AudioCommand::SetMusic(Some(voice)));
+        self.music_position
+            .store(0.0f64.to_bits(), Ordering::Relaxed);
+        voice
+    }
+
+    /// Start `sound` as the music track, fading it in over `seconds` while the current
+    /// track fades out and stops.
+    pub fn crossfade_music(&mut self, sound: &Sound, params: PlayParams, seconds: f32) -> VoiceId {
+        if let Some(current) = self.music {
+            self.fade_out(current, seconds);
+        }
+        let params = PlayParams {
+            fade_in: seconds,
+            ..params
+        };
+        self.play_music(sound, params)
+    }
+
+    /// Fade the music track out over `seconds` and stop it.
+    pub fn stop_music(&mut self, seconds: f32) {
+        if let Some(current) = self.music.take() {
+            self.fade_out(current, seconds);
+            self.pending.push(AudioCommand::SetMusic(None));
+        }
+    }
+
+    /// The voice last started with `play_music` or `crossfade_music`.
+    pub fn music(&self) -> Option<VoiceId> {
+        self.music
+    }
+
+    pub fn push(&mut self, command: AudioCommand) {
+        match command {
+            AudioCommand::SetBusVolume { bus, volume, .. } => {
+                self.bus_volumes[bus.index()] = volume
+            }
+            AudioCommand::SetBusMuted(bus, muted) => self.bus_muted[bus.index()] = muted,
+            _ => {}
+        }
+        self.pending.push(command);
+    }
+
+    /// Fade a voice to silence over `seconds`, then stop it.
+    pub fn fade_out(&mut self, voice: VoiceId, seconds: f32) {
+        self.push(AudioCommand::Fade {
+            voice,
+            gain: 0.0,
+            seconds,
+            stop: true,
+        });
+    }
+
+    /// Set a bus's volume, ramping over `seconds` (0 for an immediate change).
+    pub fn set_bus_volume(&mut self, bus: Bus, volume: f32, seconds: f32) {
+        self.push(AudioCommand::SetBusVolume {
+            bus,
+            volume,
+            seconds,
+        });
+    }
+
+    /// Volume last set for `bus` (the fade target while fading).
+    pub fn bus_volume(&self, bus: Bus) -> f32 {
+        self.bus_volumes[bus.index()]
+    }
+
+    pub fn set_bus_muted(&mut self, bus: Bus, muted: bool) {
+        self.push(AudioCommand::SetBusMuted(bus, muted));
+    }
+
+    pub fn is_bus_muted(&self, bus: Bus) -> bool {
+        self.bus_muted[bus.index()]
+    }
+
+    /// Send everything queued since the last flush as one message. Never blocks.
+    ///
+    /// Called automatically by `end_drawing()`. If the mixer was dropped the batch is discarded.
+    pub fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let batch = std::mem::take(&mut self.pending);
+        let _ = self.sender.send(batch);
+    }
+
+    /// Playback position of the music voice in seconds, as of the mixer's last buffer.
+    pub fn music_position(&self) -> f64 {
+        f64::from_bits(self.music_position.load(Ordering::Relaxed))
+    }
+}
+
+/// A gain moving linearly towards a target, one step per output frame.
+#[derive(Clone, Copy, Debug)]
+struct Ramp {
+    value: f32,
+    target: f32,
+    step: f32,
+}
+
+impl Ramp {
+    fn new(value: f32) -> Self {
+        Self {
+            value,
+            target: value,
+            step: 0.0,
+        }
+    }
+
+    /// Move to `target` over `frames` output frames (immediately when `frames < 1`).
+    fn set(&mut self, target: f32, frames: f32) {
+        self.target = target;
+        if frames < 1.0 {
+            self.value = target;
+            self.step = 0.0;
+        } else {
+            self.step = (target - self.value) / frames;
+        }
+    }
+
+    /// The gain for the next frame.
+    fn next(&mut self) -> f32 {
+        if self.step != 0.0 {
+            self.value += self.step;
+            let reached = if self.step > 0.0 {
+                self.value >= self.target
+            } else {
+                self.value <= self.target
+            };
+            if reached {
+                self.value = self.target;
+                self.step = 0.0;
+            }
+        }
+        self.value
+    }
+
+    fn done(&self) -> bool {
+        self.step == 0.0
+    }
+}
+
+struct Voice {
+    id: VoiceId,
+    sound: Sound,
+    params: PlayParams,
+    // Position in source frames.
+    pos: f64,
+    // Fade gain on top of `params.volume`.
+    fade: Ramp,
+    // Stop once `fade` reaches its target.
+    stop_after_fade: bool,
+}
+
+impl Voice {
+    /// Source frame range the voice plays through before stopping or looping.
+    fn span(&self) -> (f64, f64) {
+        let frames = self.sound.frames() as f64;
+        if !self.params.looping {
+            return (0.0, frames);
+        }
+        let rate = self.sound.sample_rate as f64;
+        let end = self
+            .params
+            .loop_end
+            .map_or(frames, |end| (end.max(0.0) as f64 * rate).min(frames));
+        let start = (self.params.loop_start.max(0.0) as f64 * rate).min(end);
+        (start, end)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct BusState {
+    volume: Ramp,
+    muted: bool,
+}
+
+impl BusState {
+    /// Gain for the next frame; the ramp keeps moving while muted.
+    fn next(&mut self) -> f32 {
+        let volume = self.volume.next();
+        if self.muted { 0.0 } else { volume }
+    }
+}
+
+/// Audio-thread side: owns the playing voices and mixes them into output buffers.
+pub struct Mixer {
+    receiver: Receiver<Vec<AudioCommand>>,
+    voices: Vec<Voice>,
+    sample_rate: u32,
+    /// Master gain applied after mixing, on top of the `Bus::Master` volume.
+    pub master_volume: f32,
+    buses: [BusState; 3],
+    // Per-bus mix of the voices, indexed like `buses`; reused between buffers.
+    scratch: [Vec<f32>; 3],
+    music: Option<VoiceId>,
+    // Seconds (f64 bits) into the music voice, read by `AudioQueue::music_position`.
+    music_position: Arc<AtomicU64>,
+}
+
+/// Create a connected queue/mixer pair producing interleaved stereo at `sample_rate`.
+pub fn channel_pair(sample_rate: u32) -> (AudioQueue, Mixer) {
+    let (sender, receiver) = channel();
+    let music_position = Arc::new(AtomicU64::new(0.0f64.to_bits()));
+    (
+        AudioQueue {
+            sender,
+            pending: Vec::new(),
+            next_voice: 0,
+            music_position: music_position.clone(),
+            music: None,
+            bus_volumes: [1.0; 3],
+            bus_muted: [false; 3],
+        },
+        Mixer {
+            receiver,
+            voices: Vec::new(),
+            sample_rate: sample_rate.max(1),
+            master_volume: 1.0,
+            buses: [BusState {
+                volume: Ramp::new(1.0),
+                muted: false,
+            }; 3],
+            scratch: Default::default(),
+            music: None,
+            music_position,
+        },
+    )
+}
+
+/// Equal-power stereo gains for `pan` in `[-1, 1]`.
+fn pan_gains(pan: f32) -> (f32, f32) {
+    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+    (angle.cos(), angle.sin())
+}
+
+impl Mixer {
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Number of voices currently playing.
+    pub fn active_voices(&self) -> usize {
+        self.voices.len()
+    }
+
+    fn apply(&mut self, command: AudioCommand) {
+        match command {
+            AudioCommand::Play {
+                voice,
+                sound,
+                params,
+            } => {
+                let mut fade = Ramp::new(if params.fade_in > 0.0 { 0.0 } else { 1.0 });
+                fade.set(1.0, self.frames(params.fade_in));
+                self.voices.push(Voice {
+                    id: voice,
+                    sound,
+                    params,
+                    pos: 0.0,
+                    fade,
+                    stop_after_fade: false,
+                });
+            }
+            AudioCommand::Stop(id) => self.voices.retain(|v| v.id != id),
+            AudioCommand::SetVolume(id, volume) => {
+                if let Some(v) = self.voices.iter_mut().find(|v| v.id == id) {
+                    v.params.volume = volume;
+                }
+            }
+            AudioCommand::SetPan(id, pan) => {
+                if let Some(v) = self.voices.iter_mut().find(|v| v.id == id) {
+                    v.params.pan = pan;
+                }
+            }
+            AudioCommand::StopAll => self.voices.clear(),
+            AudioCommand::SetMusic(voice) => {
+                self.music = voice;
+                self.music_position
+                    .store(0.0f64.to_bits(), Ordering::Relaxed);
+            }
+            AudioCommand::Fade {
+                voice,
+                gain,
+                seconds,
+                stop,
+            } => {
+                let frames = self.frames(seconds);
+                if let Some(v) = self.voices.iter_mut().find(|v| v.id == voice) {
+                    v.fade.set(gain, frames);
+                    v.stop_after_fade = stop;
+                }
+            }
+            AudioCommand::SetBusVolume {
+                bus,
+                volume,
+                seconds,
+            } => {
+                let frames = self.frames(seconds);
+                self.buses[bus.index()].volume.set(volume, frames);
+            }
+            AudioCommand::SetBusMuted(bus, muted) => self.buses[bus.index()].muted = muted,
+        }
+    }
+
+    /// Output frames in `seconds`.
+    fn frames(&self, seconds: f32) -> f32 {
+        seconds.max(0.0) * self.sample_rate as f32
+    }
+
+    /// Fill `out` (interleaved stereo) with the mix of all voices, replacing its contents.
+    ///
+    /// Applies any command batches that arrived since the last call first; never blocks.
+    pub fn mix(&mut self, out: &mut [f32]) {
+        while let Ok(batch) = self.receiver.try_recv() {
+            for command in batch {
+                self.apply(command);
+            }
+        }
+
+        for buffer in &mut self.scratch {
+            buffer.clear();
+            buffer.resize(out.len(), 0.0);
+        }
+        let out_rate = self.sample_rate as f64;
+        for voice in &mut self.voices {
+            let step =
+                voice.sound.sample_rate as f64 / out_rate * voice.params.speed.max(0.0) as f64;
+            let (gl, gr) = pan_gains(voice.params.pan);
+            let (gl, gr) = (gl * voice.params.volume, gr * voice.params.volume);
+            let (start, end) = voice.span();
+
+            for frame in self.scratch[voice.params.bus.index()].chunks_exact_mut(2) {
+                if voice.pos >= end {
+                    if !voice.params.looping || end <= start {
+                        break;
+                    }
+                    voice.pos = start + (voice.pos - end) % (end - start);
+                }
+                let (l, r) = voice.sound.frame_at(voice.pos);
+                let fade = voice.fade.next();
+                frame[0] += l * gl * fade;
+                frame[1] += r * gr * fade;
+                voice.pos += step;
+            }
+        }
+        if let Some(music) = self.voices.iter().find(|v| Some(v.id) == self.music) {
+            let seconds = music.pos / music.sound.sample_rate as f64;
+            self.music_position
+                .store(seconds.to_bits(), Ordering::Relaxed);
+        }
+        self.voices.retain(|v| {
+            (v.params.looping || v.pos < v.sound.frames() as f64)
+                && !(v.stop_after_fade && v.fade.done())
+        });
+
+        let [master, music, sfx] = &self.scratch;
+        for (i, frame) in out.chunks_exact_mut(2).enumerate() {
+            let [master_gain, music_gain, sfx_gain] = self.buses.each_mut().map(BusState::next);
+            let gain = master_gain * self.master_volume;
+            for (c, s) in frame.iter_mut().enumerate() {
+                let j = i * 2 + c;
+                let mixed = master[j] + music[j] * music_gain + sfx[j] * sfx_gain;
+                *s = (mixed * gain).clamp(-1.0, 1.0);
+            }
+        }
+    }
+}
+
+/// Tempo of a music track.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tempo {
+    pub bpm: f32,
+    /// Seconds into the track where the first beat falls.
+    pub offset: f32,
+}
+
+/// Where the music is, in beats, as sampled at the start of a frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MusicBeat {
+    /// Playback position in seconds.
+    pub position: f64,
+    /// Continuous beat count since `Tempo::offset` (negative before the first beat).
+    pub beat: f64,
+    /// Index of the current beat (`beat.floor()`).
+    pub index: i64,
+    /// Progress through the current beat in `[0, 1)`; pulse visuals with `1.0 - phase`.
+    pub phase: f32,
+    /// Beats started since the previous frame (usually 0 or 1, more after a hitch).
+    /// A backwards jump, e.g. the track looping, counts as one.
+    pub crossed: u32,
+}
+
+impl MusicBeat {
+    /// Whether a new beat started since the previous frame.
+    pub fn just_crossed(&self) -> bool {
+        self.crossed > 0
+    }
+}
+
+/// Converts music positions into beats and detects beat crossings between updates.
+#[derive(Clone, Debug)]
+pub struct BeatClock {
+    pub tempo: Tempo,
+    last_index: Option<i64>,
+}
+
+impl BeatClock {
+    pub fn new(tempo: Tempo) -> Self {
+        Self {
+            tempo,
+            last_index: None,
+        }
+    }
+
+    /// Sample the clock at `position` seconds into the track.
+    pub fn update(&mut self, position: f64) -> MusicBeat {
+        let beat = (position - self.tempo.offset as f64) * self.tempo.bpm.max(0.0) as f64 / 60.0;
+        let index = beat.floor() as i64;
+        let crossed = match self.last_index {
+            Some(last) if index > last => (index - last) as u32,
+            Some(last) if index < last => 1,
+            _ => 0,
+        };
+        self.last_index = Some(index);
+        MusicBeat {
+            position,
+            beat,
+            index,
+            phase: (beat - index as f64) as f32,
+            crossed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queued_batches_are_mixed_and_finished_voices_dropped() {
+        let (mut queue, mut mixer) = channel_pair(4);
+        let sound = Sound::from_samples(4, 1, vec![0.5, 0.5, 0.5]).unwrap();
+        queue.play(
+            &sound,
+            PlayParams {
+                pan: -1.0,
+                ..PlayParams::default()
+            },
+        );
+
+        // Nothing reaches the mixer before the per-frame flush.
+        let mut out = [0.0f32; 8];
+        mixer.mix(&mut out);
+        assert_eq!(mixer.active_voices(), 0);
+
+        queue.flush();
+        mixer.mix(&mut out);
+        // Hard left: full gain on the left channel, none on the right, silence after 3 frames.
+        assert!((out[0] - 0.5).abs() < 1e-6 && out[1].abs() < 1e-6);
+        assert_eq!(out[6], 0.0);
+        assert_eq!(mixer.active_voices(), 0);
+    }
+
+    #[test]
+    fn buses_scale_mute_and_fade_their_voices() {
+        let (mut queue, mut mixer) = channel_pair(4);
+        let sound = Sound::from_samples(4, 1, vec![1.0; 64]).unwrap();
+        let looping = PlayParams {
+            looping: true,
+            ..PlayParams::default()
+        };
+        queue.play_music(&sound, looping);
+        let sfx = queue.play(&sound, looping);
+        queue.set_bus_volume(Bus::Music, 0.5, 0.0);
+        queue.set_bus_muted(Bus::Sfx, true);
+        queue.set_bus_volume(Bus::Master, 0.5, 0.0);
+        assert_eq!(queue.bus_volume(Bus::Music), 0.5);
+        assert!(queue.is_bus_muted(Bus::Sfx));
+        queue.flush();
+
+        // Center pan: each channel gets cos(45°) of the music at 0.5 * 0.5.
+        let mut out = [0.0f32; 8];
+        mixer.mix(&mut out);
+        let center = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((out[0] - 0.25 * center).abs() < 1e-6);
+
+        // A two-frame fade-out ramps the voice down, then stops it.
+        queue.set_bus_muted(Bus::Sfx, false);
+        queue.set_bus_volume(Bus::Music, 0.0, 0.0);
+        queue.fade_out(sfx, 0.5);
+        queue.flush();
+        mixer.mix(&mut out);
+        assert!((out[0] - 0.25 * center).abs() < 1e-6);
+        assert!(out[2].abs() < 1e-6);
+        assert_eq!(mixer.active_voices(), 1);
+    }
+
+    #[test]
+    fn loop_region_repeats_after_the_intro() {
+        let (mut queue, mut mixer) = channel_pair(1);
+        // Frames 0-1 are the intro, 2-3 the loop, 4 is never reached.
+        let sound = Sound::from_samples(1, 1, vec![0.1, 0.2, 0.3, 0.4, 0.5]).unwrap();
+        queue.play(
+            &sound,
+            PlayParams {
+                pan: -1.0,
+                looping: true,
+                loop_start: 2.0,
+                loop_end: Some(4.0),
+                ..PlayParams::default()
+            },
+        );
+        queue.flush();
+        let mut out = [0.0f32; 14];
+        mixer.mix(&mut out);
+        let left: Vec<f32> = out.iter().step_by(2).copied().collect();
+        assert_eq!(left, [0.1, 0.2, 0.3, 0.4, 0.3, 0.4, 0.3]);
+    }
+
+    #[test]
+    fn crossfade_swaps_the_music_voice() {
+        let (mut queue, mut mixer) = channel_pair(4);
+        let sound = Sound::from_samples(4, 1, vec![1.0; 64]).unwrap();
+        let first = queue.play_music(&sound, PlayParams::default());
+        let second = queue.crossfade_music(&sound, PlayParams::default(), 0.5);
+        assert_eq!(queue.music(), Some(second));
+        queue.flush();
+
+        // Two-frame fades: the old track goes 0.5, 0.0 while the new one goes 0.5, 1.0.
+        let mut out = [0.0f32; 8];
+        mixer.mix(&mut out);
+        let center = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((out[0] - center).abs() < 1e-6);
+        assert!((out[2] - center).abs() < 1e-6);
+        assert_eq!(mixer.active_voices(), 1);
+        assert_ne!(first, second);
+
+        queue.stop_music(0.0);
+        assert_eq!(queue.music(), None);
+    }
+
+    #[test]
+    fn beat_clock_reports_crossings_and_phase() {
+        let mut clock = BeatClock::new(Tempo {
+            bpm: 120.0,
+            offset: 0.25,
+        });
+        let first = clock.update(0.0);
+        assert_eq!((first.index, first.crossed), (-1, 0));
+
+        let b = clock.update(0.5);
+        assert_eq!((b.index, b.crossed), (0, 1));
+        assert!((b.phase - 0.5).abs() < 1e-6);
+
+        assert!(!clock.update(0.6).just_crossed());
+        assert_eq!(clock.update(2.0).crossed, 3);
+        assert_eq!(clock.update(0.1).crossed, 1); // looped back
+
+        // The mixer publishes the music voice position for the clock.
+        let (mut queue, mut mixer) = channel_pair(4);
+        let sound = Sound::from_samples(4, 1, vec![0.0; 16]).unwrap();
+        queue.play_music(&sound, PlayParams::default());
+        queue.flush();
+        mixer.mix(&mut [0.0; 12]);
+        assert_eq!(queue.music_position(), 1.5);
+    }
+
+    #[test]
+    fn wav_16bit_decodes() {
+        let mut wav = Vec::new();
+        let data: [i16; 2] = [16384, -32768];
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + 4u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        for v in [1u16, 1] {
+            wav.extend_from_slice(&v.to_le_bytes());
+        }
+        wav.extend_from_slice(&22050u32.to_le_bytes());
+        wav.extend_from_slice(&44100u32.to_le_bytes());
+        for v in [2u16, 16] {
+            wav.extend_from_slice(&v.to_le_bytes());
+        }
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&4u32.to_le_bytes());
+        for v in data {
+            wav.extend_from_slice(&v.to_le_bytes());
+        }
+
+        let sound = Sound::from_wav_bytes(&wav).unwrap();
+        assert_eq!(
+            (sound.sample_rate(), sound.channels(), sound.frames()),
+            (22050, 1, 2)
+        );
+        assert_eq!(sound.frame_at(1.0), (-1.0, -1.0));
+    }
+
+    #[test]
+    fn extensible_wav_dispatches_on_subformat() {
+        let extensible = |subformat: u16| {
+            let mut wav = Vec::new();
+            wav.extend_from_slice(b"RIFF");
+            wav.extend_from_slice(&(60 + 8u32).to_le_bytes());
+            wav.extend_from_slice(b"WAVEfmt ");
+            wav.extend_from_slice(&40u32.to_le_bytes());
+            for v in [0xFFFEu16, 1] {
+                wav.extend_from_slice(&v.to_le_bytes());
+            }
+            wav.extend_from_slice(&48000u32.to_le_bytes());
+            wav.extend_from_slice(&192000u32.to_le_bytes());
+            // Block align, bits, cbSize, valid bits.
+            for v in [4u16, 32, 22, 32] {
+                wav.extend_from_slice(&v.to_le_bytes());
+            }
+            wav.extend_from_slice(&4u32.to_le_bytes());
+            wav.extend_from_slice(&subformat.to_le_bytes());
+            wav.extend_from_slice(&SUBFORMAT_GUID_SUFFIX);
+            wav.extend_from_slice(b"data");
+            wav.extend_from_slice(&8u32.to_le_bytes());
+            for v in [i32::MIN, 1 << 30] {
+                wav.extend_from_slice(&v.to_le_bytes());
+            }
+            wav
+        };
+
+        // 32-bit integer PCM, not float.
+        let sound = Sound::from_wav_bytes(&extensible(1)).unwrap();
+        assert_eq!((sound.sample_rate(), sound.frames()), (48000, 2));
+        assert_eq!(sound.frame_at(0.0), (-1.0, -1.0));
+        assert_eq!(sound.frame_at(1.0), (0.5, 0.5));
+
+        assert!(Sound::from_wav_bytes(&extensible(2)).is_err());
+        let mut unknown = extensible(1);
+        unknown[52] ^= 0xFF;
+        assert!(Sound::from_wav_bytes(&unknown).is_err());
+    }
+}
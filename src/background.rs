@@ -0,0 +1,68 @@
+use crate::Color;
+
+/// Procedural pattern generated by a background layer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum BackgroundKind {
+    /// Sparse twinkling points, one candidate star per cell.
+    Starfield,
+    /// Smooth fractal value noise (clouds, nebulae, fog).
+    Noise,
+    /// Outlined hexagon tiling.
+    HexGrid,
+}
+
+impl BackgroundKind {
+    fn shader_index(self) -> f32 {
+        match self {
+            BackgroundKind::Starfield => 0.0,
+            BackgroundKind::Noise => 1.0,
+            BackgroundKind::HexGrid => 2.0,
+        }
+    }
+}
+
+/// An infinite, procedurally generated backdrop rendered entirely in the fragment shader.
+///
+/// Draw with `draw_background(&layer, dest, time)`. The pattern scrolls by
+/// `speed * time`, so stacking layers with different speeds gives cheap parallax.
+#[derive(Clone, Copy, Debug)]
+pub struct BackgroundLayer {
+    pub kind: BackgroundKind,
+    /// Seed for the pattern; different seeds give unrelated layouts.
+    pub seed: u32,
+    /// Scroll speed in pixels per second.
+    pub speed: (f32, f32),
+    /// Size of one pattern cell in pixels.
+    pub scale: f32,
+    /// Color of the pattern (alpha is multiplied by the pattern intensity).
+    pub color: Color,
+}
+
+impl BackgroundLayer {
+    pub fn new(kind: BackgroundKind, seed: u32) -> Self {
+        Self {
+            kind,
+            seed,
+            speed: (0.0, 0.0),
+            scale: 48.0,
+            color: Color::WHITE,
+        }
+    }
+
+    /// Pack the layer into the `DrawParams.effect` uniform layout.
+    ///
+    /// The seed is bit-cast (not converted) so every `u32` survives the trip exactly.
+    pub(crate) fn effect_uniform(&self) -> [f32; 4] {
+        [
+            self.kind.shader_index(),
+            f32::from_bits(self.seed),
+            self.scale.max(1e-3),
+            0.0,
+        ]
+    }
+
+    /// Pattern-space offset (in pixels) at `time` seconds.
+    pub(crate) fn scroll_at(&self, time: f32) -> [f32; 2] {
+        [self.speed.0 * time, self.speed.1 * time]
+    }
+}
@@ -1,3 +1,4 @@
+use crate::noise::noise1;
 use glam::Mat4;
 
 /// A 2D camera for world-space rendering.
@@ -5,13 +6,92 @@ use glam::Mat4;
 /// Use with `begin_mode_2d(camera)` to enter world-space drawing mode.
 /// - `x, y`: camera position (world units)
 /// - `rotation`: rotation in radians (positive = counter-clockwise)
-/// - `zoom`: scale factor (values > 1.0 zoom out, < 1.0 zoom in)
+/// - `zoom`: scale factor; with the default `ZoomConvention::ZoomOut` values > 1.0 zoom out,
+///   after `with_standard_zoom()` values > 1.0 zoom in (as in raylib)
 #[derive(Clone, Copy, Debug)]
 pub struct Camera2D {
     pub x: f32,
     pub y: f32,
     pub rotation: f32,
     pub zoom: f32,
+    /// What `zoom` means; `with_standard_zoom()` switches to `ZoomConvention::Standard`.
+    pub zoom_convention: ZoomConvention,
+    /// Screen shake, driven by `add_trauma()` and `update_shake()`; tune `decay`,
+    /// `max_offset`, ... here.
+    pub shake: CameraShake,
+}
+
+/// What a larger `Camera2D::zoom` means.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ZoomConvention {
+    /// `zoom` is world units per pixel: `2.0` shows twice as much of the world. The
+    /// original libforge behavior.
+    #[default]
+    ZoomOut,
+    /// `zoom` is pixels per world unit: `2.0` draws the world twice as large, like raylib
+    /// and most engines.
+    Standard,
+}
+
+/// Trauma-based screen shake ("Math for Game Programmers: Juicing Your Cameras With Math").
+///
+/// Hits add trauma in `[0, 1]`, which decays linearly; the shake strength is trauma squared,
+/// so small hits barely move the camera and big ones fall off quickly. Offset and rotation
+/// follow smooth noise rather than per-frame random jumps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraShake {
+    pub trauma: f32,
+    /// Trauma removed per second.
+    pub decay: f32,
+    /// Offset at full trauma, in world units.
+    pub max_offset: f32,
+    /// Rotation at full trauma, in radians.
+    pub max_angle: f32,
+    /// How fast the shake wobbles, in noise periods per second.
+    pub frequency: f32,
+    pub seed: u64,
+    time: f32,
+    offset: (f32, f32),
+    angle: f32,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        Self {
+            trauma: 0.0,
+            decay: 1.0,
+            max_offset: 16.0,
+            max_angle: 0.1,
+            frequency: 15.0,
+            seed: 0,
+            time: 0.0,
+            offset: (0.0, 0.0),
+            angle: 0.0,
+        }
+    }
+}
+
+impl CameraShake {
+    /// Current `(x, y)` offset in world units.
+    pub fn offset(&self) -> (f32, f32) {
+        self.offset
+    }
+
+    /// Current rotation offset in radians.
+    pub fn angle(&self) -> f32 {
+        self.angle
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.time += dt;
+        self.trauma = (self.trauma - self.decay * dt).max(0.0);
+        let strength = self.trauma * self.trauma;
+        let t = self.time * self.frequency;
+        // Three decorrelated noise channels, one per degree of freedom.
+        let channel = |i: u64| noise1(t, self.seed.wrapping_add(i)) * strength;
+        self.offset = (channel(0) * self.max_offset, channel(1) * self.max_offset);
+        self.angle = channel(2) * self.max_angle;
+    }
 }
 
 impl Default for Camera2D {
@@ -28,22 +108,307 @@ impl Camera2D {
             rotation: 0.0,
             // `zoom` is a scale factor. `1.0` means "no zoom".
             zoom: 1.0,
+            zoom_convention: ZoomConvention::ZoomOut,
+            shake: CameraShake::default(),
+        }
+    }
+
+    /// This camera with `ZoomConvention::Standard`: larger `zoom` moves closer.
+    pub fn with_standard_zoom(self) -> Self {
+        Self {
+            zoom_convention: ZoomConvention::Standard,
+            ..self
+        }
+    }
+
+    /// Screen pixels per world unit, whichever the zoom convention.
+    pub fn scale(&self) -> f32 {
+        // Prevent division-by-zero and keep the view matrix well-defined.
+        let zoom = if self.zoom <= 0.0 { 1.0 } else { self.zoom };
+        match self.zoom_convention {
+            ZoomConvention::ZoomOut => 1.0 / zoom,
+            ZoomConvention::Standard => zoom,
         }
     }
 
+    /// Draw the world `factor` times larger, adjusting `zoom` in this camera's convention.
+    pub fn magnify(&mut self, factor: f32) {
+        match self.zoom_convention {
+            ZoomConvention::ZoomOut => self.zoom /= factor,
+            ZoomConvention::Standard => self.zoom *= factor,
+        }
+    }
+
+    /// Add screen-shake trauma (clamped to `[0, 1]`); e.g. `0.3` for a hit, `0.8` for an
+    /// explosion.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.shake.trauma = (self.shake.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    /// Advance the shake by `dt` seconds. Call once per frame before `begin_mode_2d`.
+    pub fn update_shake(&mut self, dt: f32) {
+        self.shake.update(dt);
+    }
+
+    /// Move toward `(target_x, target_y)` with exponential smoothing.
+    ///
+    /// `rate` is how quickly the gap closes (about 63% after `1 / rate` seconds); the
+    /// result is the same at any frame rate, unlike lerping by a fixed fraction per frame.
+    pub fn smooth_follow(&mut self, target_x: f32, target_y: f32, rate: f32, dt: f32) {
+        let t = 1.0 - (-rate * dt).exp();
+        self.x += (target_x - self.x) * t;
+        self.y += (target_y - self.y) * t;
+    }
+
     /// Returns the view matrix (maps world -> camera space).
     /// We produce the matrix that should be multiplied on the left of model:
     /// final = projection * view * model
+    ///
+    /// Includes the shake offset but not the shake rotation, which turns the view about
+    /// its centre and so needs the canvas size; see `view_matrix_for`.
     pub fn view_matrix(&self) -> glam::Mat4 {
-        // Prevent division-by-zero and keep the view matrix well-defined.
-        let zoom = if self.zoom <= 0.0 { 1.0 } else { self.zoom };
-
-        // Note: this convention means zoom > 1.0 zooms *out* (world appears smaller).
-        // If you want zoom > 1.0 to zoom *in*, change this to `Mat4::from_scale(vec3(zoom, zoom, 1.0))`.
-        let scale = Mat4::from_scale(glam::vec3(1.0 / zoom, 1.0 / zoom, 1.0));
+        let scale = self.scale();
+        let scale = Mat4::from_scale(glam::vec3(scale, scale, 1.0));
+        let (shake_x, shake_y) = self.shake.offset;
         let rotation = Mat4::from_rotation_z(-self.rotation);
-        let translation = Mat4::from_translation(glam::vec3(-self.x, -self.y, 0.0));
+        let translation =
+            Mat4::from_translation(glam::vec3(-(self.x + shake_x), -(self.y + shake_y), 0.0));
 
         scale * rotation * translation
     }
+
+    /// The view matrix as drawn on a canvas of `canvas_size` pixels: `view_matrix` with the
+    /// shake rotation applied about the canvas centre, so shaking wobbles the view in place.
+    pub fn view_matrix_for(&self, canvas_size: (f32, f32)) -> glam::Mat4 {
+        if self.shake.angle == 0.0 {
+            return self.view_matrix();
+        }
+        let center = glam::vec3(canvas_size.0 * 0.5, canvas_size.1 * 0.5, 0.0);
+        let shake = Mat4::from_translation(center)
+            * Mat4::from_rotation_z(-self.shake.angle)
+            * Mat4::from_translation(-center);
+        shake * self.view_matrix()
+    }
+
+    /// World position drawn at `screen`, in canvas pixels (e.g. `ctx.virtual_mouse_position()`).
+    /// Ignores the shake rotation (see `view_matrix`).
+    pub fn screen_to_world(&self, screen: (f32, f32)) -> (f32, f32) {
+        let p = self
+            .view_matrix()
+            .inverse()
+            .transform_point3(glam::vec3(screen.0, screen.1, 0.0));
+        (p.x, p.y)
+    }
+
+    /// Canvas pixel position where the world point `world` is drawn.
+    pub fn world_to_screen(&self, world: (f32, f32)) -> (f32, f32) {
+        let p = self
+            .view_matrix()
+            .transform_point3(glam::vec3(world.0, world.1, 0.0));
+        (p.x, p.y)
+    }
+
+    /// World position drawn at the centre of a canvas of `canvas_size` pixels: the point the
+    /// camera looks at, after zoom, rotation and shake.
+    pub fn view_center(&self, canvas_size: (f32, f32)) -> (f32, f32) {
+        self.screen_to_world((canvas_size.0 * 0.5, canvas_size.1 * 0.5))
+    }
+
+    /// Magnify by `factor` (`> 1.0` moves closer) while keeping the world point under
+    /// `screen_point` fixed, like map apps zooming around the cursor or pinch center.
+    ///
+    /// The camera position is the canvas's top-left corner, so no screen size is needed:
+    /// `camera.zoom_at(ctx.virtual_mouse_position(), 1.1f32.powf(ctx.mouse_wheel().1))`.
+    pub fn zoom_at(&mut self, screen_point: (f32, f32), factor: f32) {
+        if !(factor.is_finite() && factor > 0.0) {
+            return;
+        }
+        let before = self.screen_to_world(screen_point);
+        self.magnify(factor);
+        let after = self.screen_to_world(screen_point);
+        self.x += before.0 - after.0;
+        self.y += before.1 - after.1;
+    }
+}
+
+/// Stereo placement of a sound relative to a camera listener.
+///
+/// Feed `volume` and `pan` into `PlayParams` for `ctx.play_sound`, or into any audio crate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpatialSound {
+    /// Linear gain in `[0.0, 1.0]` after distance attenuation.
+    pub volume: f32,
+    /// Stereo pan in `[-1.0, 1.0]` (`-1.0` = fully left, `1.0` = fully right).
+    pub pan: f32,
+}
+
+impl Camera2D {
+    /// Compute volume and pan for a sound at world position `(x, y)`, heard from the centre
+    /// of this camera's view of a canvas of `canvas_size` pixels (`ctx.canvas_size()`).
+    ///
+    /// Volume falls off linearly to zero at `max_distance` (world units). Pan uses the
+    /// horizontal offset on screen, so a rotated camera pans relative to the screen.
+    pub fn spatial_sound(
+        &self,
+        canvas_size: (f32, f32),
+        x: f32,
+        y: f32,
+        max_distance: f32,
+    ) -> SpatialSound {
+        let (cx, cy) = self.view_center(canvas_size);
+        let dx = x - cx;
+        let dy = y - cy;
+        let distance = (dx * dx + dy * dy).sqrt();
+
+        let volume = if max_distance > 0.0 {
+            (1.0 - distance / max_distance).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        // Horizontal offset from the screen centre, back in world units.
+        let local_x = (self.world_to_screen((x, y)).0 - canvas_size.0 * 0.5) / self.scale();
+        let pan = if max_distance > 0.0 {
+            (local_x / max_distance).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+
+        SpatialSound { volume, pan }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spatial_sound_pans_and_attenuates() {
+        let canvas = (800.0, 600.0);
+        let mut cam = Camera2D::new();
+        cam.x = 100.0;
+        cam.y = 50.0;
+        cam.zoom = 2.0;
+
+        // The listener is the middle of the view, not the camera's top-left corner.
+        let (cx, cy) = cam.view_center(canvas);
+        assert!((cx - 900.0).abs() < 1e-3 && (cy - 650.0).abs() < 1e-3);
+        let center = cam.spatial_sound(canvas, cx, cy, 100.0);
+        assert!((center.volume - 1.0).abs() < 1e-4);
+        assert!(center.pan.abs() < 1e-4);
+
+        let right = cam.spatial_sound(canvas, cx + 50.0, cy, 100.0);
+        assert!((right.volume - 0.5).abs() < 1e-4);
+        assert!((right.pan - 0.5).abs() < 1e-4);
+
+        let far_left = cam.spatial_sound(canvas, cx - 500.0, cy, 100.0);
+        assert_eq!(far_left.volume, 0.0);
+        assert_eq!(far_left.pan, -1.0);
+
+        // Rotated a quarter turn, the screen centre still pans to the middle and the point
+        // drawn right of it pans right.
+        cam.rotation = std::f32::consts::FRAC_PI_2;
+        let (cx, cy) = cam.view_center(canvas);
+        let center = cam.spatial_sound(canvas, cx, cy, 100.0);
+        assert!(center.pan.abs() < 1e-4);
+        let (rx, ry) = cam.screen_to_world((425.0, 300.0));
+        let right = cam.spatial_sound(canvas, rx, ry, 100.0);
+        assert!((right.pan - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn zoom_at_keeps_the_point_under_the_cursor() {
+        let mut cam = Camera2D {
+            x: 40.0,
+            y: -10.0,
+            rotation: 0.6,
+            zoom: 1.5,
+            ..Camera2D::new()
+        };
+        let cursor = (300.0, 120.0);
+        let world = cam.screen_to_world(cursor);
+        let screen = cam.world_to_screen(world);
+        assert!((screen.0 - cursor.0).abs() < 1e-3 && (screen.1 - cursor.1).abs() < 1e-3);
+
+        cam.zoom_at(cursor, 2.0);
+        assert!((cam.zoom - 0.75).abs() < 1e-6);
+        let after = cam.screen_to_world(cursor);
+        assert!((after.0 - world.0).abs() < 1e-3 && (after.1 - world.1).abs() < 1e-3);
+
+        cam.zoom_at(cursor, 0.0);
+        assert!((cam.zoom - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn zoom_conventions_are_reciprocal() {
+        let out = Camera2D {
+            zoom: 2.0,
+            ..Camera2D::new()
+        };
+        let standard = Camera2D {
+            zoom: 0.5,
+            ..Camera2D::new().with_standard_zoom()
+        };
+        assert_eq!(out.scale(), 0.5);
+        assert_eq!(out.view_matrix(), standard.view_matrix());
+        // A world unit at standard zoom 2.0 spans two pixels.
+        let closer = Camera2D {
+            zoom: 2.0,
+            ..Camera2D::new().with_standard_zoom()
+        };
+        assert_eq!(closer.world_to_screen((10.0, 5.0)), (20.0, 10.0));
+
+        let mut cam = closer;
+        cam.zoom_at((100.0, 100.0), 2.0);
+        assert_eq!(cam.zoom, 4.0);
+        let mut cam = out;
+        cam.zoom_at((100.0, 100.0), 2.0);
+        assert_eq!(cam.zoom, 1.0);
+    }
+
+    #[test]
+    fn shake_decays_and_follow_is_frame_rate_independent() {
+        let mut cam = Camera2D::new();
+        let still = cam.view_matrix();
+        cam.add_trauma(0.7);
+        cam.add_trauma(0.7);
+        assert_eq!(cam.shake.trauma, 1.0);
+
+        let mut moved = false;
+        for _ in 0..30 {
+            cam.update_shake(1.0 / 60.0);
+            let (x, y) = cam.shake.offset();
+            assert!(x.abs() <= 16.0 && y.abs() <= 16.0 && cam.shake.angle().abs() <= 0.1);
+            moved |= cam.view_matrix() != still;
+        }
+        assert!(moved);
+        assert!((cam.shake.trauma - 0.5).abs() < 1e-4);
+        cam.update_shake(1.0);
+        assert_eq!(cam.shake.trauma, 0.0);
+        assert_eq!(cam.view_matrix(), still);
+
+        // The shake rotation turns the view about the canvas centre, which stays put.
+        let mut shaken = Camera2D::new();
+        shaken.x = 30.0;
+        shaken.shake.angle = 0.1;
+        let canvas = (800.0, 600.0);
+        let center = shaken.screen_to_world((400.0, 300.0));
+        let drawn = shaken
+            .view_matrix_for(canvas)
+            .transform_point3(glam::vec3(center.0, center.1, 0.0));
+        assert!((drawn.x - 400.0).abs() < 1e-3 && (drawn.y - 300.0).abs() < 1e-3);
+        let corner = shaken
+            .view_matrix_for(canvas)
+            .transform_point3(glam::vec3(30.0, 0.0, 0.0));
+        assert!(corner.x.abs() > 1.0 || corner.y.abs() > 1.0);
+
+        let mut fast = Camera2D::new();
+        let mut slow = Camera2D::new();
+        for _ in 0..4 {
+            fast.smooth_follow(100.0, -50.0, 5.0, 0.025);
+        }
+        slow.smooth_follow(100.0, -50.0, 5.0, 0.1);
+        assert!((fast.x - slow.x).abs() < 1e-3 && (fast.y - slow.y).abs() < 1e-3);
+        assert!(slow.x > 0.0 && slow.x < 100.0);
+    }
 }
@@ -0,0 +1,236 @@
+//! Microphone capture (enabled with the `capture` feature).
+//!
+//! `ctx.open_microphone()` opens the default input device through cpal. To feed samples
+//! from elsewhere (SDL, a web AudioWorklet, a file), `ctx.create_capture(..)` instead
+//! returns a `CaptureSink` to move into your input callback, which calls
+//! `sink.push(&samples)`. Each frame, `ctx.captured_audio()` holds the PCM received since
+//! the previous frame; `rms` and `spectrum` turn it into a loudness level or frequency bins
+//! for voice activation and visualizers.
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample};
+use std::sync::mpsc::{Receiver, SyncSender, sync_channel};
+
+// Buffers held before the game drains them; older input is dropped beyond this.
+const MAX_PENDING_BUFFERS: usize = 64;
+
+/// Input-callback side: forwards captured samples to the game without blocking.
+pub struct CaptureSink {
+    sender: SyncSender<Vec<f32>>,
+}
+
+impl CaptureSink {
+    /// Forward interleaved samples in `[-1, 1]`. Drops the buffer if the game has stopped
+    /// draining captures (or was dropped), so the input thread never waits.
+    pub fn push(&mut self, samples: &[f32]) {
+        let _ = self.sender.try_send(samples.to_vec());
+    }
+}
+
+/// An open input device streaming into its `Capture`; recording stops when it is dropped.
+pub struct Microphone {
+    _stream: cpal::Stream,
+}
+
+/// Open the system's default input device in its default configuration.
+pub fn open_default_microphone() -> Result<(Microphone, Capture), String> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or("no input device available")?;
+    let supported = device
+        .default_input_config()
+        .map_err(|e| format!("cannot query the input device: {e}"))?;
+    let config = supported.config();
+    let (sink, capture) = capture_pair(config.sample_rate, config.channels);
+    let stream = match supported.sample_format() {
+        SampleFormat::F32 => input_stream::<f32>(&device, config, sink),
+        SampleFormat::I16 => input_stream::<i16>(&device, config, sink),
+        SampleFormat::U16 => input_stream::<u16>(&device, config, sink),
+        SampleFormat::I32 => input_stream::<i32>(&device, config, sink),
+        SampleFormat::U8 => input_stream::<u8>(&device, config, sink),
+        format => return Err(format!("unsupported input sample format {format}")),
+    }
+    .map_err(|e| format!("cannot open the input stream: {e}"))?;
+    stream
+        .play()
+        .map_err(|e| format!("cannot start the input stream: {e}"))?;
+    Ok((Microphone { _stream: stream }, capture))
+}
+
+fn input_stream<T>(
+    device: &cpal::Device,
+    config: cpal::StreamConfig,
+    sink: CaptureSink,
+) -> Result<cpal::Stream, cpal::Error>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            let samples = data.iter().map(|s| s.to_sample::<f32>()).collect();
+            let _ = sink.sender.try_send(samples);
+        },
+        |e| log_warn!("microphone stream error: {e}"),
+        None,
+    )
+}
+
+/// Game side: collects the samples captured since the last frame.
+pub struct Capture {
+    receiver: Receiver<Vec<f32>>,
+    sample_rate: u32,
+    channels: u16,
+    frame: Vec<f32>,
+}
+
+/// Create a connected sink/capture pair for interleaved input at `sample_rate`.
+pub fn capture_pair(sample_rate: u32, channels: u16) -> (CaptureSink, Capture) {
+    let (sender, receiver) = sync_channel(MAX_PENDING_BUFFERS);
+    (
+        CaptureSink { sender },
+        Capture {
+            receiver,
+            sample_rate: sample_rate.max(1),
+            channels: channels.max(1),
+            frame: Vec::new(),
+        },
+    )
+}
+
+impl Capture {
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Replace this frame's samples with everything received since the last call.
+    ///
+    /// Called automatically by `begin_drawing()`.
+    pub fn begin_frame(&mut self) {
+        self.frame.clear();
+        while let Ok(buffer) = self.receiver.try_recv() {
+            self.frame.extend_from_slice(&buffer);
+        }
+    }
+
+    /// Interleaved samples captured during the previous frame.
+    pub fn samples(&self) -> &[f32] {
+        &self.frame
+    }
+
+    /// Samples of the previous frame averaged down to mono.
+    pub fn mono(&self) -> Vec<f32> {
+        self.frame
+            .chunks(self.channels as usize)
+            .map(|f| f.iter().sum::<f32>() / f.len() as f32)
+            .collect()
+    }
+}
+
+/// Root-mean-square level of `samples` (0 for silence, ~0.707 for a full-scale sine).
+pub fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// Magnitude spectrum of mono `samples` using a Hann-windowed radix-2 FFT.
+///
+/// Uses the last power-of-two run of samples (up to 4096) and returns half that many bins;
+/// bin `i` is centered on `i * sample_rate / (2 * bins.len())` Hz. Magnitudes are normalized
+/// so a full-scale sine peaks near 0.5.
+pub fn spectrum(samples: &[f32]) -> Vec<f32> {
+    let available = samples.len().min(4096);
+    if available < 2 {
+        return Vec::new();
+    }
+    let n = 1usize << (usize::BITS - 1 - available.leading_zeros());
+    let input = &samples[samples.len() - n..];
+
+    let mut re: Vec<f32> = input
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let w = 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / (n - 1) as f32).cos();
+            s * w
+        })
+        .collect();
+    let mut im = vec![0.0f32; n];
+    fft(&mut re, &mut im);
+
+    (0..n / 2)
+        .map(|i| re[i].hypot(im[i]) * 2.0 / n as f32)
+        .collect()
+}
+
+/// In-place iterative radix-2 FFT; `re.len()` must be a power of two.
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if j > i {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -std::f32::consts::TAU / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let tr = re[b] * cos - im[b] * sin;
+                let ti = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        len *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captured_buffers_arrive_per_frame() {
+        let (mut sink, mut capture) = capture_pair(8000, 2);
+        sink.push(&[0.5, -0.5]);
+        sink.push(&[1.0, 1.0]);
+
+        capture.begin_frame();
+        assert_eq!(capture.samples(), [0.5, -0.5, 1.0, 1.0]);
+        assert_eq!(capture.mono(), [0.0, 1.0]);
+
+        capture.begin_frame();
+        assert!(capture.samples().is_empty());
+        assert_eq!(rms(&[1.0, -1.0, 1.0, -1.0]), 1.0);
+    }
+
+    #[test]
+    fn spectrum_peaks_at_sine_frequency() {
+        // 8 cycles over 256 samples lands exactly on bin 8.
+        let samples: Vec<f32> = (0..256)
+            .map(|i| (std::f32::consts::TAU * 8.0 * i as f32 / 256.0).sin())
+            .collect();
+        let bins = spectrum(&samples);
+        assert_eq!(bins.len(), 128);
+        let peak = (0..bins.len())
+            .max_by(|&a, &b| bins[a].total_cmp(&bins[b]))
+            .unwrap();
+        assert_eq!(peak, 8);
+        assert!(bins[40] < 0.01);
+    }
+}
@@ -0,0 +1,60 @@
+/// Text clipboard behind `clipboard_text()` / `set_clipboard_text()`.
+///
+/// With the `clipboard` feature this talks to the OS clipboard through `arboard`, opened on
+/// first use. Without the feature, or when the OS clipboard can't be opened (headless CI,
+/// no display server), text is kept in-process so copy/paste still works inside the game.
+#[derive(Default)]
+pub(crate) struct Clipboard {
+    #[cfg(feature = "clipboard")]
+    system: Option<arboard::Clipboard>,
+    #[cfg(feature = "clipboard")]
+    system_failed: bool,
+    local: Option<String>,
+}
+
+impl Clipboard {
+    #[cfg(feature = "clipboard")]
+    fn system(&mut self) -> Option<&mut arboard::Clipboard> {
+        if self.system.is_none() && !self.system_failed {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.system = Some(clipboard),
+                Err(e) => {
+                    log_warn!("system clipboard unavailable, using in-process clipboard: {e}");
+                    self.system_failed = true;
+                }
+            }
+        }
+        self.system.as_mut()
+    }
+
+    pub fn text(&mut self) -> Option<String> {
+        #[cfg(feature = "clipboard")]
+        if let Some(system) = self.system() {
+            return system.get_text().ok();
+        }
+        self.local.clone()
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        #[cfg(feature = "clipboard")]
+        if let Some(system) = self.system()
+            && system.set_text(text).is_ok()
+        {
+            return;
+        }
+        self.local = Some(text.to_string());
+    }
+}
+
+#[cfg(all(test, not(feature = "clipboard")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_clipboard_round_trips() {
+        let mut clipboard = Clipboard::default();
+        assert_eq!(clipboard.text(), None);
+        clipboard.set_text("level-01");
+        assert_eq!(clipboard.text().as_deref(), Some("level-01"));
+    }
+}
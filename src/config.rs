@@ -0,0 +1,124 @@
+use crate::WindowPlacement;
+#[cfg(feature = "serde")]
+use crate::storage::{Storage, StorageError};
+use std::path::Path;
+
+/// User-facing display settings worth restoring between sessions.
+///
+/// Capture with `ctx.config(&window)` and restore with `ctx.apply_config(&window, &config)`,
+/// or use `ctx.save_config` / `ctx.load_config` to go through a file. With the `serde`
+/// feature, `save_to` / `load_from` keep it as JSON in a `Storage` slot, and the struct can
+/// be embedded in a game's own settings file.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContextConfig {
+    /// Windowed position and size. `None` leaves the window where the OS put it.
+    pub placement: Option<WindowPlacement>,
+    /// Borderless fullscreen on the window's current monitor.
+    pub fullscreen: bool,
+    pub vsync: bool,
+    /// MSAA sample count; 1 disables multisampling.
+    pub msaa_samples: u32,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            placement: None,
+            fullscreen: false,
+            vsync: true,
+            msaa_samples: 1,
+        }
+    }
+}
+
+impl ContextConfig {
+    /// Serialize to the same `key=value` text form as `WindowPlacement::to_text`.
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "fullscreen={}\nvsync={}\nmsaa_samples={}\n",
+            self.fullscreen, self.vsync, self.msaa_samples
+        );
+        if let Some(placement) = &self.placement {
+            out.push_str(&placement.to_text());
+        }
+        out
+    }
+
+    /// Parse the text form produced by `to_text`. Missing keys keep their defaults;
+    /// returns `None` if a value is malformed.
+    pub fn from_text(text: &str) -> Option<Self> {
+        let mut config = Self {
+            placement: WindowPlacement::from_text(text),
+            ..Self::default()
+        };
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "fullscreen" => config.fullscreen = value.parse().ok()?,
+                "vsync" => config.vsync = value.parse().ok()?,
+                "msaa_samples" => config.msaa_samples = value.parse().ok()?,
+                _ => continue,
+            }
+        }
+        Some(config)
+    }
+
+    /// Write the config to `path` in the text form, atomically (see `storage::write_atomic`).
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        crate::storage::write_atomic(path.as_ref(), self.to_text().as_bytes())
+    }
+
+    /// Load a config from `path`. Returns `None` if the file is missing or malformed.
+    pub fn load(path: impl AsRef<Path>) -> Option<Self> {
+        Self::from_text(&std::fs::read_to_string(path).ok()?)
+    }
+
+    /// Save the config as JSON in `storage`'s `slot`.
+    #[cfg(feature = "serde")]
+    pub fn save_to(&self, storage: &Storage, slot: &str) -> Result<(), StorageError> {
+        storage.save_json(slot, self)
+    }
+
+    /// Load a config saved with `save_to`, or `None` if the slot was never saved.
+    #[cfg(feature = "serde")]
+    pub fn load_from(storage: &Storage, slot: &str) -> Result<Option<Self>, StorageError> {
+        storage.load_json(slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_text_round_trip() {
+        let config = ContextConfig {
+            placement: Some(WindowPlacement {
+                monitor: None,
+                x: 10,
+                y: 20,
+                width: 1280,
+                height: 720,
+                maximized: false,
+            }),
+            fullscreen: true,
+            vsync: false,
+            msaa_samples: 4,
+        };
+        assert_eq!(
+            ContextConfig::from_text(&config.to_text()),
+            Some(config.clone())
+        );
+        assert_eq!(ContextConfig::from_text(""), Some(ContextConfig::default()));
+        assert_eq!(ContextConfig::from_text("vsync=maybe"), None);
+
+        let path = std::env::temp_dir().join("libforge-config-test.txt");
+        config.save(&path).unwrap();
+        assert_eq!(ContextConfig::load(&path), Some(config));
+        let _ = std::fs::remove_file(&path);
+    }
+}
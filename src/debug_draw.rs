@@ -0,0 +1,104 @@
+//! Geometry for the debug draw layer (`debug_line`, `debug_rect`, ...).
+//!
+//! Shapes are outlines built from thin quads so colliders and gizmos never hide the
+//! scene underneath.
+
+use crate::debug_font;
+use crate::renderer::{line_to_quad, quad_to_vertices};
+use crate::vertex::Vertex;
+use crate::{Color, Rect};
+
+/// Outline thickness of debug shapes, in the units of the current transform.
+pub(crate) const THICKNESS: f32 = 1.0;
+
+const CIRCLE_SEGMENTS: usize = 32;
+
+pub(crate) fn line(out: &mut Vec<Vertex>, x1: f32, y1: f32, x2: f32, y2: f32, color: Color) {
+    let quad = line_to_quad(x1, y1, x2, y2, THICKNESS);
+    out.extend(quad_to_vertices(quad, color.0));
+}
+
+pub(crate) fn rect(out: &mut Vec<Vertex>, r: Rect, color: Color) {
+    let (x0, y0, x1, y1) = (r.x, r.y, r.x + r.w, r.y + r.h);
+    line(out, x0, y0, x1, y0, color);
+    line(out, x1, y0, x1, y1, color);
+    line(out, x1, y1, x0, y1, color);
+    line(out, x0, y1, x0, y0, color);
+}
+
+pub(crate) fn circle(out: &mut Vec<Vertex>, x: f32, y: f32, radius: f32, color: Color) {
+    let step = std::f32::consts::TAU / CIRCLE_SEGMENTS as f32;
+    let point = |i: usize| {
+        let a = i as f32 * step;
+        (x + a.cos() * radius, y + a.sin() * radius)
+    };
+    for i in 0..CIRCLE_SEGMENTS {
+        let (ax, ay) = point(i);
+        let (bx, by) = point(i + 1);
+        line(out, ax, ay, bx, by, color);
+    }
+}
+
+/// Line from `(x1, y1)` to `(x2, y2)` with an open arrowhead at the end.
+pub(crate) fn arrow(out: &mut Vec<Vertex>, x1: f32, y1: f32, x2: f32, y2: f32, color: Color) {
+    line(out, x1, y1, x2, y2, color);
+
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let len = (dx * dx + dy * dy).sqrt();
+    if len <= f32::EPSILON {
+        return;
+    }
+    let head = (len * 0.25).min(10.0);
+    let (ux, uy) = (dx / len, dy / len);
+    // Two barbs at +-30 degrees from the reversed direction.
+    let (s, c) = (0.5f32, 0.866_025_4f32);
+    for sign in [1.0, -1.0] {
+        let bx = -ux * c - sign * -uy * s;
+        let by = -uy * c - sign * ux * s;
+        line(out, x2, y2, x2 + bx * head, y2 + by * head, color);
+    }
+}
+
+/// Text in the built-in pixel font; `size` is the glyph height.
+pub(crate) fn text(out: &mut Vec<Vertex>, text: &str, x: f32, y: f32, size: f32, color: Color) {
+    let pixel = size / debug_font::GLYPH_H as f32;
+    debug_font::for_each_rect(text, x, y, pixel, |r| {
+        let quad = [
+            [r.x, r.y],
+            [r.x + r.w, r.y],
+            [r.x + r.w, r.y + r.h],
+            [r.x, r.y + r.h],
+        ];
+        out.extend(quad_to_vertices(quad, color.0));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrow_head_points_back_along_the_shaft() {
+        let mut verts = Vec::new();
+        arrow(&mut verts, 0.0, 0.0, 100.0, 0.0, Color::WHITE);
+        // Shaft plus two barbs, six vertices each.
+        assert_eq!(verts.len(), 18);
+        for v in &verts[6..] {
+            assert!(v.pos[0] <= 100.0 + THICKNESS);
+            assert!(v.pos[0] >= 100.0 - 10.0 - THICKNESS);
+        }
+
+        let mut outline = Vec::new();
+        rect(
+            &mut outline,
+            Rect {
+                x: 0.0,
+                y: 0.0,
+                w: 10.0,
+                h: 10.0,
+            },
+            Color::WHITE,
+        );
+        assert_eq!(outline.len(), 24);
+    }
+}
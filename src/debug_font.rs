@@ -0,0 +1,214 @@
+//! Tiny built-in 3x5 pixel font for debug text, drawn as plain rectangles.
+//!
+//! Covers digits, A-Z (lowercase is drawn as uppercase) and a little punctuation, which is
+//! enough for stats readouts without loading a font asset.
+
+use crate::Rect;
+
+/// Glyph cell size in font pixels; characters advance by `GLYPH_W + 1`.
+pub(crate) const GLYPH_W: u32 = 3;
+pub(crate) const GLYPH_H: u32 = 5;
+
+/// Rows of a glyph, top to bottom; bit 2 is the left column.
+const fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        ' ' => [0; 5],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '(' => [0b010, 0b100, 0b100, 0b100, 0b010],
+        ')' => [0b010, 0b001, 0b001, 0b001, 0b010],
+        _ => [0b111, 0b001, 0b010, 0b000, 0b010], // '?'
+    }
+}
+
+/// Horizontal runs of lit pixels in one glyph, as `(row, col, len)`.
+#[derive(Clone, Copy)]
+struct GlyphRuns {
+    runs: [(u8, u8, u8); (GLYPH_W * GLYPH_H) as usize],
+    len: usize,
+}
+
+impl GlyphRuns {
+    const fn new(rows: [u8; 5]) -> Self {
+        let mut out = GlyphRuns {
+            runs: [(0, 0, 0); (GLYPH_W * GLYPH_H) as usize],
+            len: 0,
+        };
+        let mut row = 0;
+        while row < GLYPH_H as usize {
+            let bits = rows[row];
+            let mut col = 0;
+            while col < GLYPH_W {
+                if bits & (1 << (GLYPH_W - 1 - col)) == 0 {
+                    col += 1;
+                    continue;
+                }
+                let start = col;
+                while col < GLYPH_W && bits & (1 << (GLYPH_W - 1 - col)) != 0 {
+                    col += 1;
+                }
+                out.runs[out.len] = (row as u8, start as u8, (col - start) as u8);
+                out.len += 1;
+            }
+            row += 1;
+        }
+        out
+    }
+
+    fn emit(&self, pen_x: f32, pen_y: f32, pixel: f32, f: &mut impl FnMut(Rect)) {
+        for &(row, col, len) in &self.runs[..self.len] {
+            f(Rect {
+                x: pen_x + col as f32 * pixel,
+                y: pen_y + row as f32 * pixel,
+                w: len as f32 * pixel,
+                h: pixel,
+            });
+        }
+    }
+}
+
+/// Runs for `0`-`9` and `-`, precomputed for `for_each_number_rect`.
+static NUMBER_RUNS: [GlyphRuns; 11] = [
+    GlyphRuns::new(glyph('0')),
+    GlyphRuns::new(glyph('1')),
+    GlyphRuns::new(glyph('2')),
+    GlyphRuns::new(glyph('3')),
+    GlyphRuns::new(glyph('4')),
+    GlyphRuns::new(glyph('5')),
+    GlyphRuns::new(glyph('6')),
+    GlyphRuns::new(glyph('7')),
+    GlyphRuns::new(glyph('8')),
+    GlyphRuns::new(glyph('9')),
+    GlyphRuns::new(glyph('-')),
+];
+
+const ADVANCE: u32 = GLYPH_W + 1;
+
+/// Call `f` with one rectangle per horizontal run of lit pixels in `text`.
+///
+/// `(x, y)` is the top-left corner and `pixel` the size of one font pixel. `\n` starts a
+/// new line.
+pub(crate) fn for_each_rect(text: &str, x: f32, y: f32, pixel: f32, mut f: impl FnMut(Rect)) {
+    let mut pen_x = x;
+    let mut pen_y = y;
+    for c in text.chars() {
+        if c == '\n' {
+            pen_x = x;
+            pen_y += (GLYPH_H + 2) as f32 * pixel;
+            continue;
+        }
+        GlyphRuns::new(glyph(c)).emit(pen_x, pen_y, pixel, &mut f);
+        pen_x += ADVANCE as f32 * pixel;
+    }
+}
+
+/// Like `for_each_rect` for the decimal form of `value`, without formatting or allocating.
+pub(crate) fn for_each_number_rect(
+    value: i64,
+    x: f32,
+    y: f32,
+    pixel: f32,
+    mut f: impl FnMut(Rect),
+) {
+    // Digits are produced least-significant first into a stack buffer.
+    let mut digits = [0u8; 20];
+    let mut n = value.unsigned_abs();
+    let mut len = 0;
+    loop {
+        digits[len] = (n % 10) as u8;
+        len += 1;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+
+    let mut pen_x = x;
+    if value < 0 {
+        NUMBER_RUNS[10].emit(pen_x, y, pixel, &mut f);
+        pen_x += ADVANCE as f32 * pixel;
+    }
+    for &d in digits[..len].iter().rev() {
+        NUMBER_RUNS[d as usize].emit(pen_x, y, pixel, &mut f);
+        pen_x += ADVANCE as f32 * pixel;
+    }
+}
+
+/// Width of the longest line of `text` at the given pixel size.
+pub(crate) fn text_width(text: &str, pixel: f32) -> f32 {
+    let chars = text.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+    if chars == 0 {
+        return 0.0;
+    }
+    (chars as u32 * ADVANCE - 1) as f32 * pixel
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyph_rows_merge_into_runs() {
+        let mut rects = Vec::new();
+        for_each_rect("1", 10.0, 20.0, 2.0, |r| rects.push(r));
+        // '1': one pixel per row except the solid bottom row and the "110" second row.
+        assert_eq!(rects.len(), 5);
+        let bottom = rects.last().unwrap();
+        assert_eq!(
+            (bottom.x, bottom.y, bottom.w, bottom.h),
+            (10.0, 28.0, 6.0, 2.0)
+        );
+
+        assert_eq!(text_width("FPS 60", 1.0), 23.0);
+
+        // The number fast path matches the general text path.
+        let mut text = Vec::new();
+        let mut number = Vec::new();
+        for_each_rect("-9040", 0.0, 0.0, 1.0, |r| text.push((r.x, r.y, r.w)));
+        for_each_number_rect(-9040, 0.0, 0.0, 1.0, |r| number.push((r.x, r.y, r.w)));
+        assert_eq!(text, number);
+        assert_eq!(text_width("", 1.0), 0.0);
+    }
+}
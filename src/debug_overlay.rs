@@ -0,0 +1,173 @@
+use crate::debug_font;
+use crate::renderer::FrameStats;
+use crate::{Color, Rect};
+use std::collections::VecDeque;
+
+/// Number of frames shown in the frame-time graph.
+const HISTORY_LEN: usize = 120;
+
+const PIXEL: f32 = 2.0;
+const LINE_H: f32 = (debug_font::GLYPH_H + 2) as f32 * PIXEL;
+const PADDING: f32 = 6.0;
+const GRAPH_H: f32 = 40.0;
+// Frame time that fills the graph height (anything slower is clipped).
+const GRAPH_MAX_DT: f32 = 1.0 / 20.0;
+
+const PANEL: Color = Color([0.0, 0.0, 0.0, 0.7]);
+const TEXT: Color = Color([1.0, 1.0, 1.0, 1.0]);
+const GOOD: Color = Color([0.3, 0.9, 0.3, 1.0]);
+const SLOW: Color = Color([0.95, 0.8, 0.2, 1.0]);
+const BAD: Color = Color([0.95, 0.25, 0.2, 1.0]);
+const TARGET_LINE: Color = Color([1.0, 1.0, 1.0, 0.35]);
+
+/// Rolling window of recent frame times, in seconds.
+#[derive(Default)]
+pub(crate) struct FrameHistory {
+    samples: VecDeque<f32>,
+}
+
+impl FrameHistory {
+    pub(crate) fn push(&mut self, dt: f32) {
+        if self.samples.len() == HISTORY_LEN {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(dt);
+    }
+
+    pub(crate) fn average(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        self.samples.iter().sum::<f32>() / self.samples.len() as f32
+    }
+}
+
+/// Everything the overlay shows for one frame.
+pub(crate) struct OverlayData<'a> {
+    pub history: &'a FrameHistory,
+    pub stats: FrameStats,
+    pub latency: Option<f32>,
+}
+
+fn frame_color(dt: f32) -> Color {
+    if dt <= 1.0 / 55.0 {
+        GOOD
+    } else if dt <= 1.0 / 28.0 {
+        SLOW
+    } else {
+        BAD
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= MB {
+        format!("{:.1}MB", bytes as f64 / MB)
+    } else {
+        format!("{}KB", bytes.div_ceil(1024))
+    }
+}
+
+/// Lay out the overlay panel with its top-left corner at `(x, y)` as colored rectangles.
+pub(crate) fn layout(data: &OverlayData, x: f32, y: f32) -> Vec<(Rect, Color)> {
+    let avg = data.history.average();
+    let fps = if avg > 0.0 { 1.0 / avg } else { 0.0 };
+
+    let mut lines = vec![
+        format!("FPS {fps:.0}  {:.2}MS", avg * 1000.0),
+        format!("VERTS {}", data.stats.vertices),
+        format!("DRAWS {}", data.stats.draw_calls),
+        format!(
+            "TEX {} {}",
+            data.stats.textures,
+            format_bytes(data.stats.texture_bytes)
+        ),
+    ];
+    if let Some(latency) = data.latency {
+        lines.push(format!("LATENCY {:.1}MS", latency * 1000.0));
+    }
+
+    let graph_w = HISTORY_LEN as f32;
+    let text_w = lines
+        .iter()
+        .map(|l| debug_font::text_width(l, PIXEL))
+        .fold(graph_w, f32::max);
+    let panel_h = PADDING * 3.0 + GRAPH_H + lines.len() as f32 * LINE_H;
+
+    let mut rects = vec![(
+        Rect {
+            x,
+            y,
+            w: text_w + PADDING * 2.0,
+            h: panel_h,
+        },
+        PANEL,
+    )];
+
+    // First line, then the graph, then the counters.
+    let mut pen_y = y + PADDING;
+    let push_text = |rects: &mut Vec<(Rect, Color)>, text: &str, pen_y: f32| {
+        debug_font::for_each_rect(text, x + PADDING, pen_y, PIXEL, |r| rects.push((r, TEXT)));
+    };
+    push_text(&mut rects, &lines[0], pen_y);
+    pen_y += LINE_H + PADDING;
+
+    let graph_bottom = pen_y + GRAPH_H;
+    for (i, &dt) in data.history.samples.iter().enumerate() {
+        let h = (dt / GRAPH_MAX_DT).min(1.0) * GRAPH_H;
+        rects.push((
+            Rect {
+                x: x + PADDING + i as f32,
+                y: graph_bottom - h,
+                w: 1.0,
+                h,
+            },
+            frame_color(dt),
+        ));
+    }
+    // 60 FPS reference line.
+    rects.push((
+        Rect {
+            x: x + PADDING,
+            y: graph_bottom - (1.0 / 60.0) / GRAPH_MAX_DT * GRAPH_H,
+            w: graph_w,
+            h: 1.0,
+        },
+        TARGET_LINE,
+    ));
+    pen_y = graph_bottom + PADDING;
+
+    for line in &lines[1..] {
+        push_text(&mut rects, line, pen_y);
+        pen_y += LINE_H;
+    }
+
+    rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_is_bounded_and_layout_fits_panel() {
+        let mut history = FrameHistory::default();
+        for i in 0..200 {
+            history.push(if i % 2 == 0 { 0.01 } else { 0.03 });
+        }
+        assert_eq!(history.samples.len(), HISTORY_LEN);
+        assert!((history.average() - 0.02).abs() < 1e-6);
+
+        let data = OverlayData {
+            history: &history,
+            stats: FrameStats::default(),
+            latency: Some(0.012),
+        };
+        let rects = layout(&data, 8.0, 8.0);
+        let (panel, _) = rects[0];
+        for (r, _) in &rects[1..] {
+            assert!(r.x >= panel.x && r.x + r.w <= panel.x + panel.w + 1e-3);
+            assert!(r.y >= panel.y && r.y + r.h <= panel.y + panel.h + 1e-3);
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use crate::renderer::{TextureId, circle_to_vertices, line_to_quad, quad_to_vertices};
+use crate::vertex::Vertex;
+use crate::{Color, Rect};
+use glam::Mat4;
+
+/// Handle to a shape stored in a `DrawList`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct ShapeId(pub usize);
+
+struct Shape {
+    // Vertices as authored (before the shape transform).
+    local: Vec<Vertex>,
+    transform: Mat4,
+    // Offset of this shape's vertices inside `DrawList::vertices`.
+    start: usize,
+}
+
+/// A texture draw recorded into a `DrawList`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ListSprite {
+    pub(crate) tex: TextureId,
+    // Source region in pixels, resolved to UVs at submission; `None` is the whole texture.
+    pub(crate) src: Option<Rect>,
+    pub(crate) dst: Rect,
+    pub(crate) tint: Color,
+    // Number of shape vertices recorded before this sprite, which keeps draw order.
+    pub(crate) at: usize,
+}
+
+/// A retained list of shapes and sprites that is tessellated once and reused every frame.
+///
+/// Shapes keep a fixed vertex range, so `set_transform`/`set_color` rewrite only that
+/// shape's vertices in place. Mostly-static scenes with a few moving elements skip
+/// re-tessellation entirely. Submit with `ctx.draw_list(&list)`.
+///
+/// Building a list needs no renderer access, so lists can be filled on worker threads
+/// (e.g. one per world chunk with rayon), combined with `append`, and handed to
+/// `ctx.submit_draw_list(list)`, which draws everything in recording order.
+#[derive(Default)]
+pub struct DrawList {
+    shapes: Vec<Shape>,
+    vertices: Vec<Vertex>,
+    sprites: Vec<ListSprite>,
+}
+
+impl DrawList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a filled rectangle.
+    pub fn add_rect(&mut self, rect: Rect, color: Color) -> ShapeId {
+        let quad = [
+            [rect.x, rect.y],
+            [rect.x + rect.w, rect.y],
+            [rect.x + rect.w, rect.y + rect.h],
+            [rect.x, rect.y + rect.h],
+        ];
+        self.add_vertices(quad_to_vertices(quad, color.0))
+    }
+
+    /// Add a thick line.
+    pub fn add_line(
+        &mut self,
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        thickness: f32,
+        color: Color,
+    ) -> ShapeId {
+        let quad = line_to_quad(x1, y1, x2, y2, thickness);
+        self.add_vertices(quad_to_vertices(quad, color.0))
+    }
+
+    /// Add a filled circle.
+    pub fn add_circle(
+        &mut self,
+        x: f32,
+        y: f32,
+        radius: f32,
+        segments: usize,
+        color: Color,
+    ) -> ShapeId {
+        self.add_vertices(circle_to_vertices(x, y, radius, segments, color.0))
+    }
+
+    /// Add a shape from raw triangle-list vertices.
+    pub fn add_vertices(&mut self, local: Vec<Vertex>) -> ShapeId {
+        let id = ShapeId(self.shapes.len());
+        let start = self.vertices.len();
+        self.vertices.extend_from_slice(&local);
+        self.shapes.push(Shape {
+            local,
+            transform: Mat4::IDENTITY,
+            start,
+        });
+        id
+    }
+
+    /// Add a texture drawn into `dst`.
+    pub fn add_texture(&mut self, tex: TextureId, dst: Rect, tint: Color) {
+        self.add_sprite(tex, None, dst, tint);
+    }
+
+    /// Add the `src` region (in pixels) of a texture drawn into `dst`.
+    pub fn add_subtexture(&mut self, tex: TextureId, src: Rect, dst: Rect, tint: Color) {
+        self.add_sprite(tex, Some(src), dst, tint);
+    }
+
+    fn add_sprite(&mut self, tex: TextureId, src: Option<Rect>, dst: Rect, tint: Color) {
+        self.sprites.push(ListSprite {
+            tex,
+            src,
+            dst,
+            tint,
+            at: self.vertices.len(),
+        });
+    }
+
+    /// Move everything from `other` to the end of this list, keeping its order.
+    ///
+    /// Shape ids from `other` are offset by this list's previous `len()`.
+    pub fn append(&mut self, other: DrawList) {
+        let base = self.vertices.len();
+        self.vertices.extend(other.vertices);
+        self.shapes
+            .extend(other.shapes.into_iter().map(|mut shape| {
+                shape.start += base;
+                shape
+            }));
+        self.sprites
+            .extend(other.sprites.into_iter().map(|sprite| ListSprite {
+                at: sprite.at + base,
+                ..sprite
+            }));
+    }
+
+    /// Replace a shape's transform, rewriting only its vertices.
+    pub fn set_transform(&mut self, id: ShapeId, transform: Mat4) {
+        let Some(shape) = self.shapes.get_mut(id.0) else {
+            return;
+        };
+        shape.transform = transform;
+        let dst = &mut self.vertices[shape.start..shape.start + shape.local.len()];
+        for (out, v) in dst.iter_mut().zip(&shape.local) {
+            let p = transform * glam::vec4(v.pos[0], v.pos[1], 0.0, 1.0);
+            out.pos = [p.x, p.y];
+        }
+    }
+
+    /// Current transform of a shape (identity if the id is unknown).
+    pub fn transform(&self, id: ShapeId) -> Mat4 {
+        self.shapes
+            .get(id.0)
+            .map(|s| s.transform)
+            .unwrap_or(Mat4::IDENTITY)
+    }
+
+    /// Replace a shape's color, rewriting only its vertices.
+    pub fn set_color(&mut self, id: ShapeId, color: Color) {
+        let Some(shape) = self.shapes.get_mut(id.0) else {
+            return;
+        };
+        for v in &mut shape.local {
+            v.color = color.0;
+        }
+        for v in &mut self.vertices[shape.start..shape.start + shape.local.len()] {
+            v.color = color.0;
+        }
+    }
+
+    /// Number of shapes in the list.
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    /// True when the list has neither shapes nor sprites.
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty() && self.sprites.is_empty()
+    }
+
+    /// Remove all shapes and sprites.
+    pub fn clear(&mut self) {
+        self.shapes.clear();
+        self.vertices.clear();
+        self.sprites.clear();
+    }
+
+    /// The transformed vertices of every shape, ready for submission.
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    /// Sprites in recording order; each is drawn after the first `at` shape vertices.
+    pub(crate) fn sprites(&self) -> &[ListSprite] {
+        &self.sprites
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_transform_and_color_update_only_that_shape() {
+        let mut list = DrawList::new();
+        let a = list.add_rect(
+            Rect {
+                x: 0.0,
+                y: 0.0,
+                w: 10.0,
+                h: 10.0,
+            },
+            Color::WHITE,
+        );
+        let b = list.add_rect(
+            Rect {
+                x: 0.0,
+                y: 0.0,
+                w: 10.0,
+                h: 10.0,
+            },
+            Color::WHITE,
+        );
+        assert_eq!(list.vertices().len(), 12);
+
+        list.set_transform(b, Mat4::from_translation(glam::vec3(5.0, 0.0, 0.0)));
+        list.set_color(b, Color::BLACK);
+
+        assert_eq!(list.vertices()[0].pos, [0.0, 0.0]);
+        assert_eq!(list.vertices()[6].pos, [5.0, 0.0]);
+        assert_eq!(list.vertices()[0].color, Color::WHITE.0);
+        assert_eq!(list.vertices()[6].color, Color::BLACK.0);
+        assert_eq!(list.transform(a), Mat4::IDENTITY);
+    }
+
+    #[test]
+    fn lists_built_on_threads_append_in_order() {
+        fn assert_send<T: Send>() {}
+        assert_send::<DrawList>();
+
+        let tile = |i: usize| Rect {
+            x: i as f32 * 16.0,
+            y: 0.0,
+            w: 16.0,
+            h: 16.0,
+        };
+        let chunks: Vec<DrawList> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..3)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        let mut list = DrawList::new();
+                        list.add_rect(tile(chunk), Color::BLACK);
+                        list.add_texture(TextureId(chunk as u32), tile(chunk), Color::WHITE);
+                        list
+                    })
+                })
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+
+        let mut list = DrawList::new();
+        for chunk in chunks {
+            list.append(chunk);
+        }
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.vertices()[6].pos, [16.0, 0.0]);
+        let order: Vec<(u32, usize)> = list.sprites().iter().map(|s| (s.tex.0, s.at)).collect();
+        assert_eq!(order, [(0, 6), (1, 12), (2, 18)]);
+    }
+}
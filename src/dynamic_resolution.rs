@@ -0,0 +1,102 @@
+/// Lowest render scale, for both `set_render_scale` and dynamic resolution.
+pub(crate) const MIN_RENDER_SCALE: f32 = 0.5;
+
+// Weight of the newest frame in the smoothed frame time.
+const SMOOTHING: f32 = 0.1;
+// Frames to wait after a change before judging the new scale.
+const SETTLE_FRAMES: u32 = 30;
+// Consecutive frames within budget before trying a higher scale again.
+const PROBE_FRAMES: u32 = 120;
+const STEP_UP: f32 = 0.05;
+
+/// Picks a render scale that holds a target frame time.
+///
+/// Frames over budget scale down at once (pixel cost goes with the square of the scale).
+/// Under vsync a frame within budget looks the same at any scale, so after a couple of
+/// seconds on budget the scaler probes one step up, and steps back if that misses.
+#[derive(Clone, Debug)]
+pub(crate) struct ResolutionScaler {
+    target: f32,
+    scale: f32,
+    smoothed: f32,
+    settle: u32,
+    on_budget: u32,
+}
+
+impl ResolutionScaler {
+    /// Hold frames to `target` seconds, starting from `scale`.
+    pub(crate) fn new(target: f32, scale: f32) -> Self {
+        Self {
+            target,
+            scale,
+            smoothed: target,
+            settle: SETTLE_FRAMES,
+            on_budget: 0,
+        }
+    }
+
+    pub(crate) fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Feed the last frame's duration in seconds; returns the scale for the next frame.
+    pub(crate) fn update(&mut self, frame_time: f32) -> f32 {
+        self.smoothed += (frame_time - self.smoothed) * SMOOTHING;
+        if self.settle > 0 {
+            self.settle -= 1;
+            return self.scale;
+        }
+
+        let mut next = self.scale;
+        if self.smoothed > self.target * 1.1 {
+            next = self.scale * (self.target / self.smoothed).sqrt();
+            self.on_budget = 0;
+        } else if self.smoothed <= self.target * 1.02 {
+            self.on_budget += 1;
+            if self.on_budget >= PROBE_FRAMES {
+                self.on_budget = 0;
+                next = self.scale + STEP_UP;
+            }
+        } else {
+            self.on_budget = 0;
+        }
+
+        let next = next.clamp(MIN_RENDER_SCALE, 1.0);
+        if next != self.scale {
+            self.scale = next;
+            self.smoothed = self.target;
+            self.settle = SETTLE_FRAMES;
+        }
+        self.scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(scaler: &mut ResolutionScaler, frames: u32, frame_time: f32) -> f32 {
+        (0..frames).fold(0.0, |_, _| scaler.update(frame_time))
+    }
+
+    #[test]
+    fn scales_down_under_load_and_back_up_when_fast() {
+        let target = 1.0 / 60.0;
+        let mut scaler = ResolutionScaler::new(target, 1.0);
+
+        // Running at 30 FPS: drops until the minimum.
+        let loaded = run(&mut scaler, 200, 1.0 / 30.0);
+        assert_eq!(loaded, MIN_RENDER_SCALE);
+
+        // On budget again: creeps back up to full resolution.
+        let recovered = run(&mut scaler, 3000, target);
+        assert_eq!(recovered, 1.0);
+    }
+
+    #[test]
+    fn small_overshoots_are_ignored() {
+        let target = 1.0 / 60.0;
+        let mut scaler = ResolutionScaler::new(target, 1.0);
+        assert_eq!(run(&mut scaler, 600, target * 1.05), 1.0);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::renderer::TextureId;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -7,6 +8,15 @@ pub enum LibforgeError {
 
     #[error("platform error: {0}")]
     Platform(String),
+
+    #[error("recording error: {0}")]
+    Recording(String),
+
+    #[error("font error: {0}")]
+    Font(String),
+
+    #[error("audio capture error: {0}")]
+    Capture(String),
 }
 
 #[derive(Error, Debug)]
@@ -19,4 +29,13 @@ pub enum RendererError {
 
     #[error("internal error: {0}")]
     Internal(String),
+
+    #[error("unbalanced frame state: {0}")]
+    UnbalancedState(String),
+
+    #[error("texture {0:?} is not loaded")]
+    MissingTexture(TextureId),
+
+    #[error("shader error: {0}")]
+    Shader(String),
 }
@@ -0,0 +1,399 @@
+use crate::Camera2D;
+use std::collections::BTreeMap;
+use winit::event::TouchPhase;
+
+/// Two-finger gesture deltas accumulated over one frame.
+///
+/// Filled from trackpad gesture events (macOS/iOS) and from raw two-finger touch input on
+/// every platform. Read with `ctx.gesture()`; resets at `begin_drawing()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gesture {
+    /// Multiplicative pinch factor for this frame (`> 1.0` = fingers spreading apart).
+    pub zoom: f32,
+    /// Two-finger pan in window pixels.
+    pub pan: (f32, f32),
+    /// Rotation in radians, positive = counter-clockwise on screen.
+    pub rotation: f32,
+}
+
+impl Gesture {
+    /// No gesture this frame.
+    pub const NONE: Gesture = Gesture {
+        zoom: 1.0,
+        pan: (0.0, 0.0),
+        rotation: 0.0,
+    };
+
+    pub fn is_active(&self) -> bool {
+        *self != Self::NONE
+    }
+
+    /// Move `camera` so the world follows the fingers: pinch zooms, pan drags, twist rotates.
+    pub fn apply_to_camera(&self, camera: &mut Camera2D) {
+        if self.zoom.is_finite() && self.zoom > 0.0 {
+            camera.magnify(self.zoom);
+        }
+
+        // Screen delta -> world delta is the inverse of the view's rotate-then-scale.
+        let (s, c) = camera.rotation.sin_cos();
+        let (dx, dy) = self.pan;
+        let units_per_pixel = 1.0 / camera.scale();
+        camera.x -= (dx * c - dy * s) * units_per_pixel;
+        camera.y -= (dx * s + dy * c) * units_per_pixel;
+
+        camera.rotation -= self.rotation;
+    }
+}
+
+impl Default for Gesture {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// Direction of a swipe, by its dominant axis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// A single-finger gesture recognized this frame, in window pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TouchGesture {
+    /// A short touch that didn't move.
+    Tap((f32, f32)),
+    /// A tap soon after and near the previous one; reported instead of a second `Tap`.
+    DoubleTap((f32, f32)),
+    /// A touch held still for `GestureThresholds::long_press`; it won't also tap or swipe.
+    LongPress((f32, f32)),
+    Swipe {
+        direction: SwipeDirection,
+        start: (f32, f32),
+        end: (f32, f32),
+    },
+}
+
+/// Timing and distance limits for `TouchGesture` recognition.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GestureThresholds {
+    /// Movement (pixels) a touch may make and still tap or long-press.
+    pub tap_slop: f32,
+    /// Longest touch (seconds) that still counts as a tap.
+    pub tap_time: f32,
+    /// Seconds and pixels between taps for a double tap.
+    pub double_tap_time: f32,
+    pub double_tap_distance: f32,
+    /// Seconds a still touch is held before it long-presses.
+    pub long_press: f32,
+    /// Minimum distance (pixels) and maximum duration (seconds) of a swipe.
+    pub swipe_distance: f32,
+    pub swipe_time: f32,
+}
+
+impl Default for GestureThresholds {
+    fn default() -> Self {
+        Self {
+            tap_slop: 10.0,
+            tap_time: 0.3,
+            double_tap_time: 0.3,
+            double_tap_distance: 40.0,
+            long_press: 0.5,
+            swipe_distance: 50.0,
+            swipe_time: 0.5,
+        }
+    }
+}
+
+/// The finger of a touch sequence that started with no other finger down.
+#[derive(Debug, Clone, Copy)]
+struct SingleTouch {
+    id: u64,
+    start: (f32, f32),
+    start_time: f64,
+    position: (f32, f32),
+    // Moved beyond the tap slop at some point.
+    moved: bool,
+    // A second finger joined: this is a two-finger gesture, not a tap or swipe.
+    multi: bool,
+    long_pressed: bool,
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    (b.0 - a.0).hypot(b.1 - a.1)
+}
+
+/// Turns gesture and touch events into per-frame `Gesture` deltas and `TouchGesture`s.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct GestureState {
+    // Active touch points by id; the two lowest ids drive the gesture.
+    touches: BTreeMap<u64, (f32, f32)>,
+    frame: Gesture,
+    pub thresholds: GestureThresholds,
+    // Frame timestamp from `begin_frame`, in seconds.
+    time: f64,
+    single: Option<SingleTouch>,
+    // Time and position of the last tap, for double taps.
+    last_tap: Option<(f64, (f32, f32))>,
+    recognized: Vec<TouchGesture>,
+}
+
+/// Centroid, finger distance and angle (y-down) of a two-finger pair.
+fn pair_metrics(a: (f32, f32), b: (f32, f32)) -> ((f32, f32), f32, f32) {
+    let center = ((a.0 + b.0) * 0.5, (a.1 + b.1) * 0.5);
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    (center, (dx * dx + dy * dy).sqrt(), dy.atan2(dx))
+}
+
+impl GestureState {
+    /// Start a frame at `time` seconds; reports long presses that matured since the last.
+    pub(crate) fn begin_frame(&mut self, time: f64) {
+        self.frame = Gesture::NONE;
+        self.time = time;
+        self.recognized.clear();
+        let long_press = self.thresholds.long_press as f64;
+        if let Some(touch) = &mut self.single
+            && !(touch.moved || touch.multi || touch.long_pressed)
+            && time - touch.start_time >= long_press
+        {
+            touch.long_pressed = true;
+            self.recognized
+                .push(TouchGesture::LongPress(touch.position));
+        }
+    }
+
+    pub(crate) fn gesture(&self) -> Gesture {
+        self.frame
+    }
+
+    pub(crate) fn touch_gestures(&self) -> &[TouchGesture] {
+        &self.recognized
+    }
+
+    /// Tap, double tap or swipe for a single finger lifted now.
+    fn finish_single(&mut self, touch: SingleTouch) {
+        if touch.multi || touch.long_pressed {
+            return;
+        }
+        let t = self.thresholds;
+        let duration = (self.time - touch.start_time) as f32;
+        if !touch.moved && duration <= t.tap_time {
+            let double = self.last_tap.is_some_and(|(time, at)| {
+                (self.time - time) as f32 <= t.double_tap_time
+                    && distance(at, touch.position) <= t.double_tap_distance
+            });
+            if double {
+                self.last_tap = None;
+                self.recognized
+                    .push(TouchGesture::DoubleTap(touch.position));
+            } else {
+                self.last_tap = Some((self.time, touch.position));
+                self.recognized.push(TouchGesture::Tap(touch.position));
+            }
+            return;
+        }
+        let (dx, dy) = (
+            touch.position.0 - touch.start.0,
+            touch.position.1 - touch.start.1,
+        );
+        if distance(touch.start, touch.position) >= t.swipe_distance && duration <= t.swipe_time {
+            let direction = match (dx.abs() >= dy.abs(), dx >= 0.0, dy >= 0.0) {
+                (true, true, _) => SwipeDirection::Right,
+                (true, false, _) => SwipeDirection::Left,
+                (false, _, true) => SwipeDirection::Down,
+                (false, _, false) => SwipeDirection::Up,
+            };
+            self.recognized.push(TouchGesture::Swipe {
+                direction,
+                start: touch.start,
+                end: touch.position,
+            });
+        }
+    }
+
+    fn pair(&self) -> Option<((f32, f32), (f32, f32))> {
+        let mut it = self.touches.values();
+        Some((*it.next()?, *it.next()?))
+    }
+
+    pub(crate) fn handle_touch(&mut self, id: u64, phase: TouchPhase, position: (f32, f32)) {
+        match phase {
+            TouchPhase::Started => {
+                match &mut self.single {
+                    Some(touch) => touch.multi = true,
+                    None if self.touches.is_empty() => {
+                        self.single = Some(SingleTouch {
+                            id,
+                            start: position,
+                            start_time: self.time,
+                            position,
+                            moved: false,
+                            multi: false,
+                            long_pressed: false,
+                        });
+                    }
+                    None => {}
+                }
+                self.touches.insert(id, position);
+            }
+            TouchPhase::Moved => {
+                let before = self.pair();
+                if let Some(p) = self.touches.get_mut(&id) {
+                    *p = position;
+                }
+                let slop = self.thresholds.tap_slop;
+                if let Some(touch) = &mut self.single
+                    && touch.id == id
+                {
+                    touch.position = position;
+                    touch.moved |= distance(touch.start, position) > slop;
+                }
+                if let (Some((a0, b0)), Some((a1, b1))) = (before, self.pair()) {
+                    let (c0, d0, r0) = pair_metrics(a0, b0);
+                    let (c1, d1, r1) = pair_metrics(a1, b1);
+                    self.frame.pan.0 += c1.0 - c0.0;
+                    self.frame.pan.1 += c1.1 - c0.1;
+                    if d0 > f32::EPSILON {
+                        self.frame.zoom *= d1 / d0;
+                    }
+                    // Wrap to (-pi, pi] so crossing the atan2 seam doesn't spin.
+                    let turn = (r1 - r0 + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU)
+                        - std::f32::consts::PI;
+                    // Screen y points down, so a positive atan2 turn is clockwise.
+                    self.frame.rotation -= turn;
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.touches.remove(&id);
+                // The sequence lasts until the last finger lifts.
+                if self.touches.is_empty()
+                    && let Some(mut touch) = self.single.take()
+                    && phase == TouchPhase::Ended
+                    && touch.id == id
+                {
+                    touch.position = position;
+                    self.finish_single(touch);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn handle_pinch(&mut self, delta: f64) {
+        if delta.is_finite() {
+            self.frame.zoom *= (1.0 + delta as f32).max(0.01);
+        }
+    }
+
+    pub(crate) fn handle_pan(&mut self, delta: (f32, f32)) {
+        self.frame.pan.0 += delta.0;
+        self.frame.pan.1 += delta.1;
+    }
+
+    pub(crate) fn handle_rotation(&mut self, degrees: f32) {
+        self.frame.rotation += degrees.to_radians();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_finger_touch_produces_pan_zoom_and_rotation() {
+        let mut state = GestureState::default();
+        state.handle_touch(1, TouchPhase::Started, (100.0, 100.0));
+        state.handle_touch(2, TouchPhase::Started, (200.0, 100.0));
+
+        // Spread to double the distance around the same center.
+        state.handle_touch(1, TouchPhase::Moved, (50.0, 100.0));
+        state.handle_touch(2, TouchPhase::Moved, (250.0, 100.0));
+        let g = state.gesture();
+        assert!((g.zoom - 2.0).abs() < 1e-5);
+        assert!(g.pan.0.abs() < 1e-5 && g.pan.1.abs() < 1e-5);
+
+        // Second finger swings up (y-down screen): counter-clockwise.
+        state.begin_frame(0.0);
+        state.handle_touch(2, TouchPhase::Moved, (50.0, -100.0));
+        assert!((state.gesture().rotation - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+
+        // One finger alone is not a gesture.
+        state.begin_frame(0.0);
+        state.handle_touch(2, TouchPhase::Ended, (50.0, -100.0));
+        state.handle_touch(1, TouchPhase::Moved, (0.0, 0.0));
+        assert!(!state.gesture().is_active());
+    }
+
+    #[test]
+    fn single_touches_become_taps_long_presses_and_swipes() {
+        let mut state = GestureState::default();
+        let tap = |state: &mut GestureState, start: f64, at| {
+            state.begin_frame(start);
+            state.handle_touch(1, TouchPhase::Started, at);
+            state.begin_frame(start + 0.1);
+            state.handle_touch(1, TouchPhase::Ended, at);
+            state.touch_gestures().to_vec()
+        };
+        assert_eq!(
+            tap(&mut state, 0.0, (10.0, 10.0)),
+            [TouchGesture::Tap((10.0, 10.0))]
+        );
+        assert_eq!(
+            tap(&mut state, 0.2, (15.0, 10.0)),
+            [TouchGesture::DoubleTap((15.0, 10.0))]
+        );
+        assert_eq!(
+            tap(&mut state, 2.0, (15.0, 10.0)),
+            [TouchGesture::Tap((15.0, 10.0))]
+        );
+
+        // Held still: a long press on the frame it matures, and no tap when lifted.
+        state.begin_frame(3.0);
+        state.handle_touch(1, TouchPhase::Started, (5.0, 5.0));
+        state.begin_frame(3.6);
+        assert_eq!(
+            state.touch_gestures(),
+            [TouchGesture::LongPress((5.0, 5.0))]
+        );
+        state.begin_frame(3.7);
+        state.handle_touch(1, TouchPhase::Ended, (5.0, 5.0));
+        assert!(state.touch_gestures().is_empty());
+
+        // A quick upward flick.
+        state.begin_frame(4.0);
+        state.handle_touch(1, TouchPhase::Started, (100.0, 300.0));
+        state.handle_touch(1, TouchPhase::Moved, (110.0, 200.0));
+        state.begin_frame(4.2);
+        state.handle_touch(1, TouchPhase::Ended, (110.0, 150.0));
+        assert!(matches!(
+            state.touch_gestures(),
+            [TouchGesture::Swipe {
+                direction: SwipeDirection::Up,
+                ..
+            }]
+        ));
+
+        // Two-finger touches are pinches, never taps.
+        state.begin_frame(5.0);
+        state.handle_touch(1, TouchPhase::Started, (0.0, 0.0));
+        state.handle_touch(2, TouchPhase::Started, (50.0, 0.0));
+        state.handle_touch(2, TouchPhase::Ended, (50.0, 0.0));
+        state.handle_touch(1, TouchPhase::Ended, (0.0, 0.0));
+        assert!(state.touch_gestures().is_empty());
+    }
+
+    #[test]
+    fn pan_moves_camera_against_finger_motion() {
+        let mut camera = Camera2D::new();
+        camera.zoom = 2.0;
+        let g = Gesture {
+            pan: (10.0, 0.0),
+            zoom: 2.0,
+            ..Gesture::NONE
+        };
+        g.apply_to_camera(&mut camera);
+        assert_eq!(camera.zoom, 1.0);
+        assert_eq!((camera.x, camera.y), (-10.0, 0.0));
+    }
+}
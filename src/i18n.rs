@@ -0,0 +1,282 @@
+//! Keyed string tables for localization (enabled with the `i18n` feature).
+//!
+//! Tables use a small subset of Fluent (FTL): `key = value` lines, `#` comments, and
+//! `{ $name }` placeholders filled from `tr_args`. Multiline messages, selectors and
+//! attributes are not supported.
+//!
+//! ```text
+//! # en.ftl
+//! menu-start = Start game
+//! hud-score = Score: { $points }
+//! ```
+//!
+//! With the `serde` feature, tables can also be flat JSON objects of key to message, as
+//! exported by most translation platforms.
+
+use crate::text::FontId;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Writing system of a language, used to pick a font that has the right glyphs.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Script {
+    Latin,
+    Cyrillic,
+    Greek,
+    Arabic,
+    Hebrew,
+    Devanagari,
+    Thai,
+    /// Chinese, Japanese and Korean.
+    Cjk,
+}
+
+impl Script {
+    /// Script of a BCP 47 language tag such as `"ja"` or `"pt-BR"` (Latin if unknown).
+    pub fn for_language(lang: &str) -> Script {
+        let primary = lang.split(['-', '_']).next().unwrap_or(lang);
+        match primary.to_ascii_lowercase().as_str() {
+            "ru" | "uk" | "bg" | "sr" | "be" | "kk" | "mk" | "mn" => Script::Cyrillic,
+            "el" => Script::Greek,
+            "ar" | "fa" | "ur" => Script::Arabic,
+            "he" | "yi" => Script::Hebrew,
+            "hi" | "mr" | "ne" => Script::Devanagari,
+            "th" => Script::Thai,
+            "zh" | "ja" | "ko" => Script::Cjk,
+            _ => Script::Latin,
+        }
+    }
+}
+
+/// Error from parsing a string table.
+#[derive(Debug, thiserror::Error)]
+pub enum I18nError {
+    #[error("line {line}: expected `key = value`")]
+    Syntax { line: usize },
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "serde")]
+    #[error("invalid JSON string table: {0}")]
+    Json(String),
+}
+
+/// Messages for one language.
+#[derive(Clone, Debug, Default)]
+pub struct StringTable {
+    messages: HashMap<String, String>,
+}
+
+impl StringTable {
+    /// Parse FTL-subset source text.
+    pub fn parse(source: &str) -> Result<Self, I18nError> {
+        let mut messages = HashMap::new();
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(I18nError::Syntax { line: i + 1 });
+            };
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(I18nError::Syntax { line: i + 1 });
+            }
+            messages.insert(key.to_string(), value.trim().to_string());
+        }
+        Ok(Self { messages })
+    }
+
+    /// Parse a JSON object mapping keys to messages, e.g. `{"menu-start": "Start game"}`.
+    /// Messages use the same `{ $name }` placeholders as FTL.
+    #[cfg(feature = "serde")]
+    pub fn from_json(source: &str) -> Result<Self, I18nError> {
+        let messages = serde_json::from_str(source).map_err(|e| I18nError::Json(e.to_string()))?;
+        Ok(Self { messages })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.messages.get(key).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+/// String tables for several languages with one active language.
+///
+/// Lookups fall back to the fallback language (the first one added) and then to the key
+/// itself, so a missing translation shows up on screen instead of crashing.
+#[derive(Clone, Debug, Default)]
+pub struct Localization {
+    tables: HashMap<String, StringTable>,
+    fonts: HashMap<Script, Vec<FontId>>,
+    fallback: Option<String>,
+    current: Option<String>,
+}
+
+impl Localization {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register (or replace) the table for `lang`. The first language added is the fallback.
+    pub fn add_language(&mut self, lang: &str, table: StringTable) {
+        if self.fallback.is_none() {
+            self.fallback = Some(lang.to_string());
+        }
+        if self.current.is_none() {
+            self.current = Some(lang.to_string());
+        }
+        self.tables.insert(lang.to_string(), table);
+    }
+
+    /// Parse and register a table from FTL-subset source text.
+    pub fn add_language_from_str(&mut self, lang: &str, source: &str) -> Result<(), I18nError> {
+        self.add_language(lang, StringTable::parse(source)?);
+        Ok(())
+    }
+
+    /// Parse and register a table from a JSON object of key to message.
+    #[cfg(feature = "serde")]
+    pub fn add_language_from_json(&mut self, lang: &str, source: &str) -> Result<(), I18nError> {
+        self.add_language(lang, StringTable::from_json(source)?);
+        Ok(())
+    }
+
+    /// Load and register a table from an `.ftl` file, or a `.json` file with the `serde`
+    /// feature.
+    pub fn add_language_from_file(
+        &mut self,
+        lang: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<(), I18nError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)?;
+        #[cfg(feature = "serde")]
+        if path.extension().is_some_and(|ext| ext == "json") {
+            return self.add_language_from_json(lang, &source);
+        }
+        self.add_language_from_str(lang, &source)
+    }
+
+    /// Switch the active language. Returns `false` (and keeps the old one) if it isn't loaded.
+    pub fn set_language(&mut self, lang: &str) -> bool {
+        if !self.tables.contains_key(lang) {
+            return false;
+        }
+        self.current = Some(lang.to_string());
+        true
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    /// Script of the active language.
+    pub fn script(&self) -> Script {
+        self.current
+            .as_deref()
+            .map(Script::for_language)
+            .unwrap_or(Script::Latin)
+    }
+
+    /// Set the loaded fonts that `ctx.draw_text_localized` tries, in order, for each
+    /// character of text in `script`; the first also sets the line height.
+    pub fn set_font_fallback(&mut self, script: Script, fonts: &[FontId]) {
+        self.fonts.insert(script, fonts.to_vec());
+    }
+
+    /// Fonts to try for the active language: those of its script, else the Latin ones
+    /// (empty if neither was registered).
+    pub fn font_fallback(&self) -> &[FontId] {
+        [self.script(), Script::Latin]
+            .iter()
+            .find_map(|script| self.fonts.get(script))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    fn lookup(&self, key: &str) -> Option<&str> {
+        [self.current.as_deref(), self.fallback.as_deref()]
+            .into_iter()
+            .flatten()
+            .filter_map(|lang| self.tables.get(lang))
+            .find_map(|table| table.get(key))
+    }
+
+    /// Translate `key` in the active language.
+    pub fn tr<'a>(&'a self, key: &'a str) -> &'a str {
+        self.lookup(key).unwrap_or(key)
+    }
+
+    /// Translate `key`, replacing `{ $name }` placeholders with the given values.
+    pub fn tr_args(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let template = self.tr(key);
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            out.push_str(&rest[..open]);
+            let Some(close) = rest[open..].find('}') else {
+                rest = &rest[open..];
+                break;
+            };
+            let inner = rest[open + 1..open + close].trim();
+            let value = inner
+                .strip_prefix('$')
+                .and_then(|name| args.iter().find(|(n, _)| *n == name.trim()))
+                .map(|(_, v)| *v);
+            match value {
+                Some(v) => out.push_str(v),
+                None => out.push_str(&rest[open..=open + close]),
+            }
+            rest = &rest[open + close + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_falls_back_and_fills_placeholders() {
+        let mut l10n = Localization::new();
+        l10n.add_language_from_str("en", "# menu\nstart = Start\nscore = Score: { $points }")
+            .unwrap();
+        l10n.add_language_from_str("ja", "start = スタート")
+            .unwrap();
+        l10n.set_font_fallback(Script::Cjk, &[FontId(1), FontId(0)]);
+
+        assert!(l10n.set_language("ja"));
+        assert_eq!(l10n.tr("start"), "スタート");
+        assert_eq!(l10n.tr_args("score", &[("points", "42")]), "Score: 42");
+        assert_eq!(l10n.tr("missing"), "missing");
+        assert_eq!(l10n.font_fallback(), [FontId(1), FontId(0)]);
+        assert!(!l10n.set_language("de"));
+
+        assert!(matches!(
+            StringTable::parse("no equals sign"),
+            Err(I18nError::Syntax { line: 1 })
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_tables_load_like_ftl() {
+        let mut l10n = Localization::new();
+        l10n.add_language_from_json("en", r#"{"score": "Score: { $points }"}"#)
+            .unwrap();
+        assert_eq!(l10n.tr_args("score", &[("points", "7")]), "Score: 7");
+        assert!(matches!(
+            StringTable::from_json(r#"{"score": 7}"#),
+            Err(I18nError::Json(_))
+        ));
+    }
+}
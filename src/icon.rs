@@ -0,0 +1,52 @@
+use crate::error::LibforgeError;
+use winit::window::Icon;
+
+/// Largest icon edge worth keeping; Windows taskbars and Linux docks top out at 256 pixels.
+const MAX_ICON_SIZE: u32 = 256;
+
+/// Decode an encoded image (PNG, ...) into a window icon, downscaling large art.
+pub(crate) fn icon_from_bytes(bytes: &[u8]) -> Result<Icon, LibforgeError> {
+    let img = image::load_from_memory(bytes)
+        .map_err(|e| LibforgeError::Platform(format!("icon image decode failed: {e}")))?;
+    let (w, h) = icon_size(img.width(), img.height());
+    let img = if (w, h) == (img.width(), img.height()) {
+        img.to_rgba8()
+    } else {
+        image::imageops::resize(&img, w, h, image::imageops::FilterType::Lanczos3)
+    };
+    Icon::from_rgba(img.into_raw(), w, h)
+        .map_err(|e| LibforgeError::Platform(format!("invalid icon image: {e}")))
+}
+
+/// Size that fits `MAX_ICON_SIZE` while keeping the aspect ratio.
+fn icon_size(width: u32, height: u32) -> (u32, u32) {
+    let longest = width.max(height);
+    if longest <= MAX_ICON_SIZE {
+        return (width, height);
+    }
+    let scale = |side: u32| ((side as u64 * MAX_ICON_SIZE as u64) / longest as u64).max(1) as u32;
+    (scale(width), scale(height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_icons_are_downscaled_keeping_aspect() {
+        assert_eq!(icon_size(64, 64), (64, 64));
+        assert_eq!(icon_size(1024, 1024), (256, 256));
+        assert_eq!(icon_size(1024, 512), (256, 128));
+        assert_eq!(icon_size(4096, 1), (256, 1));
+    }
+
+    #[test]
+    fn decodes_png_bytes() {
+        let mut png = Vec::new();
+        image::RgbaImage::new(512, 512)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        assert!(icon_from_bytes(&png).is_ok());
+        assert!(icon_from_bytes(b"not an image").is_err());
+    }
+}
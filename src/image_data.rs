@@ -0,0 +1,197 @@
+//! CPU-side RGBA8 images for preparing texture variations before upload.
+//!
+//! ```ignore
+//! let base = ctx.load_image_from_bytes(include_bytes!("knight.png"))?;
+//! let mut red_team = base.clone();
+//! red_team.map_pixels(|[r, g, b, a]| if b > r && b > g { [b, g, r, a] } else { [r, g, b, a] });
+//! let tex = ctx.texture_from_image("knight_red", &red_team)?;
+//! ```
+
+use crate::error::LibforgeError;
+use image::RgbaImage;
+use image::imageops::{self, FilterType};
+
+/// An RGBA8 (sRGB, straight alpha) image in memory.
+///
+/// Transformations return a new image so they can be chained:
+/// `img.crop(0, 0, 32, 32).flip_x().resize(64, 64)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageData {
+    image: RgbaImage,
+}
+
+impl ImageData {
+    /// A fully transparent image.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            image: RgbaImage::new(width, height),
+        }
+    }
+
+    /// Decode an encoded image (PNG, JPEG, ...).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LibforgeError> {
+        let image = image::load_from_memory(bytes)
+            .map_err(|e| LibforgeError::Platform(format!("image decode failed: {e}")))?;
+        Ok(Self {
+            image: image.to_rgba8(),
+        })
+    }
+
+    /// Wrap tightly packed RGBA8 pixels. `None` if `rgba` isn't `width * height * 4` bytes.
+    pub fn from_rgba(width: u32, height: u32, rgba: Vec<u8>) -> Option<Self> {
+        RgbaImage::from_raw(width, height, rgba).map(|image| Self { image })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.image.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.image.height()
+    }
+
+    /// Tightly packed RGBA8 pixels, row by row from the top.
+    pub fn as_rgba(&self) -> &[u8] {
+        &self.image
+    }
+
+    /// The pixel at `(x, y)`, or `None` outside the image.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        self.image.get_pixel_checked(x, y).map(|p| p.0)
+    }
+
+    /// Overwrite the pixel at `(x, y)`; ignored outside the image.
+    pub fn set_pixel(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        if let Some(p) = self.image.get_pixel_mut_checked(x, y) {
+            p.0 = rgba;
+        }
+    }
+
+    /// Replace every pixel with `f(pixel)`, e.g. to recolor a sprite for another team.
+    pub fn map_pixels(&mut self, mut f: impl FnMut([u8; 4]) -> [u8; 4]) {
+        for p in self.image.pixels_mut() {
+            p.0 = f(p.0);
+        }
+    }
+
+    /// The `width` x `height` region at `(x, y)`, clipped to the image.
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        let x = x.min(self.width());
+        let y = y.min(self.height());
+        let width = width.min(self.width() - x);
+        let height = height.min(self.height() - y);
+        Self {
+            image: imageops::crop_imm(&self.image, x, y, width, height).to_image(),
+        }
+    }
+
+    /// Scale to `width` x `height` with smooth filtering.
+    pub fn resize(&self, width: u32, height: u32) -> Self {
+        self.resize_with(width, height, FilterType::CatmullRom)
+    }
+
+    /// Scale to `width` x `height` keeping hard pixel edges, for pixel art.
+    pub fn resize_nearest(&self, width: u32, height: u32) -> Self {
+        self.resize_with(width, height, FilterType::Nearest)
+    }
+
+    fn resize_with(&self, width: u32, height: u32, filter: FilterType) -> Self {
+        Self {
+            image: imageops::resize(&self.image, width, height, filter),
+        }
+    }
+
+    /// Mirror left to right.
+    pub fn flip_x(&self) -> Self {
+        Self {
+            image: imageops::flip_horizontal(&self.image),
+        }
+    }
+
+    /// Mirror top to bottom.
+    pub fn flip_y(&self) -> Self {
+        Self {
+            image: imageops::flip_vertical(&self.image),
+        }
+    }
+
+    /// Rotate a quarter turn clockwise; width and height swap.
+    pub fn rotate90(&self) -> Self {
+        Self {
+            image: imageops::rotate90(&self.image),
+        }
+    }
+
+    pub fn rotate180(&self) -> Self {
+        Self {
+            image: imageops::rotate180(&self.image),
+        }
+    }
+
+    /// Rotate a quarter turn counter-clockwise; width and height swap.
+    pub fn rotate270(&self) -> Self {
+        Self {
+            image: imageops::rotate270(&self.image),
+        }
+    }
+
+    /// The underlying `image` crate buffer, for operations not covered here.
+    pub fn as_image(&self) -> &RgbaImage {
+        &self.image
+    }
+
+    pub fn into_image(self) -> RgbaImage {
+        self.image
+    }
+}
+
+impl From<RgbaImage> for ImageData {
+    fn from(image: RgbaImage) -> Self {
+        Self { image }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    // 2x1 image: red on the left, blue on the right.
+    fn red_blue() -> ImageData {
+        ImageData::from_rgba(2, 1, [RED, BLUE].concat()).unwrap()
+    }
+
+    #[test]
+    fn transforms_move_pixels_where_expected() {
+        let img = red_blue();
+        assert_eq!(img.flip_x().pixel(0, 0), Some(BLUE));
+        assert_eq!(img.flip_y(), img);
+
+        let rotated = img.rotate90();
+        assert_eq!((rotated.width(), rotated.height()), (1, 2));
+        assert_eq!(rotated.pixel(0, 0), Some(RED));
+        assert_eq!(rotated.pixel(0, 1), Some(BLUE));
+        assert_eq!(img.rotate270().pixel(0, 0), Some(BLUE));
+        assert_eq!(img.rotate180().pixel(0, 0), Some(BLUE));
+
+        let cropped = img.crop(1, 0, 10, 10);
+        assert_eq!((cropped.width(), cropped.height()), (1, 1));
+        assert_eq!(cropped.pixel(0, 0), Some(BLUE));
+
+        let big = img.resize_nearest(4, 2);
+        assert_eq!(big.pixel(1, 1), Some(RED));
+        assert_eq!(big.pixel(2, 0), Some(BLUE));
+    }
+
+    #[test]
+    fn pixel_access_is_bounds_checked() {
+        let mut img = red_blue();
+        img.set_pixel(5, 5, BLUE);
+        assert_eq!(img.pixel(5, 5), None);
+        img.map_pixels(|[r, g, b, a]| [b, g, r, a]);
+        assert_eq!(img.pixel(0, 0), Some(BLUE));
+        assert!(ImageData::from_rgba(2, 2, vec![0; 4]).is_none());
+    }
+}
@@ -1,11 +1,14 @@
-use std::collections::HashSet;
+use crate::Rect;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Instant;
 use winit::dpi::PhysicalPosition;
 use winit::event::{ElementState, MouseButton as WinitMouseButton, MouseScrollDelta};
-use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::keyboard::{Key as LogicalKey, KeyCode, NamedKey, PhysicalKey};
 
 /// Keyboard keys supported by the input system.
 ///
-/// Uses physical key codes (layout-independent).
+/// Uses physical key codes (layout-independent): `Key::W` is the key left of `E` on every
+/// layout, labelled "Z" on AZERTY. Show it to players with `ctx.key_display_name(key)`.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum Key {
     Left,
@@ -46,6 +49,52 @@ impl Key {
             _ => return None,
         })
     }
+
+    fn keycode(self) -> KeyCode {
+        match self {
+            Key::Left => KeyCode::ArrowLeft,
+            Key::Right => KeyCode::ArrowRight,
+            Key::Up => KeyCode::ArrowUp,
+            Key::Down => KeyCode::ArrowDown,
+            Key::W => KeyCode::KeyW,
+            Key::A => KeyCode::KeyA,
+            Key::S => KeyCode::KeyS,
+            Key::D => KeyCode::KeyD,
+            Key::Q => KeyCode::KeyQ,
+            Key::E => KeyCode::KeyE,
+            Key::Space => KeyCode::Space,
+            Key::Enter => KeyCode::Enter,
+            Key::Minus => KeyCode::Minus,
+            Key::Equal => KeyCode::Equal,
+            Key::Escape => KeyCode::Escape,
+        }
+    }
+
+    /// Label on a US QWERTY keyboard.
+    fn us_label(self) -> &'static str {
+        match self {
+            Key::Left => "Left",
+            Key::Right => "Right",
+            Key::Up => "Up",
+            Key::Down => "Down",
+            Key::W => "W",
+            Key::A => "A",
+            Key::S => "S",
+            Key::D => "D",
+            Key::Q => "Q",
+            Key::E => "E",
+            Key::Space => "Space",
+            Key::Enter => "Enter",
+            Key::Minus => "-",
+            Key::Equal => "=",
+            Key::Escape => "Esc",
+        }
+    }
+}
+
+/// Lowercase form of a single-character key, so `'Z'` and `'z'` match.
+fn fold_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
 }
 
 /// Mouse buttons.
@@ -77,10 +126,32 @@ pub struct InputState {
 
     mouse_position: (f32, f32),
     mouse_wheel: (f32, f32),
+
+    cursor_inside: bool,
+    // Reported cursor positions are clamped into this rect while set.
+    cursor_confine: Option<Rect>,
+
+    // Case-folded character each held key produced, for `is_logical_key_down`.
+    logical_down: HashMap<KeyCode, char>,
+    // Unshifted character each key last produced: the user's layout, learned as they type,
+    // since winit has no layout query.
+    layout: HashMap<KeyCode, char>,
+    shift: bool,
+    alt: bool,
+
+    // Timestamps (seconds) of this frame and the previous one, from `begin_frame`.
+    time: f64,
+    prev_time: f64,
+    // Frame time of each key's latest press, and of the press before it (for double taps).
+    pressed_at: HashMap<Key, f64>,
+    previous_press: HashMap<Key, f64>,
 }
 
 impl InputState {
-    pub fn begin_frame(&mut self) {
+    /// Start a frame at `time` seconds (the frame clock's elapsed time).
+    pub fn begin_frame(&mut self, time: f64) {
+        self.prev_time = self.time;
+        self.time = time;
         self.prev_keys_down = self.keys_down.clone();
         self.prev_mouse_down = self.mouse_down.clone();
         self.mouse_wheel = (0.0, 0.0);
@@ -95,8 +166,13 @@ impl InputState {
         };
 
         match state {
+            // Key repeats don't restart the hold or count as taps.
             ElementState::Pressed => {
-                self.keys_down.insert(key);
+                if self.keys_down.insert(key)
+                    && let Some(previous) = self.pressed_at.insert(key, self.time)
+                {
+                    self.previous_press.insert(key, previous);
+                }
             }
             ElementState::Released => {
                 self.keys_down.remove(&key);
@@ -104,6 +180,65 @@ impl InputState {
         }
     }
 
+    /// Track the character a key event produced on the user's layout.
+    pub fn handle_logical_key(
+        &mut self,
+        physical_key: PhysicalKey,
+        logical_key: &LogicalKey,
+        state: ElementState,
+    ) {
+        let PhysicalKey::Code(code) = physical_key else {
+            return;
+        };
+        if state == ElementState::Released {
+            self.logical_down.remove(&code);
+            return;
+        }
+        let c = match logical_key {
+            LogicalKey::Character(text) => {
+                let mut chars = text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => fold_case(c),
+                    _ => return,
+                }
+            }
+            LogicalKey::Named(NamedKey::Space) => ' ',
+            _ => return,
+        };
+        self.logical_down.insert(code, c);
+        // Shifted digits and symbols would mislabel the key.
+        if !self.shift || c.is_alphabetic() {
+            self.layout.insert(code, c);
+        }
+    }
+
+    pub fn set_shift(&mut self, shift: bool) {
+        self.shift = shift;
+    }
+
+    pub fn set_alt(&mut self, alt: bool) {
+        self.alt = alt;
+    }
+
+    pub fn is_alt_down(&self) -> bool {
+        self.alt
+    }
+
+    /// Whether a key producing `c` on the user's layout is held (case-insensitive).
+    pub fn is_logical_key_down(&self, c: char) -> bool {
+        let c = fold_case(c);
+        self.logical_down.values().any(|&down| down == c)
+    }
+
+    /// The label of `key` on the user's layout, once a press of it has been seen; the US
+    /// QWERTY label before that.
+    pub fn key_display_name(&self, key: Key) -> String {
+        match self.layout.get(&key.keycode()) {
+            Some(&c) if !c.is_whitespace() => c.to_uppercase().collect(),
+            _ => key.us_label().to_string(),
+        }
+    }
+
     pub fn handle_mouse_button(&mut self, button: WinitMouseButton, state: ElementState) {
         let Some(button) = MouseButton::from_winit(button) else {
             return;
@@ -121,7 +256,50 @@ impl InputState {
     }
 
     pub fn handle_cursor_moved(&mut self, position: PhysicalPosition<f64>) {
-        self.mouse_position = (position.x as f32, position.y as f32);
+        self.cursor_inside = true;
+        self.mouse_position = self.confined((position.x as f32, position.y as f32));
+    }
+
+    /// Move the cursor programmatically (e.g. from a virtual cursor).
+    pub fn set_mouse_position(&mut self, position: (f32, f32)) {
+        self.mouse_position = self.confined(position);
+    }
+
+    /// Press or release a mouse button programmatically.
+    pub fn set_mouse_button(&mut self, button: MouseButton, pressed: bool) {
+        if pressed {
+            self.mouse_down.insert(button);
+        } else {
+            self.mouse_down.remove(&button);
+        }
+    }
+
+    pub fn handle_cursor_inside(&mut self, inside: bool) {
+        self.cursor_inside = inside;
+    }
+
+    pub fn set_cursor_confine(&mut self, rect: Option<Rect>) {
+        self.cursor_confine = rect;
+        self.mouse_position = self.confined(self.mouse_position);
+    }
+
+    pub fn cursor_confine(&self) -> Option<Rect> {
+        self.cursor_confine
+    }
+
+    /// Whether the cursor is over the window (or held inside the confine rect).
+    pub fn cursor_inside(&self) -> bool {
+        self.cursor_inside || self.cursor_confine.is_some()
+    }
+
+    fn confined(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        match self.cursor_confine {
+            Some(r) => (
+                x.clamp(r.x, r.x + r.w.max(0.0)),
+                y.clamp(r.y, r.y + r.h.max(0.0)),
+            ),
+            None => (x, y),
+        }
     }
 
     pub fn handle_mouse_wheel(&mut self, delta: MouseScrollDelta) {
@@ -142,6 +320,45 @@ impl InputState {
         self.keys_down.contains(&key) && !self.prev_keys_down.contains(&key)
     }
 
+    pub fn is_key_released(&self, key: Key) -> bool {
+        !self.keys_down.contains(&key) && self.prev_keys_down.contains(&key)
+    }
+
+    /// All `keys` are held and the last of them went down this frame, so a chord fires once
+    /// however its keys are ordered.
+    pub fn is_chord_pressed(&self, keys: &[Key]) -> bool {
+        !keys.is_empty()
+            && keys.iter().all(|&k| self.is_key_down(k))
+            && keys.iter().any(|&k| self.is_key_pressed(k))
+    }
+
+    /// `key` was pressed this frame within `window` seconds of its previous press.
+    pub fn is_key_double_tapped(&self, key: Key, window: f32) -> bool {
+        self.is_key_pressed(key)
+            && match (self.pressed_at.get(&key), self.previous_press.get(&key)) {
+                (Some(now), Some(before)) => now - before <= window as f64,
+                _ => false,
+            }
+    }
+
+    /// Seconds `key` has been held, by frame timestamps; `None` when it is up.
+    pub fn key_hold_time(&self, key: Key) -> Option<f32> {
+        if !self.is_key_down(key) {
+            return None;
+        }
+        let pressed = self.pressed_at.get(&key).copied().unwrap_or(self.time);
+        Some((self.time - pressed) as f32)
+    }
+
+    /// `key`'s hold reached `duration` seconds this frame (true for one frame per hold).
+    pub fn is_key_long_pressed(&self, key: Key, duration: f32) -> bool {
+        let Some(held) = self.key_hold_time(key) else {
+            return false;
+        };
+        let before = self.prev_time - self.pressed_at.get(&key).copied().unwrap_or(self.time);
+        held >= duration && (self.is_key_pressed(key) || (before as f32) < duration)
+    }
+
     pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
         self.mouse_down.contains(&button)
     }
@@ -150,6 +367,10 @@ impl InputState {
         self.mouse_down.contains(&button) && !self.prev_mouse_down.contains(&button)
     }
 
+    pub fn is_mouse_button_released(&self, button: MouseButton) -> bool {
+        !self.mouse_down.contains(&button) && self.prev_mouse_down.contains(&button)
+    }
+
     pub fn mouse_position(&self) -> (f32, f32) {
         self.mouse_position
     }
@@ -158,3 +379,220 @@ impl InputState {
         self.mouse_wheel
     }
 }
+
+/// Pan direction for RTS-style edge scrolling.
+///
+/// Each axis is in `[-1, 1]`: 0 outside the `margin` band along the edges of `bounds`,
+/// ramping linearly to full speed at the edge itself.
+pub fn edge_scroll(position: (f32, f32), bounds: Rect, margin: f32) -> (f32, f32) {
+    if margin <= 0.0 {
+        return (0.0, 0.0);
+    }
+    let axis = |p: f32, lo: f32, hi: f32| {
+        if p < lo + margin {
+            -((lo + margin - p) / margin).min(1.0)
+        } else if p > hi - margin {
+            ((p - (hi - margin)) / margin).min(1.0)
+        } else {
+            0.0
+        }
+    };
+    (
+        axis(position.0, bounds.x, bounds.x + bounds.w),
+        axis(position.1, bounds.y, bounds.y + bounds.h),
+    )
+}
+
+/// Number of recent latency samples kept for averaging.
+const LATENCY_SAMPLES: usize = 120;
+
+/// Measures input-to-present latency when enabled.
+///
+/// The first input event after a present starts the clock; the next present stops it.
+/// Only the oldest pending event is timed, which is the worst case the player feels.
+#[derive(Debug, Default, Clone)]
+pub struct LatencyTracker {
+    enabled: bool,
+    pending: Option<Instant>,
+    samples: VecDeque<f32>,
+}
+
+impl LatencyTracker {
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.pending = None;
+            self.samples.clear();
+        }
+    }
+
+    pub fn record_input(&mut self, now: Instant) {
+        if self.enabled && self.pending.is_none() {
+            self.pending = Some(now);
+        }
+    }
+
+    pub fn record_present(&mut self, now: Instant) {
+        let Some(start) = self.pending.take() else {
+            return;
+        };
+        if self.samples.len() == LATENCY_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((now - start).as_secs_f32());
+    }
+
+    /// Most recent input-to-present latency in seconds.
+    pub fn last(&self) -> Option<f32> {
+        self.samples.back().copied()
+    }
+
+    /// Average latency over the recent sample window, in seconds.
+    pub fn average(&self) -> Option<f32> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        Some(self.samples.iter().sum::<f32>() / self.samples.len() as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn latency_measures_oldest_event_to_present() {
+        let t0 = Instant::now();
+        let mut tracker = LatencyTracker::default();
+
+        // Disabled trackers record nothing.
+        tracker.record_input(t0);
+        tracker.record_present(t0 + Duration::from_millis(5));
+        assert_eq!(tracker.last(), None);
+
+        tracker.set_enabled(true);
+        tracker.record_input(t0);
+        tracker.record_input(t0 + Duration::from_millis(4));
+        tracker.record_present(t0 + Duration::from_millis(10));
+        assert!((tracker.last().unwrap() - 0.010).abs() < 1e-6);
+
+        // A present without new input adds no sample.
+        tracker.record_present(t0 + Duration::from_millis(30));
+        tracker.record_input(t0 + Duration::from_millis(40));
+        tracker.record_present(t0 + Duration::from_millis(60));
+        assert!((tracker.average().unwrap() - 0.015).abs() < 1e-6);
+    }
+
+    #[test]
+    fn release_edge_lasts_one_frame() {
+        let mut input = InputState::default();
+        input.handle_keyboard_input(PhysicalKey::Code(KeyCode::Space), ElementState::Pressed);
+        input.set_mouse_button(MouseButton::Left, true);
+        input.begin_frame(0.0);
+        assert!(!input.is_key_released(Key::Space));
+
+        input.handle_keyboard_input(PhysicalKey::Code(KeyCode::Space), ElementState::Released);
+        input.set_mouse_button(MouseButton::Left, false);
+        assert!(input.is_key_released(Key::Space));
+        assert!(input.is_mouse_button_released(MouseButton::Left));
+        assert!(!input.is_key_pressed(Key::Space));
+
+        input.begin_frame(0.0);
+        assert!(!input.is_key_released(Key::Space));
+        assert!(!input.is_mouse_button_released(MouseButton::Left));
+    }
+
+    #[test]
+    fn chords_double_taps_and_long_presses_use_frame_times() {
+        let code = |key| PhysicalKey::Code(key);
+        let mut input = InputState::default();
+        let frame = |input: &mut InputState, time: f64, key, state| {
+            input.begin_frame(time);
+            if let Some(key) = key {
+                input.handle_keyboard_input(code(key), state);
+            }
+        };
+
+        frame(&mut input, 0.0, Some(KeyCode::KeyA), ElementState::Pressed);
+        assert!(!input.is_chord_pressed(&[Key::A, Key::D]));
+        frame(&mut input, 0.1, Some(KeyCode::KeyD), ElementState::Pressed);
+        assert!(input.is_chord_pressed(&[Key::A, Key::D]));
+        frame(&mut input, 0.2, None, ElementState::Pressed);
+        assert!(!input.is_chord_pressed(&[Key::A, Key::D]));
+
+        // A held since 0.0: long press fires once, on the frame it crosses 0.5 s.
+        frame(&mut input, 0.4, None, ElementState::Pressed);
+        assert!(!input.is_key_long_pressed(Key::A, 0.5));
+        frame(&mut input, 0.6, None, ElementState::Pressed);
+        assert!(input.is_key_long_pressed(Key::A, 0.5));
+        assert_eq!(input.key_hold_time(Key::A), Some(0.6));
+        frame(&mut input, 0.7, None, ElementState::Pressed);
+        assert!(!input.is_key_long_pressed(Key::A, 0.5));
+
+        // A press 1 s after the previous one isn't a double tap; one 0.2 s later is.
+        frame(&mut input, 0.8, Some(KeyCode::KeyA), ElementState::Released);
+        assert_eq!(input.key_hold_time(Key::A), None);
+        frame(&mut input, 1.0, Some(KeyCode::KeyA), ElementState::Pressed);
+        assert!(!input.is_key_double_tapped(Key::A, 0.3));
+        frame(&mut input, 1.1, Some(KeyCode::KeyA), ElementState::Released);
+        frame(&mut input, 1.2, Some(KeyCode::KeyA), ElementState::Pressed);
+        assert!(input.is_key_double_tapped(Key::A, 0.3));
+        // Key repeat events neither restart the hold nor count as taps.
+        input.handle_keyboard_input(code(KeyCode::KeyA), ElementState::Pressed);
+        assert_eq!(input.pressed_at[&Key::A], 1.2);
+    }
+
+    #[test]
+    fn logical_keys_follow_the_layout() {
+        let mut input = InputState::default();
+        assert_eq!(input.key_display_name(Key::W), "W");
+
+        // AZERTY: the physical W key types "z".
+        let w = PhysicalKey::Code(KeyCode::KeyW);
+        input.handle_logical_key(w, &LogicalKey::Character("z".into()), ElementState::Pressed);
+        assert!(input.is_logical_key_down('Z'));
+        assert!(!input.is_logical_key_down('w'));
+        assert_eq!(input.key_display_name(Key::W), "Z");
+        input.handle_logical_key(
+            w,
+            &LogicalKey::Character("z".into()),
+            ElementState::Released,
+        );
+        assert!(!input.is_logical_key_down('z'));
+
+        // Shifted symbols count as held but don't relabel the key.
+        let minus = PhysicalKey::Code(KeyCode::Minus);
+        input.set_shift(true);
+        input.handle_logical_key(
+            minus,
+            &LogicalKey::Character("_".into()),
+            ElementState::Pressed,
+        );
+        assert!(input.is_logical_key_down('_'));
+        assert_eq!(input.key_display_name(Key::Minus), "-");
+        assert_eq!(input.key_display_name(Key::Escape), "Esc");
+    }
+
+    #[test]
+    fn confine_clamps_cursor_and_edge_scroll_ramps() {
+        let bounds = Rect {
+            x: 0.0,
+            y: 0.0,
+            w: 800.0,
+            h: 600.0,
+        };
+        assert_eq!(edge_scroll((400.0, 300.0), bounds, 20.0), (0.0, 0.0));
+        assert_eq!(edge_scroll((0.0, 590.0), bounds, 20.0), (-1.0, 0.5));
+
+        let mut input = InputState::default();
+        input.set_cursor_confine(Some(Rect {
+            x: 100.0,
+            y: 100.0,
+            w: 200.0,
+            h: 100.0,
+        }));
+        input.handle_cursor_moved(PhysicalPosition::new(50.0, 500.0));
+        assert_eq!(input.mouse_position(), (100.0, 200.0));
+    }
+}
@@ -0,0 +1,103 @@
+//! Smooth rendering between fixed simulation ticks.
+//!
+//! Simulate at a fixed rate with `ctx.fixed_steps()` and draw each value blended between
+//! its last two ticks with `ctx.interpolation_alpha()`:
+//!
+//! ```ignore
+//! ctx.begin_drawing();
+//! for _ in 0..ctx.fixed_steps() {
+//!     let next = ball.current().0 + speed * ctx.fixed_timestep();
+//!     ball.set((next, 300.0));
+//! }
+//! let (x, y) = ball.lerp(ctx.interpolation_alpha());
+//! ctx.draw_circle(x, y, 10.0, 24, Color::WHITE);
+//! ```
+
+/// Values that can be blended linearly.
+pub trait Lerp: Copy {
+    /// `self` at `t = 0`, `to` at `t = 1`.
+    fn lerp(self, to: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for (f32, f32) {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        (self.0.lerp(to.0, t), self.1.lerp(to.1, t))
+    }
+}
+
+impl Lerp for [f32; 2] {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        [self[0].lerp(to[0], t), self[1].lerp(to[1], t)]
+    }
+}
+
+impl Lerp for glam::Vec2 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        glam::Vec2::lerp(self, to, t)
+    }
+}
+
+/// A simulated value as of the previous and the latest fixed tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interpolated<T> {
+    previous: T,
+    current: T,
+}
+
+impl<T: Lerp> Interpolated<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            previous: value,
+            current: value,
+        }
+    }
+
+    /// Record the value after a tick; the old one becomes `previous`.
+    pub fn set(&mut self, value: T) {
+        self.previous = self.current;
+        self.current = value;
+    }
+
+    /// Jump to `value` without blending from the old one, e.g. after a teleport or respawn.
+    pub fn reset(&mut self, value: T) {
+        *self = Self::new(value);
+    }
+
+    pub fn current(&self) -> T {
+        self.current
+    }
+
+    pub fn previous(&self) -> T {
+        self.previous
+    }
+
+    /// The value `alpha` of the way from the previous tick to the latest one.
+    pub fn lerp(&self, alpha: f32) -> T {
+        self.previous.lerp(self.current, alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blends_between_the_last_two_ticks() {
+        let mut pos = Interpolated::new((0.0, 10.0));
+        pos.set((10.0, 10.0));
+        pos.set((20.0, 30.0));
+        assert_eq!(pos.previous(), (10.0, 10.0));
+        assert_eq!(pos.lerp(0.0), (10.0, 10.0));
+        assert_eq!(pos.lerp(0.5), (15.0, 20.0));
+        assert_eq!(pos.lerp(1.0), (20.0, 30.0));
+
+        pos.reset((100.0, 0.0));
+        assert_eq!(pos.lerp(0.5), (100.0, 0.0));
+    }
+}
@@ -1,29 +1,149 @@
+#[macro_use]
+mod logging;
+
+pub mod atlas;
+pub mod audio;
+pub mod background;
 pub mod camera;
+#[cfg(feature = "capture")]
+pub mod capture;
+mod clipboard;
+pub mod config;
+mod debug_draw;
+mod debug_font;
+mod debug_overlay;
+pub mod draw_list;
+mod dynamic_resolution;
 pub mod error;
+mod gesture;
+#[cfg(feature = "i18n")]
+pub mod i18n;
+mod icon;
+pub mod image_data;
 mod input;
+pub mod interpolation;
+pub mod lut;
+pub mod monitor;
+pub mod noise;
+pub mod parallax;
+#[cfg(feature = "paths")]
+pub mod path;
+#[cfg(feature = "physics")]
+pub mod physics;
+pub mod random;
+mod recording;
+mod redraw;
+pub mod reference_overlay;
 pub mod renderer;
+#[cfg(feature = "gamepad")]
+pub mod rumble;
+pub mod scene;
+mod shader_watch;
 pub mod sprite_animation;
+pub mod storage;
+pub mod testing;
+pub mod text;
+mod time;
+pub mod transition;
+mod upload;
 pub mod vertex;
+pub mod viewport;
+pub mod virtual_cursor;
+mod visualizer;
+mod window_events;
+pub mod window_state;
 
-pub use crate::camera::Camera2D;
-pub use crate::renderer::TextureId;
+pub use crate::atlas::{AtlasBuilder, SubTexture, TextureAtlas};
+pub use crate::audio::{Bus, Mixer, MusicBeat, PlayParams, Sound, Tempo, VoiceId};
+pub use crate::background::{BackgroundKind, BackgroundLayer};
+pub use crate::camera::{Camera2D, CameraShake, SpatialSound, ZoomConvention};
+pub use crate::config::ContextConfig;
+pub use crate::draw_list::{DrawList, ShapeId};
+pub use crate::gesture::{Gesture, GestureThresholds, SwipeDirection, TouchGesture};
+pub use crate::image_data::ImageData;
+pub use crate::interpolation::{Interpolated, Lerp};
+pub use crate::monitor::MonitorInfo;
+pub use crate::parallax::ParallaxLayer;
+#[cfg(feature = "paths")]
+pub use crate::path::{FillRule, LineCap, LineJoin, Path, StrokeStyle};
+pub use crate::random::Rng;
+pub use crate::redraw::ControlFlowHint;
+pub use crate::reference_overlay::ReferenceOverlay;
+pub use crate::renderer::{
+    ColorSpace, Feature, Flip, FrameStats, MeshId, MissingTexturePolicy, RenderPlugin,
+    RenderTarget, RendererConfig, TextureId, TextureInfo, TextureOptions, TextureWrap,
+};
+#[cfg(feature = "gamepad")]
+pub use crate::rumble::Rumble;
+pub use crate::scene::{NodeId, SceneGraph, SpriteNode, Transform2D};
 use crate::sprite_animation::SpriteAnimation;
+pub use crate::storage::{Storage, StorageError};
+pub use crate::text::{FontFamily, FontId};
 
 use error::LibforgeError;
 pub use input::{Key, MouseButton};
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use renderer::Renderer;
 use std::time::Instant;
+pub use time::TimeSource;
+pub use transition::{Transition, TransitionEvent};
+pub use viewport::{Anchor, FillMode, ScalingMode};
+pub use virtual_cursor::VirtualCursor;
+pub use window_events::WindowSignal;
+pub use window_state::WindowPlacement;
+pub use winit::window::{Cursor, CursorIcon, CustomCursor};
 
 /// RGBA color with values in the range `[0.0, 1.0]`.
 ///
-/// Example: `Color([1.0, 0.0, 0.0, 1.0])` is opaque red.
+/// Values are sRGB-encoded by default (see `ColorSpace`), matching hex codes from image
+/// editors. Example: `Color([1.0, 0.0, 0.0, 1.0])` is opaque red.
 #[derive(Clone, Copy, Debug)]
 pub struct Color(pub [f32; 4]);
 
 impl Color {
     pub const WHITE: Color = Color([1.0, 1.0, 1.0, 1.0]);
     pub const BLACK: Color = Color([0.0, 0.0, 0.0, 1.0]);
+    /// Clear color that lets the desktop show through a transparent window.
+    pub const TRANSPARENT: Color = Color([0.0, 0.0, 0.0, 0.0]);
+
+    /// Opaque color from a `0xRRGGBB` hex code.
+    pub fn from_hex(rgb: u32) -> Color {
+        Self::from_rgba8((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8, 255)
+    }
+
+    /// Color from 8-bit channels.
+    pub fn from_rgba8(r: u8, g: u8, b: u8, a: u8) -> Color {
+        Color([r, g, b, a].map(|c| c as f32 / 255.0))
+    }
+}
+
+/// HSV-style color grading applied to texture draws in the fragment shader.
+///
+/// The adjustment runs on the sampled texel before the tint is multiplied in, so
+/// `saturation: 0.0` plus a blue tint gives a "frozen" look without extra assets.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorAdjust {
+    /// Hue rotation in radians.
+    pub hue_shift: f32,
+    /// Saturation multiplier (`0.0` = grayscale, `1.0` = unchanged).
+    pub saturation: f32,
+    /// Brightness (HSV value) multiplier (`1.0` = unchanged).
+    pub brightness: f32,
+}
+
+impl ColorAdjust {
+    /// No adjustment: the texture is drawn as-is.
+    pub const NONE: ColorAdjust = ColorAdjust {
+        hue_shift: 0.0,
+        saturation: 1.0,
+        brightness: 1.0,
+    };
+}
+
+impl Default for ColorAdjust {
+    fn default() -> Self {
+        Self::NONE
+    }
 }
 
 /// Rectangle in logical pixels.
@@ -44,8 +164,63 @@ pub struct Rect {
 pub struct LibContext<W> {
     renderer: Renderer<W>,
     input: input::InputState,
-    last_frame_instant: Instant,
+    latency: input::LatencyTracker,
+    gestures: gesture::GestureState,
+    window_events: window_events::WindowEvents,
+    virtual_cursor: VirtualCursor,
+    #[cfg(feature = "gamepad")]
+    rumble: rumble::RumbleState,
+    // Running scene transition, and the milestone it reached this frame.
+    transition: Option<transition::TransitionState>,
+    transition_event: Option<TransitionEvent>,
+    // Adjusts the render scale to the frame time when dynamic resolution is on.
+    resolution_scaler: Option<dynamic_resolution::ResolutionScaler>,
+    clipboard: clipboard::Clipboard,
+    audio: Option<audio::AudioQueue>,
+    audio_update: Option<audio::AudioUpdate>,
+    beat_clock: Option<audio::BeatClock>,
+    // Camera of the last `begin_mode_2d`, the listener for `play_sound_at`.
+    listener: Camera2D,
+    uploads: upload::UploadQueue,
+    reference: Option<ReferenceOverlay>,
+    recorder: Option<recording::Recorder>,
+    shader_watch: Option<shader_watch::ShaderWatch>,
+    // Why the watched shader last failed to reload; cleared by a successful reload.
+    shader_error: Option<error::RendererError>,
+    text: text::TextSystem,
+    // Reused by `draw_waveform`/`draw_spectrum` so visualizers don't allocate per frame.
+    scratch_vertices: Vec<vertex::Vertex>,
+    #[cfg(feature = "capture")]
+    capture: Option<capture::Capture>,
+    // Keeps the device opened by `open_microphone` streaming into `capture`.
+    #[cfg(feature = "capture")]
+    microphone: Option<capture::Microphone>,
+    music_beat: Option<MusicBeat>,
+    clock: time::FrameClock,
+    fixed: time::FixedTimestep,
     frame_dt: f32,
+    frame_history: debug_overlay::FrameHistory,
+    // Deterministic randomness: `frame_seed` is derived from `base_seed` and `frame_index`.
+    base_seed: u64,
+    frame_index: u64,
+    rng: random::Rng,
+    // `handle_window_event` resizes the surface on `Resized` unless turned off.
+    auto_resize: bool,
+    scale_factor: f64,
+    redraw: redraw::RedrawState,
+    // Windowed geometry to restore when leaving fullscreen entered through the context.
+    windowed_placement: Option<WindowPlacement>,
+}
+
+/// Poll `future` once, returning its output if it completed without waiting.
+#[cfg(any(target_os = "android", target_os = "ios"))]
+fn poll_ready<F: std::future::Future>(future: F) -> Option<F::Output> {
+    let mut future = std::pin::pin!(future);
+    let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+    match future.as_mut().poll(&mut cx) {
+        std::task::Poll::Ready(output) => Some(output),
+        std::task::Poll::Pending => None,
+    }
 }
 
 impl<W> LibContext<W>
@@ -56,22 +231,129 @@ where
     ///
     /// In examples, this is typically a `winit::window::Window` wrapped in an `Arc`.
     pub fn new_from_window(window: W) -> Result<Self, LibforgeError> {
-        let renderer = pollster::block_on(Renderer::new(window))?;
-        Ok(LibContext {
+        Self::new_from_window_with_config(window, RendererConfig::default())
+    }
+
+    /// Like `new_from_window`, requesting the optional GPU features and limits in `config`.
+    ///
+    /// Features the adapter lacks are left out rather than failing; check them with
+    /// `supports`.
+    pub fn new_from_window_with_config(
+        window: W,
+        config: RendererConfig,
+    ) -> Result<Self, LibforgeError> {
+        let renderer = Renderer::with_config(window, &config);
+        // Mobile main threads must not block. Native wgpu backends finish adapter and device
+        // requests immediately, so the future is polled once instead of parked on.
+        #[cfg(any(target_os = "android", target_os = "ios"))]
+        let renderer = poll_ready(renderer).ok_or_else(|| {
+            LibforgeError::Platform("GPU device creation did not complete synchronously".into())
+        })?;
+        #[cfg(not(any(target_os = "android", target_os = "ios")))]
+        let renderer = pollster::block_on(renderer);
+        Ok(Self::from_renderer(renderer?))
+    }
+
+    /// Create the context without blocking, for executors that can await device creation
+    /// (e.g. the web).
+    pub async fn new_from_window_async(
+        window: W,
+        config: RendererConfig,
+    ) -> Result<Self, LibforgeError> {
+        let renderer = Renderer::with_config(window, &config).await?;
+        Ok(Self::from_renderer(renderer))
+    }
+
+    fn from_renderer(renderer: Renderer<W>) -> Self {
+        LibContext {
             renderer,
             input: input::InputState::default(),
-            last_frame_instant: Instant::now(),
+            latency: input::LatencyTracker::default(),
+            gestures: gesture::GestureState::default(),
+            window_events: window_events::WindowEvents::default(),
+            virtual_cursor: VirtualCursor::default(),
+            #[cfg(feature = "gamepad")]
+            rumble: rumble::RumbleState::default(),
+            transition: None,
+            transition_event: None,
+            resolution_scaler: None,
+            clipboard: clipboard::Clipboard::default(),
+            audio: None,
+            audio_update: None,
+            beat_clock: None,
+            listener: Camera2D::new(),
+            uploads: upload::UploadQueue::default(),
+            reference: None,
+            recorder: None,
+            shader_watch: None,
+            shader_error: None,
+            text: text::TextSystem::default(),
+            scratch_vertices: Vec::new(),
+            #[cfg(feature = "capture")]
+            capture: None,
+            #[cfg(feature = "capture")]
+            microphone: None,
+            music_beat: None,
+            clock: time::FrameClock::default(),
+            fixed: time::FixedTimestep::default(),
             frame_dt: 1.0 / 60.0,
-        })
+            frame_history: debug_overlay::FrameHistory::default(),
+            base_seed: 0,
+            frame_index: 0,
+            rng: random::Rng::new(0),
+            auto_resize: true,
+            scale_factor: 1.0,
+            redraw: redraw::RedrawState::default(),
+            windowed_placement: None,
+        }
     }
 
     /// Call once per frame before any draw calls
     pub fn begin_drawing(&mut self) {
-        let now = Instant::now();
-        self.frame_dt = (now - self.last_frame_instant).as_secs_f32();
-        self.last_frame_instant = now;
+        self.frame_dt = self.clock.next_frame();
+        self.fixed.advance(self.frame_dt);
+        self.frame_history.push(self.frame_dt);
+        #[cfg(feature = "gamepad")]
+        {
+            self.rumble.update(self.frame_dt);
+            self.rumble.sync();
+        }
+        self.transition_event = self
+            .transition
+            .as_mut()
+            .and_then(|t| t.advance(self.frame_dt));
+        if self.transition.is_some_and(|t| t.is_finished()) {
+            self.transition = None;
+        }
+        if let Some(scaler) = &mut self.resolution_scaler {
+            self.renderer.set_render_scale(scaler.update(self.frame_dt));
+        }
+        self.frame_index += 1;
+        self.redraw.begin_frame(Instant::now());
+        if let Some(recorder) = &mut self.recorder
+            && recorder.wants_frame(self.frame_dt)
+        {
+            self.renderer.capture_next_frame();
+        }
+
+        self.input.begin_frame(self.clock.elapsed());
+        self.gestures.begin_frame(self.clock.elapsed());
+        self.window_events.begin_frame();
+        #[cfg(feature = "capture")]
+        if let Some(capture) = &mut self.capture {
+            capture.begin_frame();
+        }
+        if let (Some(clock), Some(queue)) = (&mut self.beat_clock, &self.audio) {
+            self.music_beat = Some(clock.update(queue.music_position()));
+        }
+
+        if cfg!(debug_assertions) {
+            self.poll_shader_watch();
+        }
 
-        self.input.begin_frame();
+        let renderer = &mut self.renderer;
+        self.uploads
+            .process(|u| renderer.load_texture_from_rgba(&u.name, u.width, u.height, &u.rgba));
 
         // Start a frame with no implicit clear.
         self.renderer.begin_frame(None);
@@ -82,7 +364,119 @@ where
     /// This submits all draw commands to the GPU and displays the result.
     /// Call after all drawing is complete.
     pub fn end_drawing(&mut self) -> Result<(), crate::error::RendererError> {
-        self.renderer.end_frame()
+        if let Some(queue) = &mut self.audio {
+            if let Some(update) = &mut self.audio_update {
+                update(queue);
+            }
+            queue.flush();
+        }
+
+        self.draw_reference_overlay();
+        self.text.end_frame(&mut self.renderer);
+        self.renderer
+            .set_transition(self.transition.map(|t| (t.kind(), t.coverage())));
+
+        let result = self.renderer.end_frame();
+        self.latency.record_present(Instant::now());
+        if let Some(recorder) = &mut self.recorder {
+            while let Some(frame) = self.renderer.take_captured_frame() {
+                recorder.push(frame);
+            }
+        }
+        result
+    }
+
+    /// Replace the built-in shader with a WGSL file and reload it whenever the file changes.
+    ///
+    /// The file must provide the same entry points as `src/shaders/basic.wgsl` (start from a
+    /// copy). Changes are picked up at `begin_drawing` in debug builds; release builds load
+    /// the file once. When a changed file fails to compile, the previous pipelines stay in
+    /// use and the error is kept in `shader_error` and printed to stderr (logged as a
+    /// warning with the `log` feature).
+    /// Returns the error if the file can't be read or compiled right now; it is still watched.
+    pub fn watch_shader(&mut self, path: impl AsRef<std::path::Path>) -> Result<(), LibforgeError> {
+        let mut watch = shader_watch::ShaderWatch::new(path.as_ref());
+        let source = watch.poll();
+        self.shader_watch = Some(watch);
+        self.shader_error = None;
+        let source = source.ok_or_else(|| {
+            LibforgeError::Platform(format!("cannot read shader {}", path.as_ref().display()))
+        })?;
+        self.renderer.reload_shader(&source)?;
+        Ok(())
+    }
+
+    /// Stop watching the file passed to `watch_shader` (the last loaded shader stays).
+    pub fn unwatch_shader(&mut self) {
+        self.shader_watch = None;
+        self.shader_error = None;
+    }
+
+    /// Why the watched shader's latest change wasn't loaded, until a later change compiles.
+    pub fn shader_error(&self) -> Option<&error::RendererError> {
+        self.shader_error.as_ref()
+    }
+
+    fn poll_shader_watch(&mut self) {
+        let Some(watch) = &mut self.shader_watch else {
+            return;
+        };
+        let Some(source) = watch.poll() else {
+            return;
+        };
+        let path = watch.path().display().to_string();
+        match self.renderer.reload_shader(&source) {
+            Ok(()) => {
+                log_debug!("reloaded shader {path}");
+                self.shader_error = None;
+            }
+            Err(e) => {
+                log_warn!("shader {path} not reloaded, keeping the previous one: {e}");
+                self.shader_error = Some(e);
+            }
+        }
+    }
+
+    /// Start recording gameplay at `fps` frames per second.
+    ///
+    /// A path ending in `.gif` records an animated GIF; any other path is used as a
+    /// directory of numbered PNG frames. Frames are read back from the GPU through a ring of
+    /// staging buffers, picked up a couple of frames later without waiting on the GPU, and
+    /// encoded on a background thread. Frames are dropped rather than stalling the game if
+    /// encoding falls behind.
+    pub fn start_recording(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+        fps: f32,
+    ) -> Result<(), LibforgeError> {
+        if self.recorder.is_some() {
+            return Err(LibforgeError::Recording("already recording".into()));
+        }
+        let recorder =
+            recording::Recorder::start(path.as_ref(), fps).map_err(LibforgeError::Recording)?;
+        self.recorder = Some(recorder);
+        Ok(())
+    }
+
+    /// Stop recording and wait for the output to be written. Does nothing if not recording.
+    pub fn stop_recording(&mut self) -> Result<(), LibforgeError> {
+        let Some(mut recorder) = self.recorder.take() else {
+            return Ok(());
+        };
+        // The last captured frames are still on their way back from the GPU.
+        self.renderer.flush_captures();
+        while let Some(frame) = self.renderer.take_captured_frame() {
+            recorder.push(frame);
+        }
+        let dropped = recorder.finish().map_err(LibforgeError::Recording)?;
+        if dropped > 0 {
+            log_warn!("recording dropped {dropped} frames while the encoder caught up");
+        }
+        Ok(())
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
     }
 
     /// Time elapsed since the last frame (in seconds).
@@ -92,6 +486,50 @@ where
         self.frame_dt
     }
 
+    /// Total time in seconds advanced by all frames so far (the sum of `frame_time()`).
+    pub fn elapsed_time(&self) -> f64 {
+        self.clock.elapsed()
+    }
+
+    /// Length of one fixed simulation tick in seconds (default: 1/60).
+    pub fn set_fixed_timestep(&mut self, step: f32) {
+        self.fixed.set_step(step);
+    }
+
+    pub fn fixed_timestep(&self) -> f32 {
+        self.fixed.step()
+    }
+
+    /// Fixed ticks to simulate this frame, from the time accumulated since the last one.
+    /// At most 5, so a long hitch doesn't snowball.
+    pub fn fixed_steps(&self) -> u32 {
+        self.fixed.steps()
+    }
+
+    /// How far this frame lies between the latest fixed tick and the next, in `[0, 1]`.
+    /// Pass it to `Interpolated::lerp` to draw simulated values without stutter.
+    pub fn interpolation_alpha(&self) -> f32 {
+        self.fixed.alpha()
+    }
+
+    /// Choose where frame times come from (default: `TimeSource::RealTime`).
+    ///
+    /// Use `Fixed` or `Manual` for deterministic replays and headless simulation tests, or
+    /// `Custom` to drive frames from your own clock (e.g. `performance.now()` on the web).
+    pub fn set_time_source(&mut self, source: TimeSource) {
+        self.clock.set_source(source);
+    }
+
+    pub fn time_source(&self) -> &TimeSource {
+        self.clock.source()
+    }
+
+    /// Add `dt` seconds to the next frame's `frame_time()`, switching to
+    /// `TimeSource::Manual` if another source is active.
+    pub fn tick_manual(&mut self, dt: f32) {
+        self.clock.tick_manual(dt);
+    }
+
     /// Current frames per second.
     ///
     /// Computed as `1.0 / frame_time()`.
@@ -103,196 +541,2145 @@ where
         }
     }
 
-    /// Feed winit window events into the input system.
+    /// Create the software mixer that `play_sound` feeds, producing stereo at `sample_rate`.
     ///
-    /// Call this from your event loop for each `WindowEvent`.
-    /// The library tracks keyboard, mouse button, cursor position, and scroll wheel state.
-    pub fn handle_window_event(&mut self, event: &winit::event::WindowEvent) {
-        use winit::event::WindowEvent;
+    /// Move the returned `Mixer` into your audio device callback and call `mixer.mix(buffer)`
+    /// there. Sounds queued during a frame are sent as one batch at `end_drawing()`.
+    /// Calling this again replaces the previous mixer connection.
+    pub fn create_mixer(&mut self, sample_rate: u32) -> Mixer {
+        let (queue, mixer) = audio::channel_pair(sample_rate);
+        self.audio = Some(queue);
+        mixer
+    }
 
-        match event {
-            WindowEvent::KeyboardInput { event, .. } => {
-                self.input
-                    .handle_keyboard_input(event.physical_key, event.state);
-            }
-            WindowEvent::CursorMoved { position, .. } => {
-                self.input.handle_cursor_moved(*position);
-            }
-            WindowEvent::MouseInput { button, state, .. } => {
-                self.input.handle_mouse_button(*button, *state);
-            }
-            WindowEvent::MouseWheel { delta, .. } => {
-                self.input.handle_mouse_wheel(*delta);
-            }
-            _ => {}
+    /// Queue a sound to start this frame. Returns `None` if no mixer was created.
+    pub fn play_sound(&mut self, sound: &Sound, params: PlayParams) -> Option<VoiceId> {
+        Some(self.audio.as_mut()?.play(sound, params))
+    }
+
+    /// Queue `sound` at world position `world_pos`, panned and attenuated for a listener at
+    /// the centre of the view of the camera last passed to `begin_mode_2d` (the default
+    /// camera before that).
+    ///
+    /// The sound fades out one view width from the centre; use `Camera2D::spatial_sound`
+    /// with `play_sound` for another range or other `PlayParams`.
+    pub fn play_sound_at(&mut self, sound: &Sound, world_pos: (f32, f32)) -> Option<VoiceId> {
+        let canvas = (self.canvas_width(), self.canvas_height());
+        let range = canvas.0 / self.listener.scale();
+        let spatial = self
+            .listener
+            .spatial_sound(canvas, world_pos.0, world_pos.1, range);
+        let params = PlayParams {
+            volume: spatial.volume,
+            pan: spatial.pan,
+            ..PlayParams::default()
+        };
+        self.play_sound(sound, params)
+    }
+
+    /// Stop a playing sound.
+    pub fn stop_sound(&mut self, voice: VoiceId) {
+        if let Some(queue) = &mut self.audio {
+            queue.push(audio::AudioCommand::Stop(voice));
         }
     }
 
-    /// Check if a key is currently held down.
-    pub fn is_key_down(&self, key: Key) -> bool {
-        self.input.is_key_down(key)
+    /// Change the volume of a playing sound.
+    pub fn set_sound_volume(&mut self, voice: VoiceId, volume: f32) {
+        if let Some(queue) = &mut self.audio {
+            queue.push(audio::AudioCommand::SetVolume(voice, volume));
+        }
     }
 
-    /// Check if a key was just pressed this frame (edge detection).
+    /// Fade a playing sound to silence over `seconds`, then stop it.
     ///
-    /// Returns `true` only on the frame the key transitions from up to down.
-    pub fn is_key_pressed(&self, key: Key) -> bool {
-        self.input.is_key_pressed(key)
+    /// To fade a sound in, set `PlayParams::fade_in` when playing it.
+    pub fn fade_out_sound(&mut self, voice: VoiceId, seconds: f32) {
+        if let Some(queue) = &mut self.audio {
+            queue.fade_out(voice, seconds);
+        }
     }
 
-    /// Check if a mouse button is currently held down.
-    pub fn is_mouse_button_down(&self, btn: MouseButton) -> bool {
-        self.input.is_mouse_button_down(btn)
+    /// Set the volume of a mixer bus (e.g. from "Music volume" / "SFX volume" sliders).
+    pub fn set_bus_volume(&mut self, bus: Bus, volume: f32) {
+        self.fade_bus(bus, volume, 0.0);
     }
 
-    /// Check if a mouse button was just pressed this frame (edge detection).
-    pub fn is_mouse_button_pressed(&self, btn: MouseButton) -> bool {
-        self.input.is_mouse_button_pressed(btn)
+    /// Ramp a mixer bus's volume to `volume` over `seconds`.
+    pub fn fade_bus(&mut self, bus: Bus, volume: f32, seconds: f32) {
+        if let Some(queue) = &mut self.audio {
+            queue.set_bus_volume(bus, volume, seconds);
+        }
     }
 
-    /// Current mouse cursor position in screen pixels.
+    /// Volume last set for `bus` (1.0 if never set or no mixer was created).
+    pub fn bus_volume(&self, bus: Bus) -> f32 {
+        self.audio
+            .as_ref()
+            .map_or(1.0, |queue| queue.bus_volume(bus))
+    }
+
+    /// Silence a mixer bus without changing its volume.
+    pub fn set_bus_muted(&mut self, bus: Bus, muted: bool) {
+        if let Some(queue) = &mut self.audio {
+            queue.set_bus_muted(bus, muted);
+        }
+    }
+
+    pub fn is_bus_muted(&self, bus: Bus) -> bool {
+        self.audio
+            .as_ref()
+            .is_some_and(|queue| queue.is_bus_muted(bus))
+    }
+
+    /// Play `sound` on `Bus::Music` as the music track and start a beat clock at `tempo`.
     ///
-    /// Returns `(x, y)` where `(0, 0)` is the top-left corner.
-    pub fn mouse_position(&self) -> (f32, f32) {
-        self.input.mouse_position()
+    /// `music_beat()` follows this voice's playback position from the next frame on. The
+    /// position is taken when the mixer renders a buffer, so it runs ahead of what is heard by
+    /// the device latency; add that latency to `Tempo::offset` if beats feel early.
+    pub fn play_music(
+        &mut self,
+        sound: &Sound,
+        params: PlayParams,
+        tempo: Tempo,
+    ) -> Option<VoiceId> {
+        let voice = self.audio.as_mut()?.play_music(sound, params);
+        self.beat_clock = Some(audio::BeatClock::new(tempo));
+        self.music_beat = None;
+        Some(voice)
     }
 
-    /// Mouse wheel scroll delta for this frame.
+    /// Start `sound` as the music track, fading it in over `seconds` while the current track
+    /// fades out (e.g. switching from exploration to combat music).
     ///
-    /// Returns `(horizontal, vertical)`. Positive vertical = scroll up.
-    /// Resets to `(0, 0)` at the start of each frame.
-    pub fn mouse_wheel(&self) -> (f32, f32) {
-        self.input.mouse_wheel()
+    /// The beat clock keeps its tempo; call `set_music_tempo` if the new track differs.
+    pub fn play_music_with_crossfade(
+        &mut self,
+        sound: &Sound,
+        params: PlayParams,
+        seconds: f32,
+    ) -> Option<VoiceId> {
+        Some(self.audio.as_mut()?.crossfade_music(sound, params, seconds))
     }
 
-    /// Clear the screen to a solid color. Call after `begin_drawing()` and before any draw calls.
-    pub fn clear_background(&mut self, color: Color) {
-        self.renderer.begin_frame(Some(color.0));
+    /// Fade the music track out over `seconds` and stop it.
+    pub fn stop_music(&mut self, seconds: f32) {
+        if let Some(queue) = &mut self.audio {
+            queue.stop_music(seconds);
+        }
+        self.beat_clock = None;
+        self.music_beat = None;
     }
 
-    /// Must be called at the start of each frame. Optional clear color.
-    /// 
-    /// **Deprecated:** prefer `begin_drawing()` + `clear_background(color)` for clarity.
-    #[deprecated(since = "0.1.0", note = "use `begin_drawing()` + `clear_background(color)` instead")]
-    pub fn begin_frame(&mut self, clear: Option<Color>) {
-        self.renderer.begin_frame(clear.map(|c| c.0));
+    /// Change the tempo of the current music (e.g. at a tempo change in the track).
+    pub fn set_music_tempo(&mut self, tempo: Tempo) {
+        if let Some(clock) = &mut self.beat_clock {
+            clock.tempo = tempo;
+        }
     }
 
-    /// Immediate draw a filled rectangle (in logical pixels)
-    pub fn draw_rect(&mut self, rect: Rect, color: Color) {
-        self.renderer.draw_rect(rect, color);
+    /// Music position in beats at the start of this frame, if `play_music` was called.
+    ///
+    /// `beat.just_crossed()` is true on the frame a new beat starts, for rhythm-reactive
+    /// visuals and gameplay.
+    pub fn music_beat(&self) -> Option<MusicBeat> {
+        self.music_beat
     }
 
-    /// Immediate draw a line
-    pub fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color) {
-        self.renderer.draw_line(x1, y1, x2, y2, thickness, color.0);
+    /// Start recording from the default microphone; its samples show up in
+    /// `captured_audio()` from the next frame on.
+    ///
+    /// Replaces any previous microphone or `create_capture` connection.
+    #[cfg(feature = "capture")]
+    pub fn open_microphone(&mut self) -> Result<(), LibforgeError> {
+        let (microphone, capture) =
+            capture::open_default_microphone().map_err(LibforgeError::Capture)?;
+        self.microphone = Some(microphone);
+        self.capture = Some(capture);
+        Ok(())
     }
 
-    /// Draw a filled circle centered at (x, y) with given radius (in logical pixels).
-    /// `segments` controls the tessellation (higher = smoother). Use ~32 for good quality.
-    pub fn draw_circle(&mut self, x: f32, y: f32, radius: f32, segments: usize, color: Color) {
-        self.renderer.draw_circle(x, y, radius, segments, color.0);
+    /// Stop recording from the microphone opened by `open_microphone`.
+    #[cfg(feature = "capture")]
+    pub fn close_microphone(&mut self) {
+        if self.microphone.take().is_some() {
+            self.capture = None;
+        }
     }
 
-    /// Draw a texture, scaled to fit the destination rectangle.
+    /// Connect input from your own device callback, delivering interleaved samples at
+    /// `sample_rate`.
     ///
-    /// The texture is tinted by multiplying with the `tint` color.
-    /// Use `Color::WHITE` for no tint.
-    pub fn draw_texture(&mut self, tex: TextureId, rect: Rect, tint: Color) {
-        self.renderer.draw_texture(tex, rect, tint.0);
+    /// Move the returned `CaptureSink` into the callback and call `sink.push(&samples)`
+    /// there. Calling this again replaces the previous connection, including a microphone
+    /// opened with `open_microphone`.
+    #[cfg(feature = "capture")]
+    pub fn create_capture(&mut self, sample_rate: u32, channels: u16) -> capture::CaptureSink {
+        let (sink, capture) = capture::capture_pair(sample_rate, channels);
+        self.microphone = None;
+        self.capture = Some(capture);
+        sink
     }
 
-    /// Draw a portion of a texture (subtexture/sprite).
+    /// Microphone samples (interleaved) received during the previous frame.
+    #[cfg(feature = "capture")]
+    pub fn captured_audio(&self) -> &[f32] {
+        self.capture.as_ref().map(|c| c.samples()).unwrap_or(&[])
+    }
+
+    /// RMS loudness of the previous frame's microphone input (0 when silent or unconnected).
+    #[cfg(feature = "capture")]
+    pub fn capture_level(&self) -> f32 {
+        capture::rms(self.captured_audio())
+    }
+
+    /// Direct access to this frame's audio command queue.
+    pub fn audio_queue(&mut self) -> Option<&mut audio::AudioQueue> {
+        self.audio.as_mut()
+    }
+
+    /// Run `update` once per frame, just before the frame's audio batch is sent.
     ///
-    /// `src` defines the region in the source texture (in pixels).
-    /// `dst` defines where to draw it on screen.
-    pub fn draw_subtexture(&mut self, tex: TextureId, src: Rect, dst: Rect, tint: Color) {
-        self.renderer.draw_subtexture(tex, src, dst, tint.0);
+    /// Use it for audio logic that should see the whole frame's state, such as ducking
+    /// music or culling distant one-shots.
+    pub fn set_audio_update(&mut self, update: impl FnMut(&mut audio::AudioQueue) + 'static) {
+        self.audio_update = Some(Box::new(update));
     }
 
-    /// Draw an animated sprite by sampling the current frame from a sprite animation.
-    pub fn draw_sprite_animation(
-        &mut self,
-        tex: TextureId,
-        animation: &SpriteAnimation,
-        time: f32,
-        destination: Rect,
-        tint: Color,
-    ) {
-        let src = animation.frame_at_time(time);
-        self.renderer.draw_subtexture(tex, src, destination, tint.0);
+    /// Vertex, draw-call and texture counters from the previous frame.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.renderer.frame_stats()
     }
 
-    /// Load a texture from PNG/JPEG bytes.
+    /// Time the GPU work of the draws made inside `f`; the result shows up by name in
+    /// `frame_stats().gpu_scopes` a frame or two later.
     ///
-    /// Returns a `TextureId` that can be used with `draw_texture` and `draw_subtexture`.
-    /// The `name` is for debugging only.
-    pub fn load_texture_from_bytes(
-        &mut self,
-        name: &str,
-        bytes: &[u8],
-    ) -> Result<TextureId, LibforgeError> {
-        Ok(self.renderer.load_texture_from_bytes(name, bytes)?)
+    /// ```ignore
+    /// ctx.gpu_scope("terrain", |ctx| {
+    ///     for tile in &tiles {
+    ///         ctx.draw_texture(tile.texture, tile.rect, Color::WHITE);
+    ///     }
+    /// });
+    /// for (name, ms) in &ctx.frame_stats().gpu_scopes {
+    ///     println!("{name}: {ms:.3} ms");
+    /// }
+    /// ```
+    ///
+    /// Particles and debug gizmos are drawn after everything else, so they fall outside any
+    /// scope. Needs timestamp queries inside render passes (see `supports_gpu_timing`);
+    /// elsewhere `f` still runs and no timings are reported.
+    pub fn gpu_scope<R>(&mut self, name: &str, f: impl FnOnce(&mut Self) -> R) -> R {
+        let scope = self.renderer.begin_gpu_scope(name);
+        let result = f(self);
+        self.renderer.end_gpu_scope(scope);
+        result
     }
 
-    // -------------------------------------------------------------------------
-    //
-    // Default drawing is in screen-space (pixels). To draw in world-space, enter
-    // 2D camera mode with `begin_mode_2d()` and exit with `end_mode_2d()`.
-    //
-    // Per-draw transforms are handled via a simple matrix stack.
-    // -------------------------------------------------------------------------
+    /// Draw everything up to the matching `end_group` into an offscreen layer and composite
+    /// it with a single `alpha`, e.g. to fade out a whole menu:
+    ///
+    /// ```ignore
+    /// ctx.begin_group(menu_fade);
+    /// ctx.draw_rect(panel, Color::BLACK);
+    /// ctx.draw_text(font, "Paused", 40.0, 40.0, 32.0, Color::WHITE);
+    /// ctx.end_group();
+    /// ```
+    ///
+    /// Overlapping draws inside the group don't show through each other, as they would if
+    /// each had `alpha` multiplied into its color. Groups can nest; each open level costs a
+    /// screen-sized texture and a render pass. Groups still open at `end_drawing` are
+    /// closed and reported like unbalanced `push_matrix` calls.
+    pub fn begin_group(&mut self, alpha: f32) {
+        self.renderer.begin_group(alpha);
+    }
 
-    /// Begin 2D camera mode (world-space). Camera affects subsequent draws until `end_mode_2d()`.
-    pub fn begin_mode_2d(&mut self, camera: Camera2D) {
-        self.renderer.begin_mode_2d(camera);
+    /// Composite the innermost open group.
+    pub fn end_group(&mut self) {
+        self.renderer.end_group();
     }
 
-    /// End 2D camera mode and return to screen-space drawing.
-    pub fn end_mode_2d(&mut self) {
-        self.renderer.end_mode_2d();
+    /// Load a color grading LUT from a `.cube` file or a PNG strip (see `lut`), ready for
+    /// `set_color_lut`. Loaded without mipmaps, color key or premultiplied alpha whatever
+    /// the current texture options.
+    pub fn load_color_lut(&mut self, name: &str, bytes: &[u8]) -> Result<TextureId, LibforgeError> {
+        let image = match std::str::from_utf8(bytes) {
+            Ok(text) if text.contains("LUT_") => lut::from_cube(text)?,
+            _ => ImageData::from_bytes(bytes)?,
+        };
+        if lut::strip_size(image.width(), image.height()).is_none() {
+            return Err(LibforgeError::Platform(format!(
+                "color LUT '{name}' is {}x{}, expected a strip of size * size x size",
+                image.width(),
+                image.height()
+            )));
+        }
+        Ok(self.renderer.load_texture_from_rgba_with(
+            name,
+            (image.width(), image.height()),
+            image.as_rgba(),
+            TextureOptions::default(),
+        )?)
     }
 
-    /// Push the current model transform.
-    pub fn push_matrix(&mut self) {
-        self.renderer.push_matrix();
+    /// Grade every frame through `lut` as a final full-screen pass, for day/night or
+    /// flashback looks; `None` turns grading off. Swapping LUTs between frames is free;
+    /// grading costs one extra pass over the screen.
+    pub fn set_color_lut(&mut self, lut: Option<TextureId>) -> Result<(), LibforgeError> {
+        Ok(self.renderer.set_color_lut(lut)?)
     }
 
-    /// Pop the current model transform.
-    pub fn pop_matrix(&mut self) {
-        self.renderer.pop_matrix();
+    pub fn color_lut(&self) -> Option<TextureId> {
+        self.renderer.color_lut()
     }
 
-    /// Reset the current model transform to identity.
-    pub fn load_identity(&mut self) {
-        self.renderer.load_identity();
+    /// Mark this point between draws in GPU debugger captures (RenderDoc, Xcode, PIX).
+    pub fn debug_marker(&mut self, label: &str) {
+        self.renderer.debug_marker(label);
     }
 
-    /// Apply a translation to the current model transform.
-    pub fn translate(&mut self, tx: f32, ty: f32) {
-        self.renderer.translate(tx, ty);
+    /// Name every draw call in GPU debugger captures after what it draws, e.g.
+    /// `texture 'player.png' (6 vertices)`. On by default in debug builds.
+    pub fn set_debug_groups(&mut self, enabled: bool) {
+        self.renderer.set_debug_groups(enabled);
     }
 
-    /// Apply a rotation (around Z) to the current model transform.
-    pub fn rotate_z(&mut self, radians: f32) {
-        self.renderer.rotate_z(radians);
+    /// Whether an optional GPU feature from `RendererConfig` was granted, for falling back
+    /// gracefully on older hardware.
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.renderer.supports(feature)
     }
 
-    /// Apply a scale to the current model transform.
-    pub fn scale(&mut self, sx: f32, sy: f32) {
-        self.renderer.scale(sx, sy);
+    /// Whether the window shows the desktop through the frame's transparent pixels, i.e.
+    /// `RendererConfig::transparent` was requested and the platform supports it.
+    pub fn is_transparent(&self) -> bool {
+        self.renderer.is_transparent()
     }
 
-    /// Finish the frame, flush commands to GPU, and present.
-    pub fn end_frame(&mut self) -> Result<(), LibforgeError> {
-        self.renderer.end_frame()?;
-        Ok(())
+    /// Limits the GPU device was created with.
+    pub fn device_limits(&self) -> wgpu::Limits {
+        self.renderer.device_limits()
     }
 
-    /// Handle window resize: pass the new logical size in pixels.
+    /// Whether `gpu_scope` can time GPU work on this device.
+    pub fn supports_gpu_timing(&self) -> bool {
+        self.renderer.supports_gpu_timing()
+    }
+
+    /// Draw a stats panel in the top-left corner: FPS, a frame-time graph, vertex and
+    /// draw-call counts, texture memory, and input latency when tracking is enabled.
     ///
-    /// Resizing updates the internal screen-space projection and any active camera mode.
-    pub fn resize(&mut self, width: u32, height: u32) {
-        self.renderer.resize(width, height);
+    /// Call last in the frame so it sits on top. Counters describe the previous frame.
+    /// The panel ignores the active camera and model transform.
+    pub fn draw_debug_overlay(&mut self) {
+        let data = debug_overlay::OverlayData {
+            history: &self.frame_history,
+            stats: self.renderer.frame_stats(),
+            latency: self.latency.average(),
+        };
+        let rects = debug_overlay::layout(&data, 8.0, 8.0);
+        self.renderer.screen_space(|r| {
+            for (rect, color) in rects {
+                r.draw_rect(rect, color);
+            }
+        });
+    }
+
+    /// Overlay a reference image (mockup, screenshot) on top of every frame, or remove it.
+    ///
+    /// Starts at 50% opacity at the top-left corner; arrow keys nudge it by a pixel and
+    /// `-`/`=` change opacity. Adjust further through `reference_overlay_mut()`.
+    pub fn set_reference_image(&mut self, texture: Option<TextureId>) {
+        self.reference = texture.map(ReferenceOverlay::new);
+    }
+
+    pub fn reference_overlay_mut(&mut self) -> Option<&mut ReferenceOverlay> {
+        self.reference.as_mut()
+    }
+
+    fn draw_reference_overlay(&mut self) {
+        let Some(overlay) = &mut self.reference else {
+            return;
+        };
+        let input = &self.input;
+        overlay.handle_keys(|key| input.is_key_pressed(key));
+        if !overlay.visible {
+            return;
+        }
+        let Some((w, h)) = self.renderer.texture_size(overlay.texture) else {
+            return;
+        };
+        let overlay = *overlay;
+        let dest = Rect {
+            x: overlay.offset.0,
+            y: overlay.offset.1,
+            w: w as f32 * overlay.scale,
+            h: h as f32 * overlay.scale,
+        };
+        self.renderer.screen_space(|r| {
+            r.draw_texture(overlay.texture, dest, [1.0, 1.0, 1.0, overlay.opacity]);
+        });
+    }
+
+    /// Set the context-level seed and restart the frame counter.
+    ///
+    /// All engine-internal randomness derives from `frame_seed()`, so a replay that restores
+    /// the seed and feeds the same inputs reproduces visuals exactly. Also reseeds the
+    /// gameplay RNG behind `rand_range()` and friends.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.base_seed = seed;
+        self.frame_index = 0;
+        self.rng = random::Rng::new(seed);
+    }
+
+    /// The seed passed to `set_seed` (0 by default).
+    pub fn seed(&self) -> u64 {
+        self.base_seed
+    }
+
+    /// Number of frames started with `begin_drawing()` since creation or `set_seed`.
+    pub fn frame_index(&self) -> u64 {
+        self.frame_index
+    }
+
+    /// Deterministic per-frame seed, advanced by every `begin_drawing()`.
+    pub fn frame_seed(&self) -> u64 {
+        random::frame_seed(self.base_seed, self.frame_index)
+    }
+
+    /// Uniform float in `range`, from the context RNG seeded by `set_seed`.
+    pub fn rand_range(&mut self, range: std::ops::Range<f32>) -> f32 {
+        self.rng.range(range)
+    }
+
+    /// Uniform integer in `range`, e.g. `ctx.rand_int(1..=6)`.
+    pub fn rand_int(&mut self, range: impl std::ops::RangeBounds<i32>) -> i32 {
+        self.rng.int(range)
+    }
+
+    /// A random element of `items`, or `None` if it is empty.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        self.rng.pick(items)
+    }
+
+    /// Shuffle `items` in place with the context RNG.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        self.rng.shuffle(items);
+    }
+
+    /// The context RNG, for `chance()`, raw values or handing to your own systems.
+    pub fn rng_mut(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+
+    /// Feed winit window events into the input system.
+    ///
+    /// Call this from your event loop for each `WindowEvent`.
+    /// The library tracks keyboard, mouse button, cursor position, and scroll wheel state,
+    /// and resizes the surface when the window size changes (see `set_auto_resize`).
+    pub fn handle_window_event(&mut self, event: &winit::event::WindowEvent) {
+        use winit::event::WindowEvent;
+
+        self.redraw.handle_event(event);
+        if matches!(
+            event,
+            WindowEvent::KeyboardInput { .. }
+                | WindowEvent::MouseInput { .. }
+                | WindowEvent::MouseWheel { .. }
+        ) {
+            self.latency.record_input(Instant::now());
+        }
+
+        match event {
+            WindowEvent::KeyboardInput { event, .. } => {
+                self.input
+                    .handle_keyboard_input(event.physical_key, event.state);
+                self.input
+                    .handle_logical_key(event.physical_key, &event.logical_key, event.state);
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.input.set_shift(modifiers.state().shift_key());
+                self.input.set_alt(modifiers.state().alt_key());
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.input.handle_cursor_moved(*position);
+            }
+            WindowEvent::CursorEntered { .. } => self.input.handle_cursor_inside(true),
+            WindowEvent::CursorLeft { .. } => self.input.handle_cursor_inside(false),
+            WindowEvent::MouseInput { button, state, .. } => {
+                self.input.handle_mouse_button(*button, *state);
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.input.handle_mouse_wheel(*delta);
+            }
+            WindowEvent::Touch(touch) => {
+                let pos = (touch.location.x as f32, touch.location.y as f32);
+                self.gestures.handle_touch(touch.id, touch.phase, pos);
+            }
+            WindowEvent::PinchGesture { delta, .. } => self.gestures.handle_pinch(*delta),
+            WindowEvent::PanGesture { delta, .. } => {
+                self.gestures.handle_pan((delta.x, delta.y));
+            }
+            WindowEvent::RotationGesture { delta, .. } => self.gestures.handle_rotation(*delta),
+            WindowEvent::Focused(focused) => self.window_events.handle_focus(*focused),
+            WindowEvent::Resized(size) => {
+                self.window_events.handle_resize(*size);
+                if self.auto_resize {
+                    self.resize(size.width, size.height);
+                }
+            }
+            // The new physical size arrives in the `Resized` event that follows.
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.scale_factor = *scale_factor;
+            }
+            WindowEvent::Occluded(occluded) => self.window_events.handle_occluded(*occluded),
+            WindowEvent::HoveredFile(path) => {
+                self.window_events
+                    .push(WindowSignal::FileHovered(path.clone()));
+            }
+            WindowEvent::HoveredFileCancelled => {
+                self.window_events.push(WindowSignal::FileHoverCancelled);
+            }
+            WindowEvent::DroppedFile(path) => {
+                self.window_events
+                    .push(WindowSignal::FileDropped(path.clone()));
+            }
+            _ => {}
+        }
+    }
+
+    /// Render continuously (the default) or only when something changed.
+    ///
+    /// In `ControlFlowHint::Reactive` mode, hand `control_flow()` to the event loop and only
+    /// request a redraw while `needs_redraw()` is true:
+    ///
+    /// ```ignore
+    /// fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+    ///     event_loop.set_control_flow(ctx.control_flow());
+    ///     if ctx.needs_redraw() {
+    ///         window.request_redraw();
+    ///     }
+    /// }
+    /// ```
+    pub fn set_control_flow_hint(&mut self, hint: ControlFlowHint) {
+        self.redraw.set_hint(hint);
+    }
+
+    pub fn control_flow_hint(&self) -> ControlFlowHint {
+        self.redraw.hint()
+    }
+
+    /// Ask for another frame in reactive mode, e.g. after loading data or while an
+    /// animation is playing. Window events passed to `handle_window_event` do this already.
+    pub fn invalidate(&mut self) {
+        self.redraw.invalidate();
+    }
+
+    /// Ask for a frame `delay` from now in reactive mode, e.g. to blink a text caret.
+    /// Only the earliest pending request is kept.
+    pub fn invalidate_after(&mut self, delay: std::time::Duration) {
+        self.redraw.invalidate_after(delay, Instant::now());
+    }
+
+    /// Whether a frame should be drawn: always in continuous mode, and in reactive mode
+    /// once something changed since the last `begin_drawing`.
+    pub fn needs_redraw(&self) -> bool {
+        self.redraw.needs_redraw(Instant::now())
+    }
+
+    /// Control flow for the event loop: `Poll` in continuous mode; in reactive mode `Wait`,
+    /// or `WaitUntil` the next `invalidate_after`.
+    pub fn control_flow(&self) -> winit::event_loop::ControlFlow {
+        self.redraw.control_flow()
+    }
+
+    /// Focus, minimize and drag-and-drop events received since the previous frame.
+    pub fn window_events(&self) -> &[WindowSignal] {
+        self.window_events.events()
+    }
+
+    /// Paths of files dropped onto the window since the previous frame.
+    pub fn dropped_files(&self) -> impl Iterator<Item = &std::path::Path> {
+        self.window_events.dropped_files()
+    }
+
+    /// Whether the window has keyboard focus, e.g. to pause the game when it doesn't.
+    pub fn is_focused(&self) -> bool {
+        self.window_events.is_focused()
+    }
+
+    /// Whether the window is minimized or fully hidden. Skip drawing while this is `true`.
+    pub fn is_minimized(&self) -> bool {
+        self.window_events.is_minimized()
+    }
+
+    /// Enable or disable input latency measurement.
+    ///
+    /// When enabled, the time from a key/mouse event reaching `handle_window_event` to the
+    /// next present is recorded. Cursor motion is excluded since it arrives continuously.
+    pub fn set_latency_tracking(&mut self, enabled: bool) {
+        self.latency.set_enabled(enabled);
+    }
+
+    /// Most recent input-to-present latency in seconds, if tracking is enabled.
+    pub fn input_latency(&self) -> Option<f32> {
+        self.latency.last()
+    }
+
+    /// Average input-to-present latency over the last ~120 samples, in seconds.
+    pub fn average_input_latency(&self) -> Option<f32> {
+        self.latency.average()
+    }
+
+    /// Check if a key is currently held down.
+    pub fn is_key_down(&self, key: Key) -> bool {
+        self.input.is_key_down(key)
+    }
+
+    /// Check if a key was just pressed this frame (edge detection).
+    ///
+    /// Returns `true` only on the frame the key transitions from up to down.
+    pub fn is_key_pressed(&self, key: Key) -> bool {
+        self.input.is_key_pressed(key)
+    }
+
+    /// Check if a key was just released this frame (edge detection).
+    ///
+    /// Returns `true` only on the frame the key transitions from down to up, e.g. to fire
+    /// a charged shot when the button is let go.
+    pub fn is_key_released(&self, key: Key) -> bool {
+        self.input.is_key_released(key)
+    }
+
+    /// Check if all `keys` are held and the last of them went down this frame, e.g.
+    /// `&[Key::Down, Key::Right]` for a fighting-game input. Fires once per chord.
+    pub fn is_chord_pressed(&self, keys: &[Key]) -> bool {
+        self.input.is_chord_pressed(keys)
+    }
+
+    /// Check if `key` was pressed this frame within `window_secs` of its previous press.
+    pub fn is_key_double_tapped(&self, key: Key, window_secs: f32) -> bool {
+        self.input.is_key_double_tapped(key, window_secs)
+    }
+
+    /// Check if `key` has now been held for `secs`; true only on the frame the hold
+    /// reaches it.
+    pub fn is_key_long_pressed(&self, key: Key, secs: f32) -> bool {
+        self.input.is_key_long_pressed(key, secs)
+    }
+
+    /// Seconds `key` has been held (by frame timestamps), or `None` if it is up.
+    pub fn key_hold_time(&self, key: Key) -> Option<f32> {
+        self.input.key_hold_time(key)
+    }
+
+    /// Check if a key producing `c` on the user's keyboard layout is held (case-insensitive),
+    /// e.g. `'z'` for the key labelled Z whether the layout is QWERTY or AZERTY.
+    pub fn is_logical_key_down(&self, c: char) -> bool {
+        self.input.is_logical_key_down(c)
+    }
+
+    /// The label of physical `key` on the user's layout ("Z" for `Key::W` on AZERTY), for
+    /// rebindable-controls UIs.
+    ///
+    /// winit can't query the layout, so labels are learned from key presses; a key not yet
+    /// pressed shows its US QWERTY label.
+    pub fn key_display_name(&self, key: Key) -> String {
+        self.input.key_display_name(key)
+    }
+
+    /// Check if a mouse button is currently held down.
+    pub fn is_mouse_button_down(&self, btn: MouseButton) -> bool {
+        self.input.is_mouse_button_down(btn)
+    }
+
+    /// Check if a mouse button was just pressed this frame (edge detection).
+    pub fn is_mouse_button_pressed(&self, btn: MouseButton) -> bool {
+        self.input.is_mouse_button_pressed(btn)
+    }
+
+    /// Check if a mouse button was just released this frame (edge detection).
+    pub fn is_mouse_button_released(&self, btn: MouseButton) -> bool {
+        self.input.is_mouse_button_released(btn)
+    }
+
+    /// Current mouse cursor position in screen pixels.
+    ///
+    /// Returns `(x, y)` where `(0, 0)` is the top-left corner.
+    pub fn mouse_position(&self) -> (f32, f32) {
+        self.input.mouse_position()
+    }
+
+    /// Whether either Alt (Option on macOS) key is held, e.g. for Alt+Enter.
+    pub fn is_alt_down(&self) -> bool {
+        self.input.is_alt_down()
+    }
+
+    /// Mouse wheel scroll delta for this frame.
+    ///
+    /// Returns `(horizontal, vertical)`. Positive vertical = scroll up.
+    /// Resets to `(0, 0)` at the start of each frame.
+    pub fn mouse_wheel(&self) -> (f32, f32) {
+        self.input.mouse_wheel()
+    }
+
+    /// Text currently on the clipboard, e.g. to paste into a console or name field.
+    ///
+    /// Uses the OS clipboard with the `clipboard` feature; otherwise the clipboard is
+    /// private to this process. Returns `None` if the clipboard is empty or not text.
+    pub fn clipboard_text(&mut self) -> Option<String> {
+        self.clipboard.text()
+    }
+
+    /// Put `text` on the clipboard.
+    pub fn set_clipboard_text(&mut self, text: &str) {
+        self.clipboard.set_text(text);
+    }
+
+    /// Keep the cursor inside `rect` (window pixels), e.g. for RTS camera control.
+    ///
+    /// The OS cursor is confined to the window where the platform supports it, and
+    /// `mouse_position()` is clamped to `rect`. Pass `None` to release.
+    pub fn confine_cursor_to(&mut self, window: &winit::window::Window, rect: Option<Rect>) {
+        use winit::window::CursorGrabMode;
+
+        let mode = if rect.is_some() {
+            CursorGrabMode::Confined
+        } else {
+            CursorGrabMode::None
+        };
+        // Unsupported on some platforms (e.g. macOS); the clamped position still applies.
+        let _ = window.set_cursor_grab(mode);
+        self.input.set_cursor_confine(rect);
+    }
+
+    /// All connected monitors, e.g. for a display or resolution picker.
+    pub fn monitors(&self, window: &winit::window::Window) -> Vec<MonitorInfo> {
+        let primary = window.primary_monitor();
+        window
+            .available_monitors()
+            .map(|m| MonitorInfo::from_handle(&m, primary.as_ref()))
+            .collect()
+    }
+
+    /// Refresh rate of the monitor the window is on, for picking a frame cap. `None` if
+    /// the platform doesn't report it.
+    pub fn current_refresh_rate(&self, window: &winit::window::Window) -> Option<f32> {
+        window
+            .current_monitor()?
+            .refresh_rate_millihertz()
+            .map(monitor::millihertz_to_hz)
+    }
+
+    /// Current window placement, fullscreen state, vsync and MSAA settings.
+    pub fn config(&self, window: &winit::window::Window) -> ContextConfig {
+        ContextConfig {
+            placement: Some(WindowPlacement::capture(window)),
+            fullscreen: window.fullscreen().is_some(),
+            vsync: self.renderer.vsync(),
+            msaa_samples: self.renderer.msaa_samples(),
+        }
+    }
+
+    /// Apply `config` to `window` and the renderer.
+    ///
+    /// The MSAA sample count falls back to a supported one; read it back with
+    /// `config(&window)` to see what was applied.
+    pub fn apply_config(&mut self, window: &winit::window::Window, config: &ContextConfig) {
+        if config.fullscreen {
+            self.set_fullscreen(window, true);
+            // Leaving fullscreen later goes back to the saved windowed geometry.
+            if config.placement.is_some() {
+                self.windowed_placement = config.placement.clone();
+            }
+        } else {
+            self.set_fullscreen(window, false);
+            if let Some(placement) = &config.placement {
+                placement.apply(window);
+            }
+        }
+        self.renderer.set_vsync(config.vsync);
+        self.renderer.set_msaa_samples(config.msaa_samples);
+    }
+
+    /// Switch between borderless fullscreen and the windowed placement it was entered from,
+    /// resizing the surface right away. Returns whether the window is now fullscreen.
+    ///
+    /// ```ignore
+    /// if ctx.is_alt_down() && ctx.is_key_pressed(Key::Enter) {
+    ///     ctx.toggle_fullscreen(&window);
+    /// }
+    /// ```
+    pub fn toggle_fullscreen(&mut self, window: &winit::window::Window) -> bool {
+        let fullscreen = window.fullscreen().is_none();
+        self.set_fullscreen(window, fullscreen);
+        fullscreen
+    }
+
+    /// Enter borderless fullscreen on the window's current monitor, remembering the windowed
+    /// position and size, or leave it and restore them.
+    pub fn set_fullscreen(&mut self, window: &winit::window::Window, fullscreen: bool) {
+        use winit::window::Fullscreen;
+
+        if fullscreen == window.fullscreen().is_some() {
+            return;
+        }
+        if fullscreen {
+            self.windowed_placement = Some(WindowPlacement::capture(window));
+            let monitor = window.current_monitor();
+            // The platform's `Resized` event can lag a frame behind; don't stretch that frame.
+            // Borderless fullscreen covers the monitor, whose size is in physical pixels like
+            // the surface.
+            if let Some(size) = monitor.as_ref().map(|m| m.size()) {
+                self.resize(size.width, size.height);
+            }
+            window.set_fullscreen(Some(Fullscreen::Borderless(monitor)));
+        } else {
+            window.set_fullscreen(None);
+            if let Some(placement) = self.windowed_placement.take() {
+                placement.apply(window);
+                self.resize(placement.width, placement.height);
+            }
+        }
+    }
+
+    /// Save the current `config(window)` to `path`.
+    pub fn save_config(
+        &self,
+        window: &winit::window::Window,
+        path: impl AsRef<std::path::Path>,
+    ) -> std::io::Result<()> {
+        self.config(window).save(path)
+    }
+
+    /// Load a config saved with `save_config` and apply it. Returns `None` (changing
+    /// nothing) if the file is missing or malformed.
+    pub fn load_config(
+        &mut self,
+        window: &winit::window::Window,
+        path: impl AsRef<std::path::Path>,
+    ) -> Option<ContextConfig> {
+        let config = ContextConfig::load(path)?;
+        self.apply_config(window, &config);
+        Some(config)
+    }
+
+    /// Wait for vertical sync when presenting (the default) or present immediately.
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.renderer.set_vsync(vsync);
+    }
+
+    /// Enable MSAA with `samples` samples per pixel (1 disables it). Returns the count in
+    /// use, which is lower if the GPU doesn't support the requested one.
+    pub fn set_msaa_samples(&mut self, samples: u32) -> u32 {
+        self.renderer.set_msaa_samples(samples)
+    }
+
+    /// Render the scene at `scale` (0.5 to 1.0) of the window's resolution and upscale it
+    /// when presenting, trading sharpness for fill rate. Drawing coordinates don't change.
+    /// Turns off dynamic resolution. Returns the scale in use.
+    pub fn set_render_scale(&mut self, scale: f32) -> f32 {
+        self.resolution_scaler = None;
+        self.renderer.set_render_scale(scale)
+    }
+
+    /// The scale frames are currently rendered at, e.g. to show in a debug overlay while
+    /// dynamic resolution is on.
+    pub fn render_scale(&self) -> f32 {
+        self.renderer.render_scale()
+    }
+
+    /// Lower the render scale automatically whenever frames take longer than
+    /// `target_frame_time` seconds (e.g. `Some(1.0 / 60.0)`), and raise it again once they
+    /// fit; `None` stops adjusting and keeps the current scale.
+    pub fn set_dynamic_resolution(&mut self, target_frame_time: Option<f32>) {
+        let scale = self.renderer.render_scale();
+        self.resolution_scaler = target_frame_time
+            .filter(|t| *t > 0.0)
+            .map(|t| dynamic_resolution::ResolutionScaler::new(t, scale));
+    }
+
+    /// Target frame time of dynamic resolution, if it is on.
+    pub fn dynamic_resolution(&self) -> Option<f32> {
+        self.resolution_scaler.as_ref().map(|s| s.target())
+    }
+
+    /// Set the OS cursor shape, e.g. `CursorIcon::Pointer` over buttons or
+    /// `CursorIcon::EwResize` on a splitter. Also accepts a `CustomCursor`.
+    pub fn set_cursor(&self, window: &winit::window::Window, cursor: impl Into<Cursor>) {
+        window.set_cursor(cursor);
+    }
+
+    /// Show or hide the OS cursor, e.g. to draw your own at `mouse_position()`.
+    pub fn set_cursor_visible(&self, window: &winit::window::Window, visible: bool) {
+        window.set_cursor_visible(visible);
+    }
+
+    /// Create a cursor image from straight-alpha RGBA8 pixels for `set_cursor`.
+    ///
+    /// `hotspot` is the pixel (from the top-left) that marks the click position. Create
+    /// cursors once and reuse them; each call uploads a new image to the OS.
+    pub fn create_custom_cursor(
+        &self,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        rgba: Vec<u8>,
+        width: u16,
+        height: u16,
+        hotspot: (u16, u16),
+    ) -> Result<CustomCursor, LibforgeError> {
+        let source = CustomCursor::from_rgba(rgba, width, height, hotspot.0, hotspot.1)
+            .map_err(|e| LibforgeError::Platform(format!("invalid cursor image: {e}")))?;
+        Ok(event_loop.create_custom_cursor(source))
+    }
+
+    /// Like `create_custom_cursor`, decoding an encoded image (PNG, ...) first.
+    ///
+    /// Pass the same bytes given to `load_texture_from_bytes` to reuse a texture's art;
+    /// textures don't keep a CPU copy of their pixels.
+    pub fn create_custom_cursor_from_bytes(
+        &self,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        bytes: &[u8],
+        hotspot: (u16, u16),
+    ) -> Result<CustomCursor, LibforgeError> {
+        let img = image::load_from_memory(bytes)
+            .map_err(|e| LibforgeError::Platform(format!("cursor image decode failed: {e}")))?
+            .to_rgba8();
+        let (w, h) = img.dimensions();
+        let (w, h) = (
+            u16::try_from(w).map_err(|_| LibforgeError::Platform("cursor too large".into()))?,
+            u16::try_from(h).map_err(|_| LibforgeError::Platform("cursor too large".into()))?,
+        );
+        self.create_custom_cursor(event_loop, img.into_raw(), w, h, hotspot)
+    }
+
+    /// Set the title bar and taskbar icon from an encoded image (PNG, ...). Art larger than
+    /// 256 pixels is downscaled. macOS takes the icon from the app bundle instead.
+    pub fn set_window_icon_from_bytes(
+        &self,
+        window: &winit::window::Window,
+        bytes: &[u8],
+    ) -> Result<(), LibforgeError> {
+        let icon = icon::icon_from_bytes(bytes)?;
+        #[cfg(target_os = "windows")]
+        {
+            use winit::platform::windows::WindowExtWindows;
+            window.set_taskbar_icon(Some(icon.clone()));
+        }
+        window.set_window_icon(Some(icon));
+        Ok(())
+    }
+
+    /// Decode an icon for `WindowAttributes::with_window_icon`, so the window never shows
+    /// the default one.
+    pub fn window_icon_from_bytes(bytes: &[u8]) -> Result<winit::window::Icon, LibforgeError> {
+        icon::icon_from_bytes(bytes)
+    }
+
+    /// Edge-scroll direction from the cursor position, each axis in `[-1, 1]`.
+    ///
+    /// Nonzero when the cursor is within `margin` pixels of the window edge (or of the
+    /// `confine_cursor_to` rect), growing to full speed at the edge. Multiply by a pan speed
+    /// and `frame_time()` to move the camera.
+    pub fn edge_scroll(&self, margin: f32) -> (f32, f32) {
+        if !self.input.cursor_inside() {
+            return (0.0, 0.0);
+        }
+        let bounds = self.input.cursor_confine().unwrap_or_else(|| {
+            let (w, h) = self.renderer.surface_size();
+            Rect {
+                x: 0.0,
+                y: 0.0,
+                w: w as f32,
+                h: h as f32,
+            }
+        });
+        input::edge_scroll(self.mouse_position(), bounds, margin)
+    }
+
+    /// Settings of the gamepad-driven virtual cursor (speed, deadzone, curve, snapping).
+    pub fn virtual_cursor_mut(&mut self) -> &mut VirtualCursor {
+        &mut self.virtual_cursor
+    }
+
+    /// Set the focusable UI rectangles the virtual cursor snaps to (window pixels).
+    pub fn set_cursor_snap_targets(&mut self, targets: &[Rect]) {
+        self.virtual_cursor.set_targets(targets);
+    }
+
+    /// Move the cursor with an analog stick; call once per frame with axes in `[-1, 1]`.
+    ///
+    /// `mouse_position()` follows the virtual cursor until the real mouse moves again.
+    /// Axes use screen orientation: positive `y` moves down.
+    pub fn update_virtual_cursor(&mut self, stick_x: f32, stick_y: f32) {
+        let (w, h) = self.renderer.surface_size();
+        let bounds = self.input.cursor_confine().unwrap_or(Rect {
+            x: 0.0,
+            y: 0.0,
+            w: w as f32,
+            h: h as f32,
+        });
+        let pos = self.virtual_cursor.step(
+            self.input.mouse_position(),
+            (stick_x, stick_y),
+            self.frame_dt,
+            bounds,
+        );
+        self.input.set_mouse_position(pos);
+    }
+
+    /// Press or release a mouse button from a gamepad button, for virtual cursor clicks.
+    pub fn set_virtual_cursor_button(&mut self, button: MouseButton, pressed: bool) {
+        self.input.set_mouse_button(button, pressed);
+    }
+
+    /// Rumble gamepad `idx` for `duration` seconds; `strong` and `weak` are the low- and
+    /// high-frequency motor speeds in `[0, 1]`.
+    ///
+    /// `idx` is the gilrs `GamepadId` as a `usize`. Overlapping effects combine, each motor
+    /// running at the strongest; the device is updated at the next `begin_drawing()`.
+    /// Gamepads without force feedback are ignored.
+    #[cfg(feature = "gamepad")]
+    pub fn gamepad_rumble(&mut self, idx: usize, strong: f32, weak: f32, duration: f32) {
+        self.rumble.start(idx, Rumble { strong, weak }, duration);
+    }
+
+    /// Stop all rumble effects on gamepad `idx`.
+    #[cfg(feature = "gamepad")]
+    pub fn stop_gamepad_rumble(&mut self, idx: usize) {
+        self.rumble.stop(idx);
+    }
+
+    /// Motor speeds gamepad `idx` runs at this frame (zero when idle).
+    #[cfg(feature = "gamepad")]
+    pub fn gamepad_rumble_level(&self, idx: usize) -> Rumble {
+        self.rumble.level(idx)
+    }
+
+    /// Play a full-screen transition over everything drawn for the next `seconds`: the
+    /// screen is hidden halfway through (`TransitionEvent::Covered`, the moment to swap
+    /// scenes) and revealed again by the end. Replaces a transition already running.
+    pub fn start_transition(&mut self, transition: Transition, seconds: f32) {
+        self.transition = Some(transition::TransitionState::new(transition, seconds));
+    }
+
+    /// The milestone the running transition reached this frame, if any.
+    pub fn transition_event(&self) -> Option<TransitionEvent> {
+        self.transition_event
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    /// Pinch, two-finger pan, and rotate deltas for this frame.
+    ///
+    /// Combines trackpad gesture events (macOS/iOS) with two-finger touch input.
+    /// `ctx.gesture().apply_to_camera(&mut camera)` gives map-style camera control.
+    pub fn gesture(&self) -> Gesture {
+        self.gestures.gesture()
+    }
+
+    /// Taps, double taps, long presses and swipes recognized from single-finger touches
+    /// this frame, in window pixels. Two-finger pinch and rotate are in `gesture()`.
+    pub fn touch_gestures(&self) -> &[TouchGesture] {
+        self.gestures.touch_gestures()
+    }
+
+    /// Distance and timing limits used to recognize `touch_gestures()`.
+    pub fn gesture_thresholds_mut(&mut self) -> &mut GestureThresholds {
+        &mut self.gestures.thresholds
+    }
+
+    /// Clear the screen to a solid color. Call after `begin_drawing()` and before any draw calls.
+    pub fn clear_background(&mut self, color: Color) {
+        self.renderer.begin_frame(Some(color.0));
+    }
+
+    /// Must be called at the start of each frame. Optional clear color.
+    /// 
+    /// **Deprecated:** prefer `begin_drawing()` + `clear_background(color)` for clarity.
+    #[deprecated(since = "0.1.0", note = "use `begin_drawing()` + `clear_background(color)` instead")]
+    pub fn begin_frame(&mut self, clear: Option<Color>) {
+        self.renderer.begin_frame(clear.map(|c| c.0));
+    }
+
+    /// Immediate draw a filled rectangle (in logical pixels)
+    pub fn draw_rect(&mut self, rect: Rect, color: Color) {
+        self.renderer.draw_rect(rect, color);
+    }
+
+    /// Draw a filled rectangle rotated by `rotation` radians around a pivot.
+    ///
+    /// `origin` is relative to the rect's top-left corner: `(rect.w / 2.0, rect.h / 2.0)`
+    /// rotates around the center, `(0.0, 0.0)` around the top-left corner.
+    pub fn draw_rect_ex(&mut self, rect: Rect, origin: (f32, f32), rotation: f32, color: Color) {
+        self.renderer.draw_rect_ex(rect, origin, rotation, color.0);
+    }
+
+    /// Immediate draw a line
+    pub fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color) {
+        self.renderer.draw_line(x1, y1, x2, y2, thickness, color.0);
+    }
+
+    /// Draw a filled circle centered at (x, y) with given radius (in logical pixels).
+    /// `segments` controls the tessellation (higher = smoother). Use ~32 for good quality.
+    pub fn draw_circle(&mut self, x: f32, y: f32, radius: f32, segments: usize, color: Color) {
+        self.renderer.draw_circle(x, y, radius, segments, color.0);
+    }
+
+    /// Draw a filled circle with the segment count chosen automatically.
+    ///
+    /// Detail follows the circle's size on screen (including camera zoom), so circles stay
+    /// smooth when zoomed in and cost only a few triangles when tiny.
+    pub fn draw_circle_auto(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        self.renderer.draw_ellipse(x, y, radius, radius, color.0);
+    }
+
+    /// Draw a pixel-perfect filled circle as a single SDF quad.
+    ///
+    /// Unlike `draw_circle`, the edge is computed per pixel, so it stays smooth at any zoom
+    /// and costs 6 vertices regardless of size.
+    pub fn draw_sdf_circle(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        self.renderer
+            .draw_sdf_box([x, y], [radius, radius], radius, 0.0, color.0);
+    }
+
+    /// Draw a filled rectangle with rounded corners (SDF, anti-aliased).
+    pub fn draw_rounded_rect(&mut self, rect: Rect, corner_radius: f32, color: Color) {
+        let half = [rect.w * 0.5, rect.h * 0.5];
+        let center = [rect.x + half[0], rect.y + half[1]];
+        self.renderer
+            .draw_sdf_box(center, half, corner_radius, 0.0, color.0);
+    }
+
+    /// Draw a capsule (a line with round caps) from `(x1, y1)` to `(x2, y2)` (SDF, anti-aliased).
+    pub fn draw_capsule(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, radius: f32, color: Color) {
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let length = (dx * dx + dy * dy).sqrt();
+        let center = [(x1 + x2) * 0.5, (y1 + y2) * 0.5];
+        self.renderer.draw_sdf_box(
+            center,
+            [length * 0.5 + radius, radius],
+            radius,
+            dy.atan2(dx),
+            color.0,
+        );
+    }
+
+    /// Draw a filled, axis-aligned ellipse centered at (cx, cy) with radii `rx` and `ry`.
+    ///
+    /// Tessellation is chosen automatically like `draw_circle_auto`.
+    pub fn draw_ellipse(&mut self, cx: f32, cy: f32, rx: f32, ry: f32, color: Color) {
+        self.renderer.draw_ellipse(cx, cy, rx, ry, color.0);
+    }
+
+    /// Draw a filled triangle.
+    pub fn draw_triangle(&mut self, p1: (f32, f32), p2: (f32, f32), p3: (f32, f32), color: Color) {
+        self.draw_triangle_gradient(p1, color, p2, color, p3, color);
+    }
+
+    /// Draw a filled triangle with a color per corner, blended across its area.
+    pub fn draw_triangle_gradient(
+        &mut self,
+        p1: (f32, f32),
+        c1: Color,
+        p2: (f32, f32),
+        c2: Color,
+        p3: (f32, f32),
+        c3: Color,
+    ) {
+        let vertex = |p: (f32, f32), c: Color| vertex::Vertex {
+            pos: [p.0, p.1],
+            uv: [0.0, 0.0],
+            color: c.0,
+        };
+        self.renderer
+            .draw_color_vertices(&[vertex(p1, c1), vertex(p2, c2), vertex(p3, c3)]);
+    }
+
+    /// Draw raw triangles: every three vertices form one triangle, in the same coordinates
+    /// as the other draw calls (the model transform and camera apply).
+    ///
+    /// With `Some(texture)` each vertex's `uv` samples the texture and its `color` tints it;
+    /// with `None` the vertex colors are drawn directly. Draws batch with neighbouring
+    /// shapes and sprites, so custom tessellators and vector renderers can feed their
+    /// output straight in. A trailing partial triangle is ignored.
+    pub fn draw_vertices(&mut self, vertices: &[vertex::Vertex], texture: Option<TextureId>) {
+        let vertices = &vertices[..vertices.len() / 3 * 3];
+        match texture {
+            Some(texture) => self.renderer.draw_texture_vertices(texture, vertices),
+            None => self.renderer.draw_color_vertices(vertices),
+        }
+    }
+
+    /// Fill a vector path under `rule`. Curves are flattened finely enough for the current
+    /// camera zoom.
+    #[cfg(feature = "paths")]
+    pub fn fill_path(&mut self, path: &path::Path, rule: path::FillRule, color: Color) {
+        let tolerance = 0.25 / self.renderer.pixels_per_unit().max(1e-6);
+        let vertices: Vec<vertex::Vertex> = path
+            .fill_triangles(rule, tolerance)
+            .into_iter()
+            .map(|pos| vertex::Vertex {
+                pos,
+                uv: [0.0, 0.0],
+                color: color.0,
+            })
+            .collect();
+        self.renderer.draw_color_vertices(&vertices);
+    }
+
+    /// Stroke a vector path's outline with `style`'s width, joins, caps and dashes.
+    #[cfg(feature = "paths")]
+    pub fn stroke_path(&mut self, path: &path::Path, style: &path::StrokeStyle, color: Color) {
+        let tolerance = 0.25 / self.renderer.pixels_per_unit().max(1e-6);
+        let vertices: Vec<vertex::Vertex> = path
+            .stroke_triangles(style, tolerance)
+            .into_iter()
+            .map(|pos| vertex::Vertex {
+                pos,
+                uv: [0.0, 0.0],
+                color: color.0,
+            })
+            .collect();
+        self.renderer.draw_color_vertices(&vertices);
+    }
+
+    /// Draw a filled quad with a color per corner, e.g. a fake-3D ground plane or a
+    /// gradient panel. `points` go around the quad in order; it is split along the
+    /// `points[0]`-`points[2]` diagonal, so keep it convex.
+    pub fn draw_quad(&mut self, points: [(f32, f32); 4], colors: [Color; 4]) {
+        let vertex = |i: usize| vertex::Vertex {
+            pos: [points[i].0, points[i].1],
+            uv: [0.0, 0.0],
+            color: colors[i].0,
+        };
+        self.renderer.draw_color_vertices(&[
+            vertex(0),
+            vertex(1),
+            vertex(2),
+            vertex(0),
+            vertex(2),
+            vertex(3),
+        ]);
+    }
+
+    /// Draw a texture, scaled to fit the destination rectangle.
+    ///
+    /// The texture is tinted by multiplying with the `tint` color.
+    /// Use `Color::WHITE` for no tint.
+    pub fn draw_texture(&mut self, tex: TextureId, rect: Rect, tint: Color) {
+        self.renderer.draw_texture(tex, rect, tint.0);
+    }
+
+    /// Draw a texture with UVs `uv_offset .. uv_offset + uv_scale` instead of `0..1`.
+    ///
+    /// Scroll water or conveyor belts by animating `uv_offset`, and tile with a scale above
+    /// one. Both need `set_texture_wrap(tex, TextureWrap::Repeat, TextureWrap::Repeat)` (or
+    /// `TextureOptions::wrap`); clamped textures stretch their edge texels instead.
+    pub fn draw_texture_uv(
+        &mut self,
+        tex: TextureId,
+        dst: Rect,
+        uv_offset: (f32, f32),
+        uv_scale: (f32, f32),
+        tint: Color,
+    ) {
+        self.renderer
+            .draw_texture_uv(tex, dst, uv_offset, uv_scale, tint.0);
+    }
+
+    /// Draw a portion of a texture (subtexture/sprite).
+    ///
+    /// `src` defines the region in the source texture (in pixels).
+    /// `dst` defines where to draw it on screen.
+    pub fn draw_subtexture(&mut self, tex: TextureId, src: Rect, dst: Rect, tint: Color) {
+        self.renderer.draw_subtexture(tex, src, dst, tint.0);
+    }
+
+    /// Draw a portion of a texture mirrored by `flip`, e.g. `Flip::X` for a character
+    /// facing left. The UVs are swapped, so `dst` and culling behave as for
+    /// `draw_subtexture`.
+    pub fn draw_subtexture_flipped(
+        &mut self,
+        tex: TextureId,
+        src: Rect,
+        dst: Rect,
+        tint: Color,
+        flip: Flip,
+    ) {
+        self.renderer
+            .draw_subtexture_flipped(tex, src, dst, tint.0, flip);
+    }
+
+    /// Draw every visible sprite in `scene`, updating dirty world transforms first.
+    ///
+    /// Sprites are drawn in `z` order on top of the current model transform, so a scene can
+    /// be placed with `translate`/`scale` or drawn inside `begin_mode_2d`.
+    pub fn draw_scene(&mut self, scene: &mut SceneGraph) {
+        let base = self.renderer.get_transform();
+        self.renderer.push_matrix();
+        for (world, sprite) in scene.draw_list() {
+            let Some(tex) = sprite.texture else {
+                continue;
+            };
+            self.renderer.set_transform(base * world);
+            match sprite.src {
+                Some(src) => self
+                    .renderer
+                    .draw_subtexture(tex, src, sprite.rect, sprite.tint.0),
+                None => self.renderer.draw_texture(tex, sprite.rect, sprite.tint.0),
+            }
+        }
+        self.renderer.pop_matrix();
+    }
+
+    /// Register custom GPU rendering that runs in every `end_drawing`, after libforge's
+    /// pass and before present (e.g. egui, or a 3D view drawn on top).
+    ///
+    /// The plugin gets the device, queue, surface view and command encoder through a
+    /// `RenderTarget`; closures taking `&mut RenderTarget` work as plugins.
+    pub fn add_render_plugin(&mut self, plugin: impl RenderPlugin + 'static) {
+        self.renderer.add_render_plugin(Box::new(plugin));
+    }
+
+    /// Remove all plugins added with `add_render_plugin`.
+    pub fn clear_render_plugins(&mut self) {
+        self.renderer.clear_render_plugins();
+    }
+
+    /// Run `f` with the GPU handles once, at the end of the current frame.
+    pub fn with_render_pass(&mut self, f: impl FnOnce(&mut RenderTarget<'_>) + 'static) {
+        self.renderer.with_render_pass(Box::new(f));
+    }
+
+    /// Like `draw_subtexture`, but returns an error if `tex` isn't loaded instead of
+    /// applying the missing-texture policy.
+    pub fn try_draw_subtexture(
+        &mut self,
+        tex: TextureId,
+        src: Rect,
+        dst: Rect,
+        tint: Color,
+    ) -> Result<(), LibforgeError> {
+        Ok(self.renderer.try_draw_subtexture(tex, src, dst, tint.0)?)
+    }
+
+    /// Choose what texture draws do with a `TextureId` that isn't loaded: warn once per
+    /// id (the default; on stderr, or through `log` with the `log` feature), draw a magenta
+    /// placeholder, or ignore it.
+    pub fn set_missing_texture_policy(&mut self, policy: MissingTexturePolicy) {
+        self.renderer.set_missing_texture_policy(policy);
+    }
+
+    /// Draw a texture with HSV color grading (hue shift, saturation, brightness).
+    ///
+    /// Useful for enemy recolors and status effects without separate textures.
+    pub fn draw_texture_adjusted(
+        &mut self,
+        tex: TextureId,
+        rect: Rect,
+        tint: Color,
+        adjust: ColorAdjust,
+    ) {
+        self.renderer
+            .draw_texture_adjusted(tex, rect, tint.0, adjust);
+    }
+
+    /// Draw a portion of a texture with HSV color grading.
+    pub fn draw_subtexture_adjusted(
+        &mut self,
+        tex: TextureId,
+        src: Rect,
+        dst: Rect,
+        tint: Color,
+        adjust: ColorAdjust,
+    ) {
+        self.renderer
+            .draw_subtexture_adjusted(tex, src, dst, tint.0, adjust);
+    }
+
+    /// Upload static triangle-list vertices to a GPU buffer once.
+    ///
+    /// Use for large static geometry (terrain outlines, level art) that would otherwise be
+    /// re-tessellated and re-uploaded every frame. Vertex positions are in local space.
+    pub fn create_mesh(&mut self, vertices: &[vertex::Vertex]) -> MeshId {
+        self.renderer.create_mesh(vertices)
+    }
+
+    /// Free a mesh created with `create_mesh`.
+    pub fn destroy_mesh(&mut self, mesh: MeshId) {
+        self.renderer.destroy_mesh(mesh);
+    }
+
+    /// Draw a retained mesh. `transform` is applied on the GPU after the current model matrix,
+    /// and `tint` multiplies the mesh's vertex colors.
+    pub fn draw_mesh(&mut self, mesh: MeshId, transform: glam::Mat4, tint: Color) {
+        self.renderer.draw_mesh(mesh, transform, tint.0);
+    }
+
+    /// Draw a soft glowing particle on the additive particle layer.
+    ///
+    /// Particles are rendered after everything else in the frame with additive blending,
+    /// so they can be emitted in any order without sorting artifacts. Particles drawn while
+    /// depth sorting is on take the depth of their center (or `set_draw_depth`) and fade
+    /// into sorted sprites in front of them; see `set_particle_softness`.
+    pub fn draw_particle(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        self.renderer.draw_particle(x, y, radius, color.0);
+    }
+
+    /// How many depth units (same as y) in front of a depth-sorted particle a sorted sprite
+    /// starts to fade it out. The default 0 hides the particle sharply where the sprite is.
+    pub fn set_particle_softness(&mut self, softness: f32) {
+        self.renderer.set_particle_softness(softness);
+    }
+
+    pub fn particle_softness(&self) -> f32 {
+        self.renderer.particle_softness()
+    }
+
+    // -------------------------------------------------------------------------
+    //
+    // Debug draw layer: gizmos drawn on top of everything else in the frame, through the
+    // camera and model transform active at the call. Enabled by default in debug builds
+    // only; when disabled the calls cost nothing.
+    // -------------------------------------------------------------------------
+
+    /// Turn the debug draw layer on or off.
+    pub fn set_debug_draw(&mut self, enabled: bool) {
+        self.renderer.set_debug_draw(enabled);
+    }
+
+    /// Whether `debug_*` draws are currently shown.
+    pub fn debug_draw_enabled(&self) -> bool {
+        self.renderer.debug_draw_enabled()
+    }
+
+    fn debug_shape(&mut self, build: impl FnOnce(&mut Vec<vertex::Vertex>)) {
+        if !self.renderer.debug_draw_enabled() {
+            return;
+        }
+        let mut verts = Vec::new();
+        build(&mut verts);
+        self.renderer.draw_debug_vertices(&verts);
+    }
+
+    /// Debug line from `(x1, y1)` to `(x2, y2)`.
+    pub fn debug_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, color: Color) {
+        self.debug_shape(|v| debug_draw::line(v, x1, y1, x2, y2, color));
+    }
+
+    /// Debug rectangle outline.
+    pub fn debug_rect(&mut self, rect: Rect, color: Color) {
+        self.debug_shape(|v| debug_draw::rect(v, rect, color));
+    }
+
+    /// Debug circle outline.
+    pub fn debug_circle(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        self.debug_shape(|v| debug_draw::circle(v, x, y, radius, color));
+    }
+
+    /// Debug outlines of every collider in `world` (dynamic green, kinematic yellow,
+    /// static gray) plus contact normals in red.
+    #[cfg(feature = "physics")]
+    pub fn debug_draw_physics(&mut self, world: &physics::PhysicsWorld) {
+        use physics::{BodyKind, Collider};
+
+        for (_, body) in world.bodies() {
+            let color = match body.kind {
+                BodyKind::Dynamic => Color([0.2, 1.0, 0.3, 1.0]),
+                BodyKind::Kinematic => Color([1.0, 0.9, 0.2, 1.0]),
+                BodyKind::Static => Color([0.6, 0.6, 0.6, 1.0]),
+            };
+            match body.collider {
+                Collider::Rect { .. } => {
+                    let corners = body.corners();
+                    for (i, &(x1, y1)) in corners.iter().enumerate() {
+                        let (x2, y2) = corners[(i + 1) % corners.len()];
+                        self.debug_line(x1, y1, x2, y2, color);
+                    }
+                }
+                Collider::Circle { radius } => {
+                    let (x, y) = body.position;
+                    self.debug_circle(x, y, radius, color);
+                    // A spoke so rolling is visible.
+                    let (sin, cos) = body.rotation.sin_cos();
+                    self.debug_line(x, y, x + cos * radius, y + sin * radius, color);
+                }
+            }
+        }
+        for contact in world.contacts() {
+            let (x, y) = contact.point;
+            let (nx, ny) = contact.normal;
+            self.debug_arrow(
+                x,
+                y,
+                x + nx * 12.0,
+                y + ny * 12.0,
+                Color([1.0, 0.2, 0.2, 1.0]),
+            );
+        }
+    }
+
+    /// Debug arrow from `(x1, y1)` pointing at `(x2, y2)` (velocities, normals, forces).
+    pub fn debug_arrow(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, color: Color) {
+        self.debug_shape(|v| debug_draw::arrow(v, x1, y1, x2, y2, color));
+    }
+
+    /// Debug label in the built-in pixel font; `size` is the glyph height.
+    pub fn debug_text(&mut self, text: &str, x: f32, y: f32, size: f32, color: Color) {
+        self.debug_shape(|v| debug_draw::text(v, text, x, y, size, color));
+    }
+
+    /// Draw an integer in `font`, as `draw_text` would draw its decimal form.
+    ///
+    /// Formats into a stack buffer and reuses the cached layout while the value stays the
+    /// same, so it is cheap enough for score counters redrawn every frame.
+    pub fn draw_number(
+        &mut self,
+        font: FontId,
+        value: i64,
+        x: f32,
+        y: f32,
+        size: f32,
+        color: Color,
+    ) {
+        let mut buf = [0; 20];
+        let digits = text::format_int(value, &mut buf);
+        let key = text::LayoutKey::plain(font, size);
+        self.text
+            .draw(&mut self.renderer, key, digits, (x, y), color);
+    }
+
+    /// `draw_number` in the built-in pixel font; `size` is the glyph height.
+    ///
+    /// Needs no loaded font and reuses precomputed digit quads, for profiler readouts.
+    pub fn draw_debug_number(&mut self, value: i64, x: f32, y: f32, size: f32, color: Color) {
+        let pixel = size / debug_font::GLYPH_H as f32;
+        debug_font::for_each_number_rect(value, x, y, pixel, |r| {
+            self.renderer.draw_rect(r, color);
+        });
+    }
+
+    /// Load a TrueType/OpenType font (`.ttf`/`.otf` bytes) for `draw_text`.
+    pub fn load_font_from_bytes(&mut self, bytes: &[u8]) -> Result<FontId, LibforgeError> {
+        self.text
+            .load_font(bytes)
+            .map_err(|e| LibforgeError::Font(e.to_string()))
+    }
+
+    /// Register a fallback chain of loaded fonts as one font: each character is drawn from
+    /// the first font in `family` that has it, so mixed Latin/CJK strings need a single
+    /// `draw_text` call.
+    pub fn create_font_family(&mut self, family: FontFamily) -> Result<FontId, LibforgeError> {
+        self.text
+            .create_family(&family)
+            .ok_or_else(|| LibforgeError::Font(format!("invalid font family {:?}", family.fonts())))
+    }
+
+    /// Draw `text` in `font` with its top-left corner at `(x, y)`; `size` is the pixel
+    /// height from ascender to descender. `\n` starts a new line.
+    ///
+    /// Glyphs are rasterized into a shared atlas texture on first use and the string's
+    /// layout is cached while it is drawn every frame, so static labels are cheap to redraw.
+    pub fn draw_text(&mut self, font: FontId, text: &str, x: f32, y: f32, size: f32, color: Color) {
+        let key = text::LayoutKey::plain(font, size);
+        self.text.draw(&mut self.renderer, key, text, (x, y), color);
+    }
+
+    /// Width and height `draw_text` would cover for `text` at `size`.
+    pub fn measure_text(&mut self, font: FontId, text: &str, size: f32) -> (f32, f32) {
+        self.text.measure(text::LayoutKey::plain(font, size), text)
+    }
+
+    /// The active language's font fallback in `l10n` as one font for `draw_text` (e.g. with
+    /// `l10n.tr_args`), registered on first use. `None` if no fonts were set for its script.
+    #[cfg(feature = "i18n")]
+    pub fn localized_font(&mut self, l10n: &i18n::Localization) -> Option<FontId> {
+        self.text.family_for(l10n.font_fallback())
+    }
+
+    /// Draw the active language's message for `key` (see `Localization::tr`) in the font
+    /// fallback set for its script, so switching to Japanese picks up a CJK font.
+    ///
+    /// Draws nothing if no fonts were set for the script (or for Latin).
+    #[cfg(feature = "i18n")]
+    pub fn draw_text_localized(
+        &mut self,
+        l10n: &i18n::Localization,
+        key: &str,
+        x: f32,
+        y: f32,
+        size: f32,
+        color: Color,
+    ) {
+        if let Some(font) = self.localized_font(l10n) {
+            self.draw_text(font, l10n.tr(key), x, y, size, color);
+        }
+    }
+
+    /// Draw `[b]` markup spans in `bold` instead of thickening `regular`'s glyphs.
+    pub fn set_bold_font(&mut self, regular: FontId, bold: FontId) {
+        self.text.set_bold_variant(regular, bold);
+    }
+
+    /// Like `draw_text`, with inline markup for mixed styles in one string:
+    /// `[color=#ff0000]` ... `[/color]` (also `#rrggbbaa`) and `[b]` ... `[/b]`, nestable.
+    /// `[[` draws a literal `[`. Span colors keep `color`'s alpha.
+    ///
+    /// Bold uses the font registered with `set_bold_font`, or draws each glyph twice
+    /// slightly offset when there is none.
+    pub fn draw_rich_text(
+        &mut self,
+        font: FontId,
+        markup: &str,
+        x: f32,
+        y: f32,
+        size: f32,
+        color: Color,
+    ) {
+        let key = text::LayoutKey::markup(font, size, None);
+        self.text
+            .draw(&mut self.renderer, key, markup, (x, y), color);
+    }
+
+    /// `draw_rich_text` starting at the top-left of `bounds`, word-wrapped to `bounds.w`.
+    ///
+    /// Spans are wrapped together, so a colored word moves to the next line like any other.
+    /// Lines continue below `bounds.h` if the text doesn't fit; check `measure_rich_text`.
+    pub fn draw_rich_text_wrapped(
+        &mut self,
+        font: FontId,
+        markup: &str,
+        bounds: Rect,
+        size: f32,
+        color: Color,
+    ) {
+        let key = text::LayoutKey::markup(font, size, Some(bounds.w));
+        let pos = (bounds.x, bounds.y);
+        self.text.draw(&mut self.renderer, key, markup, pos, color);
+    }
+
+    /// Width and height of `markup` drawn by `draw_rich_text` (or wrapped at `max_width`),
+    /// excluding the tags.
+    pub fn measure_rich_text(
+        &mut self,
+        font: FontId,
+        markup: &str,
+        size: f32,
+        max_width: Option<f32>,
+    ) -> (f32, f32) {
+        let key = text::LayoutKey::markup(font, size, max_width);
+        self.text.measure(key, markup)
+    }
+
+    /// Draw PCM `samples` (in `[-1, 1]`) as a waveform filling `rect`.
+    ///
+    /// Samples are reduced to a min/max bar per pixel column, so passing a whole frame of
+    /// audio stays one cheap batch. `thickness` is the minimum bar height (the "silence" line).
+    pub fn draw_waveform(&mut self, samples: &[f32], rect: Rect, thickness: f32, color: Color) {
+        self.scratch_vertices.clear();
+        visualizer::waveform(&mut self.scratch_vertices, samples, rect, thickness, color);
+        self.renderer.draw_color_vertices(&self.scratch_vertices);
+    }
+
+    /// Draw spectrum magnitudes (e.g. from `capture::spectrum`) as bottom-aligned bars.
+    ///
+    /// Each value in `[0, 1]` maps to a bar height fraction of `rect.h`; `gap` is the spacing
+    /// between bars in pixels. Bins beyond one per pixel column are merged by their maximum.
+    pub fn draw_spectrum(&mut self, bins: &[f32], rect: Rect, gap: f32, color: Color) {
+        self.scratch_vertices.clear();
+        visualizer::spectrum(&mut self.scratch_vertices, bins, rect, gap, color);
+        self.renderer.draw_color_vertices(&self.scratch_vertices);
+    }
+
+    /// Draw a retained `DrawList` through the current model transform.
+    pub fn draw_list(&mut self, list: &DrawList) {
+        let vertices = list.vertices();
+        let mut drawn = 0;
+        for sprite in list.sprites() {
+            self.renderer
+                .draw_color_vertices(&vertices[drawn..sprite.at]);
+            drawn = sprite.at;
+            match sprite.src {
+                Some(src) => {
+                    self.renderer
+                        .draw_subtexture(sprite.tex, src, sprite.dst, sprite.tint.0)
+                }
+                None => self
+                    .renderer
+                    .draw_texture(sprite.tex, sprite.dst, sprite.tint.0),
+            }
+        }
+        self.renderer.draw_color_vertices(&vertices[drawn..]);
+    }
+
+    /// Draw a `DrawList` built elsewhere (typically on a worker thread) in recording order.
+    ///
+    /// Submit chunk lists one after another, or `append` them first, to keep their order.
+    pub fn submit_draw_list(&mut self, list: DrawList) {
+        self.draw_list(&list);
+    }
+
+    /// Draw a procedural background layer (starfield, noise, hex grid) filling `dest`.
+    ///
+    /// `time` drives scrolling via the layer's `speed`; pass an accumulated game time.
+    pub fn draw_background(&mut self, layer: &BackgroundLayer, dest: Rect, time: f32) {
+        self.renderer.draw_background(layer, dest, time);
+    }
+
+    /// Draw textured parallax layers (back to front) scrolled by `camera`'s position.
+    ///
+    /// Call in screen space, before `begin_mode_2d(camera)`, to fill the canvas behind
+    /// the world.
+    pub fn draw_parallax(&mut self, layers: &[ParallaxLayer], camera: Camera2D) {
+        self.renderer.draw_parallax(layers, &camera);
+    }
+
+    /// Draw an animated sprite by sampling the current frame from a sprite animation.
+    pub fn draw_sprite_animation(
+        &mut self,
+        tex: TextureId,
+        animation: &SpriteAnimation,
+        time: f32,
+        destination: Rect,
+        tint: Color,
+    ) {
+        let src = animation.frame_at_time(time);
+        self.renderer.draw_subtexture(tex, src, destination, tint.0);
+    }
+
+    /// Load a texture from PNG/JPEG bytes.
+    ///
+    /// Returns a `TextureId` that can be used with `draw_texture` and `draw_subtexture`.
+    /// The `name` is for debugging only.
+    pub fn load_texture_from_bytes(
+        &mut self,
+        name: &str,
+        bytes: &[u8],
+    ) -> Result<TextureId, LibforgeError> {
+        Ok(self.renderer.load_texture_from_bytes(name, bytes)?)
+    }
+
+    /// Load many textures at once, decoding them on all CPU cores instead of one after
+    /// another; uploads still happen on this thread. `progress(loaded, total)` runs after
+    /// each texture is uploaded, e.g. to log or report startup progress.
+    ///
+    /// ```ignore
+    /// let ids = ctx.load_textures_parallel(
+    ///     &[("player", PLAYER_PNG), ("tiles", TILES_PNG), ("ui", UI_PNG)],
+    ///     |loaded, total| println!("loaded {loaded}/{total}"),
+    /// )?;
+    /// ```
+    ///
+    /// Returns the ids in the same order as `textures`.
+    pub fn load_textures_parallel(
+        &mut self,
+        textures: &[(&str, &[u8])],
+        progress: impl FnMut(usize, usize),
+    ) -> Result<Vec<TextureId>, LibforgeError> {
+        Ok(self.renderer.load_textures_parallel(textures, progress)?)
+    }
+
+    /// Create a texture from tightly packed RGBA8 (sRGB) pixels, e.g. generated with
+    /// [`noise`] at runtime.
+    pub fn create_texture_from_pixels(
+        &mut self,
+        name: &str,
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+    ) -> Result<TextureId, LibforgeError> {
+        Ok(self
+            .renderer
+            .load_texture_from_rgba(name, width, height, rgba)?)
+    }
+
+    /// Decode PNG/JPEG bytes into an `ImageData` to crop, recolor or rotate before
+    /// uploading it with `texture_from_image`.
+    pub fn load_image_from_bytes(&self, bytes: &[u8]) -> Result<ImageData, LibforgeError> {
+        ImageData::from_bytes(bytes)
+    }
+
+    /// Upload `image` as a texture, with the current texture options.
+    pub fn texture_from_image(
+        &mut self,
+        name: &str,
+        image: &ImageData,
+    ) -> Result<TextureId, LibforgeError> {
+        self.create_texture_from_pixels(name, image.width(), image.height(), image.as_rgba())
+    }
+
+    /// Look a texture up by the name it was loaded under, for data-driven games that refer
+    /// to art by string. When several textures share a name, the latest one wins.
+    pub fn texture_by_name(&self, name: &str) -> Option<TextureId> {
+        self.renderer.texture_by_name(name)
+    }
+
+    /// Every loaded texture with its name, size and GPU memory, oldest first, e.g. for an
+    /// in-game asset inspector. Includes libforge's own textures such as the glyph atlas.
+    pub fn textures(&self) -> Vec<TextureInfo<'_>> {
+        self.renderer.textures()
+    }
+
+    /// Copy a texture back from the GPU, e.g. to save procedurally generated art, inspect
+    /// an atlas, or compare against a golden image. Blocks until the GPU is done, so keep
+    /// it out of the per-frame path.
+    pub fn get_texture_data(&self, tex: TextureId) -> Result<ImageData, LibforgeError> {
+        Ok(self.renderer.read_texture_pixels(tex)?.into())
+    }
+
+    /// Skip `draw_rect`, `draw_texture` and `draw_subtexture` calls that fall entirely
+    /// outside the canvas, before any vertices are generated (default: on).
+    ///
+    /// The check uses the current model transform and camera, so off-screen tiles of a large
+    /// world cost only a bounds test. `frame_stats().culled_draws` reports how many were skipped.
+    pub fn set_culling(&mut self, enabled: bool) {
+        self.renderer.set_culling(enabled);
+    }
+
+    /// Smooth the edges of `draw_rect`, `draw_rect_ex`, `draw_line`, `draw_circle`,
+    /// `draw_circle_auto` and `draw_ellipse` with a one-pixel alpha ramp (default: off).
+    ///
+    /// A cheap alternative to MSAA for vector art: each shape gains a thin transparent rim
+    /// instead of multiplying the cost of every pixel. Leave off when shapes must tile
+    /// seamlessly, since adjacent edges each fade to half coverage.
+    pub fn set_shape_antialiasing(&mut self, enabled: bool) {
+        self.renderer.set_shape_antialiasing(enabled);
+    }
+
+    pub fn shape_antialiasing(&self) -> bool {
+        self.renderer.shape_antialiasing()
+    }
+
+    /// Sort subsequent draws per pixel by their y coordinate (or `set_draw_depth`) instead
+    /// of call order, for top-down games where characters overlap.
+    ///
+    /// Uses a depth buffer, so thousands of sprites need no CPU sorting. Fragments under
+    /// half alpha are discarded; draw translucent effects and UI with sorting turned off.
+    /// Meshes, SDF shapes and backgrounds are never sorted; particles stay on their additive
+    /// layer but fade behind sorted sprites (`set_particle_softness`).
+    pub fn set_depth_sorting(&mut self, enabled: bool) {
+        self.renderer.set_depth_sorting(enabled);
+    }
+
+    pub fn depth_sorting(&self) -> bool {
+        self.renderer.depth_sorting()
+    }
+
+    /// Explicit depth for subsequent depth-sorted draws, in the same units as y (larger is
+    /// nearer). `None` sorts by each draw's lowest point again.
+    pub fn set_draw_depth(&mut self, depth: Option<f32>) {
+        self.renderer.set_draw_depth(depth);
+    }
+
+    /// Choose how colors are interpreted: `ColorSpace::Srgb` (default) decodes them so they
+    /// match your art program, `ColorSpace::Linear` passes raw linear values through.
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.renderer.set_color_space(color_space);
+    }
+
+    /// Mipmap and filtering options for textures loaded from now on.
+    ///
+    /// Enable `mipmaps` for sprites that are drawn smaller than their source size (e.g. when
+    /// the camera zooms out); existing textures are not changed.
+    pub fn set_texture_options(&mut self, options: TextureOptions) {
+        self.renderer.set_texture_options(options);
+    }
+
+    /// Change how a loaded texture is sampled outside the `0..1` UV range, per axis.
+    ///
+    /// `TextureWrap::Repeat` lets one quad tile the texture; the default for new textures
+    /// comes from `TextureOptions::wrap`.
+    pub fn set_texture_wrap(&mut self, tex: TextureId, wrap_u: TextureWrap, wrap_v: TextureWrap) {
+        self.renderer.set_texture_wrap(tex, wrap_u, wrap_v);
+    }
+
+    /// Queue decoded RGBA8 pixels for upload in a later frame, within the upload budget.
+    ///
+    /// Use this when many textures become ready at once (e.g. a level load) so their GPU
+    /// uploads are spread over several frames instead of causing a hitch. `on_done` runs
+    /// from `begin_drawing()` once the texture exists, or with the error if it failed.
+    pub fn queue_texture_upload(
+        &mut self,
+        name: &str,
+        width: u32,
+        height: u32,
+        rgba: Vec<u8>,
+        on_done: impl FnOnce(Result<TextureId, error::RendererError>) + 'static,
+    ) {
+        self.uploads.push(upload::PendingUpload {
+            name: name.to_string(),
+            width,
+            height,
+            rgba,
+            on_done: Box::new(on_done),
+        });
+    }
+
+    /// Time per frame spent on queued texture uploads (default 2 ms).
+    ///
+    /// At least one queued texture is uploaded every frame regardless of the budget.
+    pub fn set_upload_budget(&mut self, budget: std::time::Duration) {
+        self.uploads.budget = budget;
+    }
+
+    /// Number of queued texture uploads not yet on the GPU.
+    pub fn pending_uploads(&self) -> usize {
+        self.uploads.len()
+    }
+
+    /// Load a texture with its own `TextureOptions`, e.g. premultiplied alpha for one asset.
+    pub fn load_texture_with_options(
+        &mut self,
+        name: &str,
+        bytes: &[u8],
+        options: TextureOptions,
+    ) -> Result<TextureId, LibforgeError> {
+        Ok(self
+            .renderer
+            .load_texture_from_bytes_with(name, bytes, options)?)
+    }
+
+    /// Load a BC1-BC7 compressed texture from DDS or KTX2 bytes, keeping it compressed in
+    /// GPU memory when supported.
+    ///
+    /// `load_texture_from_bytes` also detects these containers, so this is only needed to
+    /// reject other formats. On GPUs without BC support, BC1-BC5 are decoded on the CPU.
+    pub fn load_compressed_texture(
+        &mut self,
+        name: &str,
+        bytes: &[u8],
+    ) -> Result<TextureId, LibforgeError> {
+        Ok(self.renderer.load_compressed_texture(name, bytes)?)
+    }
+
+    /// Pack the builder's images into one texture and return the named regions.
+    ///
+    /// Draw a region with `draw_subtexture(sub.texture, sub.rect, dst, tint)`.
+    pub fn build_atlas(&mut self, builder: &AtlasBuilder) -> Result<TextureAtlas, LibforgeError> {
+        let packed = builder.build_pixels()?;
+        let texture = self.renderer.load_texture_from_rgba(
+            "libforge_atlas",
+            packed.width,
+            packed.height,
+            &packed.pixels,
+        )?;
+        Ok(TextureAtlas::new(texture, packed))
+    }
+
+    // -------------------------------------------------------------------------
+    //
+    // Default drawing is in screen-space (pixels). To draw in world-space, enter
+    // 2D camera mode with `begin_mode_2d()` and exit with `end_mode_2d()`.
+    //
+    // Per-draw transforms are handled via a simple matrix stack. Every `push_matrix` needs a
+    // matching `pop_matrix` (and `begin_mode_2d` an `end_mode_2d`) within the frame: the
+    // frame end resets unbalanced stacks, warning in release and erroring in debug builds.
+    // -------------------------------------------------------------------------
+
+    /// Begin 2D camera mode (world-space). Camera affects subsequent draws until `end_mode_2d()`.
+    pub fn begin_mode_2d(&mut self, camera: Camera2D) {
+        self.listener = camera;
+        self.renderer.begin_mode_2d(camera);
+    }
+
+    /// End 2D camera mode and return to screen-space drawing.
+    pub fn end_mode_2d(&mut self) {
+        self.renderer.end_mode_2d();
+    }
+
+    /// Push the current model transform.
+    pub fn push_matrix(&mut self) {
+        self.renderer.push_matrix();
+    }
+
+    /// Pop the current model transform.
+    pub fn pop_matrix(&mut self) {
+        self.renderer.pop_matrix();
+    }
+
+    /// Reset the current model transform to identity.
+    pub fn load_identity(&mut self) {
+        self.renderer.load_identity();
+    }
+
+    /// Current model transform (top of the matrix stack).
+    pub fn get_transform(&self) -> glam::Mat4 {
+        self.renderer.get_transform()
+    }
+
+    /// Replace the current model transform (top of the matrix stack).
+    pub fn set_transform(&mut self, mat: glam::Mat4) {
+        self.renderer.set_transform(mat);
+    }
+
+    /// Apply a translation to the current model transform.
+    pub fn translate(&mut self, tx: f32, ty: f32) {
+        self.renderer.translate(tx, ty);
+    }
+
+    /// Apply a rotation (around Z) to the current model transform.
+    pub fn rotate_z(&mut self, radians: f32) {
+        self.renderer.rotate_z(radians);
+    }
+
+    /// Apply a scale to the current model transform.
+    pub fn scale(&mut self, sx: f32, sy: f32) {
+        self.renderer.scale(sx, sy);
+    }
+
+    /// Finish the frame, flush commands to GPU, and present.
+    pub fn end_frame(&mut self) -> Result<(), LibforgeError> {
+        self.end_drawing()?;
+        Ok(())
+    }
+
+    /// Draw in a fixed virtual resolution (e.g. 320x180 for pixel art).
+    ///
+    /// All draw coordinates become virtual units; the renderer scales the canvas to the
+    /// window according to `mode`, filling any leftover area with black bars.
+    pub fn set_virtual_resolution(&mut self, width: u32, height: u32, mode: ScalingMode) {
+        self.renderer
+            .set_virtual_resolution(Some((width, height, mode)));
+    }
+
+    /// Return to drawing directly in window pixels.
+    pub fn clear_virtual_resolution(&mut self) {
+        self.renderer.set_virtual_resolution(None);
+    }
+
+    /// Lock the canvas to `aspect` (width / height, e.g. `16.0 / 9.0`).
+    ///
+    /// The canvas becomes the largest rectangle of that shape in window pixels, recomputed
+    /// on every resize, and the rest of the window is cleared to black (`FillMode::Letterbox`).
+    /// Draw within `canvas_size()` and read the mouse with `virtual_mouse_position()`.
+    /// Replaces any virtual resolution; `clear_aspect_ratio()` unlocks.
+    pub fn set_aspect_ratio(&mut self, aspect: f32, mode: FillMode) {
+        self.renderer.set_aspect_ratio(Some((aspect, mode)));
+    }
+
+    pub fn clear_aspect_ratio(&mut self) {
+        self.renderer.set_aspect_ratio(None);
+    }
+
+    /// Size of the drawing canvas: the virtual or aspect-locked resolution if set,
+    /// otherwise the window surface.
+    pub fn canvas_size(&self) -> (u32, u32) {
+        self.renderer.logical_size()
+    }
+
+    /// Canvas size as floats, for layout math.
+    pub fn canvas_width(&self) -> f32 {
+        self.canvas_size().0 as f32
+    }
+
+    pub fn canvas_height(&self) -> f32 {
+        self.canvas_size().1 as f32
+    }
+
+    /// A `size` rect at `anchor` of the canvas, `offset` pixels in from the anchored edges.
+    /// Recomputed every frame, HUD elements stay in their corners through resizes.
+    ///
+    /// ```ignore
+    /// let minimap = ctx.anchored_rect(Anchor::TopRight, (16.0, 16.0), (160.0, 160.0));
+    /// ctx.draw_texture(minimap_tex, minimap, Color::WHITE);
+    /// ```
+    pub fn anchored_rect(&self, anchor: Anchor, offset: (f32, f32), size: (f32, f32)) -> Rect {
+        viewport::anchored_rect(
+            (self.canvas_width(), self.canvas_height()),
+            anchor,
+            offset,
+            size,
+        )
+    }
+
+    /// A `size` rect next to the cursor (in canvas coordinates), moved to the other side of
+    /// it near the canvas edges, e.g. for tooltips or drag previews.
+    pub fn cursor_rect(&self, offset: (f32, f32), size: (f32, f32)) -> Rect {
+        viewport::cursor_rect(
+            (self.canvas_width(), self.canvas_height()),
+            self.virtual_mouse_position(),
+            offset,
+            size,
+        )
+    }
+
+    /// Convert a window-pixel position (e.g. from `mouse_position`) to canvas coordinates.
+    ///
+    /// Without a virtual resolution this returns the input unchanged.
+    pub fn screen_to_virtual(&self, x: f32, y: f32) -> (f32, f32) {
+        let Some(viewport) = self.renderer.viewport() else {
+            return (x, y);
+        };
+        let (w, h) = self.renderer.logical_size();
+        viewport::surface_to_canvas(viewport, w as f32, h as f32, x, y)
+    }
+
+    /// Mouse position in canvas coordinates (virtual units when a virtual resolution is set).
+    pub fn virtual_mouse_position(&self) -> (f32, f32) {
+        let (x, y) = self.mouse_position();
+        self.screen_to_virtual(x, y)
+    }
+
+    /// Handle window resize: pass the new size in physical pixels, as `WindowEvent::Resized`
+    /// and `window.inner_size()` report it.
+    ///
+    /// Resizing updates the internal screen-space projection and any active camera mode.
+    /// `handle_window_event` already does this for `WindowEvent::Resized` unless
+    /// `set_auto_resize(false)`; resizing to the current size is a no-op.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.renderer.resize(width, height);
+    }
+
+    /// Release the window surface from `ApplicationHandler::suspended`.
+    ///
+    /// Android destroys the native window when the app goes to the background; the surface
+    /// must be dropped before that. Textures, fonts and pipelines are kept, and frames drawn
+    /// while suspended are discarded.
+    ///
+    /// ```ignore
+    /// fn suspended(&mut self, _: &ActiveEventLoop) {
+    ///     ctx.suspend();
+    /// }
+    ///
+    /// fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+    ///     let window = Arc::new(event_loop.create_window(attributes).unwrap());
+    ///     match &mut self.ctx {
+    ///         Some(ctx) => ctx.resume(window).unwrap(),
+    ///         None => self.ctx = Some(LibContext::new_from_window(window).unwrap()),
+    ///     }
+    /// }
+    /// ```
+    pub fn suspend(&mut self) {
+        self.renderer.suspend();
+    }
+
+    /// Recreate the surface for the window the platform handed back after `suspend`.
+    pub fn resume(&mut self, window: W) -> Result<(), LibforgeError> {
+        self.renderer.resume(window)?;
+        self.redraw.invalidate();
+        Ok(())
+    }
+
+    /// Whether the context is between `suspend` and `resume`.
+    pub fn is_suspended(&self) -> bool {
+        self.renderer.is_suspended()
+    }
+
+    /// Resize the surface from `WindowEvent::Resized` inside `handle_window_event`
+    /// (default: on). Turn off to call `resize()` yourself, e.g. to debounce live resizing.
+    pub fn set_auto_resize(&mut self, enabled: bool) {
+        self.auto_resize = enabled;
+    }
+
+    pub fn auto_resize(&self) -> bool {
+        self.auto_resize
+    }
+
+    /// Window DPI scale from the last `WindowEvent::ScaleFactorChanged` seen by
+    /// `handle_window_event` (1.0 until then).
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
     }
 }
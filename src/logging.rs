@@ -0,0 +1,44 @@
+//! Internal logging macros.
+//!
+//! With the `log` feature enabled these forward to the `log` crate under the `libforge`
+//! target; without it trace and debug compile to nothing and warnings go to stderr, so
+//! problems such as a missing texture are still reported in default builds.
+
+#[cfg(feature = "log")]
+macro_rules! log_trace {
+    ($($arg:tt)*) => { log::trace!(target: "libforge", $($arg)*) };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! log_trace {
+    // Never runs; keeps the arguments "used" without evaluating them.
+    ($($arg:tt)*) => {{
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
+}
+
+#[cfg(feature = "log")]
+macro_rules! log_debug {
+    ($($arg:tt)*) => { log::debug!(target: "libforge", $($arg)*) };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {{
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
+}
+
+#[cfg(feature = "log")]
+macro_rules! log_warn {
+    ($($arg:tt)*) => { log::warn!(target: "libforge", $($arg)*) };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! log_warn {
+    ($($arg:tt)*) => { eprintln!("libforge warning: {}", format_args!($($arg)*)) };
+}
@@ -0,0 +1,143 @@
+//! Color grading lookup tables for `ctx.set_color_lut`.
+//!
+//! A LUT maps every on-screen color to a graded one. libforge keeps it in a texture as a
+//! *strip*: `size` tiles of `size` x `size` pixels side by side, with red increasing across
+//! each tile, green down it, and blue from tile to tile.
+//!
+//! To author one, paste `neutral(32)` into a screenshot of the game, grade the screenshot
+//! in any image editor, then crop the strip back out and load it with `ctx.load_color_lut`.
+//! Adobe `.cube` 3D LUTs (exported by Resolve, Photoshop and most grading tools) load
+//! directly too.
+
+use crate::error::LibforgeError;
+use crate::image_data::ImageData;
+
+/// Largest `.cube` size accepted; a 64-step LUT is already a 4096 x 64 strip.
+const MAX_CUBE_SIZE: u32 = 64;
+
+/// The identity LUT with `size` steps per channel: grading through it changes nothing.
+pub fn neutral(size: u32) -> ImageData {
+    let size = size.max(2);
+    let step = |i: u32| ((i * 255) as f32 / (size - 1) as f32).round() as u8;
+    let mut image = ImageData::new(size * size, size);
+    for b in 0..size {
+        for g in 0..size {
+            for r in 0..size {
+                image.set_pixel(b * size + r, g, [step(r), step(g), step(b), 255]);
+            }
+        }
+    }
+    image
+}
+
+/// The steps per channel of a strip `width` x `height` pixels, or `None` if it isn't one.
+pub fn strip_size(width: u32, height: u32) -> Option<u32> {
+    (height >= 2 && height.checked_mul(height) == Some(width)).then_some(height)
+}
+
+/// Convert the text of an Adobe `.cube` 3D LUT into a strip.
+///
+/// 1D LUTs and custom input domains (`DOMAIN_MIN`/`DOMAIN_MAX` other than 0..1) are not
+/// supported.
+pub fn from_cube(text: &str) -> Result<ImageData, LibforgeError> {
+    let invalid = |msg: String| LibforgeError::Platform(format!("invalid .cube LUT: {msg}"));
+    let mut size = None;
+    let mut values = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let keyword = words.next().unwrap_or_default();
+        match keyword {
+            "TITLE" => {}
+            "LUT_3D_SIZE" => {
+                let n = words
+                    .next()
+                    .and_then(|w| w.parse::<u32>().ok())
+                    .filter(|n| (2..=MAX_CUBE_SIZE).contains(n))
+                    .ok_or_else(|| invalid(format!("bad size in '{line}'")))?;
+                size = Some(n);
+            }
+            "LUT_1D_SIZE" => return Err(invalid("1D LUTs are not supported".into())),
+            "DOMAIN_MIN" | "DOMAIN_MAX" => {
+                let expected = if keyword == "DOMAIN_MIN" { 0.0 } else { 1.0 };
+                if !words.all(|w| w.parse::<f32>() == Ok(expected)) {
+                    return Err(invalid(format!("unsupported '{line}'")));
+                }
+            }
+            _ => {
+                let rgb: Vec<f32> = line
+                    .split_whitespace()
+                    .map(|w| w.parse::<f32>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| invalid(format!("unexpected line '{line}'")))?;
+                if rgb.len() != 3 {
+                    return Err(invalid(format!("expected 3 values in '{line}'")));
+                }
+                values.push([rgb[0], rgb[1], rgb[2]]);
+            }
+        }
+    }
+
+    let size = size.ok_or_else(|| invalid("missing LUT_3D_SIZE".into()))?;
+    let expected = (size * size * size) as usize;
+    if values.len() != expected {
+        return Err(invalid(format!(
+            "expected {expected} entries, found {}",
+            values.len()
+        )));
+    }
+
+    // Entries run with red fastest, then green, then blue.
+    let encode = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let mut image = ImageData::new(size * size, size);
+    for (i, [r, g, b]) in values.into_iter().enumerate() {
+        let i = i as u32;
+        let (ri, gi, bi) = (i % size, i / size % size, i / (size * size));
+        image.set_pixel(bi * size + ri, gi, [encode(r), encode(g), encode(b), 255]);
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neutral_strip_layout() {
+        let lut = neutral(4);
+        assert_eq!((lut.width(), lut.height()), (16, 4));
+        assert_eq!(strip_size(lut.width(), lut.height()), Some(4));
+        assert_eq!(lut.pixel(0, 0), Some([0, 0, 0, 255]));
+        // Red within a tile, green down it, blue across tiles.
+        assert_eq!(lut.pixel(3, 0), Some([255, 0, 0, 255]));
+        assert_eq!(lut.pixel(0, 3), Some([0, 255, 0, 255]));
+        assert_eq!(lut.pixel(12, 0), Some([0, 0, 255, 255]));
+        assert_eq!(lut.pixel(5, 2), Some([85, 170, 85, 255]));
+
+        assert_eq!(strip_size(64, 8), Some(8));
+        assert_eq!(strip_size(512, 512), None);
+        assert_eq!(strip_size(1, 1), None);
+    }
+
+    #[test]
+    fn cube_files_become_strips() {
+        let mut cube =
+            String::from("# identity\nTITLE \"id\"\nLUT_3D_SIZE 2\nDOMAIN_MIN 0 0 0\n\n");
+        for b in 0..2 {
+            for g in 0..2 {
+                for r in 0..2 {
+                    cube.push_str(&format!("{r}.0 {g}.0 {b}.0\n"));
+                }
+            }
+        }
+        assert_eq!(from_cube(&cube).unwrap(), neutral(2));
+
+        assert!(from_cube("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(from_cube("LUT_1D_SIZE 16\n").is_err());
+        assert!(from_cube("0 0 0\n").is_err());
+        assert!(from_cube("LUT_3D_SIZE 2\nDOMAIN_MAX 2 2 2\n").is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use winit::monitor::MonitorHandle;
+
+/// A connected display, for resolution pickers and frame caps.
+///
+/// List them with `ctx.monitors(&window)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonitorInfo {
+    /// Name reported by the platform, if any.
+    pub name: Option<String>,
+    /// Current resolution in physical pixels.
+    pub size: (u32, u32),
+    /// Top-left corner in the desktop's physical coordinates.
+    pub position: (i32, i32),
+    /// Current refresh rate; `None` where the platform doesn't report it (e.g. the web).
+    pub refresh_rate_hz: Option<f32>,
+    pub scale_factor: f64,
+    pub is_primary: bool,
+    /// Fullscreen resolutions the monitor supports, largest first, without duplicates.
+    pub resolutions: Vec<(u32, u32)>,
+}
+
+impl MonitorInfo {
+    pub(crate) fn from_handle(handle: &MonitorHandle, primary: Option<&MonitorHandle>) -> Self {
+        let size = handle.size();
+        let position = handle.position();
+        Self {
+            name: handle.name(),
+            size: (size.width, size.height),
+            position: (position.x, position.y),
+            refresh_rate_hz: handle.refresh_rate_millihertz().map(millihertz_to_hz),
+            scale_factor: handle.scale_factor(),
+            is_primary: primary == Some(handle),
+            resolutions: unique_resolutions(handle.video_modes().map(|mode| {
+                let size = mode.size();
+                (size.width, size.height)
+            })),
+        }
+    }
+}
+
+pub(crate) fn millihertz_to_hz(millihertz: u32) -> f32 {
+    millihertz as f32 / 1000.0
+}
+
+/// Sort by pixel count, largest first, dropping repeats (video modes differ by refresh rate
+/// and bit depth too).
+fn unique_resolutions(sizes: impl Iterator<Item = (u32, u32)>) -> Vec<(u32, u32)> {
+    let mut sizes: Vec<_> = sizes.collect();
+    sizes.sort_by_key(|&(w, h)| std::cmp::Reverse((w as u64 * h as u64, w)));
+    sizes.dedup();
+    sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolutions_are_deduplicated_largest_first() {
+        let modes = [
+            (1280, 720),
+            (1920, 1080),
+            (1280, 720),
+            (800, 600),
+            (1920, 1080),
+        ];
+        assert_eq!(
+            unique_resolutions(modes.into_iter()),
+            [(1920, 1080), (1280, 720), (800, 600)]
+        );
+        assert_eq!(millihertz_to_hz(59_940), 59.94);
+    }
+}
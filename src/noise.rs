@@ -0,0 +1,177 @@
+//! Coherent noise for procedural terrain, clouds and wobble.
+//!
+//! Every function is a pure function of its coordinates and `seed`, returns values in
+//! `[-1, 1]`, and varies smoothly over roughly one unit, so scale the input to set the
+//! feature size:
+//!
+//! ```ignore
+//! let mut pixels = Vec::with_capacity(256 * 256 * 4);
+//! for y in 0..256 {
+//!     for x in 0..256 {
+//!         let v = noise::fbm2(x as f32 / 64.0, y as f32 / 64.0, seed, 5);
+//!         let shade = ((v * 0.5 + 0.5) * 255.0) as u8;
+//!         pixels.extend_from_slice(&[shade, shade, shade, 255]);
+//!     }
+//! }
+//! let clouds = ctx.create_texture_from_pixels("clouds", 256, 256, &pixels)?;
+//! ```
+
+use crate::random::splitmix64;
+
+/// Hash of an integer lattice point.
+fn hash(seed: u64, x: i32, y: i32) -> u64 {
+    splitmix64(seed ^ splitmix64((x as u32 as u64) | ((y as u32 as u64) << 32)))
+}
+
+/// Quintic fade curve: zero first and second derivatives at 0 and 1.
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// One of eight unit gradient directions, dotted with `(x, y)`.
+fn gradient(hash: u64, x: f32, y: f32) -> f32 {
+    const D: f32 = std::f32::consts::FRAC_1_SQRT_2;
+    let (gx, gy) = match hash & 7 {
+        0 => (1.0, 0.0),
+        1 => (-1.0, 0.0),
+        2 => (0.0, 1.0),
+        3 => (0.0, -1.0),
+        4 => (D, D),
+        5 => (-D, D),
+        6 => (D, -D),
+        _ => (-D, -D),
+    };
+    gx * x + gy * y
+}
+
+/// 1D gradient noise, e.g. for camera shake or flicker over time.
+pub fn noise1(x: f32, seed: u64) -> f32 {
+    let x0 = x.floor();
+    let t = x - x0;
+    let i = x0 as i32;
+    let slope = |i: i32| (hash(seed, i, 0) >> 40) as f32 / (1u32 << 23) as f32 - 1.0;
+    let a = slope(i) * t;
+    let b = slope(i.wrapping_add(1)) * (t - 1.0);
+    // Gradient noise peaks at half the slope, so double it to fill [-1, 1].
+    (lerp(a, b, fade(t)) * 2.0).clamp(-1.0, 1.0)
+}
+
+/// 2D Perlin (gradient) noise: zero at integer points, smooth hills in between.
+pub fn noise2(x: f32, y: f32, seed: u64) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (tx, ty) = (x - x0, y - y0);
+    let (ix, iy) = (x0 as i32, y0 as i32);
+    let (ix1, iy1) = (ix.wrapping_add(1), iy.wrapping_add(1));
+    let n00 = gradient(hash(seed, ix, iy), tx, ty);
+    let n10 = gradient(hash(seed, ix1, iy), tx - 1.0, ty);
+    let n01 = gradient(hash(seed, ix, iy1), tx, ty - 1.0);
+    let n11 = gradient(hash(seed, ix1, iy1), tx - 1.0, ty - 1.0);
+    let (u, v) = (fade(tx), fade(ty));
+    let n = lerp(lerp(n00, n10, u), lerp(n01, n11, u), v);
+    (n * std::f32::consts::SQRT_2).clamp(-1.0, 1.0)
+}
+
+/// 2D value noise: random heights at integer points, smoothly interpolated. Blockier
+/// than [`noise2`] but cheaper.
+pub fn value2(x: f32, y: f32, seed: u64) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (ix, iy) = (x0 as i32, y0 as i32);
+    let value = |x: i32, y: i32| (hash(seed, x, y) >> 40) as f32 / (1u32 << 23) as f32 - 1.0;
+    let (u, v) = (fade(x - x0), fade(y - y0));
+    let top = lerp(value(ix, iy), value(ix.wrapping_add(1), iy), u);
+    let bottom = lerp(
+        value(ix, iy.wrapping_add(1)),
+        value(ix.wrapping_add(1), iy.wrapping_add(1)),
+        u,
+    );
+    lerp(top, bottom, v)
+}
+
+/// 2D simplex noise: fewer directional artifacts than [`noise2`].
+pub fn simplex2(x: f32, y: f32, seed: u64) -> f32 {
+    // Skew to the triangular lattice and back (Gustavson's formulation).
+    const F2: f32 = 0.366_025_42; // (sqrt(3) - 1) / 2
+    const G2: f32 = 0.211_324_87; // (3 - sqrt(3)) / 6
+    let s = (x + y) * F2;
+    let (i, j) = ((x + s).floor(), (y + s).floor());
+    let t = (i + j) * G2;
+    let (x0, y0) = (x - (i - t), y - (j - t));
+    let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+    let corners = [
+        (0, 0, x0, y0),
+        (i1, j1, x0 - i1 as f32 + G2, y0 - j1 as f32 + G2),
+        (1, 1, x0 - 1.0 + 2.0 * G2, y0 - 1.0 + 2.0 * G2),
+    ];
+    let (i, j) = (i as i32, j as i32);
+    let n: f32 = corners
+        .iter()
+        .map(|&(di, dj, cx, cy)| {
+            let falloff = 0.5 - cx * cx - cy * cy;
+            if falloff <= 0.0 {
+                return 0.0;
+            }
+            let h = hash(seed, i.wrapping_add(di), j.wrapping_add(dj));
+            falloff.powi(4) * gradient(h, cx, cy)
+        })
+        .sum();
+    (n * 99.0).clamp(-1.0, 1.0)
+}
+
+/// Fractal Brownian motion: `octaves` layers of [`noise2`], each at double the frequency
+/// and half the amplitude of the last, normalized back to `[-1, 1]`.
+pub fn fbm2(x: f32, y: f32, seed: u64, octaves: u32) -> f32 {
+    fbm(x, y, seed, octaves, noise2)
+}
+
+/// [`fbm2`] built from [`simplex2`].
+pub fn fbm_simplex2(x: f32, y: f32, seed: u64, octaves: u32) -> f32 {
+    fbm(x, y, seed, octaves, simplex2)
+}
+
+fn fbm(x: f32, y: f32, seed: u64, octaves: u32, noise: fn(f32, f32, u64) -> f32) -> f32 {
+    let (mut sum, mut total, mut amplitude, mut frequency) = (0.0, 0.0, 1.0, 1.0);
+    for octave in 0..octaves.max(1) {
+        // Decorrelate octaves so their lattice points don't line up.
+        let seed = splitmix64(seed.wrapping_add(octave as u64));
+        sum += amplitude * noise(x * frequency, y * frequency, seed);
+        total += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    sum / total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_is_deterministic_bounded_and_smooth() {
+        let all: [fn(f32, f32, u64) -> f32; 5] = [
+            noise2,
+            value2,
+            simplex2,
+            |x, y, s| fbm2(x, y, s, 4),
+            |x, _, s| noise1(x, s),
+        ];
+        for noise in all {
+            let mut spread = (f32::MAX, f32::MIN);
+            for i in 0..2000 {
+                let (x, y) = (i as f32 * 0.173 - 150.0, i as f32 * 0.091 - 80.0);
+                let v = noise(x, y, 9);
+                assert_eq!(v, noise(x, y, 9));
+                assert!((-1.0..=1.0).contains(&v));
+                assert!((noise(x + 0.001, y, 9) - v).abs() < 0.05);
+                spread = (spread.0.min(v), spread.1.max(v));
+            }
+            assert!(spread.1 - spread.0 > 0.5, "{spread:?}");
+            assert_ne!(noise(0.3, 0.7, 9), noise(0.3, 0.7, 10));
+        }
+        assert_eq!(noise2(3.0, -4.0, 1), 0.0);
+        assert_eq!(noise1(-2.0, 1), 0.0);
+    }
+}
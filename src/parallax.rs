@@ -0,0 +1,116 @@
+use crate::renderer::TextureId;
+use crate::{Camera2D, Color, Rect};
+
+/// A textured backdrop layer that scrolls slower (or faster) than the camera.
+///
+/// Draw back-to-front with `ctx.draw_parallax(&layers, camera)` in screen space (outside
+/// `begin_mode_2d`). Repeated axes tile the texture across the whole canvas using the
+/// `Repeat` wrap mode; other axes draw the texture once at `offset`, scrolled by the camera.
+#[derive(Clone, Copy, Debug)]
+pub struct ParallaxLayer {
+    pub texture: TextureId,
+    /// Fraction of the camera movement applied to the layer per axis: `0.0` is fixed
+    /// to the screen (sky), `1.0` moves with the world.
+    pub scroll_factor: (f32, f32),
+    /// Tile horizontally / vertically.
+    pub repeat: (bool, bool),
+    /// Screen position of the texture's top-left corner when the camera is at the origin.
+    pub offset: (f32, f32),
+    /// Size of one tile relative to the texture's pixel size.
+    pub scale: f32,
+    pub tint: Color,
+}
+
+impl ParallaxLayer {
+    /// A layer tiled horizontally, e.g. hills or clouds along a side-scroller.
+    pub fn new(texture: TextureId, scroll_factor: (f32, f32)) -> Self {
+        Self {
+            texture,
+            scroll_factor,
+            repeat: (true, false),
+            offset: (0.0, 0.0),
+            scale: 1.0,
+            tint: Color::WHITE,
+        }
+    }
+
+    /// Destination rectangle and `[u0, v0, u1, v1]` UVs of the layer for a canvas of
+    /// `canvas` pixels viewed by `camera`. `None` if the layer has no area.
+    pub(crate) fn quad(
+        &self,
+        texture_size: (u32, u32),
+        canvas: (f32, f32),
+        camera: &Camera2D,
+    ) -> Option<(Rect, [f32; 4])> {
+        let (x, u0, u1) = axis(
+            texture_size.0 as f32 * self.scale,
+            canvas.0,
+            self.offset.0 - camera.x * self.scroll_factor.0,
+            self.repeat.0,
+        )?;
+        let (y, v0, v1) = axis(
+            texture_size.1 as f32 * self.scale,
+            canvas.1,
+            self.offset.1 - camera.y * self.scroll_factor.1,
+            self.repeat.1,
+        )?;
+        Some((
+            Rect {
+                x: x.0,
+                y: y.0,
+                w: x.1,
+                h: y.1,
+            },
+            [u0, v0, u1, v1],
+        ))
+    }
+}
+
+/// Screen span `(start, length)` and UV range along one axis for a tile of `tile` pixels
+/// whose origin is at screen position `origin`.
+fn axis(tile: f32, canvas: f32, origin: f32, repeat: bool) -> Option<((f32, f32), f32, f32)> {
+    if tile <= 0.0 || canvas <= 0.0 {
+        return None;
+    }
+    if !repeat {
+        return Some(((origin, tile), 0.0, 1.0));
+    }
+    // Wrap the start into one tile so UVs stay small (and precise) far from the origin.
+    let start = (-origin / tile).rem_euclid(1.0);
+    Some(((0.0, canvas), start, start + canvas / tile))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_axis_covers_canvas_and_scrolls_by_factor() {
+        let mut layer = ParallaxLayer::new(TextureId(0), (0.5, 0.0));
+        layer.offset.1 = 40.0;
+        let camera = Camera2D {
+            x: 100.0,
+            y: 300.0,
+            ..Camera2D::new()
+        };
+        let (dst, uv) = layer.quad((200, 50), (400.0, 300.0), &camera).unwrap();
+
+        // Horizontal: spans the canvas, starting 50px (a quarter tile) into the texture.
+        assert_eq!((dst.x, dst.w), (0.0, 400.0));
+        assert_eq!((uv[0], uv[2]), (0.25, 2.25));
+        // Vertical: not repeated and a zero factor, so drawn once at the offset.
+        assert_eq!((dst.y, dst.h), (40.0, 50.0));
+        assert_eq!((uv[1], uv[3]), (0.0, 1.0));
+    }
+
+    #[test]
+    fn wrapped_uvs_stay_in_first_tile() {
+        let layer = ParallaxLayer::new(TextureId(0), (1.0, 1.0));
+        let camera = Camera2D {
+            x: -1_000_050.0,
+            ..Camera2D::new()
+        };
+        let (_, uv) = layer.quad((100, 100), (100.0, 100.0), &camera).unwrap();
+        assert!((uv[0] - 0.5).abs() < 1e-3, "{uv:?}");
+    }
+}
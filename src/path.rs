@@ -0,0 +1,447 @@
+//! Vector paths: build outlines from lines and Bézier curves, then fill or stroke them.
+//!
+//! ```ignore
+//! let mut path = Path::new();
+//! path.move_to(10.0, 10.0)
+//!     .line_to(200.0, 40.0)
+//!     .cubic_to(260.0, 60.0, 260.0, 160.0, 120.0, 180.0)
+//!     .close();
+//! ctx.fill_path(&path, FillRule::NonZero, Color::WHITE);
+//! ctx.stroke_path(&path, &StrokeStyle::new(4.0).with_join(LineJoin::Round), Color::BLACK);
+//! ```
+//!
+//! Tessellation is done by lyon's `FillTessellator` and `StrokeTessellator` (`paths`
+//! feature); this module only records the outline and converts lyon's output into the
+//! renderer's triangles. Curves are flattened to a tolerance picked from the on-screen scale.
+
+use lyon::algorithms::measure::{PathMeasurements, SampleType};
+use lyon::math::point;
+use lyon::tessellation::{
+    BuffersBuilder, FillOptions, FillTessellator, FillVertex, StrokeOptions, StrokeTessellator,
+    StrokeVertex, VertexBuffers,
+};
+
+/// Which regions of a self-overlapping outline count as inside.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FillRule {
+    /// Inside where the outline winds around the point a nonzero number of times; a hole
+    /// needs the opposite winding.
+    #[default]
+    NonZero,
+    /// Inside where a ray from the point crosses the outline an odd number of times; any
+    /// nested contour is a hole.
+    EvenOdd,
+}
+
+/// Shape of the outer corner where two stroked segments meet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LineJoin {
+    /// Sharp corner, beveled past `StrokeStyle::miter_limit`.
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+/// Shape of the ends of open subpaths and dashes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LineCap {
+    /// Ends exactly at the endpoint.
+    #[default]
+    Butt,
+    /// Extends half the width past the endpoint.
+    Square,
+    Round,
+}
+
+/// How `stroke_path` outlines a path.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Longest miter, as a multiple of the width, before a miter join becomes a bevel.
+    pub miter_limit: f32,
+    /// Alternating dash and gap lengths; empty for a solid line. An odd-length pattern is
+    /// repeated to make it even, as in SVG.
+    pub dash: Vec<f32>,
+    /// Distance into the dash pattern at which the stroke starts.
+    pub dash_offset: f32,
+}
+
+impl StrokeStyle {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+            dash: Vec::new(),
+            dash_offset: 0.0,
+        }
+    }
+
+    pub fn with_join(self, join: LineJoin) -> Self {
+        Self { join, ..self }
+    }
+
+    pub fn with_cap(self, cap: LineCap) -> Self {
+        Self { cap, ..self }
+    }
+
+    pub fn with_dash(self, pattern: &[f32], offset: f32) -> Self {
+        Self {
+            dash: pattern.to_vec(),
+            dash_offset: offset,
+            ..self
+        }
+    }
+}
+
+type Point = [f32; 2];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Segment {
+    Line(Point),
+    Quad(Point, Point),
+    Cubic(Point, Point, Point),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct SubPath {
+    start: Point,
+    segments: Vec<Segment>,
+    closed: bool,
+}
+
+/// An outline made of subpaths of lines and quadratic or cubic Bézier curves.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    subpaths: Vec<SubPath>,
+}
+
+/// Control-point distance for approximating a quarter circle with a cubic.
+const KAPPA: f32 = 0.552_284_8;
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Closed rectangle path.
+    pub fn rect(rect: crate::Rect) -> Self {
+        let mut path = Self::new();
+        path.move_to(rect.x, rect.y)
+            .line_to(rect.x + rect.w, rect.y)
+            .line_to(rect.x + rect.w, rect.y + rect.h)
+            .line_to(rect.x, rect.y + rect.h)
+            .close();
+        path
+    }
+
+    /// Closed circle path made of four cubic arcs.
+    pub fn circle(cx: f32, cy: f32, radius: f32) -> Self {
+        let (r, k) = (radius, radius * KAPPA);
+        let mut path = Self::new();
+        path.move_to(cx + r, cy)
+            .cubic_to(cx + r, cy + k, cx + k, cy + r, cx, cy + r)
+            .cubic_to(cx - k, cy + r, cx - r, cy + k, cx - r, cy)
+            .cubic_to(cx - r, cy - k, cx - k, cy - r, cx, cy - r)
+            .cubic_to(cx + k, cy - r, cx + r, cy - k, cx + r, cy)
+            .close();
+        path
+    }
+
+    /// Start a new subpath at `(x, y)`.
+    pub fn move_to(&mut self, x: f32, y: f32) -> &mut Self {
+        self.subpaths.push(SubPath {
+            start: [x, y],
+            segments: Vec::new(),
+            closed: false,
+        });
+        self
+    }
+
+    pub fn line_to(&mut self, x: f32, y: f32) -> &mut Self {
+        self.current().segments.push(Segment::Line([x, y]));
+        self
+    }
+
+    /// Quadratic Bézier through control point `(cx, cy)` to `(x, y)`.
+    pub fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) -> &mut Self {
+        self.current()
+            .segments
+            .push(Segment::Quad([cx, cy], [x, y]));
+        self
+    }
+
+    /// Cubic Bézier through control points `(c1x, c1y)` and `(c2x, c2y)` to `(x, y)`.
+    pub fn cubic_to(
+        &mut self,
+        c1x: f32,
+        c1y: f32,
+        c2x: f32,
+        c2y: f32,
+        x: f32,
+        y: f32,
+    ) -> &mut Self {
+        self.current()
+            .segments
+            .push(Segment::Cubic([c1x, c1y], [c2x, c2y], [x, y]));
+        self
+    }
+
+    /// Close the current subpath back to its start; the next segment starts a new one there.
+    pub fn close(&mut self) -> &mut Self {
+        let current = self.current();
+        current.closed = true;
+        let start = current.start;
+        self.subpaths.push(SubPath {
+            start,
+            segments: Vec::new(),
+            closed: false,
+        });
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subpaths.iter().all(|s| s.segments.is_empty())
+    }
+
+    /// The subpath being built; segments before any `move_to` start at the origin.
+    fn current(&mut self) -> &mut SubPath {
+        if self.subpaths.is_empty() {
+            self.move_to(0.0, 0.0);
+        }
+        self.subpaths.last_mut().unwrap()
+    }
+
+    /// Each subpath with segments as a lyon path.
+    fn subpaths_to_lyon(&self) -> impl Iterator<Item = lyon::path::Path> + '_ {
+        self.subpaths
+            .iter()
+            .filter(|sub| !sub.segments.is_empty())
+            .map(|sub| {
+                let p = |[x, y]: Point| point(x, y);
+                let mut builder = lyon::path::Path::builder();
+                builder.begin(p(sub.start));
+                for segment in &sub.segments {
+                    match *segment {
+                        Segment::Line(to) => {
+                            builder.line_to(p(to));
+                        }
+                        Segment::Quad(c, to) => {
+                            builder.quadratic_bezier_to(p(c), p(to));
+                        }
+                        Segment::Cubic(c1, c2, to) => {
+                            builder.cubic_bezier_to(p(c1), p(c2), p(to));
+                        }
+                    }
+                }
+                builder.end(sub.closed);
+                builder.build()
+            })
+    }
+
+    /// The whole outline as one lyon path.
+    fn to_lyon(&self) -> lyon::path::Path {
+        let mut builder = lyon::path::Path::builder();
+        for sub in self.subpaths_to_lyon() {
+            for event in sub.iter() {
+                builder.path_event(event);
+            }
+        }
+        builder.build()
+    }
+
+    /// Triangles (three points each) covering the path's interior under `rule`, with curves
+    /// flattened to within `tolerance` units. Fills always close their subpaths.
+    pub fn fill_triangles(&self, rule: FillRule, tolerance: f32) -> Vec<Point> {
+        let options = FillOptions::tolerance(tolerance.max(1e-4)).with_fill_rule(match rule {
+            FillRule::NonZero => lyon::tessellation::FillRule::NonZero,
+            FillRule::EvenOdd => lyon::tessellation::FillRule::EvenOdd,
+        });
+        let mut buffers = VertexBuffers::new();
+        let result = FillTessellator::new().tessellate_path(
+            &self.to_lyon(),
+            &options,
+            &mut BuffersBuilder::new(&mut buffers, |v: FillVertex| v.position().to_array()),
+        );
+        if let Err(e) = result {
+            log_warn!("path fill failed: {e:?}");
+            return Vec::new();
+        }
+        triangles(buffers)
+    }
+
+    /// Triangles covering the path's stroke outline.
+    pub fn stroke_triangles(&self, style: &StrokeStyle, tolerance: f32) -> Vec<Point> {
+        if !(style.width > 0.0 && style.width.is_finite()) {
+            return Vec::new();
+        }
+        let cap = match style.cap {
+            LineCap::Butt => lyon::tessellation::LineCap::Butt,
+            LineCap::Square => lyon::tessellation::LineCap::Square,
+            LineCap::Round => lyon::tessellation::LineCap::Round,
+        };
+        let join = match style.join {
+            LineJoin::Miter => lyon::tessellation::LineJoin::Miter,
+            LineJoin::Round => lyon::tessellation::LineJoin::Round,
+            LineJoin::Bevel => lyon::tessellation::LineJoin::Bevel,
+        };
+        let tolerance = tolerance.max(1e-4);
+        let options = StrokeOptions::tolerance(tolerance)
+            .with_line_width(style.width)
+            .with_line_cap(cap)
+            .with_line_join(join)
+            .with_miter_limit(style.miter_limit.max(StrokeOptions::MINIMUM_MITER_LIMIT));
+
+        let path = match dash_pattern(&style.dash) {
+            Some(pattern) => {
+                let mut dashes = lyon::path::Path::builder();
+                for sub in self.subpaths_to_lyon() {
+                    dash_path(&sub, &pattern, style.dash_offset, tolerance, &mut dashes);
+                }
+                dashes.build()
+            }
+            None => self.to_lyon(),
+        };
+        let mut buffers = VertexBuffers::new();
+        let result = StrokeTessellator::new().tessellate_path(
+            &path,
+            &options,
+            &mut BuffersBuilder::new(&mut buffers, |v: StrokeVertex| v.position().to_array()),
+        );
+        if let Err(e) = result {
+            log_warn!("path stroke failed: {e:?}");
+            return Vec::new();
+        }
+        triangles(buffers)
+    }
+}
+
+/// Expand lyon's indexed output into a plain triangle list.
+fn triangles(buffers: VertexBuffers<Point, u32>) -> Vec<Point> {
+    buffers
+        .indices
+        .iter()
+        .map(|&i| buffers.vertices[i as usize])
+        .collect()
+}
+
+/// The dash pattern made even-length, or `None` for a solid line.
+fn dash_pattern(dash: &[f32]) -> Option<Vec<f32>> {
+    let valid = dash.iter().all(|d| d.is_finite() && *d >= 0.0);
+    if !valid || dash.iter().sum::<f32>() <= 0.0 {
+        return None;
+    }
+    let mut pattern = dash.to_vec();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_within(..);
+    }
+    Some(pattern)
+}
+
+/// Append the "on" pieces of `pattern` along `subpath` to `out` as open subpaths; the
+/// pattern restarts on every subpath, as in SVG.
+fn dash_path(
+    subpath: &lyon::path::Path,
+    pattern: &[f32],
+    offset: f32,
+    tolerance: f32,
+    out: &mut lyon::path::path::Builder,
+) {
+    let measurements = PathMeasurements::from_path(subpath, tolerance);
+    let length = measurements.length();
+    let mut sampler = measurements.create_sampler(subpath, SampleType::Distance);
+    let total: f32 = pattern.iter().sum();
+    // Start one whole pattern early so an offset never leaves the start uncovered.
+    let mut pos = -offset.rem_euclid(total);
+    let mut index = 0;
+    while pos < length {
+        let end = pos + pattern[index];
+        if index % 2 == 0 && end > 0.0 {
+            sampler.split_range(pos.max(0.0)..end.min(length), out);
+        }
+        pos = end;
+        index = (index + 1) % pattern.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(triangles: &[Point]) -> f32 {
+        triangles
+            .chunks_exact(3)
+            .map(|t| {
+                let (a, b) = (
+                    [t[1][0] - t[0][0], t[1][1] - t[0][1]],
+                    [t[2][0] - t[0][0], t[2][1] - t[0][1]],
+                );
+                (a[0] * b[1] - a[1] * b[0]).abs() * 0.5
+            })
+            .sum()
+    }
+
+    #[test]
+    fn fills_follow_the_fill_rule() {
+        // 10x10 square with a 4x4 hole wound the same way.
+        let mut path = Path::rect(crate::Rect {
+            x: 0.0,
+            y: 0.0,
+            w: 10.0,
+            h: 10.0,
+        });
+        path.move_to(3.0, 3.0)
+            .line_to(7.0, 3.0)
+            .line_to(7.0, 7.0)
+            .line_to(3.0, 7.0)
+            .close();
+        assert!((area(&path.fill_triangles(FillRule::NonZero, 0.1)) - 100.0).abs() < 1e-3);
+        assert!((area(&path.fill_triangles(FillRule::EvenOdd, 0.1)) - 84.0).abs() < 1e-3);
+
+        // A bow tie crosses itself: two triangles of area 25.
+        let mut bow = Path::new();
+        bow.move_to(0.0, 0.0)
+            .line_to(10.0, 10.0)
+            .line_to(10.0, 0.0)
+            .line_to(0.0, 10.0)
+            .close();
+        assert!((area(&bow.fill_triangles(FillRule::NonZero, 0.1)) - 50.0).abs() < 1e-3);
+
+        let circle = Path::circle(0.0, 0.0, 10.0).fill_triangles(FillRule::NonZero, 0.01);
+        assert!((area(&circle) - std::f32::consts::PI * 100.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn strokes_cover_caps_joins_and_dashes_once() {
+        let mut line = Path::new();
+        line.move_to(0.0, 0.0).line_to(100.0, 0.0);
+        let stroke = |style: StrokeStyle| area(&line.stroke_triangles(&style, 0.01));
+        assert!((stroke(StrokeStyle::new(4.0)) - 400.0).abs() < 1e-2);
+        let square = StrokeStyle::new(4.0).with_cap(LineCap::Square);
+        assert!((stroke(square) - 416.0).abs() < 1e-2);
+        let round = StrokeStyle::new(4.0).with_cap(LineCap::Round);
+        assert!((stroke(round) - (400.0 + std::f32::consts::PI * 4.0)).abs() < 0.1);
+        // Five 10-unit dashes with 10-unit gaps.
+        let dashed = StrokeStyle::new(4.0).with_dash(&[10.0], 0.0);
+        assert!((stroke(dashed) - 200.0).abs() < 1e-2);
+
+        // A right-angle corner: the overlapping segment quads count once, plus the join.
+        let mut corner = Path::new();
+        corner
+            .move_to(0.0, 0.0)
+            .line_to(10.0, 0.0)
+            .line_to(10.0, 10.0);
+        let corner_area = |join| {
+            let style = StrokeStyle::new(2.0).with_join(join);
+            area(&corner.stroke_triangles(&style, 0.001))
+        };
+        assert!((corner_area(LineJoin::Bevel) - 39.5).abs() < 1e-2);
+        assert!((corner_area(LineJoin::Miter) - 40.0).abs() < 1e-2);
+        let round = 39.0 + std::f32::consts::PI / 4.0;
+        assert!((corner_area(LineJoin::Round) - round).abs() < 1e-2);
+    }
+}
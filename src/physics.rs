@@ -0,0 +1,434 @@
+//! 2D physics on rapier2d in libforge's y-down pixel coordinates.
+//!
+//! Bodies are rectangles or circles created straight from draw-call geometry and stepped
+//! at a fixed timestep. Positions, sizes and velocities are in pixels and pixels per
+//! second, and gravity points down the screen, so no unit conversion is needed: the
+//! rapier world runs in pixels with `length_unit` set to `PIXELS_PER_METER`. Joints,
+//! scene queries and events are on the underlying world via `rapier()` / `rapier_mut()`.
+//!
+//! ```no_run
+//! # let mut ctx: libforge::LibContext<std::sync::Arc<winit::window::Window>> = todo!();
+//! use libforge::Rect;
+//! use libforge::physics::{BodyKind, PhysicsWorld};
+//!
+//! let mut world = PhysicsWorld::new();
+//! world.add_rect(BodyKind::Static, Rect { x: 0.0, y: 500.0, w: 800.0, h: 40.0 });
+//! let ball = world.add_circle(BodyKind::Dynamic, (400.0, 100.0), 16.0);
+//!
+//! // each frame:
+//! world.step(ctx.frame_time());
+//! let (x, y) = world.body(ball).unwrap().position;
+//! ctx.debug_draw_physics(&world);
+//! ```
+
+use crate::Rect;
+use rapier2d::prelude::{
+    CoefficientCombineRule, ColliderBuilder, LockedAxes, RigidBody, RigidBodyBuilder,
+    RigidBodyHandle, RigidBodyType, Rotation, Vector,
+};
+
+pub use rapier2d;
+
+/// How many pixels rapier treats as one meter when scaling its internal tolerances.
+pub const PIXELS_PER_METER: f32 = 100.0;
+
+// Never run more than this many fixed steps per `step` call, so a long hitch can't
+// snowball into ever-longer frames.
+const MAX_STEPS_PER_CALL: u32 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BodyKind {
+    /// Moved by gravity and collisions.
+    Dynamic,
+    /// Never moves.
+    Static,
+    /// Moved only by its velocity; pushes dynamic bodies but isn't pushed back.
+    Kinematic,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Collider {
+    /// Box with half extents, centered on the body position.
+    Rect {
+        half_w: f32,
+        half_h: f32,
+    },
+    Circle {
+        radius: f32,
+    },
+}
+
+/// Handle to a body in a `PhysicsWorld`; the wrapped rapier handle indexes
+/// `rapier().bodies`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BodyHandle(pub RigidBodyHandle);
+
+/// A body to add with `PhysicsWorld::add_body`, or a body's state as read back by
+/// `PhysicsWorld::body`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Body {
+    pub kind: BodyKind,
+    /// Center, in pixels.
+    pub position: (f32, f32),
+    /// Radians; positive turns clockwise on screen, since `y` points down.
+    pub rotation: f32,
+    /// Pixels per second.
+    pub velocity: (f32, f32),
+    pub collider: Collider,
+    /// Bounciness in `[0, 1]`; a contact uses the larger of the two bodies' values.
+    pub restitution: f32,
+    pub friction: f32,
+    /// Mass per square pixel; only the ratio between bodies matters.
+    pub density: f32,
+    /// Keep the body upright, e.g. for platformer characters.
+    pub fixed_rotation: bool,
+    /// Free for game use, e.g. an entity index.
+    pub user_data: u64,
+}
+
+impl Body {
+    /// A box body covering `rect` (top-left and size, like `draw_rect`).
+    pub fn from_rect(kind: BodyKind, rect: Rect) -> Self {
+        Self {
+            position: (rect.x + rect.w * 0.5, rect.y + rect.h * 0.5),
+            collider: Collider::Rect {
+                half_w: rect.w * 0.5,
+                half_h: rect.h * 0.5,
+            },
+            ..Self::from_circle(kind, (0.0, 0.0), 0.0)
+        }
+    }
+
+    /// A circle body centered at `center` (like `draw_circle`).
+    pub fn from_circle(kind: BodyKind, center: (f32, f32), radius: f32) -> Self {
+        Self {
+            kind,
+            position: center,
+            rotation: 0.0,
+            velocity: (0.0, 0.0),
+            collider: Collider::Circle { radius },
+            restitution: 0.0,
+            friction: 0.5,
+            density: 1.0,
+            fixed_rotation: false,
+            user_data: 0,
+        }
+    }
+
+    /// The collider's unrotated rectangle in pixels (a circle's bounding square), e.g. to
+    /// draw the body with `draw_rect` when `fixed_rotation` is set.
+    pub fn rect(&self) -> Rect {
+        let (hw, hh) = self.half_extents();
+        Rect {
+            x: self.position.0 - hw,
+            y: self.position.1 - hh,
+            w: hw * 2.0,
+            h: hh * 2.0,
+        }
+    }
+
+    /// Corners of the rotated collider box, clockwise on screen from the top-left.
+    pub fn corners(&self) -> [(f32, f32); 4] {
+        let (hw, hh) = self.half_extents();
+        let (sin, cos) = self.rotation.sin_cos();
+        let (x, y) = self.position;
+        [(-hw, -hh), (hw, -hh), (hw, hh), (-hw, hh)]
+            .map(|(dx, dy)| (x + dx * cos - dy * sin, y + dx * sin + dy * cos))
+    }
+
+    fn half_extents(&self) -> (f32, f32) {
+        match self.collider {
+            Collider::Rect { half_w, half_h } => (half_w, half_h),
+            Collider::Circle { radius } => (radius, radius),
+        }
+    }
+}
+
+/// A touching pair found during the last fixed step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+    pub a: BodyHandle,
+    pub b: BodyHandle,
+    /// Unit normal pointing from `a` to `b`.
+    pub normal: (f32, f32),
+    pub depth: f32,
+    /// A point on the contact, for debug drawing.
+    pub point: (f32, f32),
+}
+
+/// A rapier world stepped at a fixed timestep, in pixels.
+pub struct PhysicsWorld {
+    /// Pixels per second squared; positive `y` pulls down the screen.
+    pub gravity: (f32, f32),
+    /// Fixed step length in seconds.
+    pub timestep: f32,
+    world: rapier2d::prelude::PhysicsWorld,
+    accumulator: f32,
+}
+
+impl Default for PhysicsWorld {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PhysicsWorld {
+    pub fn new() -> Self {
+        let mut world = rapier2d::prelude::PhysicsWorld::new();
+        world.integration_parameters.length_unit = PIXELS_PER_METER;
+        Self {
+            gravity: (0.0, 980.0),
+            timestep: 1.0 / 60.0,
+            world,
+            accumulator: 0.0,
+        }
+    }
+
+    /// The underlying rapier world, e.g. for ray casts or contact pairs.
+    pub fn rapier(&self) -> &rapier2d::prelude::PhysicsWorld {
+        &self.world
+    }
+
+    /// The underlying rapier world, e.g. to add joints. Its `gravity` and `dt` are
+    /// overwritten from `gravity` and `timestep` before every step.
+    pub fn rapier_mut(&mut self) -> &mut rapier2d::prelude::PhysicsWorld {
+        &mut self.world
+    }
+
+    pub fn add_body(&mut self, body: Body) -> BodyHandle {
+        let builder = match body.kind {
+            BodyKind::Dynamic => RigidBodyBuilder::dynamic(),
+            BodyKind::Static => RigidBodyBuilder::fixed(),
+            BodyKind::Kinematic => RigidBodyBuilder::kinematic_velocity_based(),
+        }
+        .translation(to_vector(body.position))
+        .rotation(body.rotation)
+        .linvel(to_vector(body.velocity))
+        .user_data(body.user_data.into());
+        let builder = if body.fixed_rotation {
+            builder.lock_rotations()
+        } else {
+            builder
+        };
+        let collider = match body.collider {
+            Collider::Rect { half_w, half_h } => ColliderBuilder::cuboid(half_w, half_h),
+            Collider::Circle { radius } => ColliderBuilder::ball(radius),
+        }
+        .restitution(body.restitution)
+        .restitution_combine_rule(CoefficientCombineRule::Max)
+        .friction(body.friction)
+        .density(body.density);
+        let (handle, _) = self.world.insert(builder, collider);
+        BodyHandle(handle)
+    }
+
+    /// Add a box body covering `rect` (top-left and size, like `draw_rect`).
+    pub fn add_rect(&mut self, kind: BodyKind, rect: Rect) -> BodyHandle {
+        self.add_body(Body::from_rect(kind, rect))
+    }
+
+    /// Add a circle body centered at `center` (like `draw_circle`).
+    pub fn add_circle(&mut self, kind: BodyKind, center: (f32, f32), radius: f32) -> BodyHandle {
+        self.add_body(Body::from_circle(kind, center, radius))
+    }
+
+    /// Remove `handle` and its collider, returning its last state.
+    pub fn remove(&mut self, handle: BodyHandle) -> Option<Body> {
+        let body = self.body(handle)?;
+        self.world.remove_body_with_colliders(handle.0, true);
+        Some(body)
+    }
+
+    /// Current state of `handle`; change it with `set_position`, `set_velocity` or
+    /// `apply_impulse`.
+    pub fn body(&self, handle: BodyHandle) -> Option<Body> {
+        let rb = self.world.bodies.get(handle.0)?;
+        Some(self.read_body(rb))
+    }
+
+    /// Teleport `handle` to `position` (center, in pixels).
+    pub fn set_position(&mut self, handle: BodyHandle, position: (f32, f32)) {
+        if let Some(rb) = self.world.bodies.get_mut(handle.0) {
+            rb.set_translation(to_vector(position), true);
+        }
+    }
+
+    pub fn set_rotation(&mut self, handle: BodyHandle, rotation: f32) {
+        if let Some(rb) = self.world.bodies.get_mut(handle.0) {
+            rb.set_rotation(Rotation::new(rotation), true);
+        }
+    }
+
+    /// Set the velocity of `handle`, in pixels per second.
+    pub fn set_velocity(&mut self, handle: BodyHandle, velocity: (f32, f32)) {
+        if let Some(rb) = self.world.bodies.get_mut(handle.0) {
+            rb.set_linvel(to_vector(velocity), true);
+        }
+    }
+
+    /// Change the velocity of a dynamic body by `impulse / mass`, e.g. for a jump.
+    pub fn apply_impulse(&mut self, handle: BodyHandle, impulse: (f32, f32)) {
+        if let Some(rb) = self.world.bodies.get_mut(handle.0) {
+            rb.apply_impulse(to_vector(impulse), true);
+        }
+    }
+
+    pub fn bodies(&self) -> impl Iterator<Item = (BodyHandle, Body)> + '_ {
+        self.world
+            .bodies
+            .iter()
+            .map(|(handle, rb)| (BodyHandle(handle), self.read_body(rb)))
+    }
+
+    /// Contacts from the most recent fixed step, one per touching pair at its deepest
+    /// point.
+    pub fn contacts(&self) -> impl Iterator<Item = Contact> + '_ {
+        self.world.contact_pairs().filter_map(|pair| {
+            if !pair.has_any_active_contact() {
+                return None;
+            }
+            let (manifold, contact) = pair.find_deepest_contact()?;
+            let a = manifold.data.rigid_body1?;
+            let b = manifold.data.rigid_body2?;
+            let collider = self.world.colliders.get(pair.collider1)?;
+            let point = collider.position().transform_point(contact.local_p1);
+            Some(Contact {
+                a: BodyHandle(a),
+                b: BodyHandle(b),
+                normal: (manifold.data.normal.x, manifold.data.normal.y),
+                depth: -contact.dist,
+                point: (point.x, point.y),
+            })
+        })
+    }
+
+    /// Advance by `dt` seconds of frame time, running as many fixed steps as fit.
+    /// Returns the number of steps run.
+    pub fn step(&mut self, dt: f32) -> u32 {
+        self.world.gravity = to_vector(self.gravity);
+        self.world.integration_parameters.dt = self.timestep;
+
+        self.accumulator += dt.max(0.0);
+        let mut steps = 0;
+        while self.accumulator >= self.timestep && steps < MAX_STEPS_PER_CALL {
+            self.world.step();
+            self.accumulator -= self.timestep;
+            steps += 1;
+        }
+        if steps == MAX_STEPS_PER_CALL {
+            self.accumulator = self.accumulator.min(self.timestep);
+        }
+        steps
+    }
+
+    /// How far the leftover frame time reaches toward the next step, in `[0, 1]`, for
+    /// blending body positions with `Interpolated::lerp`.
+    pub fn interpolation_alpha(&self) -> f32 {
+        (self.accumulator / self.timestep).min(1.0)
+    }
+
+    fn read_body(&self, rb: &RigidBody) -> Body {
+        let kind = match rb.body_type() {
+            RigidBodyType::Dynamic => BodyKind::Dynamic,
+            RigidBodyType::Fixed => BodyKind::Static,
+            _ => BodyKind::Kinematic,
+        };
+        let collider = rb
+            .colliders()
+            .first()
+            .and_then(|&c| self.world.colliders.get(c));
+        let shape = collider.and_then(|c| {
+            let shape = c.shape();
+            if let Some(cuboid) = shape.as_cuboid() {
+                Some(Collider::Rect {
+                    half_w: cuboid.half_extents.x,
+                    half_h: cuboid.half_extents.y,
+                })
+            } else {
+                shape.as_ball().map(|ball| Collider::Circle {
+                    radius: ball.radius,
+                })
+            }
+        });
+        let (position, velocity) = (rb.translation(), rb.linvel());
+        Body {
+            kind,
+            position: (position.x, position.y),
+            rotation: rb.rotation().angle(),
+            velocity: (velocity.x, velocity.y),
+            collider: shape.unwrap_or(Collider::Circle { radius: 0.0 }),
+            restitution: collider.map_or(0.0, |c| c.restitution()),
+            friction: collider.map_or(0.0, |c| c.friction()),
+            density: collider.map_or(0.0, |c| c.density()),
+            fixed_rotation: rb.locked_axes().contains(LockedAxes::ROTATION_LOCKED),
+            user_data: rb.user_data as u64,
+        }
+    }
+}
+
+fn to_vector((x, y): (f32, f32)) -> Vector {
+    Vector::new(x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropped_body_comes_to_rest_on_the_ground() {
+        let mut world = PhysicsWorld::new();
+        let ground = world.add_rect(
+            BodyKind::Static,
+            Rect {
+                x: 0.0,
+                y: 100.0,
+                w: 200.0,
+                h: 20.0,
+            },
+        );
+        let ball = world.add_circle(BodyKind::Dynamic, (100.0, 0.0), 10.0);
+        for _ in 0..180 {
+            world.step(1.0 / 60.0);
+        }
+
+        let body = world.body(ball).unwrap();
+        // Resting on top of the ground (y-down: smaller y is higher).
+        assert!((body.position.1 - 90.0).abs() < 1.0, "{:?}", body.position);
+        assert!(body.velocity.1.abs() < 20.0);
+
+        let contact = world.contacts().next().unwrap();
+        let (a, b) = (
+            world.body(contact.a).unwrap(),
+            world.body(contact.b).unwrap(),
+        );
+        let towards_b = (b.position.0 - a.position.0) * contact.normal.0
+            + (b.position.1 - a.position.1) * contact.normal.1;
+        assert!(towards_b > 0.0, "{contact:?}");
+        assert!([contact.a, contact.b].contains(&ground));
+    }
+
+    #[test]
+    fn removed_bodies_are_gone_and_long_frames_are_capped() {
+        let mut world = PhysicsWorld::new();
+        let body = Body {
+            restitution: 0.8,
+            user_data: 7,
+            ..Body::from_rect(
+                BodyKind::Dynamic,
+                Rect {
+                    x: 0.0,
+                    y: 0.0,
+                    w: 20.0,
+                    h: 10.0,
+                },
+            )
+        };
+        let a = world.add_body(body);
+        assert_eq!(world.body(a), Some(body));
+
+        assert_eq!(world.remove(a), Some(body));
+        assert!(world.body(a).is_none());
+        assert_eq!(world.bodies().count(), 0);
+        assert_eq!(world.step(1.0), MAX_STEPS_PER_CALL);
+    }
+}
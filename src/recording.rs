@@ -0,0 +1,164 @@
+use image::RgbaImage;
+use image::codecs::gif::{GifEncoder, Repeat};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, SyncSender, TrySendError, sync_channel};
+use std::thread::JoinHandle;
+
+// Frames buffered for the encoder thread; further frames are dropped while it catches up.
+const QUEUE_FRAMES: usize = 8;
+
+/// Gameplay recording to an animated GIF or a numbered PNG sequence.
+///
+/// Frames are sampled at the recording frame rate and encoded on a background thread.
+pub(crate) struct Recorder {
+    sender: SyncSender<RgbaImage>,
+    worker: JoinHandle<Result<(), String>>,
+    interval: f32,
+    // Time since the last captured frame; starts at `interval` so the first frame is captured.
+    elapsed: f32,
+    dropped: usize,
+}
+
+enum Sink {
+    Gif(GifEncoder<BufWriter<File>>, image::Delay),
+    Png { dir: PathBuf, next: u32 },
+}
+
+impl Sink {
+    /// A `.gif` path records an animated GIF; any other path is a directory for
+    /// `frame_00000.png`, `frame_00001.png`, ...
+    fn open(path: &Path, fps: f32) -> Result<Self, String> {
+        let is_gif = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("gif"));
+        if is_gif {
+            let file = File::create(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            let mut encoder = GifEncoder::new(BufWriter::new(file));
+            encoder
+                .set_repeat(Repeat::Infinite)
+                .map_err(|e| e.to_string())?;
+            let delay = image::Delay::from_numer_denom_ms(1000, fps.round().max(1.0) as u32);
+            Ok(Sink::Gif(encoder, delay))
+        } else {
+            std::fs::create_dir_all(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            Ok(Sink::Png {
+                dir: path.to_path_buf(),
+                next: 0,
+            })
+        }
+    }
+
+    fn write(&mut self, frame: RgbaImage) -> Result<(), String> {
+        match self {
+            Sink::Gif(encoder, delay) => encoder
+                .encode_frame(image::Frame::from_parts(frame, 0, 0, *delay))
+                .map_err(|e| e.to_string()),
+            Sink::Png { dir, next } => {
+                let path = dir.join(format!("frame_{next:05}.png"));
+                *next += 1;
+                frame
+                    .save(&path)
+                    .map_err(|e| format!("{}: {e}", path.display()))
+            }
+        }
+    }
+}
+
+fn encode(mut sink: Sink, frames: Receiver<RgbaImage>) -> Result<(), String> {
+    let mut size = None;
+    for frame in frames {
+        // GIF frames share one canvas; frames from after a window resize are skipped.
+        if *size.get_or_insert(frame.dimensions()) != frame.dimensions() {
+            continue;
+        }
+        sink.write(frame)?;
+    }
+    Ok(())
+}
+
+impl Recorder {
+    pub fn start(path: &Path, fps: f32) -> Result<Self, String> {
+        let fps = fps.max(1.0);
+        let sink = Sink::open(path, fps)?;
+        let (sender, receiver) = sync_channel(QUEUE_FRAMES);
+        let worker = std::thread::Builder::new()
+            .name("libforge-recorder".into())
+            .spawn(move || encode(sink, receiver))
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            sender,
+            worker,
+            interval: 1.0 / fps,
+            elapsed: 1.0 / fps,
+            dropped: 0,
+        })
+    }
+
+    /// Advance by `dt` seconds; `true` when this frame should be captured.
+    pub fn wants_frame(&mut self, dt: f32) -> bool {
+        self.elapsed += dt;
+        if self.elapsed < self.interval {
+            return false;
+        }
+        // Keep the remainder so the average rate matches `fps`, but never bank more
+        // than one frame (after a hitch, capture once rather than in a burst).
+        self.elapsed = (self.elapsed - self.interval).min(self.interval);
+        true
+    }
+
+    pub fn push(&mut self, frame: RgbaImage) {
+        match self.sender.try_send(frame) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => self.dropped += 1,
+            // The worker stopped on an error; `finish` reports it.
+            Err(TrySendError::Disconnected(_)) => {}
+        }
+    }
+
+    /// Stop accepting frames and wait for the encoder to flush. Returns the number of
+    /// frames dropped because encoding fell behind.
+    pub fn finish(self) -> Result<usize, String> {
+        drop(self.sender);
+        self.worker
+            .join()
+            .map_err(|_| "recording thread panicked".to_string())??;
+        Ok(self.dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_sampled_at_the_recording_rate() {
+        let dir = std::env::temp_dir().join("libforge-recording-rate");
+        let mut recorder = Recorder::start(&dir, 10.0).unwrap();
+        let captured = (0..60).filter(|_| recorder.wants_frame(1.0 / 60.0)).count();
+        assert!((10..=11).contains(&captured), "{captured}");
+        recorder.finish().unwrap();
+    }
+
+    #[test]
+    fn writes_gif_and_png_sequence() {
+        let dir = std::env::temp_dir().join("libforge-recording-output");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let frame = RgbaImage::from_pixel(4, 4, image::Rgba([255, 0, 0, 255]));
+
+        let gif = dir.join("clip.gif");
+        let mut recorder = Recorder::start(&gif, 30.0).unwrap();
+        recorder.push(frame.clone());
+        recorder.push(frame.clone());
+        recorder.finish().unwrap();
+        assert!(std::fs::metadata(&gif).unwrap().len() > 0);
+
+        let frames = dir.join("frames");
+        let mut recorder = Recorder::start(&frames, 30.0).unwrap();
+        recorder.push(frame);
+        recorder.finish().unwrap();
+        assert!(frames.join("frame_00000.png").exists());
+    }
+}
@@ -0,0 +1,138 @@
+use std::time::{Duration, Instant};
+use winit::event::WindowEvent;
+use winit::event_loop::ControlFlow;
+
+/// How often the app wants frames: every loop iteration, or only when something changed.
+///
+/// Set with `ctx.set_control_flow_hint(..)`, then drive the event loop from
+/// `ctx.control_flow()` and `ctx.needs_redraw()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ControlFlowHint {
+    /// Render continuously, as games do.
+    #[default]
+    Continuous,
+    /// Render only after input, a resize or `invalidate()`, sleeping in between so GUI
+    /// tools and editors leave the GPU idle.
+    Reactive,
+}
+
+/// Tracks whether a frame is needed in `ControlFlowHint::Reactive` mode.
+#[derive(Debug)]
+pub(crate) struct RedrawState {
+    hint: ControlFlowHint,
+    // Something changed since the last frame began.
+    dirty: bool,
+    // Earliest requested timed redraw, e.g. for a blinking caret.
+    deadline: Option<Instant>,
+}
+
+impl Default for RedrawState {
+    fn default() -> Self {
+        Self {
+            hint: ControlFlowHint::default(),
+            // The first frame always has to be drawn.
+            dirty: true,
+            deadline: None,
+        }
+    }
+}
+
+impl RedrawState {
+    pub fn set_hint(&mut self, hint: ControlFlowHint) {
+        self.hint = hint;
+        self.dirty = true;
+    }
+
+    pub fn hint(&self) -> ControlFlowHint {
+        self.hint
+    }
+
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn invalidate_after(&mut self, delay: Duration, now: Instant) {
+        let at = now + delay;
+        self.deadline = Some(self.deadline.map_or(at, |d| d.min(at)));
+    }
+
+    /// Any window event except the redraw request itself may change what is on screen.
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        if !matches!(event, WindowEvent::RedrawRequested) {
+            self.dirty = true;
+        }
+    }
+
+    pub fn needs_redraw(&self, now: Instant) -> bool {
+        self.hint == ControlFlowHint::Continuous
+            || self.dirty
+            || self.deadline.is_some_and(|d| d <= now)
+    }
+
+    /// A frame is starting: everything so far is about to be drawn.
+    pub fn begin_frame(&mut self, now: Instant) {
+        self.dirty = false;
+        if self.deadline.is_some_and(|d| d <= now) {
+            self.deadline = None;
+        }
+    }
+
+    /// Reactive mode always sleeps: `request_redraw` wakes the loop for pending frames.
+    pub fn control_flow(&self) -> ControlFlow {
+        match self.hint {
+            ControlFlowHint::Continuous => ControlFlow::Poll,
+            ControlFlowHint::Reactive => self
+                .deadline
+                .map_or(ControlFlow::Wait, ControlFlow::WaitUntil),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reactive_mode_sleeps_until_invalidated() {
+        let now = Instant::now();
+        let mut redraw = RedrawState::default();
+        redraw.set_hint(ControlFlowHint::Reactive);
+        assert!(redraw.needs_redraw(now));
+
+        redraw.begin_frame(now);
+        assert!(!redraw.needs_redraw(now));
+        assert_eq!(redraw.control_flow(), ControlFlow::Wait);
+
+        redraw.handle_event(&WindowEvent::RedrawRequested);
+        assert!(!redraw.needs_redraw(now));
+        redraw.handle_event(&WindowEvent::Focused(true));
+        assert!(redraw.needs_redraw(now));
+    }
+
+    #[test]
+    fn timed_redraws_wake_at_the_earliest_deadline() {
+        let now = Instant::now();
+        let mut redraw = RedrawState::default();
+        redraw.set_hint(ControlFlowHint::Reactive);
+        redraw.begin_frame(now);
+
+        redraw.invalidate_after(Duration::from_millis(500), now);
+        redraw.invalidate_after(Duration::from_millis(100), now);
+        let wake = now + Duration::from_millis(100);
+        assert_eq!(redraw.control_flow(), ControlFlow::WaitUntil(wake));
+        assert!(!redraw.needs_redraw(now));
+        assert!(redraw.needs_redraw(wake));
+
+        redraw.begin_frame(wake);
+        assert_eq!(redraw.control_flow(), ControlFlow::Wait);
+    }
+
+    #[test]
+    fn continuous_mode_always_polls() {
+        let now = Instant::now();
+        let mut redraw = RedrawState::default();
+        redraw.begin_frame(now);
+        assert!(redraw.needs_redraw(now));
+        assert_eq!(redraw.control_flow(), ControlFlow::Poll);
+    }
+}
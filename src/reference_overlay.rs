@@ -0,0 +1,80 @@
+use crate::Key;
+use crate::renderer::TextureId;
+
+/// A mockup or concept-art image drawn semi-transparently over the finished frame.
+///
+/// Set one with `ctx.set_reference_image(Some(texture))`. The image is drawn at its native
+/// size (times `scale`) in screen space, so implemented scenes can be matched to the art
+/// pixel for pixel. With `keyboard` enabled, arrow keys nudge the image by one pixel and
+/// `-`/`=` change the opacity.
+#[derive(Clone, Copy, Debug)]
+pub struct ReferenceOverlay {
+    pub texture: TextureId,
+    /// Alpha of the overlay in `[0, 1]`.
+    pub opacity: f32,
+    /// Top-left corner of the image, in logical pixels.
+    pub offset: (f32, f32),
+    pub scale: f32,
+    pub visible: bool,
+    /// Handle arrow / `-` / `=` keys in `begin_drawing()`. Turn off if the game needs them.
+    pub keyboard: bool,
+}
+
+// Opacity change per `-` / `=` press.
+const OPACITY_STEP: f32 = 0.1;
+
+impl ReferenceOverlay {
+    pub fn new(texture: TextureId) -> Self {
+        Self {
+            texture,
+            opacity: 0.5,
+            offset: (0.0, 0.0),
+            scale: 1.0,
+            visible: true,
+            keyboard: true,
+        }
+    }
+
+    /// Apply this frame's nudge and opacity key presses.
+    pub(crate) fn handle_keys(&mut self, pressed: impl Fn(Key) -> bool) {
+        if !self.keyboard || !self.visible {
+            return;
+        }
+        let (x, y) = &mut self.offset;
+        for (key, dx, dy) in [
+            (Key::Left, -1.0, 0.0),
+            (Key::Right, 1.0, 0.0),
+            (Key::Up, 0.0, -1.0),
+            (Key::Down, 0.0, 1.0),
+        ] {
+            if pressed(key) {
+                *x += dx;
+                *y += dy;
+            }
+        }
+        if pressed(Key::Minus) {
+            self.opacity -= OPACITY_STEP;
+        }
+        if pressed(Key::Equal) {
+            self.opacity += OPACITY_STEP;
+        }
+        self.opacity = self.opacity.clamp(0.0, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_nudge_and_fade_only_when_enabled() {
+        let mut overlay = ReferenceOverlay::new(TextureId(0));
+        overlay.handle_keys(|k| matches!(k, Key::Right | Key::Down | Key::Equal));
+        assert_eq!(overlay.offset, (1.0, 1.0));
+        assert!((overlay.opacity - 0.6).abs() < 1e-6);
+
+        overlay.keyboard = false;
+        overlay.handle_keys(|_| true);
+        assert_eq!(overlay.offset, (1.0, 1.0));
+    }
+}
@@ -0,0 +1,387 @@
+// Block-compressed texture containers (DDS, KTX2) and a CPU fallback decoder.
+//
+// Parsing and decoding are `wgpu`-free apart from the format mapping, so they are unit-tested
+// without a device.
+
+use crate::error::RendererError;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum BcFormat {
+    Bc1,
+    Bc2,
+    Bc3,
+    Bc4,
+    Bc5,
+    Bc6hUfloat,
+    Bc6hFloat,
+    Bc7,
+}
+
+impl BcFormat {
+    pub(crate) fn block_bytes(self) -> usize {
+        match self {
+            BcFormat::Bc1 | BcFormat::Bc4 => 8,
+            _ => 16,
+        }
+    }
+
+    pub(crate) fn wgpu_format(self, srgb: bool) -> wgpu::TextureFormat {
+        use wgpu::TextureFormat as F;
+        match (self, srgb) {
+            (BcFormat::Bc1, false) => F::Bc1RgbaUnorm,
+            (BcFormat::Bc1, true) => F::Bc1RgbaUnormSrgb,
+            (BcFormat::Bc2, false) => F::Bc2RgbaUnorm,
+            (BcFormat::Bc2, true) => F::Bc2RgbaUnormSrgb,
+            (BcFormat::Bc3, false) => F::Bc3RgbaUnorm,
+            (BcFormat::Bc3, true) => F::Bc3RgbaUnormSrgb,
+            (BcFormat::Bc4, _) => F::Bc4RUnorm,
+            (BcFormat::Bc5, _) => F::Bc5RgUnorm,
+            (BcFormat::Bc6hUfloat, _) => F::Bc6hRgbUfloat,
+            (BcFormat::Bc6hFloat, _) => F::Bc6hRgbFloat,
+            (BcFormat::Bc7, false) => F::Bc7RgbaUnorm,
+            (BcFormat::Bc7, true) => F::Bc7RgbaUnormSrgb,
+        }
+    }
+}
+
+/// A parsed container: format plus the raw block data of each mip level (largest first).
+pub(crate) struct CompressedImage<'a> {
+    pub format: BcFormat,
+    pub srgb: bool,
+    pub width: u32,
+    pub height: u32,
+    pub levels: Vec<&'a [u8]>,
+}
+
+fn level_size(format: BcFormat, width: u32, height: u32, level: u32) -> usize {
+    let w = (width >> level).max(1).div_ceil(4) as usize;
+    let h = (height >> level).max(1).div_ceil(4) as usize;
+    w * h * format.block_bytes()
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Result<u32, RendererError> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| RendererError::Internal("truncated texture header".into()))
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> Result<u64, RendererError> {
+    bytes
+        .get(offset..offset + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| RendererError::Internal("truncated texture header".into()))
+}
+
+fn unsupported(what: String) -> RendererError {
+    RendererError::Internal(format!("unsupported compressed texture: {what}"))
+}
+
+const KTX2_MAGIC: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+/// Whether `bytes` start like a DDS or KTX2 file.
+pub(crate) fn is_compressed_container(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"DDS ") || bytes.starts_with(&KTX2_MAGIC)
+}
+
+/// Parse a DDS or KTX2 file holding a single 2D BC1-BC7 texture.
+pub(crate) fn parse(bytes: &[u8]) -> Result<CompressedImage<'_>, RendererError> {
+    if bytes.starts_with(b"DDS ") {
+        parse_dds(bytes)
+    } else if bytes.starts_with(&KTX2_MAGIC) {
+        parse_ktx2(bytes)
+    } else {
+        Err(unsupported("not a DDS or KTX2 file".into()))
+    }
+}
+
+fn parse_dds(bytes: &[u8]) -> Result<CompressedImage<'_>, RendererError> {
+    let height = u32_at(bytes, 12)?;
+    let width = u32_at(bytes, 16)?;
+    let mip_count = u32_at(bytes, 28)?.max(1);
+    let fourcc = bytes
+        .get(84..88)
+        .ok_or_else(|| RendererError::Internal("truncated texture header".into()))?;
+
+    let (format, srgb, data_start) = match fourcc {
+        b"DXT1" => (BcFormat::Bc1, false, 128),
+        b"DXT2" | b"DXT3" => (BcFormat::Bc2, false, 128),
+        b"DXT4" | b"DXT5" => (BcFormat::Bc3, false, 128),
+        b"ATI1" | b"BC4U" => (BcFormat::Bc4, false, 128),
+        b"ATI2" | b"BC5U" => (BcFormat::Bc5, false, 128),
+        b"DX10" => {
+            let dxgi = u32_at(bytes, 128)?;
+            let (format, srgb) = match dxgi {
+                71 => (BcFormat::Bc1, false),
+                72 => (BcFormat::Bc1, true),
+                74 => (BcFormat::Bc2, false),
+                75 => (BcFormat::Bc2, true),
+                77 => (BcFormat::Bc3, false),
+                78 => (BcFormat::Bc3, true),
+                80 => (BcFormat::Bc4, false),
+                83 => (BcFormat::Bc5, false),
+                95 => (BcFormat::Bc6hUfloat, false),
+                96 => (BcFormat::Bc6hFloat, false),
+                98 => (BcFormat::Bc7, false),
+                99 => (BcFormat::Bc7, true),
+                other => return Err(unsupported(format!("DXGI format {other}"))),
+            };
+            (format, srgb, 148)
+        }
+        other => return Err(unsupported(format!("DDS FourCC {other:?}"))),
+    };
+
+    let mut levels = Vec::new();
+    let mut offset = data_start;
+    for level in 0..mip_count {
+        let len = level_size(format, width, height, level);
+        let Some(data) = bytes.get(offset..offset + len) else {
+            break;
+        };
+        levels.push(data);
+        offset += len;
+    }
+    if levels.is_empty() {
+        return Err(RendererError::Internal("DDS file has no image data".into()));
+    }
+
+    Ok(CompressedImage {
+        format,
+        srgb,
+        width,
+        height,
+        levels,
+    })
+}
+
+fn parse_ktx2(bytes: &[u8]) -> Result<CompressedImage<'_>, RendererError> {
+    let vk_format = u32_at(bytes, 12)?;
+    let width = u32_at(bytes, 20)?;
+    let height = u32_at(bytes, 24)?;
+    let depth = u32_at(bytes, 28)?;
+    let layers = u32_at(bytes, 32)?;
+    let faces = u32_at(bytes, 36)?;
+    let level_count = u32_at(bytes, 40)?.max(1);
+    let supercompression = u32_at(bytes, 44)?;
+
+    if depth > 1 || layers > 1 || faces > 1 {
+        return Err(unsupported("KTX2 arrays, cubemaps and 3D textures".into()));
+    }
+    if supercompression != 0 {
+        return Err(unsupported(format!(
+            "KTX2 supercompression scheme {supercompression}"
+        )));
+    }
+
+    let (format, srgb) = match vk_format {
+        131 | 133 => (BcFormat::Bc1, false),
+        132 | 134 => (BcFormat::Bc1, true),
+        135 => (BcFormat::Bc2, false),
+        136 => (BcFormat::Bc2, true),
+        137 => (BcFormat::Bc3, false),
+        138 => (BcFormat::Bc3, true),
+        139 => (BcFormat::Bc4, false),
+        141 => (BcFormat::Bc5, false),
+        143 => (BcFormat::Bc6hUfloat, false),
+        144 => (BcFormat::Bc6hFloat, false),
+        145 => (BcFormat::Bc7, false),
+        146 => (BcFormat::Bc7, true),
+        other => return Err(unsupported(format!("VkFormat {other}"))),
+    };
+
+    let mut levels = Vec::new();
+    for level in 0..level_count as usize {
+        let entry = 80 + level * 24;
+        let offset = u64_at(bytes, entry)? as usize;
+        let len = u64_at(bytes, entry + 8)? as usize;
+        let data = bytes
+            .get(offset..offset + len)
+            .ok_or_else(|| RendererError::Internal("truncated KTX2 level".into()))?;
+        levels.push(data);
+    }
+
+    Ok(CompressedImage {
+        format,
+        srgb,
+        width,
+        height,
+        levels,
+    })
+}
+
+// --- CPU fallback -----------------------------------------------------------------------
+
+fn rgb565(c: u16) -> [u8; 3] {
+    let r = ((c >> 11) & 31) as u32;
+    let g = ((c >> 5) & 63) as u32;
+    let b = (c & 31) as u32;
+    [
+        ((r * 255 + 15) / 31) as u8,
+        ((g * 255 + 31) / 63) as u8,
+        ((b * 255 + 15) / 31) as u8,
+    ]
+}
+
+/// BC1 color block into 16 RGBA texels. `four_color` forces the opaque 4-color mode (BC2/BC3).
+fn decode_color_block(block: &[u8], four_color: bool, out: &mut [[u8; 4]; 16]) {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let (a, b) = (rgb565(c0), rgb565(c1));
+    let mix = |wa: u32, wb: u32, d: u32| -> [u8; 4] {
+        let f = |i: usize| ((a[i] as u32 * wa + b[i] as u32 * wb) / d) as u8;
+        [f(0), f(1), f(2), 255]
+    };
+    let palette = if four_color || c0 > c1 {
+        [mix(1, 0, 1), mix(0, 1, 1), mix(2, 1, 3), mix(1, 2, 3)]
+    } else {
+        [mix(1, 0, 1), mix(0, 1, 1), mix(1, 1, 2), [0, 0, 0, 0]]
+    };
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    for (i, texel) in out.iter_mut().enumerate() {
+        *texel = palette[((indices >> (2 * i)) & 3) as usize];
+    }
+}
+
+/// BC3/BC4-style interpolated single-channel block into 16 values.
+fn decode_channel_block(block: &[u8]) -> [u8; 16] {
+    let (a0, a1) = (block[0] as u32, block[1] as u32);
+    let mut palette = [0u8; 8];
+    palette[0] = a0 as u8;
+    palette[1] = a1 as u8;
+    if a0 > a1 {
+        for i in 1..7 {
+            palette[i + 1] = ((a0 * (7 - i as u32) + a1 * i as u32) / 7) as u8;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i + 1] = ((a0 * (5 - i as u32) + a1 * i as u32) / 5) as u8;
+        }
+        palette[6] = 0;
+        palette[7] = 255;
+    }
+    let mut bits = 0u64;
+    for (i, &b) in block[2..8].iter().enumerate() {
+        bits |= (b as u64) << (8 * i);
+    }
+    let mut out = [0u8; 16];
+    for (i, v) in out.iter_mut().enumerate() {
+        *v = palette[((bits >> (3 * i)) & 7) as usize];
+    }
+    out
+}
+
+/// Decode BC1-BC5 data to tightly packed RGBA8. BC6H/BC7 have no CPU fallback (`None`).
+pub(crate) fn decode_to_rgba(
+    format: BcFormat,
+    width: u32,
+    height: u32,
+    data: &[u8],
+) -> Option<Vec<u8>> {
+    if matches!(
+        format,
+        BcFormat::Bc6hUfloat | BcFormat::Bc6hFloat | BcFormat::Bc7
+    ) {
+        return None;
+    }
+    let block_bytes = format.block_bytes();
+    let blocks_w = width.div_ceil(4) as usize;
+    let blocks_h = height.div_ceil(4) as usize;
+    if data.len() < blocks_w * blocks_h * block_bytes {
+        return None;
+    }
+
+    let mut rgba = vec![0u8; width as usize * height as usize * 4];
+    let mut texels = [[0u8; 4]; 16];
+    for by in 0..blocks_h {
+        for bx in 0..blocks_w {
+            let block = &data[(by * blocks_w + bx) * block_bytes..][..block_bytes];
+            match format {
+                BcFormat::Bc1 => decode_color_block(block, false, &mut texels),
+                BcFormat::Bc2 => {
+                    decode_color_block(&block[8..], true, &mut texels);
+                    for (i, t) in texels.iter_mut().enumerate() {
+                        let nibble = (block[i / 2] >> (4 * (i % 2))) & 15;
+                        t[3] = nibble * 17;
+                    }
+                }
+                BcFormat::Bc3 => {
+                    decode_color_block(&block[8..], true, &mut texels);
+                    for (t, a) in texels.iter_mut().zip(decode_channel_block(block)) {
+                        t[3] = a;
+                    }
+                }
+                BcFormat::Bc4 => {
+                    for (t, r) in texels.iter_mut().zip(decode_channel_block(block)) {
+                        *t = [r, 0, 0, 255];
+                    }
+                }
+                BcFormat::Bc5 => {
+                    let red = decode_channel_block(block);
+                    let green = decode_channel_block(&block[8..]);
+                    for (i, t) in texels.iter_mut().enumerate() {
+                        *t = [red[i], green[i], 0, 255];
+                    }
+                }
+                BcFormat::Bc6hUfloat | BcFormat::Bc6hFloat | BcFormat::Bc7 => unreachable!(),
+            }
+
+            for (i, texel) in texels.iter().enumerate() {
+                let x = bx * 4 + i % 4;
+                let y = by * 4 + i / 4;
+                if x < width as usize && y < height as usize {
+                    let o = (y * width as usize + x) * 4;
+                    rgba[o..o + 4].copy_from_slice(texel);
+                }
+            }
+        }
+    }
+    Some(rgba)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dds_dxt1(width: u32, height: u32, blocks: &[u8]) -> Vec<u8> {
+        let mut dds = vec![0u8; 128];
+        dds[0..4].copy_from_slice(b"DDS ");
+        dds[12..16].copy_from_slice(&height.to_le_bytes());
+        dds[16..20].copy_from_slice(&width.to_le_bytes());
+        dds[28..32].copy_from_slice(&1u32.to_le_bytes());
+        dds[84..88].copy_from_slice(b"DXT1");
+        dds.extend_from_slice(blocks);
+        dds
+    }
+
+    #[test]
+    fn dds_bc1_parses_and_decodes() {
+        // c0 = pure red, c1 = pure blue, first row uses index 0/1/2/3.
+        let block = [0x00, 0xF8, 0x1F, 0x00, 0b1110_0100, 0, 0, 0];
+        let dds = dds_dxt1(4, 4, &block);
+        assert!(is_compressed_container(&dds));
+
+        let image = parse(&dds).unwrap();
+        assert_eq!(image.format, BcFormat::Bc1);
+        assert_eq!((image.width, image.height, image.levels.len()), (4, 4, 1));
+
+        let rgba = decode_to_rgba(image.format, 4, 4, image.levels[0]).unwrap();
+        assert_eq!(rgba[0..4], [255, 0, 0, 255]);
+        assert_eq!(rgba[4..8], [0, 0, 255, 255]);
+        assert_eq!(rgba[8..12], [170, 0, 85, 255]);
+        assert_eq!(rgba[16..20], [255, 0, 0, 255]); // second row: index 0
+
+        assert!(decode_to_rgba(BcFormat::Bc7, 4, 4, &[0; 16]).is_none());
+    }
+
+    #[test]
+    fn bc4_channel_block_interpolates() {
+        // Texel indices 0, 2, 1 (3 bits each, little-endian).
+        let block = [255, 0, 0b0101_0000, 0, 0, 0, 0, 0];
+        let values = decode_channel_block(&block);
+        assert_eq!(values[0], 255);
+        assert_eq!(values[1], 218); // (255 * 6) / 7
+        assert_eq!(values[2], 0);
+    }
+}
@@ -0,0 +1,94 @@
+use super::compressed;
+use crate::error::RendererError;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+
+/// A texture decoded off the main thread, ready to upload.
+pub(crate) enum Decoded {
+    Rgba(image::RgbaImage),
+    // DDS/KTX2 containers are uploaded from their blocks; the bytes are kept as they are.
+    Compressed,
+}
+
+/// Decode one encoded texture (PNG, JPEG, ...) to RGBA8.
+pub(crate) fn decode(bytes: &[u8]) -> Result<Decoded, RendererError> {
+    if compressed::is_compressed_container(bytes) {
+        return Ok(Decoded::Compressed);
+    }
+    let img =
+        image::load_from_memory(bytes).map_err(|e| RendererError::Internal(format!("{:?}", e)))?;
+    Ok(Decoded::Rgba(img.to_rgba8()))
+}
+
+/// Decode `images` on up to one worker thread per core, calling `done` on the calling
+/// thread with each index and result as soon as it is ready (in completion order).
+pub(crate) fn decode_parallel(
+    images: &[&[u8]],
+    mut done: impl FnMut(usize, Result<Decoded, RendererError>),
+) {
+    let workers = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(images.len());
+    let next = AtomicUsize::new(0);
+    std::thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(bytes) = images.get(index) else {
+                        break;
+                    };
+                    if sender.send((index, decode(bytes))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        // The receiver ends once every worker has dropped its sender.
+        drop(sender);
+        for (index, result) in receiver {
+            done(index, result);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::RgbaImage::new(width, height)
+            .write_to(
+                &mut std::io::Cursor::new(&mut bytes),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn every_image_is_decoded_once() {
+        let files: Vec<Vec<u8>> = (1..=9).map(|i| png(i, 2)).collect();
+        let mut images: Vec<&[u8]> = files.iter().map(Vec::as_slice).collect();
+        images.push(b"not an image");
+
+        let mut widths = vec![None; images.len()];
+        let mut failed = Vec::new();
+        decode_parallel(&images, |index, result| match result {
+            Ok(Decoded::Rgba(img)) => {
+                assert!(widths[index].replace(img.width()).is_none());
+            }
+            Ok(Decoded::Compressed) => panic!("PNG taken for a compressed container"),
+            Err(_) => failed.push(index),
+        });
+
+        let expected: Vec<Option<u32>> = (1..=9).map(Some).chain([None]).collect();
+        assert_eq!(widths, expected);
+        assert_eq!(failed, [9]);
+        decode_parallel(&[], |_, _| panic!("nothing to decode"));
+    }
+}
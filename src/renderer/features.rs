@@ -0,0 +1,107 @@
+/// Optional GPU capability the renderer can request at device creation.
+///
+/// Missing features never fail initialization: request them in [`RendererConfig`] and
+/// check what was granted with `ctx.supports(feature)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// Timestamps between draws of a render pass, needed by `gpu_scope`.
+    TimestampQuery,
+    /// BC1–BC7 textures uploaded without CPU decoding (desktop GPUs).
+    TextureCompressionBc,
+    /// ETC2/EAC compressed textures (most mobile GPUs).
+    TextureCompressionEtc2,
+    /// ASTC compressed textures (newer mobile and Apple GPUs).
+    TextureCompressionAstc,
+}
+
+impl Feature {
+    /// The wgpu features that must all be granted for this feature to be usable.
+    pub fn wgpu_features(self) -> wgpu::Features {
+        match self {
+            Feature::TimestampQuery => super::gpu_timer::GpuTimer::FEATURES,
+            Feature::TextureCompressionBc => wgpu::Features::TEXTURE_COMPRESSION_BC,
+            Feature::TextureCompressionEtc2 => wgpu::Features::TEXTURE_COMPRESSION_ETC2,
+            Feature::TextureCompressionAstc => wgpu::Features::TEXTURE_COMPRESSION_ASTC,
+        }
+    }
+}
+
+/// Device settings used when creating the renderer.
+///
+/// ```ignore
+/// let config = RendererConfig {
+///     features: vec![Feature::TextureCompressionBc],
+///     ..Default::default()
+/// };
+/// let mut ctx = LibContext::new_from_window_with_config(window, config)?;
+/// if !ctx.supports(Feature::TextureCompressionBc) {
+///     // load PNG fallbacks instead
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RendererConfig {
+    /// Requested where the adapter has them; the rest are silently left out.
+    pub features: Vec<Feature>,
+    /// Required limits: creation fails if the adapter can't meet them.
+    pub limits: wgpu::Limits,
+    /// `LowPower` prefers an integrated GPU, for tools and apps that shouldn't wake the
+    /// discrete one.
+    pub power_preference: wgpu::PowerPreference,
+    /// Composite the window over the desktop using the frame's alpha, for overlays and
+    /// desktop pets. The window must also be created with `with_transparent(true)`; clear
+    /// with `Color::TRANSPARENT` and check `ctx.is_transparent()`, as not every platform
+    /// offers it.
+    pub transparent: bool,
+}
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self {
+            features: vec![Feature::TimestampQuery, Feature::TextureCompressionBc],
+            limits: wgpu::Limits::default(),
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            transparent: false,
+        }
+    }
+}
+
+impl RendererConfig {
+    /// The requested features the adapter offers. A feature is only requested when all of
+    /// its wgpu features are available, so a granted `Feature` is always fully usable.
+    pub(crate) fn negotiate(&self, available: wgpu::Features) -> wgpu::Features {
+        self.features
+            .iter()
+            .map(|feature| feature.wgpu_features())
+            .filter(|&needed| available.contains(needed))
+            .fold(wgpu::Features::empty(), |acc, needed| acc | needed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiation_drops_features_the_adapter_lacks() {
+        let config = RendererConfig {
+            features: vec![
+                Feature::TextureCompressionBc,
+                Feature::TextureCompressionAstc,
+            ],
+            ..Default::default()
+        };
+        let available = wgpu::Features::TEXTURE_COMPRESSION_BC | wgpu::Features::DEPTH_CLIP_CONTROL;
+        assert_eq!(
+            config.negotiate(available),
+            wgpu::Features::TEXTURE_COMPRESSION_BC
+        );
+    }
+
+    #[test]
+    fn partially_available_features_are_not_requested() {
+        // Timestamps outside passes alone can't time `gpu_scope`.
+        let config = RendererConfig::default();
+        let granted = config.negotiate(wgpu::Features::TIMESTAMP_QUERY);
+        assert!(granted.is_empty());
+    }
+}
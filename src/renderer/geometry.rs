@@ -5,6 +5,19 @@
 use crate::vertex::Vertex;
 use std::f32::consts::PI;
 
+// Largest distance (in output pixels) allowed between a true circle and its polygon.
+const CIRCLE_TOLERANCE_PX: f32 = 0.25;
+
+// helper: segment count keeping a circle of `radius_px` output pixels within
+// `CIRCLE_TOLERANCE_PX` of round (sagitta r * (1 - cos(pi / n))), clamped to 8..=256
+pub(crate) fn auto_circle_segments(radius_px: f32) -> usize {
+    if radius_px.is_nan() || radius_px <= CIRCLE_TOLERANCE_PX {
+        return 8;
+    }
+    let n = PI / (1.0 - CIRCLE_TOLERANCE_PX / radius_px).acos();
+    (n.ceil() as usize).clamp(8, 256)
+}
+
 // helper: convert a line (x1,y1)-(x2,y2) and thickness into a quad (4 points)
 // Returns points in CCW order: [top-left, top-right, bottom-right, bottom-left]
 pub(crate) fn line_to_quad(x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32) -> [[f32; 2]; 4] {
@@ -76,6 +89,22 @@ pub(crate) fn quad_to_vertices(quad: [[f32; 2]; 4], color: [f32; 4]) -> Vec<Vert
     ]
 }
 
+/// Two triangles covering clip space, with UVs spanning a texture the size of the target.
+pub(crate) fn fullscreen_quad(color: [f32; 4]) -> [Vertex; 6] {
+    let corner = |x: f32, y: f32| Vertex {
+        pos: [x, y],
+        uv: [(x + 1.0) / 2.0, (1.0 - y) / 2.0],
+        color,
+    };
+    let (tl, tr, br, bl) = (
+        corner(-1.0, 1.0),
+        corner(1.0, 1.0),
+        corner(1.0, -1.0),
+        corner(-1.0, -1.0),
+    );
+    [tl, tr, br, tl, br, bl]
+}
+
 // helper: build a triangle-fan circle in pixel-space
 // returns Vec<Vertex> with triangles (center, p_i, p_i+1)
 pub(crate) fn circle_to_vertices(
@@ -84,6 +113,18 @@ pub(crate) fn circle_to_vertices(
     radius: f32,
     segments: usize,
     color: [f32; 4],
+) -> Vec<Vertex> {
+    ellipse_to_vertices(cx, cy, radius, radius, segments, color)
+}
+
+// helper: triangle-fan ellipse with radii (rx, ry) as a triangle list
+pub(crate) fn ellipse_to_vertices(
+    cx: f32,
+    cy: f32,
+    rx: f32,
+    ry: f32,
+    segments: usize,
+    color: [f32; 4],
 ) -> Vec<Vertex> {
     let mut verts = Vec::with_capacity(segments * 3);
     let step = 2.0 * PI / (segments as f32);
@@ -91,10 +132,10 @@ pub(crate) fn circle_to_vertices(
     for i in 0..segments {
         let a0 = (i as f32) * step;
         let a1 = ((i + 1) as f32) * step;
-        let x0 = cx + a0.cos() * radius;
-        let y0 = cy + a0.sin() * radius;
-        let x1 = cx + a1.cos() * radius;
-        let y1 = cy + a1.sin() * radius;
+        let x0 = cx + a0.cos() * rx;
+        let y0 = cy + a0.sin() * ry;
+        let x1 = cx + a1.cos() * rx;
+        let y1 = cy + a1.sin() * ry;
 
         // triangle (center, p0, p1)
         verts.push(Vertex {
@@ -116,3 +157,78 @@ pub(crate) fn circle_to_vertices(
 
     verts
 }
+
+// helper: points around an ellipse, for outlines and feathered fills
+pub(crate) fn ellipse_points(cx: f32, cy: f32, rx: f32, ry: f32, segments: usize) -> Vec<[f32; 2]> {
+    let step = 2.0 * PI / (segments as f32);
+    (0..segments)
+        .map(|i| {
+            let a = (i as f32) * step;
+            [cx + a.cos() * rx, cy + a.sin() * ry]
+        })
+        .collect()
+}
+
+// helper: fill a convex polygon whose edges fade out over `feather` units.
+// The solid part is inset by half the feather and the alpha ramp reaches half outside, so
+// the shape keeps its size while its edge is smoothed without MSAA. Either winding works.
+pub(crate) fn feathered_convex_to_vertices(
+    points: &[[f32; 2]],
+    feather: f32,
+    color: [f32; 4],
+) -> Vec<Vertex> {
+    let n = points.len();
+    // Twice the signed area: its sign tells which side of each edge is outside.
+    let area: f32 = (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum();
+    if n < 3 || area == 0.0 || !area.is_finite() {
+        return Vec::new();
+    }
+    let sign = area.signum();
+    let normal = |i: usize| {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+        let len = (dx * dx + dy * dy).sqrt().max(1e-12);
+        [sign * dy / len, -sign * dx / len]
+    };
+
+    // Per-vertex miter offset moving both adjacent edges by half the feather, capped at
+    // sharp corners.
+    let half = feather * 0.5;
+    let offsets: Vec<[f32; 2]> = (0..n)
+        .map(|i| {
+            let (a, b) = (normal((i + n - 1) % n), normal(i));
+            let (mx, my) = (a[0] + b[0], a[1] + b[1]);
+            let len = (mx * mx + my * my).sqrt();
+            if len < 1e-6 {
+                return [b[0] * half, b[1] * half];
+            }
+            let cos_half_angle = (mx * b[0] + my * b[1]) / len;
+            let scale = half / cos_half_angle.max(0.25) / len;
+            [mx * scale, my * scale]
+        })
+        .collect();
+
+    let transparent = [color[0], color[1], color[2], 0.0];
+    let vertex = |p: [f32; 2], o: [f32; 2], outward: f32, color| Vertex {
+        pos: [p[0] + o[0] * outward, p[1] + o[1] * outward],
+        uv: [0.0, 0.0],
+        color,
+    };
+    let inner = |i: usize| vertex(points[i], offsets[i], -1.0, color);
+    let outer = |i: usize| vertex(points[i], offsets[i], 1.0, transparent);
+
+    let mut verts = Vec::with_capacity((n - 2) * 3 + n * 6);
+    for i in 1..n - 1 {
+        verts.extend([inner(0), inner(i), inner(i + 1)]);
+    }
+    for i in 0..n {
+        let j = (i + 1) % n;
+        verts.extend([inner(i), inner(j), outer(j), inner(i), outer(j), outer(i)]);
+    }
+    verts
+}
@@ -1,47 +1,835 @@
+use super::gpu_timer::GpuTimer;
+use super::offscreen::OffscreenTarget;
 use crate::error::RendererError;
-use crate::vertex::Vertex;
+use crate::vertex::{ShapeVertex, Vertex};
 use glam::Mat4;
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
+use std::collections::HashMap;
 use wgpu::util::DeviceExt;
 
 pub(crate) struct RendererGpu<W> {
-    // These fields are kept to ensure the underlying windowing resources outlive the surface.
-    _window: W,
-    _instance: wgpu::Instance,
-    pub(crate) surface: wgpu::Surface<'static>,
-    _adapter: wgpu::Adapter,
+    // The window is kept to ensure the underlying windowing resources outlive the surface.
+    // Both are `None` while suspended (mobile platforms destroy the native window in the
+    // background); the instance stays to create the surface again on resume.
+    _window: Option<W>,
+    instance: wgpu::Instance,
+    pub(crate) surface: Option<wgpu::Surface<'static>>,
+    adapter: wgpu::Adapter,
     pub(crate) device: wgpu::Device,
     pub(crate) queue: wgpu::Queue,
     pub(crate) surface_config: wgpu::SurfaceConfiguration,
 
-    pub(crate) pipeline: wgpu::RenderPipeline,
-    pub(crate) texture_pipeline: wgpu::RenderPipeline,
+    // Present modes the surface supports, for `set_vsync`.
+    present_modes: Vec<wgpu::PresentMode>,
+
+    pipelines: PipelineCache,
+    // Depth attachment and per-vertex depth stream, created the first time a frame uses
+    // depth sorting; from then on every pipeline is keyed for a depth attachment.
+    depth: Option<DepthTarget>,
+    // Layouts soft particles read the depth attachment through, single- and multisampled.
+    scene_depth_layouts: [wgpu::BindGroupLayout; 2],
+    // Fraction of the surface's width and height the frame is rendered at; below 1 the
+    // frame goes through `scene_target` and is upscaled by the post pass.
+    render_scale: f32,
+    // Multisampled color target resolved into the surface; `None` without MSAA.
+    sample_count: u32,
+    msaa_view: Option<wgpu::TextureView>,
+    // Whether the surface can be copied from, for frame capture; and captured frames on
+    // their way back to the CPU.
+    can_capture: bool,
+    readbacks: super::readback::FrameReadbacks,
+    // Timestamp queries for `gpu_scope`; `None` when the device can't time inside a pass.
+    timer: Option<super::gpu_timer::GpuTimer>,
+    // Layers for `begin_group`, one per nesting level, kept across frames; and the
+    // sampler they are composited with.
+    group_targets: Vec<OffscreenTarget>,
+    target_sampler: wgpu::Sampler,
+    // The frame is drawn here instead of the surface when the post pass (color LUT,
+    // transitions) reads it.
+    scene_target: Option<OffscreenTarget>,
+    post: super::post::PostPass,
 
     pub(crate) vertex_buffer: wgpu::Buffer,
     pub(crate) vertex_capacity: usize,
 
+    // SDF shape quads use their own vertex layout, so they live in a separate buffer.
+    shape_vertex_buffer: wgpu::Buffer,
+    shape_vertex_capacity: usize,
+
     pub(crate) tex_bind_group_layout: wgpu::BindGroupLayout,
+    mipmaps: super::mipmap::MipmapGenerator,
 
-    pub(crate) transform_buffer: wgpu::Buffer,
+    // Per-batch draw parameters (view-projection, model, color adjust, effects),
+    // indexed with a dynamic offset into `params_buffer`.
+    pub(crate) transform_bind_group_layout: wgpu::BindGroupLayout,
     pub(crate) transform_bind_group: wgpu::BindGroup,
+    pub(crate) params_buffer: wgpu::Buffer,
+    pub(crate) params_capacity: usize,
+    pub(crate) params_stride: u64,
+}
+
+/// One per-batch uniform entry; mirrors `DrawParams` in `basic.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct DrawParams {
+    pub(crate) view_proj: [f32; 16],
+    pub(crate) model: [f32; 16],
+    pub(crate) tint: [f32; 4],
+    pub(crate) color_adjust: [f32; 4],
+    pub(crate) effect: [f32; 4],
+}
+
+impl DrawParams {
+    /// Identity transforms, white tint, no color adjust, no effect.
+    pub(crate) const NEUTRAL: DrawParams = DrawParams {
+        view_proj: [
+            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+        ],
+        model: [
+            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+        ],
+        tint: [1.0; 4],
+        color_adjust: [0.0, 1.0, 1.0, 0.0],
+        effect: [0.0; 4],
+    };
+}
+
+const PARAMS_SIZE: u64 = std::mem::size_of::<DrawParams>() as u64;
+
+/// Pack a `ColorAdjust` into the `DrawParams.color_adjust` uniform layout.
+pub(crate) fn color_adjust_uniform(adjust: crate::ColorAdjust) -> [f32; 4] {
+    [adjust.hue_shift, adjust.saturation, adjust.brightness, 0.0]
+}
+
+/// sRGB-encoded channel to linear (the standard piecewise transfer function).
+pub(crate) fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Linear channel to sRGB encoding (inverse of `srgb_to_linear`).
+pub(crate) fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Build the per-frame list of draw-parameter slots and the slot each command binds.
+///
+/// Slot 0 holds `view_proj` (the frame's starting projection) with neutral state.
+/// `decode_srgb` sets `color_adjust.w` in every slot, telling the vertex shader to convert
+/// vertex colors and tints from sRGB to linear.
+/// `SetViewProj`, `SetColorAdjust`, effect draws (backgrounds, soft particles) and mesh
+/// draws each append one slot; other commands reuse the slot of the active view-projection
+/// and color adjust.
+pub(crate) fn collect_params(
+    commands: &[super::DrawCommand],
+    view_proj: Mat4,
+    decode_srgb: bool,
+) -> (Vec<DrawParams>, Vec<u32>) {
+    let srgb_flag = if decode_srgb { 1.0 } else { 0.0 };
+    let mut base = DrawParams {
+        view_proj: view_proj.to_cols_array(),
+        ..DrawParams::NEUTRAL
+    };
+    base.color_adjust[3] = srgb_flag;
+    let mut base_slot = 0u32;
+    let mut params = vec![base];
+    let mut slots = Vec::with_capacity(commands.len());
+
+    for cmd in commands {
+        match cmd {
+            super::DrawCommand::SetColorAdjust(a) => {
+                base.color_adjust = color_adjust_uniform(*a);
+                base.color_adjust[3] = srgb_flag;
+                base_slot = params.len() as u32;
+                params.push(base);
+                slots.push(base_slot);
+            }
+            super::DrawCommand::SetViewProj(m) => {
+                base.view_proj = m.to_cols_array();
+                base_slot = params.len() as u32;
+                params.push(base);
+                slots.push(base_slot);
+            }
+            super::DrawCommand::Background { effect, .. } => {
+                slots.push(params.len() as u32);
+                params.push(DrawParams {
+                    effect: *effect,
+                    ..base
+                });
+            }
+            super::DrawCommand::Particles {
+                softness: Some(softness),
+                ..
+            } => {
+                slots.push(params.len() as u32);
+                params.push(DrawParams {
+                    effect: [*softness, 0.0, 0.0, 0.0],
+                    ..base
+                });
+            }
+            super::DrawCommand::Mesh { model, tint, .. } => {
+                slots.push(params.len() as u32);
+                params.push(DrawParams {
+                    model: model.to_cols_array(),
+                    tint: *tint,
+                    ..base
+                });
+            }
+            // Group layers are composited as-is: clip-space quad, no color adjust.
+            super::DrawCommand::EndGroup { .. } => {
+                slots.push(params.len() as u32);
+                let mut composite = DrawParams::NEUTRAL;
+                composite.color_adjust[3] = srgb_flag;
+                params.push(composite);
+            }
+            _ => slots.push(base_slot),
+        }
+    }
+
+    (params, slots)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum PipelineKind {
+    Color,
+    Texture,
+    PremultipliedTexture,
+    SortedColor,
+    SortedTexture,
+    SortedPremultipliedTexture,
+    Background,
+    Particles,
+    SoftParticles,
+    Shapes,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum VertexStream {
+    Shared,
+    Shapes,
+    Mesh(u32),
+}
+
+/// Render pass state already bound, so the submission loop only issues real changes.
+#[derive(Default)]
+struct BoundState {
+    pipeline: Option<PipelineKey>,
+    // Texture id and wrap override of the bound texture bind group.
+    texture: Option<(u32, Option<(super::TextureWrap, super::TextureWrap)>)>,
+    vertices: Option<VertexStream>,
+    depths: bool,
+}
+
+impl BoundState {
+    /// Bind the pipeline for `key`, creating it on first use.
+    fn pipeline(
+        &mut self,
+        rpass: &mut wgpu::RenderPass<'_>,
+        key: PipelineKey,
+        (cache, device): (&mut PipelineCache, &wgpu::Device),
+    ) {
+        if self.pipeline != Some(key) {
+            self.pipeline = Some(key);
+            rpass.set_pipeline(cache.get(device, key));
+        }
+    }
+
+    fn vertex_stream(
+        &mut self,
+        rpass: &mut wgpu::RenderPass<'_>,
+        stream: VertexStream,
+        buffer: &wgpu::Buffer,
+    ) {
+        if self.vertices != Some(stream) {
+            self.vertices = Some(stream);
+            rpass.set_vertex_buffer(0, buffer.slice(..));
+        }
+    }
+}
+
+/// Debug group name for a draw command; `None` for state changes and draws that will be
+/// skipped.
+pub(crate) fn draw_label(
+    cmd: &super::DrawCommand,
+    textures: &HashMap<u32, super::Texture>,
+    meshes: &HashMap<u32, super::Mesh>,
+) -> Option<String> {
+    use super::DrawCommand;
+
+    Some(match cmd {
+        DrawCommand::Color { count, .. } => format!("color ({count} vertices)"),
+        DrawCommand::Texture { tex, count, .. } => {
+            format!(
+                "texture '{}' ({count} vertices)",
+                textures.get(&tex.0)?.name
+            )
+        }
+        DrawCommand::Background { .. } => "background".to_string(),
+        DrawCommand::Particles { count, .. } => format!("particles ({count} vertices)"),
+        DrawCommand::Shapes { count, .. } => format!("shapes ({} quads)", count / 6),
+        DrawCommand::Mesh { mesh, .. } => {
+            meshes.get(&mesh.0)?;
+            format!("mesh {}", mesh.0)
+        }
+        DrawCommand::SetViewProj(_)
+        | DrawCommand::SetColorAdjust(_)
+        | DrawCommand::SetDepthSort(_)
+        | DrawCommand::GpuTimestamp(_)
+        | DrawCommand::DebugMarker(_)
+        | DrawCommand::BeginGroup
+        | DrawCommand::EndGroup { .. } => return None,
+    })
+}
+
+/// Surface format to render into: 8-bit sRGB where offered (mobile drivers may list
+/// 10-bit or linear formats first), then any sRGB format, then whatever comes first.
+pub(crate) fn choose_surface_format(formats: &[wgpu::TextureFormat]) -> wgpu::TextureFormat {
+    [
+        wgpu::TextureFormat::Bgra8UnormSrgb,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+    ]
+    .into_iter()
+    .find(|f| formats.contains(f))
+    .or_else(|| formats.iter().copied().find(|f| f.is_srgb()))
+    .unwrap_or(formats[0])
+}
+
+/// Opaque composition where offered; Android commonly lists `Inherit` first, which leaves
+/// the frame's alpha up to the platform. A `transparent` window prefers premultiplied alpha,
+/// which is what alpha blending leaves in the frame.
+pub(crate) fn choose_alpha_mode(
+    modes: &[wgpu::CompositeAlphaMode],
+    transparent: bool,
+) -> wgpu::CompositeAlphaMode {
+    use wgpu::CompositeAlphaMode::{Opaque, PostMultiplied, PreMultiplied};
+    let preferred: &[wgpu::CompositeAlphaMode] = if transparent {
+        &[PreMultiplied, PostMultiplied, Opaque]
+    } else {
+        &[Opaque]
+    };
+    preferred
+        .iter()
+        .copied()
+        .find(|mode| modes.contains(mode))
+        .unwrap_or(modes[0])
+}
+
+/// Number of times consecutive draws change pipeline (logged per frame).
+pub(crate) fn pipeline_switches(commands: &[super::DrawCommand]) -> usize {
+    use super::DrawCommand;
+
+    let mut current = None;
+    let mut switches = 0;
+    for cmd in commands {
+        // Meshes share the color pipeline.
+        let pipeline = match cmd {
+            DrawCommand::Color { .. } | DrawCommand::Mesh { .. } => 0,
+            DrawCommand::Texture { .. } | DrawCommand::EndGroup { .. } => 1,
+            DrawCommand::Background { .. } => 2,
+            DrawCommand::Particles { .. } => 3,
+            DrawCommand::Shapes { .. } => 4,
+            DrawCommand::SetViewProj(_)
+            | DrawCommand::SetColorAdjust(_)
+            | DrawCommand::SetDepthSort(_)
+            | DrawCommand::GpuTimestamp(_)
+            | DrawCommand::DebugMarker(_)
+            | DrawCommand::BeginGroup => continue,
+        };
+        if current != Some(pipeline) {
+            current = Some(pipeline);
+            switches += 1;
+        }
+    }
+    switches
+}
+
+/// Deepest `begin_group` nesting in the frame, i.e. how many group layers it needs.
+pub(crate) fn group_depth(commands: &[super::DrawCommand]) -> usize {
+    let mut depth = 0usize;
+    let mut deepest = 0;
+    for cmd in commands {
+        match cmd {
+            super::DrawCommand::BeginGroup => {
+                depth += 1;
+                deepest = deepest.max(depth);
+            }
+            super::DrawCommand::EndGroup { .. } => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    deepest
+}
+
+fn create_transform_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    params_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("transform_bind_group"),
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer: params_buffer,
+                offset: 0,
+                size: std::num::NonZeroU64::new(PARAMS_SIZE),
+            }),
+        }],
+    })
+}
+
+/// Additive blending: `dst + src * src_alpha`. Order-independent, so particles never need sorting.
+const ADDITIVE_BLENDING: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::SrcAlpha,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Zero,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+};
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+// Fragments of depth-sorted draws below this alpha are discarded (`alpha_cutoff` in the shader).
+const SORTED_ALPHA_CUTOFF: f64 = 0.5;
+
+/// Attachments of the main pass; every pipeline used in it must match them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct PassTarget {
+    format: wgpu::TextureFormat,
+    samples: u32,
+    // The pass has a depth attachment (depth sorting has been used).
+    depth: bool,
+}
+
+/// Depth buffer state for depth-sorted draws.
+struct DepthTarget {
+    view: wgpu::TextureView,
+    // `view` for soft particles to read, in the scene depth layout matching its samples.
+    bind_group: wgpu::BindGroup,
+    // Per-vertex depths parallel to the shared vertex buffer (vertex buffer slot 1).
+    buffer: wgpu::Buffer,
+    capacity: usize,
+}
+
+/// Identifies a main-pass pipeline in the `PipelineCache`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct PipelineKey {
+    // Shader entry points, vertex layout and pipeline layout.
+    kind: PipelineKind,
+    blend: wgpu::BlendState,
+    target: PassTarget,
+}
+
+impl PipelineKey {
+    /// `kind` with its default blending, for `target`.
+    fn new(kind: PipelineKind, target: PassTarget) -> Self {
+        let blend = match kind {
+            PipelineKind::PremultipliedTexture | PipelineKind::SortedPremultipliedTexture => {
+                wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING
+            }
+            // Particles are drawn last with additive blending.
+            PipelineKind::Particles | PipelineKind::SoftParticles => ADDITIVE_BLENDING,
+            _ => wgpu::BlendState::ALPHA_BLENDING,
+        };
+        Self {
+            kind,
+            blend,
+            target,
+        }
+    }
+}
+
+/// Main-pass render pipelines, built on first use for each key.
+///
+/// Pipelines for other sample counts or attachments stay cached, so toggling MSAA back
+/// doesn't rebuild anything.
+struct PipelineCache {
+    shader: wgpu::ShaderModule,
+    layout: wgpu::PipelineLayout,
+    // `layout` plus the texture bind group at index 1.
+    texture_layout: wgpu::PipelineLayout,
+    // `layout` plus the scene depth at index 1, single- and multisampled.
+    depth_layouts: [wgpu::PipelineLayout; 2],
+    pipelines: HashMap<PipelineKey, wgpu::RenderPipeline>,
+}
+
+impl PipelineCache {
+    fn new(
+        shader: wgpu::ShaderModule,
+        layout: wgpu::PipelineLayout,
+        texture_layout: wgpu::PipelineLayout,
+        depth_layouts: [wgpu::PipelineLayout; 2],
+    ) -> Self {
+        Self {
+            shader,
+            layout,
+            texture_layout,
+            depth_layouts,
+            pipelines: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, device: &wgpu::Device, key: PipelineKey) -> &wgpu::RenderPipeline {
+        self.pipelines.entry(key).or_insert_with(|| {
+            log_debug!("creating {:?} pipeline", key.kind);
+            create_keyed_pipeline(
+                device,
+                &self.shader,
+                (&self.layout, &self.texture_layout, &self.depth_layouts),
+                key,
+            )
+        })
+    }
+
+    /// A cache for `shader` holding the same keys as this one, built eagerly so pipeline
+    /// errors surface now rather than mid-frame.
+    fn with_shader(&self, device: &wgpu::Device, shader: wgpu::ShaderModule) -> Self {
+        let mut cache = Self::new(
+            shader,
+            self.layout.clone(),
+            self.texture_layout.clone(),
+            self.depth_layouts.clone(),
+        );
+        for &key in self.pipelines.keys() {
+            cache.get(device, key);
+        }
+        cache
+    }
+}
+
+fn create_keyed_pipeline(
+    device: &wgpu::Device,
+    shader: &wgpu::ShaderModule,
+    (layout, texture_layout, depth_layouts): (
+        &wgpu::PipelineLayout,
+        &wgpu::PipelineLayout,
+        &[wgpu::PipelineLayout; 2],
+    ),
+    key: PipelineKey,
+) -> wgpu::RenderPipeline {
+    let PipelineKey {
+        kind,
+        blend,
+        target,
+    } = key;
+    let basic = |label, layout, fs_entry| {
+        create_basic_pipeline(device, label, layout, shader, fs_entry, target, blend)
+    };
+    let sorted = |label, layout, fs_entry| {
+        create_sorted_pipeline(device, label, layout, shader, fs_entry, target, blend)
+    };
+    match kind {
+        PipelineKind::Color => basic("basic_pipeline", layout, "fs_color"),
+        PipelineKind::Texture => basic("texture_pipeline", texture_layout, "fs_texture"),
+        // Same as `Texture`, for textures whose alpha was premultiplied at load.
+        PipelineKind::PremultipliedTexture => basic(
+            "premultiplied_texture_pipeline",
+            texture_layout,
+            "fs_texture_premultiplied",
+        ),
+        PipelineKind::SortedColor => sorted("sorted_basic_pipeline", layout, "fs_color"),
+        PipelineKind::SortedTexture => {
+            sorted("sorted_texture_pipeline", texture_layout, "fs_texture")
+        }
+        PipelineKind::SortedPremultipliedTexture => sorted(
+            "sorted_premultiplied_texture_pipeline",
+            texture_layout,
+            "fs_texture_premultiplied",
+        ),
+        // Procedural backgrounds share the color pipeline's layout (no texture bind group).
+        PipelineKind::Background => basic("background_pipeline", layout, "fs_background"),
+        PipelineKind::Particles => basic("particle_pipeline", layout, "fs_particle"),
+        PipelineKind::SoftParticles => create_soft_particle_pipeline(
+            device,
+            &depth_layouts[(target.samples > 1) as usize],
+            shader,
+            target,
+            blend,
+        ),
+        PipelineKind::Shapes => create_pipeline(
+            device,
+            "shape_pipeline",
+            layout,
+            shader,
+            ("vs_shape", ShapeVertex::desc()),
+            "fs_shape",
+            target,
+            blend,
+        ),
+    }
+}
+
+/// Create a triangle-list pipeline using `vs_main` and the given fragment entry.
+fn create_basic_pipeline(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    fs_entry: &str,
+    target: PassTarget,
+    blend: wgpu::BlendState,
+) -> wgpu::RenderPipeline {
+    create_pipeline(
+        device,
+        label,
+        layout,
+        shader,
+        ("vs_main", Vertex::desc()),
+        fs_entry,
+        target,
+        blend,
+    )
+}
+
+/// Create a triangle-list pipeline with a custom vertex entry point and vertex layout.
+#[allow(clippy::too_many_arguments)]
+fn create_pipeline(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    (vs_entry, vertex_layout): (&str, wgpu::VertexBufferLayout<'_>),
+    fs_entry: &str,
+    target: PassTarget,
+    blend: wgpu::BlendState,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(layout),
+        cache: None,
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some(vs_entry),
+            buffers: &[vertex_layout],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some(fs_entry),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: target.format,
+                blend: Some(blend),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+        // Unsorted draws ignore the depth buffer and keep painter's order.
+        depth_stencil: target
+            .depth
+            .then(|| depth_state(false, wgpu::CompareFunction::Always)),
+        multisample: wgpu::MultisampleState {
+            count: target.samples,
+            ..Default::default()
+        },
+        multiview: None,
+    })
+}
+
+/// Create a depth-tested pipeline using `vs_sorted`, which reads per-vertex depth from
+/// vertex buffer slot 1. Nearer (larger) or equal depths win, so ties keep call order.
+fn create_sorted_pipeline(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    fs_entry: &str,
+    target: PassTarget,
+    blend: wgpu::BlendState,
+) -> wgpu::RenderPipeline {
+    let constants = [("alpha_cutoff", SORTED_ALPHA_CUTOFF)];
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(layout),
+        cache: None,
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_sorted"),
+            buffers: &[Vertex::desc(), depth_vertex_layout()],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some(fs_entry),
+            compilation_options: wgpu::PipelineCompilationOptions {
+                constants: &constants,
+                ..Default::default()
+            },
+            targets: &[Some(wgpu::ColorTargetState {
+                format: target.format,
+                blend: Some(blend),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: Some(depth_state(true, wgpu::CompareFunction::GreaterEqual)),
+        multisample: wgpu::MultisampleState {
+            count: target.samples,
+            ..Default::default()
+        },
+        multiview: None,
+    })
+}
+
+/// Create the soft particle pipeline. `vs_soft_particle` reads per-vertex depth like
+/// `vs_sorted`, and the fragment entry reads the scene depth bound at index 1, so the pass
+/// has to keep its depth attachment read-only.
+fn create_soft_particle_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    target: PassTarget,
+    blend: wgpu::BlendState,
+) -> wgpu::RenderPipeline {
+    let fs_entry = if target.samples > 1 {
+        "fs_soft_particle_ms"
+    } else {
+        "fs_soft_particle"
+    };
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("soft_particle_pipeline"),
+        layout: Some(layout),
+        cache: None,
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_soft_particle"),
+            buffers: &[Vertex::desc(), depth_vertex_layout()],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some(fs_entry),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: target.format,
+                blend: Some(blend),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: target
+            .depth
+            .then(|| depth_state(false, wgpu::CompareFunction::Always)),
+        multisample: wgpu::MultisampleState {
+            count: target.samples,
+            ..Default::default()
+        },
+        multiview: None,
+    })
+}
+
+/// Per-vertex depths at shader location 3, from vertex buffer slot 1.
+fn depth_vertex_layout() -> wgpu::VertexBufferLayout<'static> {
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<f32>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![3 => Float32],
+    }
+}
+
+fn depth_state(write: bool, compare: wgpu::CompareFunction) -> wgpu::DepthStencilState {
+    wgpu::DepthStencilState {
+        format: DEPTH_FORMAT,
+        depth_write_enabled: write,
+        depth_compare: compare,
+        stencil: wgpu::StencilState::default(),
+        bias: wgpu::DepthBiasState::default(),
+    }
+}
+
+/// Everything `RendererGpu::end_frame` needs from the CPU-side renderer for one frame.
+pub(crate) struct FrameData<'a> {
+    pub(crate) vertices: &'a [Vertex],
+    // Depth of each vertex in `vertices`; empty when the frame has no depth-sorted draws.
+    pub(crate) depths: &'a [f32],
+    pub(crate) shape_vertices: &'a [ShapeVertex],
+    pub(crate) commands: &'a [super::DrawCommand],
+    pub(crate) clear_color: Option<[f32; 4]>,
+    // Colors (vertex colors, tints, clear color) are sRGB-encoded and need decoding.
+    pub(crate) srgb_colors: bool,
+    // Projection * view at the start of the command list (later changes are commands).
+    pub(crate) view_proj: Mat4,
+    pub(crate) viewport: Option<crate::viewport::Viewport>,
+    pub(crate) textures: &'a HashMap<u32, super::Texture>,
+    pub(crate) meshes: &'a HashMap<u32, super::Mesh>,
+    pub(crate) plugins: &'a mut [Box<dyn super::RenderPlugin>],
+    pub(crate) frame_hooks: Vec<super::FrameHook>,
+    // Copy the finished frame back to the CPU.
+    pub(crate) capture: bool,
+    // Names of the frame's GPU scopes; scope `i` is timed by queries `2i` and `2i + 1`.
+    pub(crate) gpu_scopes: &'a [String],
+    // Labels for `DrawCommand::DebugMarker`, and whether to name each draw in a debug group.
+    pub(crate) debug_labels: &'a [String],
+    pub(crate) debug_groups: bool,
+    // Color grading and transitions applied to the finished frame.
+    pub(crate) post: super::post::PostEffects<'a>,
 }
 
 impl<W> RendererGpu<W>
 where
     W: HasWindowHandle + HasDisplayHandle + wgpu::WasmNotSendSync + Sync + Clone + 'static,
 {
+    /// Render and present a frame. When `frame.capture` is set and the surface supports
+    /// being copied from, its pixels are read back in the background; returns the captured
+    /// frames (from earlier `end_frame`s) that have arrived since.
     pub(crate) fn end_frame(
         &mut self,
-        vertices: &[Vertex],
-        commands: &[super::DrawCommand],
-        clear_color: Option<[f32; 4]>,
-        textures: &std::collections::HashMap<u32, super::Texture>,
-    ) -> Result<(), RendererError> {
+        frame: FrameData<'_>,
+    ) -> Result<Vec<image::RgbaImage>, RendererError> {
+        let FrameData {
+            vertices,
+            depths,
+            shape_vertices,
+            commands,
+            clear_color,
+            srgb_colors,
+            view_proj,
+            viewport,
+            textures,
+            meshes,
+            plugins,
+            frame_hooks,
+            capture,
+            gpu_scopes,
+            debug_labels,
+            debug_groups,
+            post,
+        } = frame;
+        // A non-sRGB surface stores encoded values as-is, so there is nothing to decode.
+        let decode_srgb = srgb_colors && self.surface_config.format.is_srgb();
+
+        // Nothing to present to while suspended; the frame is dropped.
+        let Some(surface) = &self.surface else {
+            return Ok(Vec::new());
+        };
+
         // acquire next texture
-        let output = match self.surface.get_current_texture() {
+        let output = match surface.get_current_texture() {
             Ok(t) => t,
             Err(e) => {
-                self.surface.configure(&self.device, &self.surface_config);
+                log_warn!("surface texture unavailable ({e:?}), reconfiguring");
+                surface.configure(&self.device, &self.surface_config);
                 return Err(RendererError::Surface(format!("{:?}", e)));
             }
         };
@@ -52,6 +840,40 @@ where
 
         // upload vertex data
         self.upload_vertices(vertices);
+        self.upload_shape_vertices(shape_vertices);
+        if !depths.is_empty() {
+            self.upload_depths(depths);
+        }
+        let (params, param_slots) = collect_params(commands, view_proj, decode_srgb);
+        self.upload_params(&params);
+        let group_depth = group_depth(commands);
+        self.ensure_group_targets(group_depth);
+        // Scaled frames are upscaled to the surface by the post pass.
+        let surface_size = (self.surface_config.width, self.surface_config.height);
+        let render_size = self.render_size();
+        self.ensure_scene_target(post.is_active() || render_size != surface_size);
+        let viewport = viewport.map(|(x, y, w, h)| {
+            let sx = render_size.0 as f32 / surface_size.0 as f32;
+            let sy = render_size.1 as f32 / surface_size.1 as f32;
+            (x * sx, y * sy, w * sx, h * sy)
+        });
+        log_trace!(
+            "frame: {} vertices, {} commands, {} param slots, {} pipeline switches",
+            vertices.len(),
+            commands.len(),
+            params.len(),
+            pipeline_switches(commands)
+        );
+
+        // Timestamps are only written while the previous frame's readback isn't in flight.
+        let timer_ready = self
+            .timer
+            .as_mut()
+            .is_some_and(|timer| timer.begin_frame(&self.device));
+        let timer = self
+            .timer
+            .as_ref()
+            .filter(|_| timer_ready && !gpu_scopes.is_empty());
 
         // command encoder
         let mut encoder = self
@@ -60,67 +882,378 @@ where
                 label: Some("command_encoder"),
             });
 
-        let clear = clear_color.unwrap_or([0.1, 0.1, 0.1, 1.0]);
+        // Outside a virtual-resolution viewport the clear color becomes the letterbox bars.
+        let clear = match viewport {
+            Some(_) => [0.0, 0.0, 0.0, 1.0],
+            None => clear_color.unwrap_or([0.1, 0.1, 0.1, 1.0]),
+        };
+        let clear = if decode_srgb {
+            let [r, g, b, a] = clear;
+            [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+        } else {
+            clear
+        };
+        // Blending keeps the frame premultiplied from here on; the clear has to start that way.
+        let clear = if self.surface_config.alpha_mode == wgpu::CompositeAlphaMode::PreMultiplied {
+            let [r, g, b, a] = clear;
+            [r * a, g * a, b * a, a]
+        } else {
+            clear
+        };
 
-        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("render_pass"),
-            occlusion_query_set: None,
-            timestamp_writes: None,
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
-                resolve_target: None,
-                depth_slice: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: clear[0] as f64,
-                        g: clear[1] as f64,
-                        b: clear[2] as f64,
-                        a: clear[3] as f64,
-                    }),
-                    store: wgpu::StoreOp::Store,
-                },
-            })],
-            depth_stencil_attachment: None,
+        // Each `begin_group` layer is its own pass; the parent's pass resumes (loading what
+        // it drew so far) when the group ends and composites the layer as its first draw.
+        // Soft particles get a pass of their own too, reading the depth buffer the frame
+        // wrote. Split passes share the depth buffer, so it must survive between them.
+        let soft_particles = self.depth.is_some()
+            && commands.iter().any(|cmd| {
+                matches!(
+                    cmd,
+                    super::DrawCommand::Particles {
+                        softness: Some(_),
+                        ..
+                    }
+                )
+            });
+        let depth_store = if group_depth > 0 || soft_particles {
+            wgpu::StoreOp::Store
+        } else {
+            wgpu::StoreOp::Discard
+        };
+        let mut color_load = wgpu::LoadOp::Clear(wgpu::Color {
+            r: clear[0] as f64,
+            g: clear[1] as f64,
+            b: clear[2] as f64,
+            a: clear[3] as f64,
         });
+        let mut depth_load = wgpu::LoadOp::Clear(0.0);
+        // Open groups; draws go to the innermost one's layer, `group_targets[open - 1]`.
+        let mut open = 0usize;
+        // Layer index and quad of a group that just ended, drawn at the start of the next pass.
+        let mut composite: Option<(usize, usize, usize)> = None;
+        // Vertices of the soft particle layer, drawn at the start of the pass that reads depth.
+        let mut particles: Option<(usize, usize)> = None;
 
-        // Bind the transform bind group at index 0 (applies to both pipelines).
-        rpass.set_bind_group(0, &self.transform_bind_group, &[]);
+        let target = self.pass_target();
+        let key = |kind| PipelineKey::new(kind, target);
+        let mut commands = commands.iter().zip(&param_slots);
+        let mut bound_slot = 0u32;
+        // Inside a depth-sorted section (`SetDepthSort`), color and texture draws are depth
+        // tested; the depth attachment exists whenever the frame has depths.
+        let mut sorted = false;
 
-        if !vertices.is_empty() {
-            rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        }
+        'passes: loop {
+            let color_attachment = match (open, &self.scene_target) {
+                (0, Some(scene)) => scene.attachment(color_load),
+                (0, None) => wgpu::RenderPassColorAttachment {
+                    view: self.msaa_view.as_ref().unwrap_or(&view),
+                    resolve_target: self.msaa_view.as_ref().map(|_| &view),
+                    depth_slice: None,
+                    ops: wgpu::Operations {
+                        load: color_load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                },
+                (n, _) => self.group_targets[n - 1].attachment(color_load),
+            };
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(match (open, particles) {
+                    (0, None) => "render_pass",
+                    (0, Some(_)) => "particle_pass",
+                    _ => "group_pass",
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: None,
+                color_attachments: &[Some(color_attachment)],
+                depth_stencil_attachment: self.depth.as_ref().map(|depth| {
+                    wgpu::RenderPassDepthStencilAttachment {
+                        view: &depth.view,
+                        // Read-only while soft particles sample it.
+                        depth_ops: particles.is_none().then_some(wgpu::Operations {
+                            load: depth_load,
+                            store: depth_store,
+                        }),
+                        stencil_ops: None,
+                    }
+                }),
+            });
+            depth_load = wgpu::LoadOp::Load;
+
+            if let Some((x, y, w, h)) = viewport {
+                rpass.set_viewport(x, y, w, h, 0.0, 1.0);
+            }
+
+            // Bind the transform bind group at index 0 (applies to all pipelines).
+            let offset = (bound_slot as u64 * self.params_stride) as u32;
+            rpass.set_bind_group(0, &self.transform_bind_group, &[offset]);
+
+            let mut bound = BoundState::default();
+            if let (true, Some(depth)) = (sorted, &self.depth) {
+                bound.depths = true;
+                rpass.set_vertex_buffer(1, depth.buffer.slice(..));
+            }
+
+            if let Some((layer, start, count)) = composite.take() {
+                // The layer covers the whole target, letterbox bars included.
+                let (w, h) = render_size;
+                if viewport.is_some() {
+                    rpass.set_viewport(0.0, 0.0, w as f32, h as f32, 0.0, 1.0);
+                }
+                bound.vertex_stream(&mut rpass, VertexStream::Shared, &self.vertex_buffer);
+                let pipelines = (&mut self.pipelines, &self.device);
+                bound.pipeline(
+                    &mut rpass,
+                    key(PipelineKind::PremultipliedTexture),
+                    pipelines,
+                );
+                rpass.set_bind_group(1, &self.group_targets[layer].bind_group, &[]);
+                rpass.draw(start as u32..(start + count) as u32, 0..1);
+                if let Some((x, y, w, h)) = viewport {
+                    rpass.set_viewport(x, y, w, h, 0.0, 1.0);
+                }
+            }
+
+            if let (Some((start, count)), Some(depth)) = (particles.take(), &self.depth) {
+                if debug_groups {
+                    rpass.push_debug_group(&format!("soft particles ({count} vertices)"));
+                }
+                bound.vertex_stream(&mut rpass, VertexStream::Shared, &self.vertex_buffer);
+                if !bound.depths {
+                    bound.depths = true;
+                    rpass.set_vertex_buffer(1, depth.buffer.slice(..));
+                }
+                let pipelines = (&mut self.pipelines, &self.device);
+                bound.pipeline(&mut rpass, key(PipelineKind::SoftParticles), pipelines);
+                rpass.set_bind_group(1, &depth.bind_group, &[]);
+                rpass.draw(start as u32..(start + count) as u32, 0..1);
+                if debug_groups {
+                    rpass.pop_debug_group();
+                }
+            }
+
+            for (cmd, &slot) in commands.by_ref() {
+                if slot != bound_slot {
+                    bound_slot = slot;
+                    let offset = (slot as u64 * self.params_stride) as u32;
+                    rpass.set_bind_group(0, &self.transform_bind_group, &[offset]);
+                }
+                let pipelines = (&mut self.pipelines, &self.device);
+
+                // Layer changes end this pass; the loop starts the next one.
+                match *cmd {
+                    super::DrawCommand::BeginGroup => {
+                        open += 1;
+                        color_load = wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT);
+                        continue 'passes;
+                    }
+                    super::DrawCommand::EndGroup { start, count } if open > 0 => {
+                        open -= 1;
+                        color_load = wgpu::LoadOp::Load;
+                        composite = Some((open, start, count));
+                        continue 'passes;
+                    }
+                    super::DrawCommand::Particles {
+                        start,
+                        count,
+                        softness: Some(_),
+                    } if soft_particles => {
+                        color_load = wgpu::LoadOp::Load;
+                        particles = Some((start, count));
+                        continue 'passes;
+                    }
+                    _ => {}
+                }
 
-        for cmd in commands {
-            match *cmd {
-                super::DrawCommand::Color { start, count } => {
-                    rpass.set_pipeline(&self.pipeline); // color pipeline
-                    let s = start as u32;
-                    let e = s + count as u32;
-                    rpass.draw(s..e, 0..1);
+                // Skipped draws (missing textures and meshes) get no label, so every pushed
+                // group is popped below.
+                let group = debug_groups
+                    .then(|| draw_label(cmd, textures, meshes))
+                    .flatten();
+                if let Some(label) = &group {
+                    rpass.push_debug_group(label);
                 }
-                super::DrawCommand::Texture { tex, start, count } => {
-                    rpass.set_pipeline(&self.texture_pipeline);
-                    if let Some(texdata) = textures.get(&tex.0) {
-                        rpass.set_bind_group(1, &texdata.bind_group, &[]);
-                    } else {
-                        continue;
+                match *cmd {
+                    super::DrawCommand::Color { start, count } => {
+                        bound.vertex_stream(&mut rpass, VertexStream::Shared, &self.vertex_buffer);
+                        let kind = if sorted {
+                            PipelineKind::SortedColor
+                        } else {
+                            PipelineKind::Color
+                        };
+                        bound.pipeline(&mut rpass, key(kind), pipelines);
+                        let s = start as u32;
+                        let e = s + count as u32;
+                        rpass.draw(s..e, 0..1);
                     }
-                    let s = start as u32;
-                    let e = s + count as u32;
-                    rpass.draw(s..e, 0..1);
+                    super::DrawCommand::Texture {
+                        tex,
+                        start,
+                        count,
+                        wrap,
+                    } => {
+                        let Some(texdata) = textures.get(&tex.0) else {
+                            continue;
+                        };
+                        bound.vertex_stream(&mut rpass, VertexStream::Shared, &self.vertex_buffer);
+                        let kind = match (sorted, texdata.premultiplied) {
+                            (true, true) => PipelineKind::SortedPremultipliedTexture,
+                            (true, false) => PipelineKind::SortedTexture,
+                            (false, true) => PipelineKind::PremultipliedTexture,
+                            (false, false) => PipelineKind::Texture,
+                        };
+                        bound.pipeline(&mut rpass, key(kind), pipelines);
+                        if bound.texture != Some((tex.0, wrap)) {
+                            bound.texture = Some((tex.0, wrap));
+                            let bind_group = wrap
+                                .and_then(|wrap| texdata.wrapped.get(&wrap))
+                                .unwrap_or(&texdata.bind_group);
+                            rpass.set_bind_group(1, bind_group, &[]);
+                        }
+                        let s = start as u32;
+                        let e = s + count as u32;
+                        rpass.draw(s..e, 0..1);
+                    }
+                    super::DrawCommand::Background { start, count, .. } => {
+                        bound.vertex_stream(&mut rpass, VertexStream::Shared, &self.vertex_buffer);
+                        bound.pipeline(&mut rpass, key(PipelineKind::Background), pipelines);
+                        let s = start as u32;
+                        let e = s + count as u32;
+                        rpass.draw(s..e, 0..1);
+                    }
+                    super::DrawCommand::Particles { start, count, .. } => {
+                        bound.vertex_stream(&mut rpass, VertexStream::Shared, &self.vertex_buffer);
+                        bound.pipeline(&mut rpass, key(PipelineKind::Particles), pipelines);
+                        let s = start as u32;
+                        let e = s + count as u32;
+                        rpass.draw(s..e, 0..1);
+                    }
+                    super::DrawCommand::Shapes { start, count } => {
+                        bound.vertex_stream(
+                            &mut rpass,
+                            VertexStream::Shapes,
+                            &self.shape_vertex_buffer,
+                        );
+                        bound.pipeline(&mut rpass, key(PipelineKind::Shapes), pipelines);
+                        let s = start as u32;
+                        let e = s + count as u32;
+                        rpass.draw(s..e, 0..1);
+                    }
+                    super::DrawCommand::Mesh { mesh, .. } => {
+                        let Some(buffer) = meshes.get(&mesh.0) else {
+                            continue;
+                        };
+                        bound.vertex_stream(&mut rpass, VertexStream::Mesh(mesh.0), &buffer.buffer);
+                        bound.pipeline(&mut rpass, key(PipelineKind::Color), pipelines);
+                        rpass.draw(0..buffer.vertex_count, 0..1);
+                    }
+                    super::DrawCommand::SetDepthSort(enabled) => {
+                        sorted = enabled && !depths.is_empty();
+                        if let (true, Some(depth)) = (sorted, &self.depth)
+                            && !bound.depths
+                        {
+                            bound.depths = true;
+                            rpass.set_vertex_buffer(1, depth.buffer.slice(..));
+                        }
+                    }
+                    super::DrawCommand::GpuTimestamp(query) => {
+                        if let Some(timer) = timer {
+                            timer.write(&mut rpass, query);
+                        }
+                    }
+                    super::DrawCommand::DebugMarker(index) => {
+                        if let Some(label) = debug_labels.get(index as usize) {
+                            rpass.insert_debug_marker(label);
+                        }
+                    }
+                    super::DrawCommand::SetColorAdjust(_)
+                    | super::DrawCommand::SetViewProj(_)
+                    | super::DrawCommand::BeginGroup
+                    | super::DrawCommand::EndGroup { .. } => {}
+                }
+
+                if group.is_some() {
+                    rpass.pop_debug_group();
                 }
             }
+
+            break;
         }
 
-        drop(rpass);
+        if let Some(scene) = &self.scene_target {
+            self.post.run(
+                (&self.device, &self.queue),
+                &mut encoder,
+                (&view, self.surface_config.format, surface_size),
+                &scene.bind_group,
+                post,
+            );
+        }
 
-        self.queue.submit(Some(encoder.finish()));
+        let timed = timer.is_some();
+        if let Some(timer) = timer {
+            timer.resolve(&mut encoder, gpu_scopes.len());
+        }
+
+        if !plugins.is_empty() || !frame_hooks.is_empty() {
+            let mut target = super::RenderTarget {
+                device: &self.device,
+                queue: &self.queue,
+                view: &view,
+                format: self.surface_config.format,
+                size: (self.surface_config.width, self.surface_config.height),
+                encoder: &mut encoder,
+            };
+            for plugin in plugins.iter_mut() {
+                plugin.render(&mut target);
+            }
+            for hook in frame_hooks {
+                hook(&mut target);
+            }
+        }
+
+        let readback = (capture && self.can_capture).then(|| {
+            self.readbacks
+                .copy(&self.device, &mut encoder, &output.texture)
+        });
+
+        let submission = self.queue.submit(Some(encoder.finish()));
         output.present();
+        if let (true, Some(timer)) = (timed, &mut self.timer) {
+            timer.map(gpu_scopes);
+        }
 
-        Ok(())
+        Ok(self.readbacks.submitted(&self.device, readback, submission))
+    }
+
+    /// Wait for captured frames still being read back and return them, oldest first.
+    pub(crate) fn flush_captures(&mut self) -> Vec<image::RgbaImage> {
+        self.readbacks.flush(&self.device)
+    }
+
+    /// Copy level 0 of an 8-bit RGBA or BGRA texture back to the CPU, waiting for the GPU.
+    /// `None` for other formats (e.g. block-compressed) or if the copy fails.
+    pub(crate) fn read_texture(&self, texture: &wgpu::Texture) -> Option<image::RgbaImage> {
+        use wgpu::TextureFormat::*;
+        if !matches!(
+            texture.format(),
+            Rgba8Unorm | Rgba8UnormSrgb | Bgra8Unorm | Bgra8UnormSrgb
+        ) {
+            return None;
+        }
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("texture_readback_encoder"),
+            });
+        let readback = super::readback::Readback::copy(&self.device, &mut encoder, texture, None);
+        self.queue.submit(Some(encoder.finish()));
+        readback.wait(&self.device)
     }
 
-    pub(crate) async fn new(window: W) -> Result<Self, RendererError> {
+    pub(crate) async fn new(
+        window: W,
+        config: &super::RendererConfig,
+    ) -> Result<Self, RendererError> {
         let backends = wgpu::Backends::all();
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
             backends,
@@ -136,7 +1269,7 @@ where
 
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
+                power_preference: config.power_preference,
                 compatible_surface: Some(&surface),
                 force_fallback_adapter: false,
             })
@@ -146,8 +1279,10 @@ where
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: Some("libforge_device"),
-                required_features: wgpu::Features::empty(),
-                required_limits: wgpu::Limits::default(),
+                // Optional: compressed textures fall back to CPU decoding without them, and
+                // GPU scopes report nothing without timestamp queries.
+                required_features: config.negotiate(adapter.features()),
+                required_limits: config.limits.clone(),
                 ..Default::default()
             })
             .await?;
@@ -157,36 +1292,29 @@ where
                 label: Some("transform_bind_group_layout"),
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: Some(
-                            std::num::NonZeroU64::new((16 * std::mem::size_of::<f32>()) as u64)
-                                .unwrap(),
-                        ),
+                        has_dynamic_offset: true,
+                        min_binding_size: std::num::NonZeroU64::new(PARAMS_SIZE),
                     },
                     count: None,
                 }],
             });
 
-        let identity = Mat4::IDENTITY;
-        let identity_cols = identity.to_cols_array();
-
-        let transform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("transform_buffer"),
-            contents: bytemuck::cast_slice(&identity_cols),
+        // Dynamic offsets must respect the adapter's uniform alignment (typically 256 bytes).
+        let params_stride = PARAMS_SIZE
+            .next_multiple_of(device.limits().min_uniform_buffer_offset_alignment as u64);
+        let params_capacity = 16;
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("params_buffer"),
+            size: params_capacity as u64 * params_stride,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
         });
 
-        let transform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("transform_bind_group"),
-            layout: &transform_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: transform_buffer.as_entire_binding(),
-            }],
-        });
+        let transform_bind_group =
+            create_transform_bind_group(&device, &transform_bind_group_layout, &params_buffer);
 
         let initial_capacity = 4096;
 
@@ -199,22 +1327,23 @@ where
 
         // Choose a surface format
         let caps = surface.get_capabilities(&adapter);
-        let surface_format = caps
-            .formats
-            .iter()
-            .copied()
-            .find(|f| f.is_srgb())
-            .unwrap_or(caps.formats[0]);
+        let surface_format = choose_surface_format(&caps.formats);
 
         let (width, height) = (800u32, 600u32);
 
+        // Copying out of the surface (frame capture) is optional on some platforms.
+        let can_capture = caps.usages.contains(wgpu::TextureUsages::COPY_SRC);
         let surface_config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            usage: if can_capture {
+                wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC
+            } else {
+                wgpu::TextureUsages::RENDER_ATTACHMENT
+            },
             format: surface_format,
             width,
             height,
             present_mode: caps.present_modes[0],
-            alpha_mode: caps.alpha_modes[0],
+            alpha_mode: choose_alpha_mode(&caps.alpha_modes, config.transparent),
             view_formats: vec![surface_format],
             desired_maximum_frame_latency: 2,
         };
@@ -232,41 +1361,14 @@ where
             push_constant_ranges: &[],
         });
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("basic_pipeline"),
-            layout: Some(&pipeline_layout),
-            cache: None,
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[Vertex::desc()],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_color"),
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_config.format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None,
-                unclipped_depth: false,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
+        let shape_vertex_capacity = 64;
+        let shape_vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("libforge_shape_vertex_buffer"),
+            size: (shape_vertex_capacity * std::mem::size_of::<ShapeVertex>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
         });
 
-        // Texture pipeline setup
         let tex_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("tex_bind_group_layout"),
             entries: &[
@@ -295,55 +1397,69 @@ where
             push_constant_ranges: &[],
         });
 
-        let texture_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("texture_pipeline"),
-            layout: Some(&texture_pipeline_layout),
-            cache: None,
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[Vertex::desc()],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_texture"),
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_config.format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None,
-                unclipped_depth: false,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
+        let scene_depth_layouts =
+            [false, true].map(|multisampled| create_scene_depth_layout(&device, multisampled));
+        let depth_pipeline_layouts = scene_depth_layouts.each_ref().map(|depth_layout| {
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("scene_depth_pipeline_layout"),
+                bind_group_layouts: &[&transform_bind_group_layout, depth_layout],
+                push_constant_ranges: &[],
+            })
+        });
+
+        let pipelines = PipelineCache::new(
+            shader,
+            pipeline_layout,
+            texture_pipeline_layout,
+            depth_pipeline_layouts,
+        );
+
+        let mipmaps = super::mipmap::MipmapGenerator::new(&device, &tex_bind_group_layout);
+
+        let timer = GpuTimer::new(&device, &queue);
+
+        let post = super::post::PostPass::new(&device, &tex_bind_group_layout);
+
+        let target_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("offscreen_target_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
         });
 
         Ok(Self {
-            _window: window,
-            _instance: instance,
-            surface,
-            _adapter: adapter,
+            _window: Some(window),
+            instance,
+            surface: Some(surface),
+            adapter,
             device,
             queue,
             surface_config,
-            pipeline,
-            texture_pipeline,
+            present_modes: caps.present_modes,
+            pipelines,
+            depth: None,
+            scene_depth_layouts,
+            render_scale: 1.0,
+            sample_count: 1,
+            msaa_view: None,
+            can_capture,
+            readbacks: Default::default(),
+            timer,
+            group_targets: Vec::new(),
+            target_sampler,
+            scene_target: None,
+            post,
+            shape_vertex_buffer,
+            shape_vertex_capacity,
             vertex_buffer,
             vertex_capacity: initial_capacity,
+            mipmaps,
             tex_bind_group_layout,
-            transform_buffer,
+            transform_bind_group_layout,
             transform_bind_group,
+            params_buffer,
+            params_capacity,
+            params_stride,
         })
     }
 
@@ -354,6 +1470,10 @@ where
 
         let new_capacity = needed.next_power_of_two();
         let new_size = (new_capacity * std::mem::size_of::<Vertex>()) as u64;
+        log_debug!(
+            "growing vertex buffer {} -> {new_capacity} vertices",
+            self.vertex_capacity
+        );
 
         self.vertex_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("libforge_vertex_buffer"),
@@ -365,6 +1485,27 @@ where
         self.vertex_capacity = new_capacity;
     }
 
+    /// Upload this frame's SDF shape vertices, growing their buffer if needed.
+    pub(crate) fn upload_shape_vertices(&mut self, vertices: &[ShapeVertex]) {
+        if vertices.len() > self.shape_vertex_capacity {
+            self.shape_vertex_capacity = vertices.len().next_power_of_two();
+            log_debug!(
+                "growing shape vertex buffer to {} vertices",
+                self.shape_vertex_capacity
+            );
+            self.shape_vertex_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("libforge_shape_vertex_buffer"),
+                size: (self.shape_vertex_capacity * std::mem::size_of::<ShapeVertex>()) as u64,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+        }
+        if !vertices.is_empty() {
+            self.queue
+                .write_buffer(&self.shape_vertex_buffer, 0, bytemuck::cast_slice(vertices));
+        }
+    }
+
     pub(crate) fn upload_vertices(&mut self, vertices: &[Vertex]) {
         let needed = vertices.len();
         self.ensure_vertex_capacity(needed);
@@ -374,10 +1515,67 @@ where
         }
     }
 
-    pub(crate) fn write_transform(&mut self, mat: Mat4) {
-        let cols = mat.to_cols_array();
-        self.queue
-            .write_buffer(&self.transform_buffer, 0, bytemuck::cast_slice(&cols));
+    /// Upload draw-parameter slots, growing the buffer (and rebuilding its bind group) if needed.
+    pub(crate) fn upload_params(&mut self, params: &[DrawParams]) {
+        if params.len() > self.params_capacity {
+            log_debug!(
+                "growing draw params buffer {} -> {} slots",
+                self.params_capacity,
+                params.len().next_power_of_two()
+            );
+            self.params_capacity = params.len().next_power_of_two();
+            self.params_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("params_buffer"),
+                size: self.params_capacity as u64 * self.params_stride,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            self.transform_bind_group = create_transform_bind_group(
+                &self.device,
+                &self.transform_bind_group_layout,
+                &self.params_buffer,
+            );
+        }
+
+        let stride = self.params_stride as usize;
+        let mut bytes = vec![0u8; params.len() * stride];
+        for (i, p) in params.iter().enumerate() {
+            let offset = i * stride;
+            bytes[offset..offset + PARAMS_SIZE as usize].copy_from_slice(bytemuck::bytes_of(p));
+        }
+        self.queue.write_buffer(&self.params_buffer, 0, &bytes);
+    }
+
+    /// Upload static vertices into their own GPU buffer.
+    pub(crate) fn create_vertex_buffer(&self, label: &str, vertices: &[Vertex]) -> wgpu::Buffer {
+        self.device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            })
+    }
+
+    /// Linear-filtered texture sampler with the given (u, v) wrap modes.
+    pub(crate) fn create_sampler(
+        &self,
+        wrap: (wgpu::AddressMode, wgpu::AddressMode),
+        trilinear: bool,
+    ) -> wgpu::Sampler {
+        self.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("libforge_sampler"),
+            address_mode_u: wrap.0,
+            address_mode_v: wrap.1,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: if trilinear {
+                wgpu::FilterMode::Linear
+            } else {
+                wgpu::FilterMode::Nearest
+            },
+            ..Default::default()
+        })
     }
 
     pub(crate) fn create_texture_bind_group(
@@ -401,12 +1599,377 @@ where
         })
     }
 
+    /// Fill mip levels 1.. of `texture` from level 0.
+    pub(crate) fn generate_mipmaps(&mut self, texture: &wgpu::Texture) {
+        self.mipmaps.generate(
+            &self.device,
+            &self.queue,
+            &self.tex_bind_group_layout,
+            texture,
+        );
+    }
+
+    fn configure_surface(&self) {
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.surface_config);
+        }
+    }
+
+    /// Drop the surface and window handle; GPU resources stay alive.
+    pub(crate) fn suspend(&mut self) {
+        log_debug!("suspending: dropping surface");
+        self.surface = None;
+        self._window = None;
+    }
+
+    pub(crate) fn is_suspended(&self) -> bool {
+        self.surface.is_none()
+    }
+
+    /// Recreate the surface for a new native window with the previous configuration.
+    pub(crate) fn resume(&mut self, window: W) -> Result<(), RendererError> {
+        let surface = self
+            .instance
+            .create_surface(window.clone())
+            .map_err(|_| RendererError::Surface("failed to create surface".into()))?;
+        // Pipelines are built for the old format, so the new surface has to support it.
+        let caps = surface.get_capabilities(&self.adapter);
+        if !caps.formats.contains(&self.surface_config.format) {
+            return Err(RendererError::Surface(format!(
+                "resumed surface does not support {:?}",
+                self.surface_config.format
+            )));
+        }
+        log_debug!("resuming: recreating surface");
+        surface.configure(&self.device, &self.surface_config);
+        self.surface = Some(surface);
+        self._window = Some(window);
+        Ok(())
+    }
+
     pub(crate) fn resize(&mut self, width: u32, height: u32) {
         if width == 0 || height == 0 {
             return;
         }
         self.surface_config.width = width;
         self.surface_config.height = height;
-        self.surface.configure(&self.device, &self.surface_config);
+        log_debug!("reconfiguring surface to {width}x{height}");
+        self.configure_surface();
+        self.readbacks.release_buffers();
+        self.msaa_view = self.create_msaa_view();
+        let size = self.render_size();
+        if let Some(depth) = &mut self.depth {
+            (depth.view, depth.bind_group) = create_depth_view(
+                &self.device,
+                &self.scene_depth_layouts,
+                size,
+                self.sample_count,
+            );
+        }
+    }
+
+    /// Wait for vertical sync (`Fifo`) or present as soon as possible (`Mailbox`, else
+    /// `Immediate`), falling back to `Fifo` where nothing else is supported.
+    pub(crate) fn set_vsync(&mut self, vsync: bool) {
+        let mode = if vsync {
+            wgpu::PresentMode::Fifo
+        } else {
+            [wgpu::PresentMode::Mailbox, wgpu::PresentMode::Immediate]
+                .into_iter()
+                .find(|m| self.present_modes.contains(m))
+                .unwrap_or(wgpu::PresentMode::Fifo)
+        };
+        if mode != self.surface_config.present_mode {
+            log_debug!("switching present mode to {mode:?}");
+            self.surface_config.present_mode = mode;
+            self.configure_surface();
+        }
+    }
+
+    pub(crate) fn vsync(&self) -> bool {
+        matches!(
+            self.surface_config.present_mode,
+            wgpu::PresentMode::Fifo | wgpu::PresentMode::FifoRelaxed | wgpu::PresentMode::AutoVsync
+        )
+    }
+
+    /// Render with `samples` MSAA samples. Unsupported counts
+    /// fall back to the next lower supported one; returns the count in use.
+    pub(crate) fn set_msaa_samples(&mut self, samples: u32) -> u32 {
+        let flags = self
+            .adapter
+            .get_texture_format_features(self.surface_config.format)
+            .flags;
+        let depth_flags = self.adapter.get_texture_format_features(DEPTH_FORMAT).flags;
+        let samples = [16, 8, 4, 2]
+            .into_iter()
+            .find(|&n| n <= samples && flags.sample_count_supported(n))
+            .filter(|&n| self.depth.is_none() || depth_flags.sample_count_supported(n))
+            .unwrap_or(1);
+        if samples == self.sample_count {
+            return samples;
+        }
+
+        log_debug!("switching to {samples}x MSAA");
+        self.sample_count = samples;
+        self.msaa_view = self.create_msaa_view();
+        let size = self.render_size();
+        if let Some(depth) = &mut self.depth {
+            (depth.view, depth.bind_group) =
+                create_depth_view(&self.device, &self.scene_depth_layouts, size, samples);
+        }
+        samples
+    }
+
+    /// Render frames at `scale` of the surface's width and height, upscaling them to the
+    /// surface at the end of the frame.
+    pub(crate) fn set_render_scale(&mut self, scale: f32) {
+        if scale == self.render_scale {
+            return;
+        }
+        self.render_scale = scale;
+        let size = self.render_size();
+        log_debug!("rendering at {}x{}", size.0, size.1);
+        if let Some(depth) = &mut self.depth {
+            (depth.view, depth.bind_group) = create_depth_view(
+                &self.device,
+                &self.scene_depth_layouts,
+                size,
+                self.sample_count,
+            );
+        }
+    }
+
+    pub(crate) fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Size of the frame's color and depth attachments before upscaling.
+    fn render_size(&self) -> (u32, u32) {
+        scaled_size(
+            (self.surface_config.width, self.surface_config.height),
+            self.render_scale,
+        )
+    }
+
+    /// Replace the main shader with `source` (a full replacement for `basic.wgsl`) and
+    /// rebuild every cached pipeline. On a compile or pipeline error the current shader is kept.
+    pub(crate) fn reload_shader(&mut self, source: &str) -> Result<(), RendererError> {
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shader = self
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("basic_shader"),
+                source: wgpu::ShaderSource::Wgsl(source.into()),
+            });
+        let pipelines = self.pipelines.with_shader(&self.device, shader);
+        if let Some(error) = pollster::block_on(self.device.pop_error_scope()) {
+            return Err(RendererError::Shader(error.to_string()));
+        }
+        self.pipelines = pipelines;
+        Ok(())
+    }
+
+    fn pass_target(&self) -> PassTarget {
+        PassTarget {
+            format: self.surface_config.format,
+            samples: self.sample_count,
+            depth: self.depth.is_some(),
+        }
+    }
+
+    /// Upload per-vertex depths, creating the depth attachment the first time depth sorting
+    /// is used.
+    fn upload_depths(&mut self, depths: &[f32]) {
+        let needed = depths.len();
+        let size = self.render_size();
+        let depth = self.depth.get_or_insert_with(|| {
+            log_debug!("enabling depth attachment for depth-sorted draws");
+            let (view, bind_group) = create_depth_view(
+                &self.device,
+                &self.scene_depth_layouts,
+                size,
+                self.sample_count,
+            );
+            DepthTarget {
+                view,
+                bind_group,
+                buffer: create_depth_buffer(&self.device, needed),
+                capacity: needed,
+            }
+        });
+        if needed > depth.capacity {
+            depth.capacity = needed.next_power_of_two();
+            depth.buffer = create_depth_buffer(&self.device, depth.capacity);
+        }
+        self.queue
+            .write_buffer(&depth.buffer, 0, bytemuck::cast_slice(depths));
+    }
+
+    /// GPU milliseconds per scope from the latest frame read back.
+    pub(crate) fn gpu_scope_timings(&self) -> &[(String, f32)] {
+        self.timer.as_ref().map_or(&[], |timer| timer.timings())
     }
+
+    /// Whether the surface is composited with the frame's alpha.
+    pub(crate) fn is_transparent(&self) -> bool {
+        matches!(
+            self.surface_config.alpha_mode,
+            wgpu::CompositeAlphaMode::PreMultiplied | wgpu::CompositeAlphaMode::PostMultiplied
+        )
+    }
+
+    /// Whether `feature` was granted at device creation.
+    pub(crate) fn supports(&self, feature: super::Feature) -> bool {
+        self.device.features().contains(feature.wgpu_features())
+    }
+
+    pub(crate) fn limits(&self) -> wgpu::Limits {
+        self.device.limits()
+    }
+
+    pub(crate) fn supports_gpu_timing(&self) -> bool {
+        self.timer.is_some()
+    }
+
+    pub(crate) fn msaa_samples(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Make sure there is a group layer for each of `depth` nesting levels, matching the
+    /// frame's render size, format and sample count.
+    fn ensure_group_targets(&mut self, depth: usize) {
+        let format = self.surface_config.format;
+        let size = self.render_size();
+        let samples = self.sample_count;
+        self.group_targets
+            .retain(|target| target.matches(format, size, samples));
+        while self.group_targets.len() < depth {
+            log_debug!("creating group layer {}", self.group_targets.len() + 1);
+            self.group_targets.push(OffscreenTarget::new(
+                &self.device,
+                "group_layer",
+                (&self.tex_bind_group_layout, &self.target_sampler),
+                format,
+                size,
+                samples,
+            ));
+        }
+    }
+
+    /// Create (or drop, when `needed` is false) the offscreen target the frame is drawn to
+    /// ahead of the post pass.
+    fn ensure_scene_target(&mut self, needed: bool) {
+        let format = self.surface_config.format;
+        let size = self.render_size();
+        let samples = self.sample_count;
+        if !needed {
+            self.scene_target = None;
+        } else if !self
+            .scene_target
+            .as_ref()
+            .is_some_and(|target| target.matches(format, size, samples))
+        {
+            self.scene_target = Some(OffscreenTarget::new(
+                &self.device,
+                "scene_target",
+                (&self.tex_bind_group_layout, &self.target_sampler),
+                format,
+                size,
+                samples,
+            ));
+        }
+    }
+
+    fn create_msaa_view(&self) -> Option<wgpu::TextureView> {
+        if self.sample_count <= 1 {
+            return None;
+        }
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("msaa_color"),
+            size: wgpu::Extent3d {
+                width: self.surface_config.width,
+                height: self.surface_config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: self.sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.surface_config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+    }
+}
+
+/// `size` scaled by `scale`, at least one pixel on each side.
+fn scaled_size((width, height): (u32, u32), scale: f32) -> (u32, u32) {
+    let scale = |side: u32| ((side as f32 * scale).round() as u32).max(1);
+    (scale(width), scale(height))
+}
+
+/// Depth attachment of `size` with `samples` samples, and the bind group soft particles
+/// read it through.
+fn create_depth_view(
+    device: &wgpu::Device,
+    layouts: &[wgpu::BindGroupLayout; 2],
+    (width, height): (u32, u32),
+    samples: u32,
+) -> (wgpu::TextureView, wgpu::BindGroup) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("depth_sort"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: samples,
+        dimension: wgpu::TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let multisampled = samples > 1;
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("scene_depth_bind_group"),
+        layout: &layouts[multisampled as usize],
+        entries: &[wgpu::BindGroupEntry {
+            binding: scene_depth_binding(multisampled),
+            resource: wgpu::BindingResource::TextureView(&view),
+        }],
+    });
+    (view, bind_group)
+}
+
+/// `scene_depth` (binding 2) or `scene_depth_ms` (binding 3) in `basic.wgsl`.
+fn scene_depth_binding(multisampled: bool) -> u32 {
+    if multisampled { 3 } else { 2 }
+}
+
+fn create_scene_depth_layout(device: &wgpu::Device, multisampled: bool) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("scene_depth_layout"),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: scene_depth_binding(multisampled),
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Depth,
+            },
+            count: None,
+        }],
+    })
+}
+
+fn create_depth_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("libforge_depth_buffer"),
+        size: (capacity.max(1) * std::mem::size_of::<f32>()) as u64,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
 }
@@ -0,0 +1,131 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Most scopes timed per frame; later scopes in a frame are ignored.
+pub(crate) const MAX_GPU_SCOPES: u32 = 64;
+
+const MAP_PENDING: u8 = 0;
+const MAP_DONE: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+/// Timestamp queries written around `gpu_scope` command ranges inside the main pass.
+///
+/// Results are read back without stalling: a frame's queries are mapped after submission
+/// and collected by a later `end_frame`, and frames rendered while a readback is still in
+/// flight are not timed.
+pub(crate) struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    // Nanoseconds per timestamp tick.
+    period: f32,
+    // Scope names and query count of the frame being read back, and its mapping state.
+    pending: Option<(Vec<String>, u32, Arc<AtomicU8>)>,
+    timings: Vec<(String, f32)>,
+}
+
+impl GpuTimer {
+    /// Features needed to write timestamps between draws of one render pass.
+    pub(crate) const FEATURES: wgpu::Features =
+        wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES);
+
+    /// `None` unless the device was created with [`Self::FEATURES`].
+    pub(crate) fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(Self::FEATURES) {
+            return None;
+        }
+        let count = MAX_GPU_SCOPES * 2;
+        let size = count as u64 * wgpu::QUERY_SIZE as u64;
+        Some(Self {
+            query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("gpu_scope_queries"),
+                ty: wgpu::QueryType::Timestamp,
+                count,
+            }),
+            resolve_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("gpu_scope_resolve"),
+                size,
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            readback_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("gpu_scope_readback"),
+                size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }),
+            period: queue.get_timestamp_period(),
+            pending: None,
+            timings: Vec::new(),
+        })
+    }
+
+    /// Collect a finished readback. Returns whether this frame can write timestamps.
+    pub(crate) fn begin_frame(&mut self, device: &wgpu::Device) -> bool {
+        let Some((_, _, state)) = &self.pending else {
+            return true;
+        };
+        let _ = device.poll(wgpu::PollType::Poll);
+        match state.load(Ordering::Acquire) {
+            MAP_PENDING => return false,
+            MAP_FAILED => {
+                self.pending = None;
+                return true;
+            }
+            _ => {}
+        }
+        let (names, count, _) = self.pending.take().unwrap();
+        let size = count as u64 * wgpu::QUERY_SIZE as u64;
+        let data = self.readback_buffer.slice(..size).get_mapped_range();
+        let ticks: &[u64] = bytemuck::cast_slice(&data);
+        self.timings = names
+            .into_iter()
+            .zip(ticks.chunks_exact(2))
+            .map(|(name, t)| {
+                let ms = t[1].saturating_sub(t[0]) as f64 * self.period as f64 / 1_000_000.0;
+                (name, ms as f32)
+            })
+            .collect();
+        drop(data);
+        self.readback_buffer.unmap();
+        true
+    }
+
+    pub(crate) fn write(&self, rpass: &mut wgpu::RenderPass<'_>, query: u32) {
+        if query < MAX_GPU_SCOPES * 2 {
+            rpass.write_timestamp(&self.query_set, query);
+        }
+    }
+
+    /// Copy the frame's timestamps towards the CPU; call after the pass, before submitting.
+    pub(crate) fn resolve(&self, encoder: &mut wgpu::CommandEncoder, scopes: usize) {
+        let count = scopes.min(MAX_GPU_SCOPES as usize) as u32 * 2;
+        let size = count as u64 * wgpu::QUERY_SIZE as u64;
+        encoder.resolve_query_set(&self.query_set, 0..count, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, size);
+    }
+
+    /// Start mapping the resolved timestamps; call after submitting.
+    pub(crate) fn map(&mut self, names: &[String]) {
+        let names: Vec<String> = names
+            .iter()
+            .take(MAX_GPU_SCOPES as usize)
+            .cloned()
+            .collect();
+        let count = names.len() as u32 * 2;
+        let state = Arc::new(AtomicU8::new(MAP_PENDING));
+        let flag = state.clone();
+        self.readback_buffer
+            .slice(..count as u64 * wgpu::QUERY_SIZE as u64)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let done = if result.is_ok() { MAP_DONE } else { MAP_FAILED };
+                flag.store(done, Ordering::Release)
+            });
+        self.pending = Some((names, count, state));
+    }
+
+    /// GPU milliseconds per scope from the most recent frame read back.
+    pub(crate) fn timings(&self) -> &[(String, f32)] {
+        &self.timings
+    }
+}
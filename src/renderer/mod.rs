@@ -1,15 +1,34 @@
+use crate::ColorAdjust;
+use crate::background::BackgroundLayer;
 use crate::camera::Camera2D;
 use crate::error::RendererError;
-use crate::vertex::Vertex;
+use crate::parallax::ParallaxLayer;
+use crate::vertex::{ShapeVertex, Vertex};
+use crate::viewport::{FillMode, ScalingMode, Viewport, fit_aspect, fit_viewport};
 use glam::Mat4;
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
+mod compressed;
+mod decode;
+mod features;
 mod geometry;
 mod gpu;
-
-use gpu::RendererGpu;
+mod gpu_timer;
+mod mipmap;
+mod offscreen;
+mod plugin;
+mod post;
+mod readback;
+
+pub use features::{Feature, RendererConfig};
+use gpu::{FrameData, RendererGpu};
+pub(crate) use plugin::FrameHook;
+pub use plugin::{RenderPlugin, RenderTarget};
 
 // Re-export internal geometry helpers for use by unit tests and other crate modules.
-pub(crate) use geometry::{circle_to_vertices, line_to_quad, quad_to_vertices};
+pub(crate) use geometry::{
+    auto_circle_segments, circle_to_vertices, ellipse_points, ellipse_to_vertices,
+    feathered_convex_to_vertices, fullscreen_quad, line_to_quad, quad_to_vertices,
+};
 
 fn transform_pos2(mat: Mat4, p: [f32; 2]) -> [f32; 2] {
     let v = mat * glam::vec4(p[0], p[1], 0.0, 1.0);
@@ -46,20 +65,337 @@ pub struct Renderer<W> {
     // Draw commands (so we know which pipeline to bind per batch)
     pub commands: Vec<DrawCommand>,
 
+    // SDF shape quads (`draw_sdf_box`), drawn from their own vertex buffer.
+    shape_vertices: Vec<ShapeVertex>,
+
+    // Additive particle vertices, appended after all other draws at end_frame; the depth of
+    // each (empty while no particle was drawn depth sorted this frame, 1 for unsorted ones);
+    // and how far in front of a sorted particle geometry starts to fade it.
+    particle_vertices: Vec<Vertex>,
+    particle_depths: Vec<f32>,
+    particle_softness: f32,
+
+    // Debug draw layer (clip-space), drawn on top of everything at end_frame.
+    debug_vertices: Vec<Vertex>,
+    debug_draw: bool,
+
+    // Color adjustment most recently recorded into `commands` (frames start at NONE).
+    recorded_adjust: ColorAdjust,
+
+    // Depth sorting (`set_depth_sorting`): the requested mode, the mode last recorded into
+    // `commands` (frames start unsorted), an explicit depth overriding the y-derived one,
+    // and the depth of each vertex (empty while no sorted draw was recorded this frame).
+    depth_sorting: bool,
+    recorded_depth_sort: bool,
+    draw_depth: Option<f32>,
+    depths: Vec<f32>,
+
     // texture manager
     pub texture: std::collections::HashMap<u32, Texture>,
     pub next_texture_id: u32,
+    // Latest texture loaded under each name, for `texture_by_name`.
+    texture_names: std::collections::HashMap<String, TextureId>,
+    texture_options: TextureOptions,
+    color_space: ColorSpace,
+    missing_texture_policy: MissingTexturePolicy,
+    // Ids already reported under `MissingTexturePolicy::Warn`, so a bad id warns once.
+    warned_missing_textures: std::collections::HashSet<u32>,
+
+    // retained meshes (static vertex buffers)
+    meshes: std::collections::HashMap<u32, Mesh>,
+    next_mesh_id: u32,
+
+    // Fixed canvas size and scaling mode (see `set_virtual_resolution`).
+    virtual_resolution: Option<(u32, u32, ScalingMode)>,
+    // Aspect ratio the canvas is locked to; recomputes `virtual_resolution` on resize.
+    aspect_lock: Option<(f32, FillMode)>,
+
+    // Projection * view for the next draw, and the one in effect when this frame's
+    // command list started (slot 0 of the per-batch uniforms).
+    current_viewproj: Mat4,
+    frame_viewproj: Mat4,
 
     // Scoped 2D camera mode: active only between begin_mode_2d/end_mode_2d.
     camera_stack: Vec<Camera2D>,
 
     // CPU-side model matrix stack (applied per-draw to vertex positions).
     model_stack: Vec<Mat4>,
+
+    // Counters from the last submitted frame.
+    last_stats: FrameStats,
+
+    // Skip rect/texture draws outside the canvas (`set_culling`), counted per frame.
+    culling: bool,
+    culled_draws: usize,
+
+    // Feather the edges of rects, lines, circles and ellipses (`set_shape_antialiasing`).
+    shape_antialiasing: bool,
+
+    // Custom rendering run after the main pass: every frame, and this frame only.
+    plugins: Vec<Box<dyn RenderPlugin>>,
+    frame_hooks: Vec<FrameHook>,
+
+    // Frame capture: requested for the next `end_frame`; frames read back so far, oldest
+    // first, kept until taken.
+    capture_requested: bool,
+    captured_frames: std::collections::VecDeque<image::RgbaImage>,
+
+    // Names of this frame's GPU timing scopes, indexed by `DrawCommand::GpuTimestamp / 2`.
+    gpu_scopes: Vec<String>,
+    // Labels of this frame's `DrawCommand::DebugMarker`s, and whether each draw is wrapped
+    // in a debug group naming it.
+    debug_labels: Vec<String>,
+    debug_groups: bool,
+
+    // Opacity of each open `begin_group`, innermost last.
+    group_alphas: Vec<f32>,
+
+    // LUT strip the finished frame is graded through (`set_color_lut`).
+    color_lut: Option<TextureId>,
+    // Transition drawn over the finished frame, and how much of the screen it hides.
+    transition: Option<(crate::transition::Transition, f32)>,
+}
+
+/// Counters describing the last submitted frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    /// Vertices drawn, including retained meshes.
+    pub vertices: usize,
+    /// GPU draw calls issued.
+    pub draw_calls: usize,
+    /// Textures currently loaded.
+    pub textures: usize,
+    /// Approximate GPU memory used by loaded textures, in bytes.
+    pub texture_bytes: u64,
+    /// Rect and texture draws skipped because they were entirely off-screen.
+    pub culled_draws: usize,
+    /// GPU milliseconds spent in each `gpu_scope`, in the order the scopes began. Timings
+    /// arrive a frame or two late and are empty when the GPU lacks timestamp queries.
+    pub gpu_scopes: Vec<(String, f32)>,
 }
 
 #[derive(Clone, Copy, Debug)]
 pub struct TextureId(pub u32);
 
+/// A loaded texture as listed by `textures()`.
+#[derive(Clone, Debug)]
+pub struct TextureInfo<'a> {
+    pub id: TextureId,
+    /// Name given at load.
+    pub name: &'a str,
+    pub width: u32,
+    pub height: u32,
+    /// GPU memory of all mip levels.
+    pub bytes: u64,
+}
+
+/// How `[f32; 4]` colors (vertex colors, tints, clear color) are interpreted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// sRGB-encoded, like hex codes from an art program; decoded to linear on the GPU so
+    /// `0.5` gray looks the same as `#808080` in an image.
+    #[default]
+    Srgb,
+    /// Raw linear values, written to the sRGB surface without conversion.
+    Linear,
+}
+
+/// What texture draws do when given a `TextureId` that isn't loaded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MissingTexturePolicy {
+    /// Draw nothing.
+    Ignore,
+    /// Draw nothing and warn the first time each id is seen: through `log` with the `log`
+    /// feature, on stderr without it.
+    #[default]
+    Warn,
+    /// Draw a magenta quad where the texture would have been.
+    Placeholder,
+}
+
+// Fill color for `MissingTexturePolicy::Placeholder`.
+const MISSING_TEXTURE_COLOR: crate::Color = crate::Color([1.0, 0.0, 1.0, 1.0]);
+
+/// Warning for a draw of the unloaded texture `id`, or `None` if `warned` shows it was
+/// already reported.
+fn missing_texture_warning(
+    warned: &mut std::collections::HashSet<u32>,
+    id: TextureId,
+) -> Option<String> {
+    warned
+        .insert(id.0)
+        .then(|| format!("texture draw skipped: {id:?} is not loaded"))
+}
+
+/// How textures loaded after `set_texture_options` are stored and sampled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureOptions {
+    /// Generate a full mip chain at load so downscaled sprites don't shimmer.
+    /// Costs a third more texture memory. Compressed files keep their own mip levels.
+    pub mipmaps: bool,
+    /// Blend between the two nearest mip levels when sampling (trilinear filtering).
+    /// Without it the nearest level is used, which can show seams while zooming.
+    pub trilinear: bool,
+    /// Multiply color by alpha at load and draw with premultiplied blending. Removes the
+    /// dark fringes that filtering straight-alpha images produces around transparent edges.
+    pub premultiply_alpha: bool,
+    /// How UVs outside `0..1` are sampled, on both axes.
+    pub wrap: TextureWrap,
+    /// Make every pixel of exactly this RGB8 color (e.g. `[255, 0, 255]` magenta) fully
+    /// transparent at load, for legacy sprite sheets without alpha. Pair with
+    /// `premultiply_alpha` so filtering doesn't darken the cut-out edges.
+    pub color_key: Option<[u8; 3]>,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            mipmaps: false,
+            trilinear: true,
+            premultiply_alpha: false,
+            wrap: TextureWrap::Clamp,
+            color_key: None,
+        }
+    }
+}
+
+/// How a texture is sampled outside the `0..1` UV range.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TextureWrap {
+    /// Repeat the edge texels (no bleeding from the opposite edge when filtering).
+    #[default]
+    Clamp,
+    /// Tile the texture; used for scrolling and parallax layers.
+    Repeat,
+    /// Tile the texture, flipping every other copy.
+    MirrorRepeat,
+}
+
+impl TextureWrap {
+    fn address_mode(self) -> wgpu::AddressMode {
+        match self {
+            TextureWrap::Clamp => wgpu::AddressMode::ClampToEdge,
+            TextureWrap::Repeat => wgpu::AddressMode::Repeat,
+            TextureWrap::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+        }
+    }
+}
+
+/// Mirror a sprite horizontally and/or vertically by swapping its UVs, e.g. to face left.
+///
+/// Unlike a negative scale, the quad's winding and position stay the same.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Flip {
+    pub x: bool,
+    pub y: bool,
+}
+
+impl Flip {
+    pub const NONE: Flip = Flip { x: false, y: false };
+    pub const X: Flip = Flip { x: true, y: false };
+    pub const Y: Flip = Flip { x: false, y: true };
+    pub const BOTH: Flip = Flip { x: true, y: true };
+
+    fn apply(self, [u0, v0, u1, v1]: [f32; 4]) -> [f32; 4] {
+        let (u0, u1) = if self.x { (u1, u0) } else { (u0, u1) };
+        let (v0, v1) = if self.y { (v1, v0) } else { (v0, v1) };
+        [u0, v0, u1, v1]
+    }
+}
+
+/// True when `rect`, transformed by `to_clip`, lies entirely on the outer side of one clip
+/// plane (so no pixel of it can be visible).
+fn rect_outside_clip(to_clip: Mat4, rect: crate::Rect) -> bool {
+    let corners = [
+        (rect.x, rect.y),
+        (rect.x + rect.w, rect.y),
+        (rect.x + rect.w, rect.y + rect.h),
+        (rect.x, rect.y + rect.h),
+    ]
+    .map(|(x, y)| to_clip * glam::vec4(x, y, 0.0, 1.0));
+    corners.iter().all(|c| c.x > c.w)
+        || corners.iter().all(|c| c.x < -c.w)
+        || corners.iter().all(|c| c.y > c.w)
+        || corners.iter().all(|c| c.y < -c.w)
+}
+
+// Sort keys within about this distance of 0 get most of the depth range; resolution stays
+// finer than a pixel out to roughly 100k units. Soft particles invert the mapping in
+// `basic.wgsl`, which repeats the constant.
+const DEPTH_SORT_SCALE: f32 = 4096.0;
+
+/// Map a depth-sort key (y or explicit depth) monotonically into `(0, 1)`.
+fn sort_depth(key: f32) -> f32 {
+    0.5 + 0.5 * key / (key.abs() + DEPTH_SORT_SCALE)
+}
+
+/// Record a texture draw, extending the previous command when it uses the same texture and
+/// wrap modes and its vertices are contiguous, so runs of sprites become one GPU draw.
+fn push_texture_command(
+    commands: &mut Vec<DrawCommand>,
+    tex: TextureId,
+    start: usize,
+    count: usize,
+    wrap: Option<(TextureWrap, TextureWrap)>,
+) {
+    match commands.last_mut() {
+        Some(DrawCommand::Texture {
+            tex: last,
+            start: last_start,
+            count: last_count,
+            wrap: last_wrap,
+        }) if last.0 == tex.0 && *last_wrap == wrap && *last_start + *last_count == start => {
+            *last_count += count
+        }
+        _ => commands.push(DrawCommand::Texture {
+            tex,
+            start,
+            count,
+            wrap,
+        }),
+    }
+}
+
+/// UV rectangle `[u0, v0, u1, v1]` for `draw_texture_uv`. Offsets on tiled axes are wrapped
+/// into one period so a long-running scroll doesn't lose precision.
+fn scrolled_uv(
+    offset: (f32, f32),
+    scale: (f32, f32),
+    wrap: (TextureWrap, TextureWrap),
+) -> [f32; 4] {
+    let wrap_axis = |offset: f32, wrap| match wrap {
+        TextureWrap::Clamp => offset,
+        TextureWrap::Repeat => offset.rem_euclid(1.0),
+        TextureWrap::MirrorRepeat => offset.rem_euclid(2.0),
+    };
+    let u0 = wrap_axis(offset.0, wrap.0);
+    let v0 = wrap_axis(offset.1, wrap.1);
+    [u0, v0, u0 + scale.0, v0 + scale.1]
+}
+
+/// Clear RGBA8 pixels whose color matches `key` to transparent black.
+pub(crate) fn apply_color_key(rgba: &mut [u8], key: [u8; 3]) {
+    for px in rgba.chunks_exact_mut(4) {
+        if px[..3] == key {
+            px.copy_from_slice(&[0; 4]);
+        }
+    }
+}
+
+/// Premultiply sRGB-encoded RGBA8 pixels in place (in linear space, then re-encoded).
+pub(crate) fn premultiply_srgb_alpha(rgba: &mut [u8]) {
+    for px in rgba.chunks_exact_mut(4) {
+        if px[3] == 255 {
+            continue;
+        }
+        let a = px[3] as f32 / 255.0;
+        for c in &mut px[..3] {
+            let linear = gpu::srgb_to_linear(*c as f32 / 255.0) * a;
+            *c = (gpu::linear_to_srgb(linear) * 255.0).round() as u8;
+        }
+    }
+}
+
 pub enum DrawCommand {
     Color {
         start: usize,
@@ -69,7 +405,61 @@ pub enum DrawCommand {
         tex: TextureId,
         start: usize,
         count: usize,
+        /// Wrap modes sampled with instead of the texture's own (parallax layers).
+        wrap: Option<(TextureWrap, TextureWrap)>,
+    },
+    /// Switch the projection * view matrix used by subsequent draws.
+    SetViewProj(Mat4),
+    /// Switch the HSV color adjustment used by subsequent texture draws.
+    SetColorAdjust(ColorAdjust),
+    /// Turn per-pixel depth testing of subsequent color and texture draws on or off.
+    SetDepthSort(bool),
+    /// Retained mesh drawn from its own vertex buffer; `model` and `tint` go to the GPU.
+    Mesh {
+        mesh: MeshId,
+        model: Mat4,
+        tint: [f32; 4],
+    },
+    /// Additive particle layer, always recorded last in the frame. With `softness`, the
+    /// layer has per-vertex depths and fades into depth-sorted geometry in front of it.
+    Particles {
+        start: usize,
+        count: usize,
+        softness: Option<f32>,
+    },
+    /// SDF shape quads; indexes the separate shape vertex list, not the main one.
+    Shapes {
+        start: usize,
+        count: usize,
+    },
+    /// Procedural background quad; `effect` selects the pattern in `fs_background`.
+    Background {
+        start: usize,
+        count: usize,
+        effect: [f32; 4],
     },
+    /// Write GPU timestamp query `n`: the start (even) or end (odd) of scope `n / 2`.
+    GpuTimestamp(u32),
+    /// Insert the frame's `n`th debug label into the render pass for GPU debuggers.
+    DebugMarker(u32),
+    /// Redirect subsequent draws into a new group layer, nested in the current target and
+    /// cleared to transparent.
+    BeginGroup,
+    /// Close the innermost group layer and composite it onto its parent with the
+    /// full-target quad at `start..start + count` (clip space, colored by the group alpha).
+    EndGroup {
+        start: usize,
+        count: usize,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct MeshId(pub u32);
+
+/// Static vertices living in their own GPU buffer, uploaded once by `create_mesh`.
+pub struct Mesh {
+    buffer: wgpu::Buffer,
+    vertex_count: u32,
 }
 
 pub struct Texture {
@@ -79,6 +469,18 @@ pub struct Texture {
     bind_group: wgpu::BindGroup,
     pub width: u32,
     pub height: u32,
+    /// Color was multiplied by alpha at load (drawn with premultiplied blending).
+    pub premultiplied: bool,
+    /// Wrap modes along u and v (see `set_texture_wrap`).
+    pub wrap: (TextureWrap, TextureWrap),
+    // Bind groups sampling with other wrap modes, for draws that override `wrap` without
+    // changing it; built on first use.
+    wrapped: std::collections::HashMap<(TextureWrap, TextureWrap), wgpu::BindGroup>,
+    trilinear: bool,
+    // GPU memory of all mip levels, for `FrameStats::texture_bytes`.
+    bytes: u64,
+    // Name given at load, for debug markers.
+    name: String,
 }
 
 fn ortho_projection_for_size(width: u32, height: u32) -> Mat4 {
@@ -93,19 +495,37 @@ fn ortho_projection_for_size(width: u32, height: u32) -> Mat4 {
     )
 }
 
-fn current_view_matrix(camera_stack: &[Camera2D]) -> Mat4 {
+fn current_view_matrix(width: u32, height: u32, camera_stack: &[Camera2D]) -> Mat4 {
     camera_stack
         .last()
-        .map(|c| c.view_matrix())
+        .map(|c| c.view_matrix_for((width as f32, height as f32)))
         .unwrap_or(Mat4::IDENTITY)
 }
 
 fn viewproj_for_size_and_camera_stack(width: u32, height: u32, camera_stack: &[Camera2D]) -> Mat4 {
     let proj = ortho_projection_for_size(width, height);
-    let view = current_view_matrix(camera_stack);
+    let view = current_view_matrix(width, height, camera_stack);
     proj * view
 }
 
+/// Corners (TL, TR, BR, BL) of `rect` rotated by `rotation` radians around `rect.xy + origin`.
+fn rotated_rect_quad(rect: crate::Rect, origin: (f32, f32), rotation: f32) -> [[f32; 2]; 4] {
+    let px = rect.x + origin.0;
+    let py = rect.y + origin.1;
+    let (sin, cos) = rotation.sin_cos();
+    let corner = |x: f32, y: f32| {
+        let dx = x - px;
+        let dy = y - py;
+        [px + dx * cos - dy * sin, py + dx * sin + dy * cos]
+    };
+    [
+        corner(rect.x, rect.y),
+        corner(rect.x + rect.w, rect.y),
+        corner(rect.x + rect.w, rect.y + rect.h),
+        corner(rect.x, rect.y + rect.h),
+    ]
+}
+
 fn model_translate(top: &mut Mat4, tx: f32, ty: f32) {
     *top *= Mat4::from_translation(glam::vec3(tx, ty, 0.0));
 }
@@ -124,7 +544,12 @@ where
 {
     /// Async init for the renderer
     pub async fn new(window: W) -> Result<Self, RendererError> {
-        let gpu = RendererGpu::new(window).await?;
+        Self::with_config(window, &RendererConfig::default()).await
+    }
+
+    /// Async init requesting the optional features and limits in `config`.
+    pub async fn with_config(window: W, config: &RendererConfig) -> Result<Self, RendererError> {
+        let gpu = RendererGpu::new(window, config).await?;
 
         let mut renderer = Self {
             gpu,
@@ -132,12 +557,48 @@ where
             clear_color: None,
             texture: std::collections::HashMap::new(),
             next_texture_id: 0,
+            texture_names: std::collections::HashMap::new(),
+            texture_options: TextureOptions::default(),
+            color_space: ColorSpace::default(),
+            missing_texture_policy: MissingTexturePolicy::default(),
+            warned_missing_textures: std::collections::HashSet::new(),
+            meshes: std::collections::HashMap::new(),
+            next_mesh_id: 0,
             commands: Vec::new(),
+            shape_vertices: Vec::new(),
+            particle_vertices: Vec::new(),
+            particle_depths: Vec::new(),
+            particle_softness: 0.0,
+            debug_vertices: Vec::new(),
+            debug_draw: cfg!(debug_assertions),
+            recorded_adjust: ColorAdjust::NONE,
+            depth_sorting: false,
+            culling: true,
+            shape_antialiasing: false,
+            culled_draws: 0,
+            recorded_depth_sort: false,
+            draw_depth: None,
+            depths: Vec::new(),
+            virtual_resolution: None,
+            aspect_lock: None,
+            current_viewproj: Mat4::IDENTITY,
+            frame_viewproj: Mat4::IDENTITY,
             camera_stack: Vec::new(),
             model_stack: vec![Mat4::IDENTITY],
+            last_stats: FrameStats::default(),
+            plugins: Vec::new(),
+            frame_hooks: Vec::new(),
+            capture_requested: false,
+            captured_frames: Default::default(),
+            gpu_scopes: Vec::new(),
+            debug_labels: Vec::new(),
+            debug_groups: cfg!(debug_assertions),
+            group_alphas: Vec::new(),
+            color_lut: None,
+            transition: None,
         };
 
-        // Default mode is screen-space (no camera). Upload projection*view to the transform uniform.
+        // Default mode is screen-space (no camera). Record projection*view for the first frame.
         renderer.update_viewproj_transform();
 
         Ok(renderer)
@@ -151,17 +612,44 @@ where
     pub fn begin_frame(&mut self, clear: Option<[f32; 4]>) {
         self.vertices.clear();
         self.commands.clear();
+        self.particle_vertices.clear();
+        self.particle_depths.clear();
+        self.debug_vertices.clear();
+        self.recorded_adjust = ColorAdjust::NONE;
+        self.recorded_depth_sort = false;
+        self.depths.clear();
+        self.frame_viewproj = self.current_viewproj;
         self.clear_color = clear;
+
+        // With a virtual resolution the pass clears the letterbox bars to black, so the
+        // requested clear color is painted over the canvas as the first draw instead.
+        if let (Some(color), Some((w, h, _))) = (clear, self.virtual_resolution) {
+            let quad = [
+                [0.0, 0.0],
+                [w as f32, 0.0],
+                [w as f32, h as f32],
+                [0.0, h as f32],
+            ];
+            self.vertices.extend(quad_to_vertices(quad, color));
+            self.commands
+                .push(DrawCommand::Color { start: 0, count: 6 });
+        }
     }
 
     /// Draw a filled rectangle in logical pixel coordinates. We convert to NDC here.
     pub fn draw_rect(&mut self, rect: crate::Rect, color: crate::Color) {
+        if self.cull(rect) {
+            return;
+        }
         let x0 = rect.x;
         let y0 = rect.y;
         let x1 = rect.x + rect.w;
         let y1 = rect.y + rect.h;
 
         let c = color.0;
+        if self.draw_feathered(&[[x0, y0], [x1, y0], [x1, y1], [x0, y1]], c) {
+            return;
+        }
 
         // two triangles (triangle list), 6 vertices
         let mut vertices = [
@@ -202,6 +690,7 @@ where
 
         let start = self.vertices.len();
         self.vertices.extend_from_slice(&vertices);
+        self.push_draw_depth(start);
 
         match self.commands.last_mut() {
             Some(DrawCommand::Color { count, .. }) => *count += vertices.len(),
@@ -212,6 +701,24 @@ where
         }
     }
 
+    /// Draw a filled rectangle rotated by `rotation` radians around `origin`.
+    ///
+    /// `origin` is relative to the rect's top-left corner, so `(w / 2, h / 2)` spins in place.
+    pub fn draw_rect_ex(
+        &mut self,
+        rect: crate::Rect,
+        origin: (f32, f32),
+        rotation: f32,
+        color: [f32; 4],
+    ) {
+        let quad = rotated_rect_quad(rect, origin, rotation);
+        if self.draw_feathered(&quad, color) {
+            return;
+        }
+        let verts = quad_to_vertices(quad, color);
+        self.draw_color_vertices(&verts);
+    }
+
     /// Draws a line (as a thick quad)
     pub fn draw_line(
         &mut self,
@@ -224,6 +731,9 @@ where
     ) {
         // compute quad in pixel space
         let quad = line_to_quad(x1, y1, x2, y2, thickness);
+        if self.draw_feathered(&quad, color) {
+            return;
+        }
         // convert quad into 6 vertices (pixel space)
         let mut verts = quad_to_vertices(quad, color);
         let model = self.current_model_matrix();
@@ -235,6 +745,7 @@ where
 
         let start = self.vertices.len();
         self.vertices.extend_from_slice(&verts);
+        self.push_draw_depth(start);
 
         match self.commands.last_mut() {
             Some(DrawCommand::Color { count, .. }) => *count += verts.len(),
@@ -247,7 +758,89 @@ where
 
     /// Draws a circle (triangle-fan) in pixel-space
     pub fn draw_circle(&mut self, x: f32, y: f32, radius: f32, segments: usize, color: [f32; 4]) {
-        let mut verts = circle_to_vertices(x, y, radius, segments, color);
+        if self.shape_antialiasing {
+            self.draw_feathered(&ellipse_points(x, y, radius, radius, segments), color);
+            return;
+        }
+        let verts = circle_to_vertices(x, y, radius, segments, color);
+        self.push_color_triangles(verts);
+    }
+
+    /// Draws a filled ellipse, choosing the segment count from its on-screen size.
+    pub fn draw_ellipse(&mut self, x: f32, y: f32, rx: f32, ry: f32, color: [f32; 4]) {
+        let segments = auto_circle_segments(rx.abs().max(ry.abs()) * self.pixels_per_unit());
+        if self.shape_antialiasing {
+            self.draw_feathered(&ellipse_points(x, y, rx, ry, segments), color);
+            return;
+        }
+        let verts = ellipse_to_vertices(x, y, rx, ry, segments, color);
+        self.push_color_triangles(verts);
+    }
+
+    /// Draws a rounded box evaluated as a signed distance field in the fragment shader.
+    ///
+    /// The box is centered at `center` with `half` extents, rotated by `rotation` radians, and
+    /// its corners rounded by `radius` (clamped to the smaller half extent, so a square with
+    /// `radius >= half` is a circle). Edges are anti-aliased at any zoom with only 6 vertices.
+    pub fn draw_sdf_box(
+        &mut self,
+        center: [f32; 2],
+        half: [f32; 2],
+        radius: f32,
+        rotation: f32,
+        color: [f32; 4],
+    ) {
+        // Grow the quad by a pixel so the anti-aliased edge isn't clipped.
+        let margin = 1.0 / self.pixels_per_unit().max(1e-6);
+        let (ex, ey) = (half[0] + margin, half[1] + margin);
+        let (sin, cos) = rotation.sin_cos();
+        let model = self.current_model_matrix();
+        let corner = |lx: f32, ly: f32| {
+            let p = [
+                center[0] + lx * cos - ly * sin,
+                center[1] + lx * sin + ly * cos,
+            ];
+            ShapeVertex {
+                pos: transform_pos2(model, p),
+                local: [lx, ly],
+                shape: [half[0], half[1], radius.max(0.0)],
+                color,
+            }
+        };
+        let (tl, tr, br, bl) = (
+            corner(-ex, -ey),
+            corner(ex, -ey),
+            corner(ex, ey),
+            corner(-ex, ey),
+        );
+
+        let start = self.shape_vertices.len();
+        self.shape_vertices
+            .extend_from_slice(&[tl, tr, br, tl, br, bl]);
+        match self.commands.last_mut() {
+            Some(DrawCommand::Shapes { count, .. }) => *count += 6,
+            _ => self.commands.push(DrawCommand::Shapes { start, count: 6 }),
+        }
+    }
+
+    /// Output pixels covered by one unit of the current transform.
+    ///
+    /// Combines camera zoom, the model matrix and virtual-resolution scaling; used to pick
+    /// tessellation detail for curved shapes.
+    pub fn pixels_per_unit(&self) -> f32 {
+        let (w, h) = match self.viewport() {
+            Some((_, _, w, h)) => (w, h),
+            None => {
+                let (w, h) = self.surface_size();
+                (w as f32, h as f32)
+            }
+        };
+        let m = self.current_viewproj * self.current_model_matrix();
+        let axis = |a: glam::Vec4| glam::vec2(a.x * w * 0.5, a.y * h * 0.5).length();
+        axis(m.x_axis).max(axis(m.y_axis))
+    }
+
+    fn push_color_triangles(&mut self, mut verts: Vec<Vertex>) {
         let model = self.current_model_matrix();
         transform_vertices_in_place(model, &mut verts);
 
@@ -257,6 +850,7 @@ where
 
         let start = self.vertices.len();
         self.vertices.extend_from_slice(&verts);
+        self.push_draw_depth(start);
 
         match self.commands.last_mut() {
             Some(DrawCommand::Color { count, .. }) => *count += verts.len(),
@@ -270,18 +864,63 @@ where
     /// Draws a texture (full image) at dest in pixel-space.
     /// UVs are (0,0)-(1,1) top-left -> bottom-right.
     pub fn draw_texture(&mut self, id: TextureId, dest: crate::Rect, tint: [f32; 4]) {
-        // Pixel-space positions
+        self.draw_texture_adjusted(id, dest, tint, ColorAdjust::NONE);
+    }
+
+    /// Like `draw_texture`, with HSV color grading applied in the fragment shader.
+    pub fn draw_texture_adjusted(
+        &mut self,
+        id: TextureId,
+        dest: crate::Rect,
+        tint: [f32; 4],
+        adjust: ColorAdjust,
+    ) {
+        if !self.texture.contains_key(&id.0) {
+            self.handle_missing_texture(id, dest);
+            return;
+        }
+        self.push_texture_quad(id, dest, [0.0, 0.0, 1.0, 1.0], tint, adjust, None);
+    }
+
+    /// Draws a texture into `dest` sampling UVs `uv_offset .. uv_offset + uv_scale`.
+    ///
+    /// With `TextureWrap::Repeat` a scale above 1 tiles the texture and animating the offset
+    /// scrolls it seamlessly.
+    pub fn draw_texture_uv(
+        &mut self,
+        id: TextureId,
+        dest: crate::Rect,
+        uv_offset: (f32, f32),
+        uv_scale: (f32, f32),
+        tint: [f32; 4],
+    ) {
+        let Some(tex) = self.texture.get(&id.0) else {
+            self.handle_missing_texture(id, dest);
+            return;
+        };
+        let uv = scrolled_uv(uv_offset, uv_scale, tex.wrap);
+        self.push_texture_quad(id, dest, uv, tint, ColorAdjust::NONE, None);
+    }
+
+    /// Record a textured quad covering `dest` with UVs `[u0, v0, u1, v1]`, corner to corner.
+    fn push_texture_quad(
+        &mut self,
+        id: TextureId,
+        dest: crate::Rect,
+        uv: [f32; 4],
+        tint: [f32; 4],
+        adjust: ColorAdjust,
+        wrap: Option<(TextureWrap, TextureWrap)>,
+    ) {
+        if self.cull(dest) {
+            return;
+        }
+        let [u0, v0, u1, v1] = uv;
         let x0 = dest.x;
         let y0 = dest.y;
         let x1 = dest.x + dest.w;
         let y1 = dest.y + dest.h;
 
-        // UV coordinates: (0,0) top-left, (1,1) bottom-right
-        let u0 = 0.0f32;
-        let v0 = 0.0f32;
-        let u1 = 1.0f32;
-        let v1 = 1.0f32;
-
         let start = self.vertices.len();
 
         let mut verts = [
@@ -324,23 +963,91 @@ where
         let model = self.current_model_matrix();
         transform_vertices_in_place(model, &mut verts);
 
+        self.record_color_adjust(adjust);
         self.vertices.extend_from_slice(&verts);
-        self.commands.push(DrawCommand::Texture {
-            tex: id,
-            start,
-            count: verts.len(),
-        });
+        self.push_draw_depth(start);
+        push_texture_command(&mut self.commands, id, start, verts.len(), wrap);
     }
+
     pub fn draw_subtexture(
         &mut self,
         tex: TextureId,
         src: crate::Rect,
         dst: crate::Rect,
         tint: [f32; 4],
+    ) {
+        self.draw_subtexture_adjusted(tex, src, dst, tint, ColorAdjust::NONE);
+    }
+
+    /// Like `draw_subtexture`, but returns an error for an unknown texture instead of
+    /// applying the missing-texture policy.
+    pub fn try_draw_subtexture(
+        &mut self,
+        tex: TextureId,
+        src: crate::Rect,
+        dst: crate::Rect,
+        tint: [f32; 4],
+    ) -> Result<(), RendererError> {
+        if !self.texture.contains_key(&tex.0) {
+            return Err(RendererError::MissingTexture(tex));
+        }
+        self.draw_subtexture(tex, src, dst, tint);
+        Ok(())
+    }
+
+    fn handle_missing_texture(&mut self, id: TextureId, dst: crate::Rect) {
+        match self.missing_texture_policy {
+            MissingTexturePolicy::Ignore => {}
+            MissingTexturePolicy::Warn => {
+                if let Some(warning) =
+                    missing_texture_warning(&mut self.warned_missing_textures, id)
+                {
+                    log_warn!("{warning}");
+                }
+            }
+            MissingTexturePolicy::Placeholder => self.draw_rect(dst, MISSING_TEXTURE_COLOR),
+        }
+    }
+
+    /// Like `draw_subtexture`, with HSV color grading applied in the fragment shader.
+    pub fn draw_subtexture_adjusted(
+        &mut self,
+        tex: TextureId,
+        src: crate::Rect,
+        dst: crate::Rect,
+        tint: [f32; 4],
+        adjust: ColorAdjust,
+    ) {
+        self.draw_subtexture_ex(tex, src, dst, tint, adjust, Flip::NONE);
+    }
+
+    /// Like `draw_subtexture`, mirrored by `flip`.
+    pub fn draw_subtexture_flipped(
+        &mut self,
+        tex: TextureId,
+        src: crate::Rect,
+        dst: crate::Rect,
+        tint: [f32; 4],
+        flip: Flip,
+    ) {
+        self.draw_subtexture_ex(tex, src, dst, tint, ColorAdjust::NONE, flip);
+    }
+
+    fn draw_subtexture_ex(
+        &mut self,
+        tex: TextureId,
+        src: crate::Rect,
+        dst: crate::Rect,
+        tint: [f32; 4],
+        adjust: ColorAdjust,
+        flip: Flip,
     ) {
         let texdata = match self.texture.get(&tex.0) {
             Some(t) => t,
-            None => return,
+            None => {
+                self.handle_missing_texture(tex, dst);
+                return;
+            }
         };
 
         let u0 = src.x / texdata.width as f32;
@@ -348,78 +1055,451 @@ where
         let u1 = (src.x + src.w) / texdata.width as f32;
         let v1 = (src.y + src.h) / texdata.height as f32;
 
-        let x0 = dst.x;
-        let y0 = dst.y;
-        let x1 = dst.x + dst.w;
-        let y1 = dst.y + dst.h;
+        self.push_texture_quad(tex, dst, flip.apply([u0, v0, u1, v1]), tint, adjust, None);
+    }
+
+    /// Append pre-built colored triangles, applying the current model matrix.
+    pub fn draw_color_vertices(&mut self, verts: &[Vertex]) {
+        if verts.is_empty() {
+            return;
+        }
+
+        let start = self.vertices.len();
+        self.ensure_vertex_capacity(start + verts.len());
+        self.vertices.extend_from_slice(verts);
+
+        let model = self.current_model_matrix();
+        transform_vertices_in_place(model, &mut self.vertices[start..]);
+        self.push_draw_depth(start);
+
+        match self.commands.last_mut() {
+            Some(DrawCommand::Color { count, .. }) => *count += verts.len(),
+            _ => self.commands.push(DrawCommand::Color {
+                start,
+                count: verts.len(),
+            }),
+        }
+    }
+
+    /// Append pre-built textured triangles, applying the current model matrix. Vertex colors
+    /// tint the texture.
+    pub fn draw_texture_vertices(&mut self, tex: TextureId, verts: &[Vertex]) {
+        if verts.is_empty() {
+            return;
+        }
+        if !self.texture.contains_key(&tex.0) {
+            let (min, max) = verts
+                .iter()
+                .fold(([f32::MAX; 2], [f32::MIN; 2]), |(min, max), v| {
+                    (
+                        [min[0].min(v.pos[0]), min[1].min(v.pos[1])],
+                        [max[0].max(v.pos[0]), max[1].max(v.pos[1])],
+                    )
+                });
+            let bounds = crate::Rect {
+                x: min[0],
+                y: min[1],
+                w: max[0] - min[0],
+                h: max[1] - min[1],
+            };
+            self.handle_missing_texture(tex, bounds);
+            return;
+        }
 
         let start = self.vertices.len();
+        self.ensure_vertex_capacity(start + verts.len());
+        self.record_color_adjust(ColorAdjust::NONE);
+        self.vertices.extend_from_slice(verts);
+        let model = self.current_model_matrix();
+        transform_vertices_in_place(model, &mut self.vertices[start..]);
+        self.push_draw_depth(start);
+        push_texture_command(&mut self.commands, tex, start, verts.len(), None);
+    }
+
+    /// Upload static triangle-list vertices once; draw them every frame with `draw_mesh`.
+    pub fn create_mesh(&mut self, vertices: &[Vertex]) -> MeshId {
+        let id = self.next_mesh_id;
+        self.next_mesh_id += 1;
+
+        let buffer = self.gpu.create_vertex_buffer("libforge_mesh", vertices);
+        self.meshes.insert(
+            id,
+            Mesh {
+                buffer,
+                vertex_count: vertices.len() as u32,
+            },
+        );
+        MeshId(id)
+    }
+
+    /// Free a mesh's GPU buffer. Drawing a destroyed mesh is a no-op.
+    pub fn destroy_mesh(&mut self, mesh: MeshId) {
+        self.meshes.remove(&mesh.0);
+    }
+
+    /// Draw a retained mesh with `transform` applied on top of the current model matrix.
+    pub fn draw_mesh(&mut self, mesh: MeshId, transform: Mat4, tint: [f32; 4]) {
+        match self.meshes.get(&mesh.0) {
+            Some(m) if m.vertex_count > 0 => {}
+            _ => return,
+        }
+        let model = self.current_model_matrix() * transform;
+        self.commands.push(DrawCommand::Mesh { mesh, model, tint });
+    }
+
+    /// Queue a soft, additively blended particle centered at (x, y).
+    ///
+    /// UVs span -1..1 across the quad; `fs_particle` turns them into a radial falloff.
+    /// While depth sorting is on, the particle takes the depth of its center's y (or the
+    /// `set_draw_depth` value) and fades into sorted geometry in front of it.
+    pub fn draw_particle(&mut self, x: f32, y: f32, radius: f32, color: [f32; 4]) {
+        let quad = [
+            [x - radius, y - radius],
+            [x + radius, y - radius],
+            [x + radius, y + radius],
+            [x - radius, y + radius],
+        ];
+        let uvs = [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]];
+        // The particle layer is drawn after everything else, when the camera may have changed,
+        // so positions are stored already projected (clip space).
+        let model = self.current_viewproj * self.current_model_matrix();
+
+        if self.depth_sorting {
+            let key = self
+                .draw_depth
+                .unwrap_or_else(|| transform_pos2(self.current_model_matrix(), [x, y])[1]);
+            // Particles drawn unsorted sit in front of everything and never fade.
+            self.particle_depths
+                .resize(self.particle_vertices.len(), 1.0);
+            self.particle_depths
+                .resize(self.particle_vertices.len() + 6, sort_depth(key));
+        }
+        for i in [0, 1, 2, 0, 2, 3] {
+            self.particle_vertices.push(Vertex {
+                pos: transform_pos2(model, quad[i]),
+                uv: uvs[i],
+                color,
+            });
+        }
+    }
+
+    /// Fade depth-sorted particles out over `softness` depth units as sorted geometry comes
+    /// that close in front of them, instead of cutting them off where it starts (the default,
+    /// 0). Depth shares units with y.
+    pub fn set_particle_softness(&mut self, softness: f32) {
+        self.particle_softness = softness.max(0.0);
+    }
+
+    pub fn particle_softness(&self) -> f32 {
+        self.particle_softness
+    }
+
+    /// Enable or disable the debug draw layer. Disabled layers drop their draws.
+    pub fn set_debug_draw(&mut self, enabled: bool) {
+        self.debug_draw = enabled;
+        if !enabled {
+            self.debug_vertices.clear();
+        }
+    }
+
+    pub fn debug_draw_enabled(&self) -> bool {
+        self.debug_draw
+    }
+
+    /// Queue colored triangles on the debug layer, through the current camera and model.
+    pub fn draw_debug_vertices(&mut self, verts: &[Vertex]) {
+        if !self.debug_draw {
+            return;
+        }
+        // Like particles, stored projected since the camera may change before end_frame.
+        let mat = self.current_viewproj * self.current_model_matrix();
+        self.debug_vertices.extend(verts.iter().map(|v| Vertex {
+            pos: transform_pos2(mat, v.pos),
+            ..*v
+        }));
+    }
+
+    /// Draws a procedural background layer filling `dest`.
+    ///
+    /// UVs carry dest-local pixel coordinates shifted by the layer's scroll offset;
+    /// the shader turns them into pattern cells.
+    pub fn draw_background(&mut self, layer: &BackgroundLayer, dest: crate::Rect, time: f32) {
+        let [sx, sy] = layer.scroll_at(time);
+        let x0 = dest.x;
+        let y0 = dest.y;
+        let x1 = dest.x + dest.w;
+        let y1 = dest.y + dest.h;
+        let u0 = sx;
+        let v0 = sy;
+        let u1 = sx + dest.w;
+        let v1 = sy + dest.h;
+        let color = layer.color.0;
+
         let mut verts = [
             Vertex {
                 pos: [x0, y0],
                 uv: [u0, v0],
-                color: tint,
+                color,
             },
             Vertex {
                 pos: [x1, y0],
                 uv: [u1, v0],
-                color: tint,
+                color,
             },
             Vertex {
                 pos: [x1, y1],
                 uv: [u1, v1],
-                color: tint,
+                color,
             },
             Vertex {
                 pos: [x0, y0],
                 uv: [u0, v0],
-                color: tint,
+                color,
             },
             Vertex {
                 pos: [x1, y1],
                 uv: [u1, v1],
-                color: tint,
+                color,
             },
             Vertex {
                 pos: [x0, y1],
                 uv: [u0, v1],
-                color: tint,
+                color,
             },
         ];
 
-        let needed_total = start + verts.len();
-        self.ensure_vertex_capacity(needed_total);
-
         let model = self.current_model_matrix();
         transform_vertices_in_place(model, &mut verts);
 
+        let start = self.vertices.len();
+        self.ensure_vertex_capacity(start + verts.len());
         self.vertices.extend_from_slice(&verts);
-
-        self.commands.push(DrawCommand::Texture {
-            tex,
+        self.commands.push(DrawCommand::Background {
             start,
             count: verts.len(),
+            effect: layer.effect_uniform(),
+        });
+    }
+
+    /// Draws parallax layers back-to-front over the canvas for `camera`'s position.
+    ///
+    /// Repeated axes sample the layer's texture with `TextureWrap::Repeat` so a single quad
+    /// tiles across the canvas. Only the layer's draw is affected; the texture keeps its own
+    /// wrap modes everywhere else.
+    pub fn draw_parallax(&mut self, layers: &[ParallaxLayer], camera: &Camera2D) {
+        let (w, h) = self.logical_size();
+        for layer in layers {
+            let Some(tex) = self.texture.get(&layer.texture.0) else {
+                self.handle_missing_texture(
+                    layer.texture,
+                    crate::Rect {
+                        x: 0.0,
+                        y: 0.0,
+                        w: w as f32,
+                        h: h as f32,
+                    },
+                );
+                continue;
+            };
+            let Some((dest, uv)) =
+                layer.quad((tex.width, tex.height), (w as f32, h as f32), camera)
+            else {
+                continue;
+            };
+            let wrap = |repeat, current| match (repeat, current) {
+                (true, _) => TextureWrap::Repeat,
+                (false, TextureWrap::Repeat) => TextureWrap::Clamp,
+                (false, other) => other,
+            };
+            let layer_wrap = (
+                wrap(layer.repeat.0, tex.wrap.0),
+                wrap(layer.repeat.1, tex.wrap.1),
+            );
+            // The texture's own wrap modes are left alone for its other draws.
+            let layer_wrap = (layer_wrap != tex.wrap).then_some(layer_wrap);
+            if let Some(layer_wrap) = layer_wrap {
+                self.ensure_wrapped_bind_group(layer.texture, layer_wrap);
+            }
+            self.push_texture_quad(
+                layer.texture,
+                dest,
+                uv,
+                layer.tint.0,
+                ColorAdjust::NONE,
+                layer_wrap,
+            );
+        }
+    }
+
+    /// Skip `draw_rect` and texture draws whose rectangle lands entirely outside the canvas
+    /// (default: on). Culled draws are counted in `FrameStats::culled_draws`.
+    pub fn set_culling(&mut self, enabled: bool) {
+        self.culling = enabled;
+    }
+
+    pub fn set_shape_antialiasing(&mut self, enabled: bool) {
+        self.shape_antialiasing = enabled;
+    }
+
+    pub fn shape_antialiasing(&self) -> bool {
+        self.shape_antialiasing
+    }
+
+    /// With shape antialiasing on, draw the convex `outline` with a one-pixel feathered
+    /// edge and return `true`; otherwise leave it to the caller's plain tessellation.
+    fn draw_feathered(&mut self, outline: &[[f32; 2]], color: [f32; 4]) -> bool {
+        if !self.shape_antialiasing {
+            return false;
+        }
+        let feather = 1.0 / self.pixels_per_unit().max(1e-6);
+        let verts = feathered_convex_to_vertices(outline, feather, color);
+        self.push_color_triangles(verts);
+        true
+    }
+
+    /// Whether a draw covering `rect` (under the current model and camera) is off-screen
+    /// and should be skipped.
+    fn cull(&mut self, rect: crate::Rect) -> bool {
+        if !self.culling {
+            return false;
+        }
+        let to_clip = self.current_viewproj * self.current_model_matrix();
+        let culled = rect_outside_clip(to_clip, rect);
+        self.culled_draws += culled as usize;
+        culled
+    }
+
+    /// Sort subsequent color and texture draws per pixel by depth instead of call order.
+    ///
+    /// Each draw's depth is the largest y of its vertices after the model transform (its
+    /// "feet"), or the value from `set_draw_depth`; larger depths are drawn in front and
+    /// ties keep call order. Fragments under half alpha are discarded so sprite cutouts
+    /// don't hide what is behind them. Meshes, SDF shapes, backgrounds and particles are
+    /// not sorted and paint over what was drawn before them.
+    pub fn set_depth_sorting(&mut self, enabled: bool) {
+        self.depth_sorting = enabled;
+    }
+
+    pub fn depth_sorting(&self) -> bool {
+        self.depth_sorting
+    }
+
+    /// Use `depth` for subsequent depth-sorted draws instead of their y (`None` restores
+    /// y-sorting). Depth shares units with y, so `y + 0.5` puts a draw just in front of
+    /// things standing at `y`.
+    pub fn set_draw_depth(&mut self, depth: Option<f32>) {
+        self.draw_depth = depth;
+    }
+
+    /// Record depth-sort state for the draw whose vertices start at `start`, and give
+    /// those vertices the draw's depth. Call before recording the draw's command.
+    fn push_draw_depth(&mut self, start: usize) {
+        if self.depth_sorting != self.recorded_depth_sort {
+            self.recorded_depth_sort = self.depth_sorting;
+            self.commands
+                .push(DrawCommand::SetDepthSort(self.depth_sorting));
+        }
+        if !self.depth_sorting {
+            return;
+        }
+        let key = self.draw_depth.unwrap_or_else(|| {
+            self.vertices[start..]
+                .iter()
+                .map(|v| v.pos[1])
+                .fold(f32::MIN, f32::max)
         });
+        // Vertices recorded while unsorted never read their depth.
+        self.depths.resize(start, 0.0);
+        self.depths.resize(self.vertices.len(), sort_depth(key));
+    }
+
+    /// Record a color-adjust state change if `adjust` differs from the active one.
+    fn record_color_adjust(&mut self, adjust: ColorAdjust) {
+        if adjust == self.recorded_adjust {
+            return;
+        }
+        self.recorded_adjust = adjust;
+        self.commands.push(DrawCommand::SetColorAdjust(adjust));
     }
 
     pub fn ortho_projection(&self) -> Mat4 {
-        ortho_projection_for_size(
+        let (w, h) = self.logical_size();
+        ortho_projection_for_size(w, h)
+    }
+
+    /// Size of the drawing canvas: the virtual resolution if set, otherwise the surface.
+    pub fn logical_size(&self) -> (u32, u32) {
+        match self.virtual_resolution {
+            Some((w, h, _)) => (w, h),
+            None => (
+                self.gpu.surface_config.width,
+                self.gpu.surface_config.height,
+            ),
+        }
+    }
+
+    /// Size of the window surface in physical pixels.
+    pub fn surface_size(&self) -> (u32, u32) {
+        (
             self.gpu.surface_config.width,
             self.gpu.surface_config.height,
         )
     }
 
-    fn set_transform_mat4(&mut self, mat: Mat4) {
-        self.gpu.write_transform(mat);
+    /// Surface viewport the canvas is drawn into (`None` = whole surface).
+    pub fn viewport(&self) -> Option<Viewport> {
+        let (w, h, mode) = self.virtual_resolution?;
+        Some(fit_viewport(
+            self.gpu.surface_config.width,
+            self.gpu.surface_config.height,
+            w,
+            h,
+            mode,
+        ))
+    }
+
+    /// Draw in a fixed `width x height` canvas scaled to the surface (`None` disables).
+    pub fn set_virtual_resolution(&mut self, resolution: Option<(u32, u32, ScalingMode)>) {
+        self.aspect_lock = None;
+        self.virtual_resolution = resolution.filter(|(w, h, _)| *w > 0 && *h > 0);
+        self.update_viewproj_transform();
+    }
+
+    /// Lock the canvas to `aspect` (width / height) in surface pixels (`None` unlocks).
+    ///
+    /// The canvas is the largest such rectangle fitting the surface, recomputed on every
+    /// resize; it replaces any virtual resolution.
+    pub fn set_aspect_ratio(&mut self, lock: Option<(f32, FillMode)>) {
+        self.aspect_lock = lock.filter(|(aspect, _)| aspect.is_finite() && *aspect > 0.0);
+        self.virtual_resolution = None;
+        self.apply_aspect_lock();
     }
 
+    fn apply_aspect_lock(&mut self) {
+        if let Some((aspect, mode)) = self.aspect_lock {
+            let (w, h) = self.surface_size();
+            let (w, h) = fit_aspect(w, h, aspect);
+            self.virtual_resolution = Some((w, h, mode.scaling_mode()));
+        }
+        self.update_viewproj_transform();
+    }
+
+    /// Recompute projection * view and record it for the draws that follow.
+    ///
+    /// Before the first command of a frame this just replaces the frame's starting matrix;
+    /// afterwards it records a `SetViewProj` so earlier draws keep the matrix they were issued with.
     fn update_viewproj_transform(&mut self) {
-        let mat = viewproj_for_size_and_camera_stack(
-            self.gpu.surface_config.width,
-            self.gpu.surface_config.height,
-            &self.camera_stack,
-        );
-        self.set_transform_mat4(mat);
+        let (w, h) = self.logical_size();
+        let mat = viewproj_for_size_and_camera_stack(w, h, &self.camera_stack);
+        if mat == self.current_viewproj {
+            return;
+        }
+        self.current_viewproj = mat;
+        if self.commands.is_empty() {
+            self.frame_viewproj = mat;
+        } else {
+            self.commands.push(DrawCommand::SetViewProj(mat));
+        }
     }
 
     /// Begin 2D camera mode (world-space). Camera only applies until `end_mode_2d()`.
@@ -446,89 +1526,590 @@ where
         }
     }
 
-    pub fn load_identity(&mut self) {
-        if let Some(top) = self.model_stack.last_mut() {
-            *top = Mat4::IDENTITY;
-        }
+    pub fn load_identity(&mut self) {
+        if let Some(top) = self.model_stack.last_mut() {
+            *top = Mat4::IDENTITY;
+        }
+    }
+
+    pub fn translate(&mut self, tx: f32, ty: f32) {
+        if let Some(top) = self.model_stack.last_mut() {
+            model_translate(top, tx, ty);
+        }
+    }
+
+    pub fn rotate_z(&mut self, radians: f32) {
+        if let Some(top) = self.model_stack.last_mut() {
+            model_rotate_z(top, radians);
+        }
+    }
+
+    pub fn scale(&mut self, sx: f32, sy: f32) {
+        if let Some(top) = self.model_stack.last_mut() {
+            model_scale(top, sx, sy);
+        }
+    }
+
+    /// Current top of the model matrix stack.
+    pub fn get_transform(&self) -> Mat4 {
+        self.current_model_matrix()
+    }
+
+    /// Replace the top of the model matrix stack.
+    pub fn set_transform(&mut self, mat: Mat4) {
+        if let Some(top) = self.model_stack.last_mut() {
+            *top = mat;
+        }
+    }
+
+    fn current_model_matrix(&self) -> Mat4 {
+        *self.model_stack.last().unwrap_or(&Mat4::IDENTITY)
+    }
+
+    /// Run `f` with no camera and an identity model matrix, then restore both.
+    ///
+    /// Used for overlays that must stay fixed on screen whatever the game has pushed.
+    pub fn screen_space<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let cameras = std::mem::take(&mut self.camera_stack);
+        let models = std::mem::replace(&mut self.model_stack, vec![Mat4::IDENTITY]);
+        self.update_viewproj_transform();
+
+        let result = f(self);
+
+        self.camera_stack = cameras;
+        self.model_stack = models;
+        self.update_viewproj_transform();
+        result
+    }
+
+    /// Start timing the GPU work of the draws recorded until the matching
+    /// [`end_gpu_scope`](Self::end_gpu_scope). Scopes may nest.
+    pub fn begin_gpu_scope(&mut self, name: &str) -> u32 {
+        let scope = self.gpu_scopes.len() as u32;
+        self.gpu_scopes.push(name.to_string());
+        self.commands.push(DrawCommand::GpuTimestamp(scope * 2));
+        scope
+    }
+
+    pub fn end_gpu_scope(&mut self, scope: u32) {
+        self.commands.push(DrawCommand::GpuTimestamp(scope * 2 + 1));
+    }
+
+    /// Insert `label` between the surrounding draws in GPU debugger captures (RenderDoc,
+    /// Xcode, PIX).
+    pub fn debug_marker(&mut self, label: &str) {
+        self.commands
+            .push(DrawCommand::DebugMarker(self.debug_labels.len() as u32));
+        self.debug_labels.push(label.to_string());
+    }
+
+    /// Wrap every draw in a debug group naming what it draws (texture name, shape type).
+    /// On by default in debug builds; costs a string per draw call.
+    pub fn set_debug_groups(&mut self, enabled: bool) {
+        self.debug_groups = enabled;
+    }
+
+    /// Draw everything up to the matching `end_group` into an offscreen layer, then
+    /// composite the layer with a single `alpha`. Unlike giving each draw that alpha,
+    /// overlapping draws in the group don't show through each other. Groups can nest.
+    pub fn begin_group(&mut self, alpha: f32) {
+        self.group_alphas.push(alpha.clamp(0.0, 1.0));
+        self.commands.push(DrawCommand::BeginGroup);
+    }
+
+    /// Composite the innermost open group onto whatever it was drawn over.
+    pub fn end_group(&mut self) {
+        let Some(alpha) = self.group_alphas.pop() else {
+            log_warn!("end_group without begin_group");
+            return;
+        };
+        let start = self.vertices.len();
+        self.ensure_vertex_capacity(start + 6);
+        self.vertices
+            .extend(fullscreen_quad([1.0, 1.0, 1.0, alpha]));
+        self.commands
+            .push(DrawCommand::EndGroup { start, count: 6 });
+    }
+
+    /// Grade every finished frame through the LUT strip in `lut` (see `crate::lut`), or
+    /// stop grading with `None`. The LUT must be an RGBA8 texture `size * size` x `size`.
+    pub fn set_color_lut(&mut self, lut: Option<TextureId>) -> Result<(), RendererError> {
+        if let Some(id) = lut {
+            let tex = self
+                .texture
+                .get(&id.0)
+                .ok_or(RendererError::MissingTexture(id))?;
+            let is_strip = crate::lut::strip_size(tex.width, tex.height).is_some();
+            if !is_strip || tex.texture.format() != wgpu::TextureFormat::Rgba8UnormSrgb {
+                return Err(RendererError::Internal(format!(
+                    "texture '{}' ({}x{} {:?}) is not a color LUT strip",
+                    tex.name,
+                    tex.width,
+                    tex.height,
+                    tex.texture.format()
+                )));
+            }
+        }
+        self.color_lut = lut;
+        Ok(())
+    }
+
+    pub fn color_lut(&self) -> Option<TextureId> {
+        self.color_lut
+    }
+
+    /// Draw `transition` over every following frame, hiding `coverage` (0..1) of the
+    /// screen; `None` stops drawing it.
+    pub fn set_transition(&mut self, transition: Option<(crate::transition::Transition, f32)>) {
+        self.transition = transition;
+    }
+
+    /// Whether the window is composited with the frame's alpha (see
+    /// `RendererConfig::transparent`).
+    pub fn is_transparent(&self) -> bool {
+        self.gpu.is_transparent()
+    }
+
+    /// Whether `feature` was granted when the device was created.
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.gpu.supports(feature)
+    }
+
+    /// Limits the device was created with.
+    pub fn device_limits(&self) -> wgpu::Limits {
+        self.gpu.limits()
+    }
+
+    /// Whether `gpu_scope` timings are available on this device.
+    pub fn supports_gpu_timing(&self) -> bool {
+        self.gpu.supports_gpu_timing()
+    }
+
+    /// Counters from the last frame passed to `end_frame`.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.last_stats.clone()
+    }
+
+    /// Pixel size of a loaded texture.
+    pub fn texture_size(&self, id: TextureId) -> Option<(u32, u32)> {
+        self.texture.get(&id.0).map(|t| (t.width, t.height))
+    }
+
+    /// The texture most recently loaded under `name`.
+    pub fn texture_by_name(&self, name: &str) -> Option<TextureId> {
+        self.texture_names.get(name).copied()
+    }
+
+    /// Every loaded texture, including the renderer's own (glyph atlas, ...), oldest first.
+    pub fn textures(&self) -> Vec<TextureInfo<'_>> {
+        let mut textures: Vec<TextureInfo<'_>> = self
+            .texture
+            .iter()
+            .map(|(&id, t)| TextureInfo {
+                id: TextureId(id),
+                name: &t.name,
+                width: t.width,
+                height: t.height,
+                bytes: t.bytes,
+            })
+            .collect();
+        textures.sort_by_key(|t| t.id.0);
+        textures
+    }
+
+    /// Choose how colors passed to draw calls are interpreted (default: sRGB).
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.color_space = color_space;
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    /// Run `plugin` inside every `end_frame`, after the main pass and before present.
+    pub fn add_render_plugin(&mut self, plugin: Box<dyn RenderPlugin>) {
+        self.plugins.push(plugin);
+    }
+
+    pub fn clear_render_plugins(&mut self) {
+        self.plugins.clear();
+    }
+
+    /// Run `hook` after the main pass of the current frame only.
+    pub fn with_render_pass(&mut self, hook: FrameHook) {
+        self.frame_hooks.push(hook);
+    }
+
+    /// Choose what texture draws do with an unknown `TextureId` (default: warn once).
+    pub fn set_missing_texture_policy(&mut self, policy: MissingTexturePolicy) {
+        self.missing_texture_policy = policy;
+    }
+
+    /// Change how `id` is sampled outside `0..1` along u and v. Unknown ids are ignored.
+    pub fn set_texture_wrap(&mut self, id: TextureId, wrap_u: TextureWrap, wrap_v: TextureWrap) {
+        let Some(tex) = self.texture.get(&id.0) else {
+            return;
+        };
+        if tex.wrap == (wrap_u, wrap_v) {
+            return;
+        }
+        let sampler = self.gpu.create_sampler(
+            (wrap_u.address_mode(), wrap_v.address_mode()),
+            tex.trilinear,
+        );
+        let bind_group = self.gpu.create_texture_bind_group(&tex.view, &sampler);
+        let tex = self.texture.get_mut(&id.0).unwrap();
+        tex.sampler = sampler;
+        tex.bind_group = bind_group;
+        tex.wrap = (wrap_u, wrap_v);
+    }
+
+    /// Build the bind group sampling texture `id` with `wrap`, for draws overriding its wrap
+    /// modes.
+    fn ensure_wrapped_bind_group(&mut self, id: TextureId, wrap: (TextureWrap, TextureWrap)) {
+        let Some(tex) = self.texture.get(&id.0) else {
+            return;
+        };
+        if tex.wrapped.contains_key(&wrap) {
+            return;
+        }
+        let sampler = self.gpu.create_sampler(
+            (wrap.0.address_mode(), wrap.1.address_mode()),
+            tex.trilinear,
+        );
+        let bind_group = self.gpu.create_texture_bind_group(&tex.view, &sampler);
+        let tex = self.texture.get_mut(&id.0).unwrap();
+        tex.wrapped.insert(wrap, bind_group);
+    }
+
+    /// Options applied to textures loaded from now on.
+    pub fn set_texture_options(&mut self, options: TextureOptions) {
+        self.texture_options = options;
+    }
+
+    pub fn texture_options(&self) -> TextureOptions {
+        self.texture_options
+    }
+
+    pub fn load_texture_from_bytes(
+        &mut self,
+        name: &str,
+        bytes: &[u8],
+    ) -> Result<TextureId, RendererError> {
+        let decoded = decode::decode(bytes)?;
+        self.upload_decoded(name, bytes, decoded)
+    }
+
+    /// Decode `textures` (name and encoded bytes) on worker threads and upload each one as
+    /// soon as it is ready. `progress(loaded, total)` runs after every upload.
+    ///
+    /// Returns the ids in the order of `textures`. On the first failure the error names the
+    /// texture; textures uploaded before it stay loaded.
+    pub fn load_textures_parallel(
+        &mut self,
+        textures: &[(&str, &[u8])],
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<Vec<TextureId>, RendererError> {
+        let images: Vec<&[u8]> = textures.iter().map(|(_, bytes)| *bytes).collect();
+        let mut ids = vec![None; textures.len()];
+        let mut loaded = 0;
+        let mut failure = None;
+        decode::decode_parallel(&images, |index, decoded| {
+            if failure.is_some() {
+                return;
+            }
+            let (name, bytes) = textures[index];
+            match decoded.and_then(|decoded| self.upload_decoded(name, bytes, decoded)) {
+                Ok(id) => {
+                    ids[index] = Some(id);
+                    loaded += 1;
+                    progress(loaded, textures.len());
+                }
+                Err(e) => failure = Some(RendererError::Internal(format!("texture '{name}': {e}"))),
+            }
+        });
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(ids.into_iter().flatten().collect()),
+        }
+    }
+
+    fn upload_decoded(
+        &mut self,
+        name: &str,
+        bytes: &[u8],
+        decoded: decode::Decoded,
+    ) -> Result<TextureId, RendererError> {
+        match decoded {
+            decode::Decoded::Rgba(rgba) => {
+                self.load_texture_from_rgba(name, rgba.width(), rgba.height(), &rgba)
+            }
+            decode::Decoded::Compressed => self.load_compressed_texture(name, bytes),
+        }
+    }
+
+    /// Create a texture from tightly packed RGBA8 (sRGB) pixels.
+    pub fn load_texture_from_rgba(
+        &mut self,
+        name: &str,
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+    ) -> Result<TextureId, RendererError> {
+        if rgba.len() != (width as usize) * (height as usize) * 4 {
+            return Err(RendererError::Internal(format!(
+                "texture '{name}': expected {}x{}x4 bytes, got {}",
+                width,
+                height,
+                rgba.len()
+            )));
+        }
+        let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+        let TextureOptions {
+            premultiply_alpha,
+            color_key,
+            ..
+        } = self.texture_options;
+        if premultiply_alpha || color_key.is_some() {
+            let mut pixels = rgba.to_vec();
+            if let Some(key) = color_key {
+                apply_color_key(&mut pixels, key);
+            }
+            if premultiply_alpha {
+                premultiply_srgb_alpha(&mut pixels);
+            }
+            return self.create_texture(name, width, height, format, &[&pixels], premultiply_alpha);
+        }
+        self.create_texture(name, width, height, format, &[rgba], false)
+    }
+
+    /// Create a transparent RGBA8 texture that is updated in place with
+    /// `write_texture_region` (e.g. the glyph atlas). It has no mipmaps, which would go stale.
+    pub(crate) fn create_dynamic_texture(
+        &mut self,
+        name: &str,
+        width: u32,
+        height: u32,
+    ) -> Result<TextureId, RendererError> {
+        let options = std::mem::take(&mut self.texture_options);
+        let pixels = vec![0u8; width as usize * height as usize * 4];
+        let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+        let result = self.create_texture(name, width, height, format, &[&pixels], false);
+        self.texture_options = options;
+        result
     }
 
-    pub fn translate(&mut self, tx: f32, ty: f32) {
-        if let Some(top) = self.model_stack.last_mut() {
-            model_translate(top, tx, ty);
-        }
+    /// Overwrite a `size` region of mip level 0 at `origin` with tightly packed RGBA8 pixels.
+    pub(crate) fn write_texture_region(
+        &mut self,
+        id: TextureId,
+        origin: (u32, u32),
+        size: (u32, u32),
+        rgba: &[u8],
+    ) {
+        let Some(texture) = self.texture.get(&id.0) else {
+            return;
+        };
+        self.gpu.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: origin.0,
+                    y: origin.1,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            rgba,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(size.0 * 4),
+                rows_per_image: Some(size.1),
+            },
+            wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+        );
     }
 
-    pub fn rotate_z(&mut self, radians: f32) {
-        if let Some(top) = self.model_stack.last_mut() {
-            model_rotate_z(top, radians);
-        }
+    /// Copy a texture's top mip level back from the GPU as RGBA8, blocking until done.
+    ///
+    /// Premultiplied textures come back premultiplied. Block-compressed textures can't be
+    /// read back.
+    pub fn read_texture_pixels(&self, id: TextureId) -> Result<image::RgbaImage, RendererError> {
+        let texture = self
+            .texture
+            .get(&id.0)
+            .ok_or(RendererError::MissingTexture(id))?;
+        self.gpu.read_texture(&texture.texture).ok_or_else(|| {
+            RendererError::Internal(format!(
+                "texture '{}' ({:?}) can't be read back",
+                texture.name,
+                texture.texture.format()
+            ))
+        })
     }
 
-    pub fn scale(&mut self, sx: f32, sy: f32) {
-        if let Some(top) = self.model_stack.last_mut() {
-            model_scale(top, sx, sy);
-        }
+    /// Load a PNG/JPEG/DDS/KTX2 texture with `options` instead of the global texture options.
+    pub fn load_texture_from_bytes_with(
+        &mut self,
+        name: &str,
+        bytes: &[u8],
+        options: TextureOptions,
+    ) -> Result<TextureId, RendererError> {
+        let global = std::mem::replace(&mut self.texture_options, options);
+        let result = self.load_texture_from_bytes(name, bytes);
+        self.texture_options = global;
+        result
     }
 
-    fn current_model_matrix(&self) -> Mat4 {
-        *self.model_stack.last().unwrap_or(&Mat4::IDENTITY)
+    /// Create a texture from RGBA8 pixels with `options` instead of the global texture
+    /// options.
+    pub fn load_texture_from_rgba_with(
+        &mut self,
+        name: &str,
+        (width, height): (u32, u32),
+        rgba: &[u8],
+        options: TextureOptions,
+    ) -> Result<TextureId, RendererError> {
+        let global = std::mem::replace(&mut self.texture_options, options);
+        let result = self.load_texture_from_rgba(name, width, height, rgba);
+        self.texture_options = global;
+        result
     }
 
-    pub fn load_texture_from_bytes(
+    /// Load a block-compressed texture from a DDS or KTX2 file (BC1-BC7).
+    ///
+    /// The blocks are uploaded as-is when the GPU supports BC compression. Otherwise BC1-BC5
+    /// are decoded to RGBA8 on the CPU (top mip level only); BC6H/BC7 fail with an error.
+    pub fn load_compressed_texture(
         &mut self,
         name: &str,
         bytes: &[u8],
     ) -> Result<TextureId, RendererError> {
-        // decode with image crate
-        let img = image::load_from_memory(bytes)
-            .map_err(|e| RendererError::Internal(format!("{:?}", e)))?;
-        let rgba = img.to_rgba8();
-        let (width, height) = (rgba.width(), rgba.height());
+        let image = compressed::parse(bytes)?;
+        let format = image.format.wgpu_format(image.srgb);
+        let blocks_aligned = image.width % 4 == 0 && image.height % 4 == 0;
+        if self.gpu.supports(Feature::TextureCompressionBc) && blocks_aligned {
+            return self.create_texture(
+                name,
+                image.width,
+                image.height,
+                format,
+                &image.levels,
+                false,
+            );
+        }
+
+        log_debug!("texture '{name}': no BC support, decoding {format:?} on the CPU");
+        let rgba =
+            compressed::decode_to_rgba(image.format, image.width, image.height, image.levels[0])
+                .ok_or_else(|| {
+                    RendererError::Internal(format!(
+                        "texture '{name}': {format:?} is not supported by this GPU"
+                    ))
+                })?;
+        let rgba_format = if image.srgb {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        } else {
+            wgpu::TextureFormat::Rgba8Unorm
+        };
+        self.create_texture(
+            name,
+            image.width,
+            image.height,
+            rgba_format,
+            &[&rgba],
+            false,
+        )
+    }
+
+    /// Create a texture of `format` and upload `levels` (mip 0 first, tightly packed).
+    ///
+    /// With a single uncompressed level and `TextureOptions::mipmaps`, the remaining levels
+    /// are rendered on the GPU. `premultiplied` selects premultiplied blending when drawn.
+    fn create_texture(
+        &mut self,
+        name: &str,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        levels: &[&[u8]],
+        premultiplied: bool,
+    ) -> Result<TextureId, RendererError> {
         let size = wgpu::Extent3d {
             width,
             height,
             depth_or_array_layers: 1,
         };
+        let generate_mips =
+            self.texture_options.mipmaps && levels.len() == 1 && !format.is_compressed();
+        let mip_level_count = if generate_mips {
+            mipmap::full_mip_count(width, height)
+        } else {
+            levels.len().max(1) as u32
+        };
+        // COPY_SRC lets `read_texture_pixels` copy the texture back.
+        let mut usage = wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::COPY_SRC;
+        if generate_mips {
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+        }
 
         let texture = self.gpu.device.create_texture(&wgpu::TextureDescriptor {
             label: Some(name),
             size,
-            mip_level_count: 1,
+            mip_level_count,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            format,
+            usage,
             view_formats: &[],
         });
 
-        // upload data
-        self.gpu.queue.write_texture(
-            wgpu::TexelCopyTextureInfo {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            &rgba,
-            wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * width),
-                rows_per_image: Some(height),
-            },
-            size,
-        );
+        // upload data, one mip level at a time
+        let (block_w, block_h) = format.block_dimensions();
+        let block_bytes = format.block_copy_size(None).unwrap_or(4);
+        let mut bytes = 0u64;
+        for level in 0..mip_level_count {
+            let physical = size
+                .mip_level_size(level, wgpu::TextureDimension::D2)
+                .physical_size(format);
+            let bytes_per_row = physical.width / block_w * block_bytes;
+            bytes += bytes_per_row as u64 * (physical.height / block_h) as u64;
+            let Some(data) = levels.get(level as usize) else {
+                continue;
+            };
+            self.gpu.queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &texture,
+                    mip_level: level,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                data,
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: Some(physical.height / block_h),
+                },
+                physical,
+            );
+        }
+        if generate_mips {
+            self.gpu.generate_mipmaps(&texture);
+        }
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = self.gpu.device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("libforge_sampler"),
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
+        let wrap = (self.texture_options.wrap, self.texture_options.wrap);
+        let trilinear = self.texture_options.trilinear;
+        let sampler = self
+            .gpu
+            .create_sampler((wrap.0.address_mode(), wrap.1.address_mode()), trilinear);
 
         // create bind group
         let bind_group = self.gpu.create_texture_bind_group(&view, &sampler);
@@ -550,6 +2131,8 @@ where
         });
         */
 
+        log_debug!("loaded texture '{name}' ({width}x{height}, {mip_level_count} mip levels)");
+
         let id = {
             let id = self.next_texture_id;
             self.next_texture_id += 1;
@@ -565,38 +2148,290 @@ where
                 bind_group,
                 width,
                 height,
+                premultiplied,
+                wrap,
+                wrapped: std::collections::HashMap::new(),
+                trilinear,
+                bytes,
+                name: name.to_string(),
             },
         );
+        self.texture_names.insert(name.to_string(), TextureId(id));
         Ok(TextureId(id))
     }
+
+    /// Copy the next submitted frame back to the CPU; fetch it with `take_captured_frame`.
+    ///
+    /// The copy arrives without stalling, usually two frames later; `flush_captures` waits
+    /// for any still in flight.
+    pub fn capture_next_frame(&mut self) {
+        self.capture_requested = true;
+    }
+
+    /// Oldest captured frame that has arrived and not been taken yet. `None` if none has
+    /// arrived, or the surface doesn't support being copied from.
+    pub fn take_captured_frame(&mut self) -> Option<image::RgbaImage> {
+        self.captured_frames.pop_front()
+    }
+
+    /// Wait for captured frames still being copied back, so `take_captured_frame` returns
+    /// every frame captured so far.
+    pub fn flush_captures(&mut self) {
+        let frames = self.gpu.flush_captures();
+        self.captured_frames.extend(frames);
+    }
+
+    /// Replace the main WGSL shader (same entry points as `basic.wgsl`) and rebuild the
+    /// pipelines. Errors leave the current shader in place.
+    pub fn reload_shader(&mut self, source: &str) -> Result<(), RendererError> {
+        self.gpu.reload_shader(source)
+    }
+
+    /// Enable or disable waiting for vertical sync when presenting.
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.gpu.set_vsync(vsync);
+    }
+
+    pub fn vsync(&self) -> bool {
+        self.gpu.vsync()
+    }
+
+    /// Set the MSAA sample count (1 disables it). Returns the count actually used, which
+    /// is lower when the GPU doesn't support the requested one.
+    pub fn set_msaa_samples(&mut self, samples: u32) -> u32 {
+        self.gpu.set_msaa_samples(samples)
+    }
+
+    pub fn msaa_samples(&self) -> u32 {
+        self.gpu.msaa_samples()
+    }
+
+    /// Render at `scale` (clamped to `0.5..=1.0`) of the surface's width and height and
+    /// upscale to the surface. Returns the scale in use.
+    pub fn set_render_scale(&mut self, scale: f32) -> f32 {
+        let scale = if scale.is_nan() {
+            1.0
+        } else {
+            scale.clamp(crate::dynamic_resolution::MIN_RENDER_SCALE, 1.0)
+        };
+        self.gpu.set_render_scale(scale);
+        scale
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.gpu.render_scale()
+    }
+
     /// Resize: reconfigure surface.
     ///
     /// Note: resizing changes the orthographic projection used by the transform pipeline,
     /// so we also refresh the transform uniform to keep pixel-space drawing correct.
     pub fn resize(&mut self, width: u32, height: u32) {
+        // Apps that still resize by hand after `handle_window_event` shouldn't reconfigure twice.
+        if (width, height) == self.surface_size() {
+            return;
+        }
         self.gpu.resize(width, height);
 
-        // Keep the default transform in sync with the new surface size.
-        self.update_viewproj_transform();
+        // Keep the aspect-locked canvas and default transform in sync with the new size.
+        self.apply_aspect_lock();
+    }
+
+    /// Drop the window surface, e.g. when Android sends the app to the background.
+    /// Textures and pipelines survive; frames ended while suspended are discarded.
+    pub fn suspend(&mut self) {
+        self.gpu.suspend();
+    }
+
+    /// Recreate the surface for `window` after `suspend`.
+    pub fn resume(&mut self, window: W) -> Result<(), RendererError> {
+        self.gpu.resume(window)
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.gpu.is_suspended()
     }
 
     /// End frame: submit draw commands to the GPU and present.
     pub fn end_frame(&mut self) -> Result<(), RendererError> {
+        // Groups left open are still composited, then reported with the other stacks below.
+        let open_groups = self.group_alphas.len();
+        for _ in 0..open_groups {
+            self.end_group();
+        }
+
+        // Particles and debug gizmos are never depth sorted.
+        if self.recorded_depth_sort {
+            self.recorded_depth_sort = false;
+            self.commands.push(DrawCommand::SetDepthSort(false));
+        }
+        let soft_particles = !self.particle_depths.is_empty();
+        if soft_particles {
+            self.particle_depths
+                .resize(self.particle_vertices.len(), 1.0);
+            self.depths.resize(self.vertices.len(), 0.0);
+            self.depths.append(&mut self.particle_depths);
+        }
+        if !self.depths.is_empty() {
+            self.depths.resize(
+                self.vertices.len() + self.particle_vertices.len() + self.debug_vertices.len(),
+                0.0,
+            );
+        }
+
+        // The particle layer goes after the world; additive blending makes its order irrelevant.
+        if !self.particle_vertices.is_empty() {
+            let start = self.vertices.len();
+            let count = self.particle_vertices.len();
+            self.vertices.append(&mut self.particle_vertices);
+            self.commands.push(DrawCommand::SetViewProj(Mat4::IDENTITY));
+            self.commands.push(DrawCommand::Particles {
+                start,
+                count,
+                softness: soft_particles.then_some(self.particle_softness),
+            });
+        }
+
+        // Debug gizmos sit on top of everything, particles included.
+        if !self.debug_vertices.is_empty() {
+            let start = self.vertices.len();
+            let count = self.debug_vertices.len();
+            self.vertices.append(&mut self.debug_vertices);
+            self.commands.push(DrawCommand::SetViewProj(Mat4::IDENTITY));
+            self.commands.push(DrawCommand::Color { start, count });
+        }
+
+        self.last_stats = FrameStats {
+            vertices: self.vertices.len()
+                + self.shape_vertices.len()
+                + mesh_vertex_count(&self.commands, &self.meshes),
+            draw_calls: count_draw_calls(&self.commands),
+            textures: self.texture.len(),
+            texture_bytes: self.texture.values().map(|t| t.bytes).sum(),
+            culled_draws: std::mem::take(&mut self.culled_draws),
+            gpu_scopes: self.gpu.gpu_scope_timings().to_vec(),
+        };
+
         // Delegate GPU submission.
-        self.gpu.end_frame(
-            &self.vertices,
-            &self.commands,
-            self.clear_color,
-            &self.texture,
-        )?;
+        let viewport = self.viewport();
+        let captured = self.gpu.end_frame(FrameData {
+            vertices: &self.vertices,
+            depths: &self.depths,
+            shape_vertices: &self.shape_vertices,
+            commands: &self.commands,
+            clear_color: self.clear_color,
+            srgb_colors: self.color_space == ColorSpace::Srgb,
+            view_proj: self.frame_viewproj,
+            viewport,
+            textures: &self.texture,
+            meshes: &self.meshes,
+            plugins: &mut self.plugins,
+            frame_hooks: std::mem::take(&mut self.frame_hooks),
+            capture: std::mem::take(&mut self.capture_requested),
+            gpu_scopes: &self.gpu_scopes,
+            debug_labels: &self.debug_labels,
+            debug_groups: self.debug_groups,
+            post: post::PostEffects {
+                // A LUT that was unloaded since just stops grading.
+                color_lut: self
+                    .color_lut
+                    .and_then(|id| self.texture.get(&id.0))
+                    .map(|tex| &tex.bind_group),
+                transition: self.transition,
+            },
+        })?;
+        self.captured_frames.extend(captured);
 
         // Clear CPU-side arrays for next frame
         self.vertices.clear();
+        self.shape_vertices.clear();
         self.commands.clear();
+        self.gpu_scopes.clear();
+        self.debug_labels.clear();
+        self.depths.clear();
+        self.recorded_adjust = ColorAdjust::NONE;
+        self.frame_viewproj = self.current_viewproj;
+
+        // Unbalanced push/pop or begin/end_mode_2d would otherwise leak into every later frame
+        // (open groups were already closed above).
+        if let Some(msg) =
+            stack_balance_error(self.model_stack.len(), self.camera_stack.len(), open_groups)
+        {
+            self.model_stack = vec![Mat4::IDENTITY];
+            self.camera_stack.clear();
+            self.update_viewproj_transform();
+
+            if cfg!(debug_assertions) {
+                return Err(RendererError::UnbalancedState(msg));
+            }
+            // Goes to stderr when the `log` feature is off.
+            log_warn!("{msg}");
+        }
 
         Ok(())
     }
 }
+
+/// Number of commands that issue a GPU draw (state changes are free).
+fn count_draw_calls(commands: &[DrawCommand]) -> usize {
+    commands
+        .iter()
+        .filter(|cmd| {
+            !matches!(
+                cmd,
+                DrawCommand::SetViewProj(_)
+                    | DrawCommand::SetColorAdjust(_)
+                    | DrawCommand::SetDepthSort(_)
+                    | DrawCommand::GpuTimestamp(_)
+                    | DrawCommand::DebugMarker(_)
+                    | DrawCommand::BeginGroup
+            )
+        })
+        .count()
+}
+
+fn mesh_vertex_count(
+    commands: &[DrawCommand],
+    meshes: &std::collections::HashMap<u32, Mesh>,
+) -> usize {
+    commands
+        .iter()
+        .filter_map(|cmd| match cmd {
+            DrawCommand::Mesh { mesh, .. } => meshes.get(&mesh.0),
+            _ => None,
+        })
+        .map(|m| m.vertex_count as usize)
+        .sum()
+}
+
+/// Describe an unbalanced matrix/camera/group stack at frame end, or `None` if balanced.
+fn stack_balance_error(
+    model_depth: usize,
+    camera_depth: usize,
+    group_depth: usize,
+) -> Option<String> {
+    let mut problems = Vec::new();
+    if model_depth != 1 {
+        problems.push(format!(
+            "model matrix stack depth is {model_depth} (expected 1): unmatched push_matrix/pop_matrix"
+        ));
+    }
+    if camera_depth != 0 {
+        problems.push(format!(
+            "{camera_depth} begin_mode_2d call(s) without end_mode_2d"
+        ));
+    }
+    if group_depth != 0 {
+        problems.push(format!(
+            "{group_depth} begin_group call(s) without end_group"
+        ));
+    }
+    if problems.is_empty() {
+        None
+    } else {
+        Some(problems.join("; "))
+    }
+}
 #[allow(dead_code)]
 pub(crate) fn rect_to_ndc_coords(rect: crate::Rect, width: u32, height: u32) -> [f32; 12] {
     let w = width as f32;
@@ -665,6 +2500,36 @@ mod tests {
         assert_eq!(coords[11], -1.0);
     }
 
+    #[test]
+    fn feathered_rect_fades_across_its_edge() {
+        let color = [0.2, 0.4, 0.6, 1.0];
+        // Clockwise on screen; the winding doesn't matter.
+        let square = [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]];
+        let verts = crate::renderer::feathered_convex_to_vertices(&square, 1.0, color);
+        // Fan of 2 triangles plus a 2-triangle skirt per edge.
+        assert_eq!(verts.len(), 2 * 3 + 4 * 6);
+
+        let (solid, edge): (Vec<_>, Vec<_>) = verts.iter().partition(|v| v.color[3] == 1.0);
+        let bounds = |vs: &[&Vertex]| {
+            vs.iter().fold([f32::MAX, f32::MIN], |b, v| {
+                [
+                    b[0].min(v.pos[0]).min(v.pos[1]),
+                    b[1].max(v.pos[0]).max(v.pos[1]),
+                ]
+            })
+        };
+        // Solid inset by half a unit, transparent rim half a unit outside.
+        assert_eq!(bounds(&solid), [0.5, 9.5]);
+        assert_eq!(bounds(&edge), [-0.5, 10.5]);
+        assert!(edge.iter().all(|v| v.color[..3] == color[..3]));
+
+        let mut reversed = square;
+        reversed.reverse();
+        let verts = crate::renderer::feathered_convex_to_vertices(&reversed, 1.0, color);
+        let solid: Vec<_> = verts.iter().filter(|v| v.color[3] == 1.0).collect();
+        assert_eq!(bounds(&solid), [0.5, 9.5]);
+    }
+
     #[test]
     fn circle_vertex_count_and_basic_positions() {
         // small segments count for deterministic test
@@ -683,6 +2548,20 @@ mod tests {
         assert!((verts[0].pos[1] - cy).abs() < 1e-6);
     }
 
+    #[test]
+    fn auto_segments_grow_with_screen_radius() {
+        assert_eq!(auto_circle_segments(0.0), 8);
+        assert_eq!(auto_circle_segments(2.0), 8);
+        let medium = auto_circle_segments(50.0);
+        let large = auto_circle_segments(800.0);
+        assert!(medium > 8 && medium < large, "{medium} vs {large}");
+        assert_eq!(auto_circle_segments(1.0e6), 256);
+
+        let verts = ellipse_to_vertices(0.0, 0.0, 20.0, 5.0, 4, [1.0; 4]);
+        assert_eq!(verts[1].pos, [20.0, 0.0]);
+        assert!((verts[2].pos[1] - 5.0).abs() < 1e-5);
+    }
+
     #[test]
     fn texture_loading_from_bytes() {
         use image::RgbaImage;
@@ -745,8 +2624,7 @@ mod tests {
         let mut stack = vec![Camera2D {
             x: 100.0,
             y: 50.0,
-            rotation: 0.0,
-            zoom: 1.0,
+            ..Camera2D::new()
         }];
         let world = viewproj_for_size_and_camera_stack(w, h, &stack);
 
@@ -768,8 +2646,7 @@ mod tests {
         let mut stack = vec![Camera2D {
             x: 10.0,
             y: 20.0,
-            rotation: 0.0,
-            zoom: 1.0,
+            ..Camera2D::new()
         }];
 
         let m1 = viewproj_for_size_and_camera_stack(800, 600, &stack);
@@ -792,6 +2669,67 @@ mod tests {
         assert_eq!(back2.to_cols_array(), screen2.to_cols_array());
     }
 
+    #[test]
+    fn rects_outside_the_canvas_are_culled() {
+        let to_clip = ortho_projection_for_size(800, 600);
+        let rect = |x, y| crate::Rect {
+            x,
+            y,
+            w: 32.0,
+            h: 32.0,
+        };
+        assert!(!rect_outside_clip(to_clip, rect(100.0, 100.0)));
+        // Partially visible on every edge.
+        assert!(!rect_outside_clip(to_clip, rect(-16.0, -16.0)));
+        assert!(!rect_outside_clip(to_clip, rect(790.0, 590.0)));
+        assert!(rect_outside_clip(to_clip, rect(-40.0, 100.0)));
+        assert!(rect_outside_clip(to_clip, rect(100.0, 601.0)));
+
+        // A camera scrolled right brings the rect at x = 1000 into view.
+        let camera = Camera2D {
+            x: 900.0,
+            ..Camera2D::new()
+        };
+        let to_clip = to_clip * camera.view_matrix();
+        assert!(!rect_outside_clip(to_clip, rect(1000.0, 100.0)));
+        assert!(rect_outside_clip(to_clip, rect(100.0, 100.0)));
+    }
+
+    #[test]
+    fn sort_depth_is_monotonic_and_inside_the_depth_range() {
+        let keys = [-1.0e6, -5000.0, -1.0, 0.0, 0.5, 1.0, 300.0, 300.5, 1.0e5];
+        let depths: Vec<f32> = keys.iter().map(|&k| sort_depth(k)).collect();
+        assert!(depths.windows(2).all(|w| w[0] < w[1]), "{depths:?}");
+        assert!(depths.iter().all(|d| *d > 0.0 && *d < 1.0));
+        assert_eq!(sort_depth(0.0), 0.5);
+    }
+
+    #[test]
+    fn flip_swaps_uvs_per_axis() {
+        let uv = [0.25, 0.5, 0.75, 1.0];
+        assert_eq!(Flip::NONE.apply(uv), uv);
+        assert_eq!(Flip::X.apply(uv), [0.75, 0.5, 0.25, 1.0]);
+        assert_eq!(Flip::Y.apply(uv), [0.25, 1.0, 0.75, 0.5]);
+        assert_eq!(Flip::BOTH.apply(uv), [0.75, 1.0, 0.25, 0.5]);
+    }
+
+    #[test]
+    fn scrolled_uv_wraps_offsets_only_on_tiled_axes() {
+        let uv = scrolled_uv(
+            (3.25, -0.5),
+            (2.0, 1.0),
+            (TextureWrap::Repeat, TextureWrap::MirrorRepeat),
+        );
+        assert_eq!(uv, [0.25, 1.5, 2.25, 2.5]);
+
+        let uv = scrolled_uv(
+            (3.25, 0.0),
+            (1.0, 1.0),
+            (TextureWrap::Clamp, TextureWrap::Clamp),
+        );
+        assert_eq!(uv, [3.25, 0.0, 4.25, 1.0]);
+    }
+
     #[test]
     fn draw_texture_generates_correct_vertices() {
         // We can't easily test the full renderer without a GPU, but we can verify
@@ -871,6 +2809,7 @@ mod tests {
             tex: TextureId(0),
             start: 6,
             count: 6,
+            wrap: None,
         };
 
         match color_cmd {
@@ -882,7 +2821,9 @@ mod tests {
         }
 
         match tex_cmd {
-            DrawCommand::Texture { tex, start, count } => {
+            DrawCommand::Texture {
+                tex, start, count, ..
+            } => {
                 assert_eq!(tex.0, 0);
                 assert_eq!(start, 6);
                 assert_eq!(count, 6);
@@ -890,4 +2831,355 @@ mod tests {
             _ => panic!("Wrong variant"),
         }
     }
+
+    #[test]
+    fn basic_shader_is_valid_wgsl() {
+        use wgpu::naga;
+
+        let module = naga::front::wgsl::parse_str(include_str!("../shaders/basic.wgsl"))
+            .expect("basic.wgsl should parse");
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .expect("basic.wgsl should validate");
+    }
+
+    #[test]
+    fn color_adjust_uniform_packing() {
+        assert_eq!(
+            gpu::color_adjust_uniform(ColorAdjust::NONE),
+            [0.0, 1.0, 1.0, 0.0]
+        );
+
+        let frozen = ColorAdjust {
+            hue_shift: 0.5,
+            saturation: 0.2,
+            brightness: 1.1,
+        };
+        assert_eq!(gpu::color_adjust_uniform(frozen), [0.5, 0.2, 1.1, 0.0]);
+        assert_eq!(ColorAdjust::default(), ColorAdjust::NONE);
+    }
+
+    #[test]
+    fn background_commands_get_their_own_param_slot() {
+        use crate::background::{BackgroundKind, BackgroundLayer};
+
+        let layer = BackgroundLayer::new(BackgroundKind::Starfield, u32::MAX);
+        let effect = layer.effect_uniform();
+        assert_eq!(effect[1].to_bits(), u32::MAX);
+
+        let commands = [
+            DrawCommand::Color { start: 0, count: 6 },
+            DrawCommand::Background {
+                start: 6,
+                count: 6,
+                effect,
+            },
+            DrawCommand::Color {
+                start: 12,
+                count: 6,
+            },
+        ];
+        let (params, slots) = gpu::collect_params(&commands, Mat4::IDENTITY, false);
+        assert_eq!(params.len(), 2);
+        assert_eq!(slots, vec![0, 1, 0]);
+        assert_eq!(params[1].effect[1].to_bits(), u32::MAX);
+    }
+
+    #[test]
+    fn rotated_rect_quad_pivots_around_origin() {
+        let rect = crate::Rect {
+            x: 10.0,
+            y: 10.0,
+            w: 20.0,
+            h: 10.0,
+        };
+
+        let unrotated = rotated_rect_quad(rect, (10.0, 5.0), 0.0);
+        assert_eq!(unrotated[0], [10.0, 10.0]);
+        assert_eq!(unrotated[2], [30.0, 20.0]);
+
+        // Half a turn around the center swaps opposite corners.
+        let flipped = rotated_rect_quad(rect, (10.0, 5.0), std::f32::consts::PI);
+        assert!((flipped[0][0] - 30.0).abs() < 1e-4);
+        assert!((flipped[0][1] - 20.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn stack_balance_is_checked_at_frame_end() {
+        assert_eq!(stack_balance_error(1, 0, 0), None);
+
+        let pushed = stack_balance_error(2, 0, 0).unwrap();
+        assert!(pushed.contains("push_matrix"));
+
+        let both = stack_balance_error(1, 1, 0).unwrap();
+        assert!(both.contains("begin_mode_2d"));
+        assert!(!both.contains("push_matrix"));
+
+        assert!(
+            stack_balance_error(1, 0, 2)
+                .unwrap()
+                .contains("2 begin_group")
+        );
+    }
+
+    #[test]
+    fn groups_become_separate_layers() {
+        let quad = |start| DrawCommand::EndGroup { start, count: 6 };
+        let commands = [
+            DrawCommand::Color { start: 0, count: 6 },
+            DrawCommand::BeginGroup,
+            DrawCommand::Color { start: 6, count: 6 },
+            DrawCommand::BeginGroup,
+            quad(12),
+            quad(18),
+            DrawCommand::BeginGroup,
+            quad(24),
+        ];
+        assert_eq!(gpu::group_depth(&commands), 2);
+        assert_eq!(count_draw_calls(&commands), 5);
+
+        // Composites get a neutral slot of their own and don't disturb the draws after them.
+        let (params, slots) = gpu::collect_params(&commands, Mat4::IDENTITY, true);
+        assert_eq!(params.len(), 4);
+        assert_eq!(slots, vec![0, 0, 0, 0, 1, 2, 0, 3]);
+        assert_eq!(params[1].color_adjust, [0.0, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn mesh_commands_carry_model_and_tint_in_their_slot() {
+        let model = Mat4::from_translation(glam::vec3(3.0, 4.0, 0.0));
+        let commands = [
+            DrawCommand::SetColorAdjust(ColorAdjust {
+                hue_shift: 1.0,
+                ..ColorAdjust::NONE
+            }),
+            DrawCommand::Mesh {
+                mesh: MeshId(0),
+                model,
+                tint: [0.5; 4],
+            },
+            DrawCommand::Color { start: 0, count: 3 },
+        ];
+        let (params, slots) = gpu::collect_params(&commands, Mat4::IDENTITY, false);
+
+        assert_eq!(slots, vec![1, 2, 1]);
+        assert_eq!(params[0], gpu::DrawParams::NEUTRAL);
+        assert_eq!(params[2].model, model.to_cols_array());
+        assert_eq!(params[2].tint, [0.5; 4]);
+        // Meshes keep the active color adjust.
+        assert_eq!(params[2].color_adjust[0], 1.0);
+        assert_eq!(params[0].model, Mat4::IDENTITY.to_cols_array());
+    }
+
+    #[test]
+    fn missing_textures_warn_once_by_default() {
+        assert_eq!(MissingTexturePolicy::default(), MissingTexturePolicy::Warn);
+        let mut warned = std::collections::HashSet::new();
+        let warning = missing_texture_warning(&mut warned, TextureId(7));
+        assert_eq!(
+            warning.as_deref(),
+            Some("texture draw skipped: TextureId(7) is not loaded")
+        );
+        assert_eq!(missing_texture_warning(&mut warned, TextureId(7)), None);
+        assert!(missing_texture_warning(&mut warned, TextureId(8)).is_some());
+    }
+
+    #[test]
+    fn soft_particles_carry_their_softness() {
+        let particles = |softness| DrawCommand::Particles {
+            start: 0,
+            count: 6,
+            softness,
+        };
+        let commands = [
+            DrawCommand::SetViewProj(Mat4::IDENTITY),
+            particles(None),
+            particles(Some(8.0)),
+            DrawCommand::Color { start: 6, count: 3 },
+        ];
+        let (params, slots) = gpu::collect_params(&commands, Mat4::IDENTITY, false);
+
+        assert_eq!(slots, vec![1, 1, 2, 1]);
+        assert_eq!(params[2].effect, [8.0, 0.0, 0.0, 0.0]);
+        assert_eq!(params[2].view_proj, params[1].view_proj);
+    }
+
+    #[test]
+    fn srgb_decoding_is_flagged_in_every_param_slot() {
+        let commands = [
+            DrawCommand::SetColorAdjust(ColorAdjust {
+                saturation: 0.0,
+                ..ColorAdjust::NONE
+            }),
+            DrawCommand::Color { start: 0, count: 3 },
+        ];
+        let (params, _) = gpu::collect_params(&commands, Mat4::IDENTITY, true);
+        assert!(params.iter().all(|p| p.color_adjust[3] == 1.0));
+
+        assert_eq!(gpu::srgb_to_linear(1.0), 1.0);
+        assert!((gpu::srgb_to_linear(0.5) - 0.214).abs() < 1e-3);
+    }
+
+    #[test]
+    fn color_key_clears_only_exact_matches() {
+        let mut px = [255, 0, 255, 255, 254, 0, 255, 255, 10, 20, 30, 128];
+        apply_color_key(&mut px, [255, 0, 255]);
+        assert_eq!(px, [0, 0, 0, 0, 254, 0, 255, 255, 10, 20, 30, 128]);
+    }
+
+    #[test]
+    fn premultiply_scales_color_in_linear_space() {
+        let mut px = [255, 128, 0, 128, 10, 20, 30, 255, 200, 200, 200, 0];
+        premultiply_srgb_alpha(&mut px);
+        // White at ~50% alpha becomes ~50% linear, which encodes to ~188 in sRGB.
+        assert_eq!(px[0], 188);
+        assert_eq!(px[2], 0);
+        assert_eq!(px[3], 128);
+        assert_eq!(px[4..8], [10, 20, 30, 255]);
+        assert_eq!(px[8..12], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn viewproj_changes_are_recorded_per_batch() {
+        let screen = viewproj_for_size_and_camera_stack(800, 600, &[]);
+        let resized = viewproj_for_size_and_camera_stack(1024, 768, &[]);
+        let commands = [
+            DrawCommand::Color { start: 0, count: 6 },
+            DrawCommand::SetViewProj(resized),
+            DrawCommand::Color { start: 6, count: 6 },
+        ];
+        let (params, slots) = gpu::collect_params(&commands, screen, false);
+
+        assert_eq!(slots, vec![0, 1, 1]);
+        assert_eq!(params[0].view_proj, screen.to_cols_array());
+        assert_eq!(params[1].view_proj, resized.to_cols_array());
+    }
+
+    #[test]
+    fn adjacent_draws_of_one_texture_merge() {
+        let mut commands = Vec::new();
+        push_texture_command(&mut commands, TextureId(1), 0, 6, None);
+        push_texture_command(&mut commands, TextureId(1), 6, 6, None);
+        push_texture_command(&mut commands, TextureId(2), 12, 6, None);
+        commands.push(DrawCommand::SetColorAdjust(ColorAdjust::NONE));
+        push_texture_command(&mut commands, TextureId(2), 18, 6, None);
+        // A parallax draw samples with other wrap modes, so it can't join the run.
+        let repeat = Some((TextureWrap::Repeat, TextureWrap::Clamp));
+        push_texture_command(&mut commands, TextureId(2), 24, 6, repeat);
+        push_texture_command(&mut commands, TextureId(2), 30, 6, repeat);
+
+        assert_eq!(commands.len(), 5);
+        assert!(matches!(
+            commands[0],
+            DrawCommand::Texture {
+                start: 0,
+                count: 12,
+                ..
+            }
+        ));
+        assert!(matches!(
+            commands[4],
+            DrawCommand::Texture {
+                start: 24,
+                count: 12,
+                wrap: Some(_),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn state_changes_are_not_counted_as_draw_calls() {
+        let commands = vec![
+            DrawCommand::Color { start: 0, count: 6 },
+            DrawCommand::SetViewProj(Mat4::IDENTITY),
+            DrawCommand::SetColorAdjust(ColorAdjust::NONE),
+            DrawCommand::GpuTimestamp(0),
+            DrawCommand::Texture {
+                tex: TextureId(0),
+                start: 6,
+                count: 6,
+                wrap: None,
+            },
+            DrawCommand::GpuTimestamp(1),
+            DrawCommand::Particles {
+                start: 12,
+                count: 6,
+                softness: None,
+            },
+        ];
+        assert_eq!(count_draw_calls(&commands), 3);
+        assert_eq!(gpu::pipeline_switches(&commands), 3);
+    }
+
+    #[test]
+    fn debug_labels_name_draws_but_not_state_changes() {
+        let (textures, meshes) = (Default::default(), Default::default());
+        let label = |cmd| gpu::draw_label(&cmd, &textures, &meshes);
+        assert_eq!(
+            label(DrawCommand::Color { start: 0, count: 6 }).as_deref(),
+            Some("color (6 vertices)")
+        );
+        assert_eq!(
+            label(DrawCommand::Shapes {
+                start: 0,
+                count: 12
+            })
+            .as_deref(),
+            Some("shapes (2 quads)")
+        );
+        assert_eq!(label(DrawCommand::SetDepthSort(true)), None);
+        assert_eq!(label(DrawCommand::DebugMarker(0)), None);
+        // A missing texture is skipped at submission, so it must not open a group.
+        let missing = DrawCommand::Texture {
+            tex: TextureId(7),
+            start: 0,
+            count: 6,
+            wrap: None,
+        };
+        assert_eq!(label(missing), None);
+    }
+
+    #[test]
+    fn surface_selection_prefers_8bit_srgb_and_opaque() {
+        use wgpu::{CompositeAlphaMode, TextureFormat};
+        let formats = [
+            TextureFormat::Rgb10a2Unorm,
+            TextureFormat::Rgba8Unorm,
+            TextureFormat::Rgba8UnormSrgb,
+        ];
+        assert_eq!(
+            gpu::choose_surface_format(&formats),
+            TextureFormat::Rgba8UnormSrgb
+        );
+        assert_eq!(
+            gpu::choose_surface_format(&[TextureFormat::Rgba8Unorm]),
+            TextureFormat::Rgba8Unorm
+        );
+        let modes = [
+            CompositeAlphaMode::Inherit,
+            CompositeAlphaMode::Opaque,
+            CompositeAlphaMode::PostMultiplied,
+            CompositeAlphaMode::PreMultiplied,
+        ];
+        assert_eq!(
+            gpu::choose_alpha_mode(&modes, false),
+            CompositeAlphaMode::Opaque
+        );
+        assert_eq!(
+            gpu::choose_alpha_mode(&modes, true),
+            CompositeAlphaMode::PreMultiplied
+        );
+        assert_eq!(
+            gpu::choose_alpha_mode(&modes[..3], true),
+            CompositeAlphaMode::PostMultiplied
+        );
+        assert_eq!(
+            gpu::choose_alpha_mode(&[CompositeAlphaMode::Inherit], true),
+            CompositeAlphaMode::Inherit
+        );
+    }
 }
@@ -0,0 +1,97 @@
+/// A color texture that draws are redirected to mid-frame and that is then sampled like any
+/// other texture, e.g. to composite a `begin_group` layer with one opacity.
+pub(crate) struct OffscreenTarget {
+    pub(crate) view: wgpu::TextureView,
+    // Multisampled attachment resolved into `view` at the end of every pass; `None` without
+    // MSAA.
+    pub(crate) msaa_view: Option<wgpu::TextureView>,
+    // `view` and a clamping sampler, bound at index 1 like a texture.
+    pub(crate) bind_group: wgpu::BindGroup,
+    format: wgpu::TextureFormat,
+    size: (u32, u32),
+    samples: u32,
+}
+
+impl OffscreenTarget {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        label: &str,
+        (layout, sampler): (&wgpu::BindGroupLayout, &wgpu::Sampler),
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+        samples: u32,
+    ) -> Self {
+        let create = |sample_count, usage| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width: size.0,
+                        height: size.1,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+        let view = create(
+            1,
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        );
+        let msaa_view =
+            (samples > 1).then(|| create(samples, wgpu::TextureUsages::RENDER_ATTACHMENT));
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+            label: Some(label),
+        });
+        Self {
+            view,
+            msaa_view,
+            bind_group,
+            format,
+            size,
+            samples,
+        }
+    }
+
+    /// Whether this target can stand in for one created with these parameters.
+    pub(crate) fn matches(
+        &self,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+        samples: u32,
+    ) -> bool {
+        (self.format, self.size, self.samples) == (format, size, samples)
+    }
+
+    /// Color attachment for a pass drawing into this target.
+    pub(crate) fn attachment(
+        &self,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'_> {
+        wgpu::RenderPassColorAttachment {
+            view: self.msaa_view.as_ref().unwrap_or(&self.view),
+            resolve_target: self.msaa_view.as_ref().map(|_| &self.view),
+            depth_slice: None,
+            ops: wgpu::Operations {
+                load,
+                store: wgpu::StoreOp::Store,
+            },
+        }
+    }
+}
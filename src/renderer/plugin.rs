@@ -0,0 +1,30 @@
+/// GPU handles passed to render plugins, after libforge's own pass and before present.
+///
+/// `view` already holds this frame's scene. Record extra passes into `encoder` (load the
+/// attachment with `LoadOp::Load` to keep what's there); it is submitted with the frame.
+pub struct RenderTarget<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub view: &'a wgpu::TextureView,
+    pub format: wgpu::TextureFormat,
+    /// Surface size in physical pixels.
+    pub size: (u32, u32),
+    pub encoder: &'a mut wgpu::CommandEncoder,
+}
+
+/// Custom rendering run inside `end_frame`, e.g. an egui overlay or a 3D view.
+///
+/// Register with `ctx.add_render_plugin(..)`. Any `FnMut(&mut RenderTarget)` closure is a
+/// plugin too.
+pub trait RenderPlugin {
+    fn render(&mut self, target: &mut RenderTarget<'_>);
+}
+
+impl<F: FnMut(&mut RenderTarget<'_>)> RenderPlugin for F {
+    fn render(&mut self, target: &mut RenderTarget<'_>) {
+        self(target)
+    }
+}
+
+/// One-shot hook from `with_render_pass`, run in the current frame only.
+pub(crate) type FrameHook = Box<dyn FnOnce(&mut RenderTarget<'_>)>;
@@ -0,0 +1,228 @@
+use crate::transition::Transition;
+use std::collections::HashMap;
+
+/// Full-frame effects applied by the post pass.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct PostEffects<'a> {
+    // Texture bind group of the color LUT the frame is graded through.
+    pub(crate) color_lut: Option<&'a wgpu::BindGroup>,
+    // Running transition and how much of the screen it hides.
+    pub(crate) transition: Option<(Transition, f32)>,
+}
+
+impl PostEffects<'_> {
+    /// Whether the frame needs the post pass at all.
+    pub(crate) fn is_active(&self) -> bool {
+        self.color_lut.is_some() || self.transition.is_some()
+    }
+}
+
+/// Uniforms of `fs_post`; mirrors `PostParams` in `post.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostParams {
+    size: [f32; 2],
+    lut_enabled: f32,
+    transition: f32,
+    coverage: f32,
+    _pad: [f32; 3],
+}
+
+impl PostParams {
+    fn new(size: (u32, u32), effects: &PostEffects<'_>) -> Self {
+        let (transition, coverage) = match effects.transition {
+            None => (0.0, 0.0),
+            Some((Transition::Fade, c)) => (1.0, c),
+            Some((Transition::CircleWipe, c)) => (2.0, c),
+            Some((Transition::Pixelate, c)) => (3.0, c),
+        };
+        Self {
+            size: [size.0 as f32, size.1 as f32],
+            lut_enabled: if effects.color_lut.is_some() {
+                1.0
+            } else {
+                0.0
+            },
+            transition,
+            coverage,
+            _pad: [0.0; 3],
+        }
+    }
+}
+
+/// Full-screen pass that reads the finished frame from an offscreen target and writes the
+/// result to the surface (see `post.wgsl`).
+pub(crate) struct PostPass {
+    shader: wgpu::ShaderModule,
+    // Scene texture at index 0, color LUT at index 1 (both the texture layout), uniforms
+    // at index 2.
+    layout: wgpu::PipelineLayout,
+    params_buffer: wgpu::Buffer,
+    params_bind_group: wgpu::BindGroup,
+    // One pipeline per output format, built on first use.
+    pipelines: HashMap<wgpu::TextureFormat, wgpu::RenderPipeline>,
+}
+
+impl PostPass {
+    pub(crate) fn new(device: &wgpu::Device, tex_layout: &wgpu::BindGroupLayout) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("post_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/post.wgsl").into()),
+        });
+        let params_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("post_params_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("post_params"),
+            size: std::mem::size_of::<PostParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let params_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("post_params_bind_group"),
+            layout: &params_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buffer.as_entire_binding(),
+            }],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("post_pipeline_layout"),
+            bind_group_layouts: &[tex_layout, tex_layout, &params_layout],
+            push_constant_ranges: &[],
+        });
+        Self {
+            shader,
+            layout,
+            params_buffer,
+            params_bind_group,
+            pipelines: HashMap::new(),
+        }
+    }
+
+    /// Write `scene` with `effects` applied to `target`, replacing its contents.
+    pub(crate) fn run(
+        &mut self,
+        (device, queue): (&wgpu::Device, &wgpu::Queue),
+        encoder: &mut wgpu::CommandEncoder,
+        (target, format, size): (&wgpu::TextureView, wgpu::TextureFormat, (u32, u32)),
+        scene: &wgpu::BindGroup,
+        effects: PostEffects<'_>,
+    ) {
+        let params = PostParams::new(size, &effects);
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+        let pipeline = self.pipelines.entry(format).or_insert_with(|| {
+            log_debug!("creating post pipeline for {format:?}");
+            create_post_pipeline(device, &self.shader, &self.layout, format)
+        });
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("post_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                depth_slice: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(pipeline);
+        rpass.set_bind_group(0, scene, &[]);
+        // Without a LUT the scene stands in; `lut_enabled` keeps it from being read.
+        rpass.set_bind_group(1, effects.color_lut.unwrap_or(scene), &[]);
+        rpass.set_bind_group(2, &self.params_bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+}
+
+fn create_post_pipeline(
+    device: &wgpu::Device,
+    shader: &wgpu::ShaderModule,
+    layout: &wgpu::PipelineLayout,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    let srgb = if format.is_srgb() { 1.0 } else { 0.0 };
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("post_pipeline"),
+        layout: Some(layout),
+        cache: None,
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_fullscreen"),
+            buffers: &[],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_post"),
+            compilation_options: wgpu::PipelineCompilationOptions {
+                constants: &[("srgb_target", srgb)],
+                ..Default::default()
+            },
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn post_shader_is_valid_wgsl() {
+        use wgpu::naga;
+
+        let module = naga::front::wgsl::parse_str(include_str!("../shaders/post.wgsl"))
+            .expect("post.wgsl should parse");
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .expect("post.wgsl should validate");
+    }
+
+    #[test]
+    fn post_params_match_the_shader_layout() {
+        // vec2 + 3 floats, padded to a 16-byte multiple for uniform buffers.
+        assert_eq!(std::mem::size_of::<PostParams>(), 32);
+        let effects = PostEffects {
+            color_lut: None,
+            transition: Some((Transition::CircleWipe, 0.25)),
+        };
+        assert!(effects.is_active());
+        let params = PostParams::new((800, 600), &effects);
+        assert_eq!(
+            (
+                params.size,
+                params.lut_enabled,
+                params.transition,
+                params.coverage
+            ),
+            ([800.0, 600.0], 0.0, 2.0, 0.25)
+        );
+        assert!(!PostEffects::default().is_active());
+    }
+}
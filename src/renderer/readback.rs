@@ -0,0 +1,209 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Staging buffers captured frames can be in flight in at once. A frame is usually
+/// collected two frames after it was rendered; with all buffers busy, the oldest is waited
+/// for before another frame is captured.
+const RING_SIZE: usize = 3;
+
+const MAP_PENDING: u8 = 0;
+const MAP_DONE: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+/// A surface or texture copy in flight to the CPU.
+pub(crate) struct Readback {
+    buffer: wgpu::Buffer,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    // Bytes per row in `buffer`, padded to `COPY_BYTES_PER_ROW_ALIGNMENT`.
+    padded_row: u32,
+}
+
+impl Readback {
+    /// Record a copy of level 0 of `texture` (the surface, or a loaded texture) into
+    /// `buffer` if it is large enough, else into a new mappable buffer.
+    pub(crate) fn copy(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        buffer: Option<wgpu::Buffer>,
+    ) -> Self {
+        let (width, height) = (texture.width(), texture.height());
+        let padded_row = (width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let size = padded_row as u64 * height as u64;
+        let buffer = buffer.filter(|b| b.size() >= size).unwrap_or_else(|| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("readback_buffer"),
+                size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            })
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+        Self {
+            buffer,
+            format: texture.format(),
+            width,
+            height,
+            padded_row,
+        }
+    }
+
+    fn size(&self) -> u64 {
+        self.padded_row as u64 * self.height as u64
+    }
+
+    /// Start mapping the copy; call after submitting it. The returned state turns
+    /// `MAP_DONE` or `MAP_FAILED` during a later `device.poll`.
+    fn map(&self) -> Arc<AtomicU8> {
+        let state = Arc::new(AtomicU8::new(MAP_PENDING));
+        let flag = state.clone();
+        self.buffer
+            .slice(..self.size())
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let done = if result.is_ok() { MAP_DONE } else { MAP_FAILED };
+                flag.store(done, Ordering::Release)
+            });
+        state
+    }
+
+    /// Wait for a submitted copy and unpack it into RGBA8.
+    pub(crate) fn wait(self, device: &wgpu::Device) -> Option<image::RgbaImage> {
+        let state = self.map();
+        if let Err(e) = device.poll(wgpu::PollType::wait_indefinitely()) {
+            log_warn!("readback failed: {e}");
+            return None;
+        }
+        if state.load(Ordering::Acquire) != MAP_DONE {
+            return None;
+        }
+        let image = self.unpack();
+        self.buffer.unmap();
+        image
+    }
+
+    /// Pixels of a mapped copy as RGBA8. Leaves the buffer mapped.
+    fn unpack(&self) -> Option<image::RgbaImage> {
+        let bgra = matches!(
+            self.format,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+        let data = self.buffer.slice(..self.size()).get_mapped_range();
+        let mut rgba = Vec::with_capacity((self.width * self.height * 4) as usize);
+        for row in data.chunks_exact(self.padded_row as usize) {
+            rgba.extend_from_slice(&row[..self.width as usize * 4]);
+        }
+        drop(data);
+
+        if bgra {
+            for px in rgba.chunks_exact_mut(4) {
+                px.swap(0, 2);
+            }
+        }
+        image::RgbaImage::from_raw(self.width, self.height, rgba)
+    }
+}
+
+/// Captured frames read back without stalling the frame that captured them.
+///
+/// Each capture is copied into a staging buffer from a small ring and mapped after
+/// submission; later frames pick up finished copies with a non-blocking poll. Only `flush`,
+/// or a capture while every buffer is still in flight, waits for the GPU.
+#[derive(Default)]
+pub(crate) struct FrameReadbacks {
+    // Copies in submission order, with the submission and mapping state of each.
+    in_flight: VecDeque<(Readback, wgpu::SubmissionIndex, Arc<AtomicU8>)>,
+    // Unmapped staging buffers ready for the next capture.
+    free: Vec<wgpu::Buffer>,
+}
+
+impl FrameReadbacks {
+    /// Record a copy of `texture` into a free staging buffer. Pass the result to `submitted`
+    /// once the encoder has been submitted.
+    pub(crate) fn copy(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) -> Readback {
+        Readback::copy(device, encoder, texture, self.free.pop())
+    }
+
+    /// Start mapping `readback` (if any) from `submission`, and return every earlier capture
+    /// that has finished, oldest first.
+    pub(crate) fn submitted(
+        &mut self,
+        device: &wgpu::Device,
+        readback: Option<Readback>,
+        submission: wgpu::SubmissionIndex,
+    ) -> Vec<image::RgbaImage> {
+        if let Some(readback) = readback {
+            let state = readback.map();
+            self.in_flight.push_back((readback, submission, state));
+        }
+        if self.in_flight.is_empty() {
+            return Vec::new();
+        }
+        let _ = device.poll(wgpu::PollType::Poll);
+        if self.in_flight.len() >= RING_SIZE {
+            // The GPU is more than two frames behind: wait for the oldest copy so the ring
+            // doesn't grow.
+            let (_, oldest, _) = &self.in_flight[0];
+            let _ = device.poll(wgpu::PollType::Wait {
+                submission_index: Some(oldest.clone()),
+                timeout: None,
+            });
+        }
+        self.collect()
+    }
+
+    /// Wait for every capture in flight and return them, oldest first.
+    pub(crate) fn flush(&mut self, device: &wgpu::Device) -> Vec<image::RgbaImage> {
+        if self.in_flight.is_empty() {
+            return Vec::new();
+        }
+        if let Err(e) = device.poll(wgpu::PollType::wait_indefinitely()) {
+            log_warn!("readback failed: {e}");
+        }
+        let frames = self.collect();
+        // Copies whose mapping failed are given up on.
+        self.in_flight.clear();
+        frames
+    }
+
+    /// Pop finished copies off the front of the queue, recycling their buffers.
+    fn collect(&mut self) -> Vec<image::RgbaImage> {
+        let mut frames = Vec::new();
+        while let Some((_, _, state)) = self.in_flight.front() {
+            let state = state.load(Ordering::Acquire);
+            if state == MAP_PENDING {
+                break;
+            }
+            let (readback, _, _) = self.in_flight.pop_front().unwrap();
+            if state == MAP_DONE {
+                frames.extend(readback.unpack());
+                readback.buffer.unmap();
+            }
+            self.free.push(readback.buffer);
+        }
+        frames
+    }
+
+    /// Drop the staging buffers, e.g. after the surface was resized.
+    pub(crate) fn release_buffers(&mut self) {
+        self.free.clear();
+    }
+}
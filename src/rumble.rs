@@ -0,0 +1,178 @@
+use gilrs::Gilrs;
+use gilrs::ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Replay, Ticks};
+
+/// Force-feedback motor speeds, each in `[0, 1]`.
+///
+/// `strong` drives the low-frequency (heavy) motor and `weak` the high-frequency one, the
+/// split XInput, SDL and gilrs all use.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rumble {
+    pub strong: f32,
+    pub weak: f32,
+}
+
+impl Rumble {
+    pub fn is_off(&self) -> bool {
+        self.strong <= 0.0 && self.weak <= 0.0
+    }
+}
+
+/// Timed rumble effects per gamepad, played through gilrs force feedback.
+///
+/// Effects started with `ctx.gamepad_rumble` overlap (each motor runs at the strongest
+/// active effect) and expire on their own. `sync` sends the combined level of each gamepad
+/// to the device as one continuous effect, rebuilt only when that level changes.
+#[derive(Default)]
+pub(crate) struct RumbleState {
+    // `(gamepad, levels, seconds left)`.
+    effects: Vec<(usize, Rumble, f32)>,
+    // Opened by the first `sync`; the inner `None` if gilrs can't run on this platform.
+    gilrs: Option<Option<Gilrs>>,
+    // `(gamepad, levels, effect)` playing on each device.
+    playing: Vec<(usize, Rumble, Effect)>,
+}
+
+impl RumbleState {
+    pub fn start(&mut self, gamepad: usize, rumble: Rumble, duration: f32) {
+        let rumble = Rumble {
+            strong: rumble.strong.clamp(0.0, 1.0),
+            weak: rumble.weak.clamp(0.0, 1.0),
+        };
+        if duration > 0.0 && !rumble.is_off() {
+            self.effects.push((gamepad, rumble, duration));
+        }
+    }
+
+    pub fn stop(&mut self, gamepad: usize) {
+        self.effects.retain(|e| e.0 != gamepad);
+    }
+
+    /// Age effects by `dt` seconds, dropping those that ran out.
+    pub fn update(&mut self, dt: f32) {
+        for effect in &mut self.effects {
+            effect.2 -= dt;
+        }
+        self.effects.retain(|e| e.2 > 0.0);
+    }
+
+    /// Motor speeds for `gamepad` right now.
+    pub fn level(&self, gamepad: usize) -> Rumble {
+        self.effects
+            .iter()
+            .filter(|e| e.0 == gamepad)
+            .fold(Rumble::default(), |acc, e| Rumble {
+                strong: acc.strong.max(e.1.strong),
+                weak: acc.weak.max(e.1.weak),
+            })
+    }
+
+    /// Play the current levels on the gamepads, opening gilrs on first use.
+    pub fn sync(&mut self) {
+        let mut pads: Vec<usize> = self.effects.iter().map(|e| e.0).collect();
+        pads.extend(self.playing.iter().map(|p| p.0));
+        pads.sort_unstable();
+        pads.dedup();
+        let targets: Vec<(usize, Rumble)> = pads.into_iter().map(|p| (p, self.level(p))).collect();
+
+        let gilrs = self.gilrs.get_or_insert_with(|| match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(err) => {
+                log_warn!("gamepad rumble unavailable: {}", err);
+                None
+            }
+        });
+        let Some(gilrs) = gilrs else {
+            return;
+        };
+        // Keeps gilrs' list of connected gamepads current.
+        while gilrs.next_event().is_some() {}
+
+        for (pad, level) in targets {
+            if let Some(i) = self.playing.iter().position(|p| p.0 == pad) {
+                if self.playing[i].1 == level {
+                    continue;
+                }
+                let _ = self.playing.swap_remove(i).2.stop();
+            }
+            if level.is_off() {
+                continue;
+            }
+            match play(gilrs, pad, level) {
+                Ok(Some(effect)) => self.playing.push((pad, level, effect)),
+                Ok(None) => {}
+                Err(err) => log_warn!("gamepad {} rumble failed: {}", pad, err),
+            }
+        }
+    }
+}
+
+/// Start `level` on gamepad `pad` until the returned effect is stopped or dropped. `None`
+/// when no connected gamepad with force feedback has that id.
+fn play(gilrs: &mut Gilrs, pad: usize, level: Rumble) -> Result<Option<Effect>, gilrs::ff::Error> {
+    let Some(id) = gilrs
+        .gamepads()
+        .find(|(id, gamepad)| usize::from(*id) == pad && gamepad.is_ff_supported())
+        .map(|(id, _)| id)
+    else {
+        return Ok(None);
+    };
+    // A one-second pulse repeated until stopped.
+    let scheduling = Replay {
+        play_for: Ticks::from_ms(1000),
+        ..Replay::default()
+    };
+    let motor = |speed: f32| (speed * u16::MAX as f32) as u16;
+    let effect = EffectBuilder::new()
+        .add_effect(BaseEffect {
+            kind: BaseEffectType::Strong {
+                magnitude: motor(level.strong),
+            },
+            scheduling,
+            envelope: Default::default(),
+        })
+        .add_effect(BaseEffect {
+            kind: BaseEffectType::Weak {
+                magnitude: motor(level.weak),
+            },
+            scheduling,
+            envelope: Default::default(),
+        })
+        .gamepads(&[id])
+        .finish(gilrs)?;
+    effect.play()?;
+    Ok(Some(effect))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_effects_take_the_strongest_motor_until_they_expire() {
+        let mut state = RumbleState::default();
+        let hit = Rumble {
+            strong: 1.0,
+            weak: 0.2,
+        };
+        let engine = Rumble {
+            strong: 0.3,
+            weak: 0.5,
+        };
+        state.start(0, hit, 0.1);
+        state.start(0, engine, 1.0);
+        state.start(1, engine, 0.0);
+        assert_eq!(
+            state.level(0),
+            Rumble {
+                strong: 1.0,
+                weak: 0.5
+            }
+        );
+        assert!(state.level(1).is_off());
+
+        state.update(0.2);
+        assert_eq!(state.level(0), engine);
+        state.stop(0);
+        assert!(state.level(0).is_off());
+    }
+}
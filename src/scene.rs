@@ -0,0 +1,353 @@
+//! Retained sprite hierarchy for UI panels, rigs and layered backgrounds.
+//!
+//! Nodes hold a local transform relative to their parent; world transforms are cached
+//! and only recomputed for subtrees whose transform changed. `ctx.draw_scene(&mut scene)`
+//! draws every visible sprite ordered by `z` (tree order breaks ties), so runs of sprites
+//! sharing a texture batch into a single draw.
+
+use crate::renderer::TextureId;
+use crate::{Color, Rect};
+use glam::{Mat4, Quat, Vec3};
+
+/// Handle to a node in a `SceneGraph`.
+///
+/// Slots are reused after `remove`, but each reuse bumps the slot's generation, so an id
+/// held past its node's removal stays dead instead of aliasing the new node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    index: u32,
+    generation: u32,
+}
+
+/// Position, rotation (radians) and scale relative to the parent node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform2D {
+    pub position: (f32, f32),
+    pub rotation: f32,
+    pub scale: (f32, f32),
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self {
+            position: (0.0, 0.0),
+            rotation: 0.0,
+            scale: (1.0, 1.0),
+        }
+    }
+}
+
+impl Transform2D {
+    pub fn at(x: f32, y: f32) -> Self {
+        Self {
+            position: (x, y),
+            ..Self::default()
+        }
+    }
+
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(
+            Vec3::new(self.scale.0, self.scale.1, 1.0),
+            Quat::from_rotation_z(self.rotation),
+            Vec3::new(self.position.0, self.position.1, 0.0),
+        )
+    }
+}
+
+/// What a node draws. A node without a texture only groups and transforms its children.
+#[derive(Clone, Copy, Debug)]
+pub struct SpriteNode {
+    pub texture: Option<TextureId>,
+    /// Region of the texture in pixels; `None` draws the whole texture.
+    pub src: Option<Rect>,
+    /// Drawn rectangle in the node's local space (e.g. `x: -w/2` to center on the node).
+    pub rect: Rect,
+    pub tint: Color,
+    /// Draw order across the whole scene; higher is drawn later (on top).
+    pub z: i32,
+    /// Hidden nodes hide their children too.
+    pub visible: bool,
+}
+
+impl SpriteNode {
+    /// An empty group node.
+    pub fn group() -> Self {
+        Self {
+            texture: None,
+            src: None,
+            rect: Rect {
+                x: 0.0,
+                y: 0.0,
+                w: 0.0,
+                h: 0.0,
+            },
+            tint: Color::WHITE,
+            z: 0,
+            visible: true,
+        }
+    }
+
+    /// A sprite drawing `texture` into `rect` (local space).
+    pub fn sprite(texture: TextureId, rect: Rect) -> Self {
+        Self {
+            texture: Some(texture),
+            rect,
+            ..Self::group()
+        }
+    }
+}
+
+struct Node {
+    sprite: SpriteNode,
+    local: Transform2D,
+    world: Mat4,
+    dirty: bool,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+#[derive(Default)]
+struct Slot {
+    generation: u32,
+    node: Option<Node>,
+}
+
+/// A tree of `SpriteNode`s with cached world transforms.
+#[derive(Default)]
+pub struct SceneGraph {
+    slots: Vec<Slot>,
+    // Indices of empty slots, reused before the slot list grows.
+    free: Vec<u32>,
+    roots: Vec<NodeId>,
+    // Reused by `draw_list` so drawing a scene doesn't allocate every frame.
+    order: Vec<(i32, usize, NodeId)>,
+}
+
+impl SceneGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a node under `parent` (or as a root), returning its id.
+    pub fn add(
+        &mut self,
+        parent: Option<NodeId>,
+        sprite: SpriteNode,
+        transform: Transform2D,
+    ) -> NodeId {
+        let node = Node {
+            sprite,
+            local: transform,
+            world: Mat4::IDENTITY,
+            dirty: true,
+            parent: parent.filter(|p| self.node(*p).is_some()),
+            children: Vec::new(),
+        };
+        let index = self.free.pop().unwrap_or_else(|| {
+            self.slots.push(Slot::default());
+            self.slots.len() as u32 - 1
+        });
+        let slot = &mut self.slots[index as usize];
+        slot.node = Some(node);
+        let id = NodeId {
+            index,
+            generation: slot.generation,
+        };
+        match self.node(id).and_then(|n| n.parent) {
+            Some(parent) => self.node_mut(parent).unwrap().children.push(id),
+            None => self.roots.push(id),
+        }
+        id
+    }
+
+    /// Remove `id` and all of its descendants.
+    pub fn remove(&mut self, id: NodeId) {
+        let Some(node) = self.take(id) else {
+            return;
+        };
+        match node.parent.and_then(|p| self.node_mut(p)) {
+            Some(parent) => parent.children.retain(|c| *c != id),
+            None => self.roots.retain(|r| *r != id),
+        }
+        let mut stack = node.children;
+        while let Some(child) = stack.pop() {
+            if let Some(child) = self.take(child) {
+                stack.extend(child.children);
+            }
+        }
+    }
+
+    pub fn sprite(&self, id: NodeId) -> Option<&SpriteNode> {
+        self.node(id).map(|n| &n.sprite)
+    }
+
+    pub fn sprite_mut(&mut self, id: NodeId) -> Option<&mut SpriteNode> {
+        self.node_mut(id).map(|n| &mut n.sprite)
+    }
+
+    pub fn transform(&self, id: NodeId) -> Option<Transform2D> {
+        self.node(id).map(|n| n.local)
+    }
+
+    /// Replace the local transform of `id`; its subtree is recomputed on the next update.
+    pub fn set_transform(&mut self, id: NodeId, transform: Transform2D) {
+        if let Some(node) = self.node_mut(id) {
+            node.local = transform;
+            node.dirty = true;
+        }
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        self.node(id).map_or(&[], |n| &n.children)
+    }
+
+    /// World transform of `id` as of the last `update` (or draw).
+    pub fn world_transform(&self, id: NodeId) -> Option<Mat4> {
+        self.node(id).map(|n| n.world)
+    }
+
+    /// Recompute world transforms of nodes whose own or an ancestor's transform changed.
+    pub fn update(&mut self) {
+        let mut stack: Vec<(NodeId, Mat4, bool)> = self
+            .roots
+            .iter()
+            .rev()
+            .map(|&r| (r, Mat4::IDENTITY, false))
+            .collect();
+        while let Some((id, parent_world, parent_dirty)) = stack.pop() {
+            let Some(node) = self.node_mut(id) else {
+                continue;
+            };
+            let dirty = node.dirty || parent_dirty;
+            if dirty {
+                node.world = parent_world * node.local.matrix();
+                node.dirty = false;
+            }
+            let world = node.world;
+            stack.extend(node.children.iter().rev().map(|&c| (c, world, dirty)));
+        }
+    }
+
+    /// Update transforms and return visible textured nodes in draw order.
+    pub(crate) fn draw_list(&mut self) -> impl Iterator<Item = (Mat4, &SpriteNode)> {
+        self.update();
+
+        self.order.clear();
+        let mut stack: Vec<NodeId> = self.roots.iter().rev().copied().collect();
+        while let Some(id) = stack.pop() {
+            let Some(node) = live_node(&self.slots, id) else {
+                continue;
+            };
+            if !node.sprite.visible {
+                continue;
+            }
+            if node.sprite.texture.is_some() {
+                self.order.push((node.sprite.z, self.order.len(), id));
+            }
+            stack.extend(node.children.iter().rev());
+        }
+        self.order.sort_unstable_by_key(|&(z, seq, _)| (z, seq));
+
+        self.order.iter().filter_map(|&(_, _, id)| {
+            let node = self.slots[id.index as usize].node.as_ref()?;
+            Some((node.world, &node.sprite))
+        })
+    }
+
+    fn node(&self, id: NodeId) -> Option<&Node> {
+        live_node(&self.slots, id)
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        let slot = self.slots.get_mut(id.index as usize)?;
+        slot.node
+            .as_mut()
+            .filter(|_| slot.generation == id.generation)
+    }
+
+    /// Empty the slot of `id`, retiring its generation and freeing it for reuse.
+    fn take(&mut self, id: NodeId) -> Option<Node> {
+        let slot = self.slots.get_mut(id.index as usize)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+        let node = slot.node.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        Some(node)
+    }
+}
+
+fn live_node(slots: &[Slot], id: NodeId) -> Option<&Node> {
+    let slot = slots.get(id.index as usize)?;
+    slot.node
+        .as_ref()
+        .filter(|_| slot.generation == id.generation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUAD: Rect = Rect {
+        x: 0.0,
+        y: 0.0,
+        w: 8.0,
+        h: 8.0,
+    };
+
+    #[test]
+    fn child_transforms_follow_parent_changes() {
+        let mut scene = SceneGraph::new();
+        let root = scene.add(None, SpriteNode::group(), Transform2D::at(100.0, 0.0));
+        let child = scene.add(
+            Some(root),
+            SpriteNode::sprite(TextureId(0), QUAD),
+            Transform2D::at(10.0, 5.0),
+        );
+        scene.update();
+        let pos = scene.world_transform(child).unwrap().w_axis;
+        assert_eq!((pos.x, pos.y), (110.0, 5.0));
+
+        scene.set_transform(root, Transform2D::at(0.0, 50.0));
+        scene.update();
+        let pos = scene.world_transform(child).unwrap().w_axis;
+        assert_eq!((pos.x, pos.y), (10.0, 55.0));
+
+        scene.remove(root);
+        assert!(scene.sprite(child).is_none());
+    }
+
+    #[test]
+    fn draw_order_sorts_by_z_then_tree_order_and_skips_hidden() {
+        let mut scene = SceneGraph::new();
+        let sprite = |z| SpriteNode {
+            z,
+            ..SpriteNode::sprite(TextureId(z as u32), QUAD)
+        };
+        scene.add(None, sprite(1), Transform2D::default());
+        let front = scene.add(None, sprite(0), Transform2D::default());
+        let hidden = scene.add(Some(front), sprite(0), Transform2D::default());
+        scene.sprite_mut(hidden).unwrap().visible = false;
+
+        let textures: Vec<u32> = scene
+            .draw_list()
+            .map(|(_, s)| s.texture.unwrap().0)
+            .collect();
+        assert_eq!(textures, [0, 1]);
+    }
+
+    #[test]
+    fn removed_ids_stay_dead_after_slot_reuse() {
+        let mut scene = SceneGraph::new();
+        let old = scene.add(None, SpriteNode::group(), Transform2D::at(1.0, 0.0));
+        scene.remove(old);
+        let new = scene.add(None, SpriteNode::group(), Transform2D::at(2.0, 0.0));
+
+        assert_eq!(new.index, old.index);
+        assert!(scene.transform(old).is_none());
+        scene.set_transform(old, Transform2D::at(9.0, 9.0));
+        scene.remove(old);
+        assert_eq!(scene.transform(new), Some(Transform2D::at(2.0, 0.0)));
+    }
+}
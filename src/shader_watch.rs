@@ -0,0 +1,65 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Polls a WGSL file's modification time for shader hot reload.
+///
+/// Polling once a frame is a single `stat` call, which avoids a file-watcher dependency and
+/// works the same on every desktop platform.
+pub(crate) struct ShaderWatch {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ShaderWatch {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            modified: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The file's source if it changed since the last successful poll (always on the first).
+    ///
+    /// A file that can't be read (e.g. mid-save) is retried on the next poll.
+    pub fn poll(&mut self) -> Option<String> {
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .ok()?;
+        if self.modified == Some(modified) {
+            return None;
+        }
+        let source = std::fs::read_to_string(&self.path).ok()?;
+        self.modified = Some(modified);
+        Some(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_source_once_per_change() {
+        let path = std::env::temp_dir().join("libforge-shader-watch.wgsl");
+        std::fs::write(&path, "// v1").unwrap();
+        let mut watch = ShaderWatch::new(&path);
+        assert_eq!(watch.poll().as_deref(), Some("// v1"));
+        assert_eq!(watch.poll(), None);
+
+        // Force a different timestamp even on filesystems with coarse mtimes.
+        std::fs::write(&path, "// v2").unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(watch.poll().as_deref(), Some("// v2"));
+        assert_eq!(watch.poll(), None);
+    }
+}
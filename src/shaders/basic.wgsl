@@ -10,28 +10,307 @@ struct VertexOutput {
     @location(1) v_color: vec4<f32>,
 };
 
+struct DrawParams {
+    // Projection * camera view, recorded per batch so mid-frame resizes and camera changes
+    // only affect the draws issued after them
+    view_proj: mat4x4<f32>,
+    // Extra model transform applied on the GPU (identity except for retained meshes)
+    model: mat4x4<f32>,
+    // Multiplied into vertex colors (white except for retained meshes)
+    tint: vec4<f32>,
+    // x = hue shift (radians), y = saturation multiplier, z = brightness multiplier,
+    // w = 1 when vertex colors and tints are sRGB-encoded and must be decoded to linear
+    color_adjust: vec4<f32>,
+    // Backgrounds: x = pattern kind, y = seed (u32 bits), z = cell size in pixels
+    effect: vec4<f32>,
+};
+
 @group(0) @binding(0)
-var<uniform> u_transform: mat4x4<f32>;
+var<uniform> u_params: DrawParams;
 
-@vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
+fn srgb_to_linear(c: vec4<f32>) -> vec4<f32> {
+    let low = c.rgb / 12.92;
+    let high = pow((c.rgb + 0.055) / 1.055, vec3<f32>(2.4));
+    return vec4<f32>(select(high, low, c.rgb <= vec3<f32>(0.04045)), c.a);
+}
+
+// Vertex color times tint, decoded to linear when colors are sRGB-encoded.
+fn vertex_color(color: vec4<f32>) -> vec4<f32> {
+    if (u_params.color_adjust.w > 0.5) {
+        return srgb_to_linear(color) * srgb_to_linear(u_params.tint);
+    }
+    return color * u_params.tint;
+}
+
+fn transform_vertex(in: VertexInput) -> VertexOutput {
     var out: VertexOutput;
-    out.position = u_transform * vec4<f32>(in.pos.x, in.pos.y, 0.0, 1.0);
+    out.position = u_params.view_proj * u_params.model * vec4<f32>(in.pos.x, in.pos.y, 0.0, 1.0);
     out.v_uv = in.uv;
-    out.v_color = in.color;
+    out.v_color = vertex_color(in.color);
     return out;
 }
 
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    return transform_vertex(in);
+}
+
+// Depth-sorted draws: per-vertex depth in 0..1 (larger is nearer) from a second vertex stream.
+@vertex
+fn vs_sorted(in: VertexInput, @location(3) depth: f32) -> VertexOutput {
+    var out = transform_vertex(in);
+    out.position.z = depth * out.position.w;
+    return out;
+}
+
+// Depth-sorted pipelines discard fragments below this alpha so transparent texels don't
+// write depth and hide what is behind them. Unsorted pipelines keep the default (never).
+override alpha_cutoff: f32 = -1.0;
+
+fn alpha_test(c: vec4<f32>) -> vec4<f32> {
+    if (c.a < alpha_cutoff) {
+        discard;
+    }
+    return c;
+}
+
 @fragment
 fn fs_color(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.v_color;
+    return alpha_test(in.v_color);
 }
 
 @group(1) @binding(0) var tex: texture_2d<f32>;
 @group(1) @binding(1) var samp: sampler;
 
+fn rgb_to_hsv(c: vec3<f32>) -> vec3<f32> {
+    let k = vec4<f32>(0.0, -1.0 / 3.0, 2.0 / 3.0, -1.0);
+    let p = mix(vec4<f32>(c.bg, k.wz), vec4<f32>(c.gb, k.xy), step(c.b, c.g));
+    let q = mix(vec4<f32>(p.xyw, c.r), vec4<f32>(c.r, p.yzx), step(p.x, c.r));
+    let d = q.x - min(q.w, q.y);
+    let e = 1.0e-10;
+    return vec3<f32>(abs(q.z + (q.w - q.y) / (6.0 * d + e)), d / (q.x + e), q.x);
+}
+
+fn hsv_to_rgb(c: vec3<f32>) -> vec3<f32> {
+    let k = vec4<f32>(1.0, 2.0 / 3.0, 1.0 / 3.0, 3.0);
+    let p = abs(fract(c.xxx + k.xyz) * 6.0 - k.www);
+    return c.z * mix(k.xxx, clamp(p - k.xxx, vec3<f32>(0.0), vec3<f32>(1.0)), c.y);
+}
+
+fn apply_color_adjust(rgb: vec3<f32>) -> vec3<f32> {
+    let adj = u_params.color_adjust;
+    var hsv = rgb_to_hsv(rgb);
+    hsv.x = fract(hsv.x + adj.x / 6.28318530718);
+    hsv.y = clamp(hsv.y * adj.y, 0.0, 1.0);
+    hsv.z = hsv.z * adj.z;
+    return hsv_to_rgb(hsv);
+}
+
 @fragment
 fn fs_texture(in: VertexOutput) -> @location(0) vec4<f32> {
     let t = textureSample(tex, samp, in.v_uv);
-    return t * in.v_color;
+    return alpha_test(vec4<f32>(apply_color_adjust(t.rgb), t.a) * in.v_color);
+}
+
+// ---------------------------------------------------------------------------
+// SDF shapes: one quad per shape, edges evaluated per pixel
+// ---------------------------------------------------------------------------
+
+struct ShapeInput {
+    @location(0) pos: vec2<f32>,
+    // Position relative to the shape center, in shape units
+    @location(1) local: vec2<f32>,
+    // xy = half extents, z = corner radius (circles and capsules are fully rounded boxes)
+    @location(2) shape: vec3<f32>,
+    @location(3) color: vec4<f32>,
+};
+
+struct ShapeOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) local: vec2<f32>,
+    @location(1) shape: vec3<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+@vertex
+fn vs_shape(in: ShapeInput) -> ShapeOutput {
+    var out: ShapeOutput;
+    out.position = u_params.view_proj * u_params.model * vec4<f32>(in.pos.x, in.pos.y, 0.0, 1.0);
+    out.local = in.local;
+    out.shape = in.shape;
+    out.color = vertex_color(in.color);
+    return out;
+}
+
+@fragment
+fn fs_shape(in: ShapeOutput) -> @location(0) vec4<f32> {
+    let half = in.shape.xy;
+    let r = min(in.shape.z, min(half.x, half.y));
+    let q = abs(in.local) - half + vec2<f32>(r);
+    let d = length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - r;
+    // One pixel of falloff at any zoom level.
+    let aa = max(fwidth(d), 1.0e-4);
+    let coverage = clamp(0.5 - d / aa, 0.0, 1.0);
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
+
+// Premultiplied-alpha texels: the tint's alpha scales color too, so output stays premultiplied.
+// Scaling a color keeps its hue and saturation, so color adjust works on premultiplied rgb.
+@fragment
+fn fs_texture_premultiplied(in: VertexOutput) -> @location(0) vec4<f32> {
+    let t = textureSample(tex, samp, in.v_uv);
+    return alpha_test(vec4<f32>(apply_color_adjust(t.rgb) * in.v_color.rgb, t.a) * in.v_color.a);
+}
+
+// Soft round particle: UVs span -1..1, alpha falls off smoothly towards the edge.
+fn particle_color(in: VertexOutput) -> vec4<f32> {
+    let d = length(in.v_uv);
+    let falloff = 1.0 - smoothstep(0.0, 1.0, d);
+    return vec4<f32>(in.v_color.rgb, in.v_color.a * falloff * falloff);
+}
+
+@fragment
+fn fs_particle(in: VertexOutput) -> @location(0) vec4<f32> {
+    return particle_color(in);
+}
+
+// ---------------------------------------------------------------------------
+// Soft particles: faded where depth-sorted geometry is close to or in front of them
+// ---------------------------------------------------------------------------
+
+// Depth of the frame drawn so far, bound at index 1 in place of the texture bind group;
+// single- or multisampled to match the pass. The bindings follow `tex` and `samp` so no
+// two variables share a slot.
+@group(1) @binding(2) var scene_depth: texture_depth_2d;
+@group(1) @binding(3) var scene_depth_ms: texture_depth_multisampled_2d;
+
+// Matches `DEPTH_SORT_SCALE` in the renderer.
+const DEPTH_SORT_SCALE: f32 = 4096.0;
+
+struct SoftParticleOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) v_uv: vec2<f32>,
+    @location(1) v_color: vec4<f32>,
+    @location(2) depth: f32,
+};
+
+@vertex
+fn vs_soft_particle(in: VertexInput, @location(3) depth: f32) -> SoftParticleOutput {
+    let v = transform_vertex(in);
+    return SoftParticleOutput(v.position, v.v_uv, v.v_color, depth);
+}
+
+// A stored depth back to its sort key (y units); the inverse of the renderer's `sort_depth`.
+fn sort_key(depth: f32) -> f32 {
+    let s = 2.0 * depth - 1.0;
+    return DEPTH_SORT_SCALE * s / max(1.0 - abs(s), 1e-7);
+}
+
+// Fade out over `effect.x` sort-key units as the geometry at this pixel comes within that
+// distance in front of the particle; hidden outright behind it when `effect.x` is 0.
+fn soft_particle(in: SoftParticleOutput, scene: f32) -> vec4<f32> {
+    let c = particle_color(VertexOutput(in.position, in.v_uv, in.v_color));
+    // Particles recorded without depth sorting sit at depth 1 and are never faded.
+    if (in.depth >= 1.0) {
+        return c;
+    }
+    let gap = sort_key(in.depth) - sort_key(scene);
+    let softness = u_params.effect.x;
+    var fade = step(0.0, gap);
+    if (softness > 0.0) {
+        fade = clamp(gap / softness, 0.0, 1.0);
+    }
+    return vec4<f32>(c.rgb, c.a * fade);
+}
+
+@fragment
+fn fs_soft_particle(in: SoftParticleOutput) -> @location(0) vec4<f32> {
+    return soft_particle(in, textureLoad(scene_depth, vec2<i32>(in.position.xy), 0));
+}
+
+@fragment
+fn fs_soft_particle_ms(in: SoftParticleOutput) -> @location(0) vec4<f32> {
+    return soft_particle(in, textureLoad(scene_depth_ms, vec2<i32>(in.position.xy), 0));
+}
+
+// ---------------------------------------------------------------------------
+// Procedural backgrounds
+// ---------------------------------------------------------------------------
+
+fn hash_u32(x: u32) -> u32 {
+    // PCG-style integer hash.
+    let state = x * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn hash_cell(cell: vec2<i32>, seed: u32) -> u32 {
+    return hash_u32(bitcast<u32>(cell.x) ^ hash_u32(bitcast<u32>(cell.y) ^ hash_u32(seed)));
+}
+
+fn rand01(h: u32) -> f32 {
+    return f32(h) / 4294967295.0;
+}
+
+fn value_noise(p: vec2<f32>, seed: u32) -> f32 {
+    let i = vec2<i32>(floor(p));
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let a = rand01(hash_cell(i, seed));
+    let b = rand01(hash_cell(i + vec2<i32>(1, 0), seed));
+    let c = rand01(hash_cell(i + vec2<i32>(0, 1), seed));
+    let d = rand01(hash_cell(i + vec2<i32>(1, 1), seed));
+    return mix(mix(a, b, u.x), mix(c, d, u.x), u.y);
+}
+
+fn fbm(p: vec2<f32>, seed: u32) -> f32 {
+    var sum = 0.0;
+    var amp = 0.5;
+    var q = p;
+    for (var i = 0u; i < 4u; i = i + 1u) {
+        sum = sum + amp * value_noise(q, seed + i);
+        q = q * 2.0;
+        amp = amp * 0.5;
+    }
+    return sum / 0.9375;
+}
+
+fn starfield(p: vec2<f32>, seed: u32) -> f32 {
+    let h = hash_cell(vec2<i32>(floor(p)), seed);
+    if (rand01(h) > 0.35) {
+        return 0.0;
+    }
+    let star = vec2<f32>(rand01(hash_u32(h)), rand01(hash_u32(h ^ 0x9e3779b9u)));
+    let size = 0.03 + 0.06 * rand01(hash_u32(h + 1u));
+    let d = length(fract(p) - star);
+    let glow = 1.0 - smoothstep(0.0, size, d);
+    return glow * (0.4 + 0.6 * rand01(hash_u32(h + 2u)));
+}
+
+fn hex_grid(p: vec2<f32>) -> f32 {
+    let r = vec2<f32>(1.0, 1.7320508);
+    let h = r * 0.5;
+    let a = p - r * floor(p / r) - h;
+    let b = (p - h) - r * floor((p - h) / r) - h;
+    let g = select(b, a, dot(a, a) < dot(b, b));
+    let q = abs(g);
+    let edge = 0.5 - max(dot(q, normalize(r)), q.x);
+    return 1.0 - smoothstep(0.0, 0.05, edge);
+}
+
+@fragment
+fn fs_background(in: VertexOutput) -> @location(0) vec4<f32> {
+    let kind = i32(u_params.effect.x);
+    let seed = bitcast<u32>(u_params.effect.y);
+    let p = in.v_uv / u_params.effect.z;
+
+    var intensity = 0.0;
+    if (kind == 0) {
+        intensity = starfield(p, seed);
+    } else if (kind == 1) {
+        intensity = fbm(p, seed);
+    } else {
+        intensity = hex_grid(p);
+    }
+    return vec4<f32>(in.v_color.rgb, in.v_color.a * intensity);
 }
@@ -0,0 +1,103 @@
+// Full-screen pass over the finished frame: color grading, then scene transitions.
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// One triangle covering the screen; no vertex buffer needed.
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var scene: texture_2d<f32>;
+@group(0) @binding(1) var scene_sampler: sampler;
+
+// Color lookup table as a strip of `n` tiles of n x n texels: red across a tile, green down
+// it, blue from tile to tile. Stored sRGB, so samples come back linear.
+@group(1) @binding(0) var lut: texture_2d<f32>;
+@group(1) @binding(1) var lut_sampler: sampler;
+
+struct PostParams {
+    // Target size in pixels.
+    size: vec2<f32>,
+    // 1 when `lut` holds a color LUT (otherwise it is a placeholder).
+    lut_enabled: f32,
+    // 0 = none, 1 = fade, 2 = circle wipe, 3 = pixelate.
+    transition: f32,
+    // How much of the screen the transition hides, 0..1.
+    coverage: f32,
+};
+
+@group(2) @binding(0) var<uniform> params: PostParams;
+
+// Whether the target (and `scene`) is an sRGB format, i.e. `scene` samples are linear.
+override srgb_target: bool = true;
+
+fn linear_to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let c = max(color, vec3<f32>(0.0));
+    let low = c * 12.92;
+    let high = 1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, c <= vec3<f32>(0.0031308));
+}
+
+// Look up an sRGB-encoded color; the GPU filters red and green, blue is blended by hand
+// between the two nearest tiles.
+fn grade(encoded: vec3<f32>) -> vec3<f32> {
+    let n = f32(textureDimensions(lut).y);
+    let c = clamp(encoded, vec3<f32>(0.0), vec3<f32>(1.0)) * (n - 1.0);
+    let tile = floor(c.b);
+    let next = min(tile + 1.0, n - 1.0);
+    // Texel centers, so filtering never bleeds into the neighbouring tile.
+    let texel = c.rg + 0.5;
+    let size = vec2<f32>(n * n, n);
+    let a = textureSampleLevel(lut, lut_sampler, vec2<f32>(tile * n + texel.x, texel.y) / size, 0.0);
+    let b = textureSampleLevel(lut, lut_sampler, vec2<f32>(next * n + texel.x, texel.y) / size, 0.0);
+    return mix(a.rgb, b.rgb, c.b - tile);
+}
+
+// Graded color in the target's encoding (linear for sRGB targets).
+fn graded(color: vec3<f32>) -> vec3<f32> {
+    if (srgb_target) {
+        return grade(linear_to_srgb(color));
+    }
+    return linear_to_srgb(grade(color));
+}
+
+// Largest pixelate block, in pixels, reached when the screen is fully covered.
+const MAX_BLOCK: f32 = 48.0;
+
+@fragment
+fn fs_post(in: VertexOutput) -> @location(0) vec4<f32> {
+    let kind = i32(params.transition);
+    let coverage = params.coverage;
+
+    var uv = in.uv;
+    if (kind == 3) {
+        let block = max(1.0, floor(coverage * MAX_BLOCK));
+        uv = (floor(uv * params.size / block) + 0.5) * block / params.size;
+    }
+    var color = textureSampleLevel(scene, scene_sampler, uv, 0.0);
+    if (params.lut_enabled > 0.5) {
+        color = vec4<f32>(graded(color.rgb), color.a);
+    }
+
+    // Share of black over the picture.
+    var hidden = 0.0;
+    if (kind == 1) {
+        hidden = coverage;
+    } else if (kind == 2) {
+        // The iris closes on the center and reaches the far corners at zero coverage.
+        let offset = (in.uv - 0.5) * params.size;
+        let radius = (1.0 - coverage) * length(params.size * 0.5);
+        hidden = clamp(length(offset) - radius + 0.5, 0.0, 1.0);
+    } else if (kind == 3) {
+        hidden = smoothstep(0.5, 1.0, coverage);
+    }
+    return vec4<f32>(color.rgb * (1.0 - hidden), mix(color.a, 1.0, hidden));
+}
@@ -1,12 +1,44 @@
 use crate::Rect;
 
+/// Frames of a sprite sheet played back at a fixed rate, looping.
+///
+/// Tags mark frames that something should happen on, such as a footstep sound or a
+/// hitbox becoming active. Ask which tagged frames started since the last update:
+///
+/// ```ignore
+/// let walk = SpriteAnimation::new(frames, 12.0)
+///     .with_tag(1, "footstep")
+///     .with_tag(4, "footstep");
+///
+/// for tag in walk.events_between(prev_time, time) {
+///     if tag == "footstep" {
+///         audio.play(step_sound);
+///     }
+/// }
+/// ```
 #[derive(Clone)]
 pub struct SpriteAnimation {
     pub frames: Vec<Rect>,
     pub fps: f32,
+    /// Frame index and tag name pairs; a frame may carry several tags.
+    pub tags: Vec<(usize, String)>,
 }
 
 impl SpriteAnimation {
+    pub fn new(frames: Vec<Rect>, fps: f32) -> Self {
+        Self {
+            frames,
+            fps,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Tag `frame` with `name`.
+    pub fn with_tag(mut self, frame: usize, name: impl Into<String>) -> Self {
+        self.tags.push((frame, name.into()));
+        self
+    }
+
     pub fn frame_at_time(&self, time: f32) -> Rect {
         if self.frames.is_empty() {
             return Rect {
@@ -21,4 +53,84 @@ impl SpriteAnimation {
         let frame = ((time * self.fps) as usize) % frame_count;
         self.frames[frame]
     }
+
+    /// Tags of the frames that start at or after `prev_time` and before `now`, in play
+    /// order. Calling it with each update's previous and current time reports every tag
+    /// once per loop, including frame 0's on the first update from time 0.
+    ///
+    /// A gap longer than the whole animation reports each frame once rather than replaying
+    /// the missed loops; going back in time reports nothing.
+    pub fn events_between(&self, prev_time: f32, now: f32) -> Vec<&str> {
+        let count = self.frames.len();
+        if count == 0 || self.tags.is_empty() || self.fps <= 0.0 || now <= prev_time {
+            return Vec::new();
+        }
+        // Frame k starts at k / fps.
+        let first = (prev_time * self.fps).ceil() as i64;
+        let end = (now * self.fps).ceil() as i64;
+        let first = first.max(end - count as i64);
+
+        let mut events = Vec::new();
+        for k in first..end {
+            let frame = k.rem_euclid(count as i64) as usize;
+            events.extend(
+                self.tags
+                    .iter()
+                    .filter(|(tagged, _)| *tagged == frame)
+                    .map(|(_, name)| name.as_str()),
+            );
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn walk() -> SpriteAnimation {
+        let frame = Rect {
+            x: 0.0,
+            y: 0.0,
+            w: 16.0,
+            h: 16.0,
+        };
+        SpriteAnimation::new(vec![frame; 4], 10.0)
+            .with_tag(0, "start")
+            .with_tag(2, "footstep")
+            .with_tag(2, "hitbox")
+    }
+
+    #[test]
+    fn tags_fire_once_per_loop_as_time_advances() {
+        let anim = walk();
+        // Updates every 0.05s (half a frame) for one and a half loops.
+        let mut fired = Vec::new();
+        for step in 0..12 {
+            let (prev, now) = (step as f32 * 0.05, (step + 1) as f32 * 0.05);
+            fired.extend(anim.events_between(prev, now));
+        }
+        assert_eq!(fired, ["start", "footstep", "hitbox", "start"]);
+
+        // Time 0.5 starts frame 1 of the second loop; frame 2 starts at 0.6.
+        assert!(anim.events_between(0.5, 0.59).is_empty());
+        assert_eq!(anim.events_between(0.5, 0.61), ["footstep", "hitbox"]);
+    }
+
+    #[test]
+    fn long_gaps_and_rewinds() {
+        let anim = walk();
+        // Ten loops in one update still report each tag once.
+        assert_eq!(
+            anim.events_between(0.05, 4.05),
+            ["footstep", "hitbox", "start"]
+        );
+        assert!(anim.events_between(1.0, 0.5).is_empty());
+        assert!(
+            SpriteAnimation::new(Vec::new(), 10.0)
+                .with_tag(0, "x")
+                .events_between(0.0, 1.0)
+                .is_empty()
+        );
+    }
 }
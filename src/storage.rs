@@ -0,0 +1,218 @@
+//! Save slots and settings files in the platform's per-user data directory.
+//!
+//! ```ignore
+//! let storage = Storage::new("my_game")?;
+//! storage.save_json("settings", &settings)?; // `serde` feature
+//! let settings: Settings = storage.load_json("settings")?.unwrap_or_default();
+//! ```
+//!
+//! Every write goes to a temporary file that is flushed to disk and then renamed over the
+//! slot, so a crash or power loss mid-save leaves the previous save intact.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Error from reading or writing a save slot.
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("storage I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("no per-user data directory on this platform")]
+    NoDataDir,
+
+    #[error("invalid slot name {0:?} (use letters, digits, '-' and '_')")]
+    InvalidSlot(String),
+
+    #[cfg(feature = "serde")]
+    #[error("invalid save data: {0}")]
+    Json(String),
+}
+
+/// Per-user data directory for `app`, following the platform's conventions (see
+/// `directories::ProjectDirs::data_dir`):
+///
+/// - Windows: `%APPDATA%\app\data`
+/// - macOS: `~/Library/Application Support/app`
+/// - Linux and other Unix: `$XDG_DATA_HOME/app`, or `~/.local/share/app`
+///
+/// `None` if the platform has none (e.g. the web) or no home directory can be found.
+pub fn data_dir(app: &str) -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", app).map(|dirs| dirs.data_dir().to_path_buf())
+}
+
+/// Replace `path` with `bytes` atomically: write a sibling temporary file, flush it to disk,
+/// then rename it over `path`. Creates missing parent directories.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    let result = (|| {
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        std::fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Named save slots stored as files in one directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Storage {
+    dir: PathBuf,
+}
+
+impl Storage {
+    /// Slots in the per-user data directory for `app` (see [`data_dir`]).
+    pub fn new(app: &str) -> Result<Self, StorageError> {
+        if !is_valid_name(app) {
+            return Err(StorageError::InvalidSlot(app.to_string()));
+        }
+        Ok(Self::with_dir(
+            data_dir(app).ok_or(StorageError::NoDataDir)?,
+        ))
+    }
+
+    /// Slots in `dir`, e.g. next to the executable for portable builds.
+    pub fn with_dir(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// File backing `slot`.
+    pub fn path(&self, slot: &str) -> Result<PathBuf, StorageError> {
+        if !is_valid_name(slot) {
+            return Err(StorageError::InvalidSlot(slot.to_string()));
+        }
+        Ok(self.dir.join(format!("{slot}.json")))
+    }
+
+    /// Atomically replace `slot`'s contents.
+    pub fn save(&self, slot: &str, bytes: &[u8]) -> Result<(), StorageError> {
+        Ok(write_atomic(&self.path(slot)?, bytes)?)
+    }
+
+    /// `slot`'s contents, or `None` if it was never saved.
+    pub fn load(&self, slot: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        match std::fs::read(self.path(slot)?) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn exists(&self, slot: &str) -> bool {
+        self.path(slot).is_ok_and(|path| path.is_file())
+    }
+
+    /// Delete `slot`; deleting a slot that doesn't exist is not an error.
+    pub fn delete(&self, slot: &str) -> Result<(), StorageError> {
+        match std::fs::remove_file(self.path(slot)?) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Serialize `value` as JSON into `slot`, atomically.
+    #[cfg(feature = "serde")]
+    pub fn save_json<T: serde::Serialize + ?Sized>(
+        &self,
+        slot: &str,
+        value: &T,
+    ) -> Result<(), StorageError> {
+        let bytes = serde_json::to_vec(value).map_err(|e| StorageError::Json(e.to_string()))?;
+        self.save(slot, &bytes)
+    }
+
+    /// Deserialize `slot` from JSON, or `None` if it was never saved.
+    #[cfg(feature = "serde")]
+    pub fn load_json<T: serde::de::DeserializeOwned>(
+        &self,
+        slot: &str,
+    ) -> Result<Option<T>, StorageError> {
+        let Some(bytes) = self.load(slot)? else {
+            return Ok(None);
+        };
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| StorageError::Json(e.to_string()))
+    }
+}
+
+/// Slot and app names become file names, so keep them to a portable, traversal-free set.
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_round_trip_and_reject_path_names() {
+        let dir = std::env::temp_dir().join("libforge-storage-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let storage = Storage::with_dir(&dir);
+
+        assert_eq!(storage.load("save_1").unwrap(), None);
+        storage.save("save_1", b"first").unwrap();
+        storage.save("save_1", b"second").unwrap();
+        assert_eq!(
+            storage.load("save_1").unwrap().as_deref(),
+            Some(&b"second"[..])
+        );
+        // The temporary file was renamed away.
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        storage.delete("save_1").unwrap();
+        storage.delete("save_1").unwrap();
+        assert!(!storage.exists("save_1"));
+        for bad in ["", "../escape", "a/b", "slot.json"] {
+            assert!(matches!(
+                storage.save(bad, b""),
+                Err(StorageError::InvalidSlot(_))
+            ));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_slots_round_trip() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Save {
+            name: String,
+            level: u32,
+            best_time: Option<f64>,
+        }
+
+        let dir = std::env::temp_dir().join("libforge-storage-json-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let storage = Storage::with_dir(&dir);
+        let save = Save {
+            name: "Ré \"the\" hero".to_string(),
+            level: 12,
+            best_time: None,
+        };
+        assert_eq!(storage.load_json::<Save>("save").unwrap(), None);
+        storage.save_json("save", &save).unwrap();
+        assert_eq!(storage.load_json::<Save>("save").unwrap(), Some(save));
+
+        storage.save("save", b"{\"name\":").unwrap();
+        assert!(matches!(
+            storage.load_json::<Save>("save"),
+            Err(StorageError::Json(_))
+        ));
+    }
+}
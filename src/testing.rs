@@ -0,0 +1,247 @@
+//! Golden-image comparison for visual regression tests.
+//!
+//! Capture a frame's RGBA8 pixels (with `ctx.set_seed(..)` called first so procedural
+//! visuals are reproducible), then compare against a checked-in PNG:
+//!
+//! ```no_run
+//! # let pixels = image::RgbaImage::new(1, 1);
+//! use libforge::testing::{DiffOptions, assert_frame_matches};
+//! assert_frame_matches("title_screen", &pixels, "tests/golden/title_screen.png", &DiffOptions::default());
+//! ```
+//!
+//! Run with `LIBFORGE_BLESS=1` to write (or overwrite) the golden images instead.
+
+use image::{Rgba, RgbaImage};
+use std::path::{Path, PathBuf};
+
+/// Environment variable that turns `assert_frame_matches` into "record the golden image".
+pub const BLESS_ENV: &str = "LIBFORGE_BLESS";
+
+/// Tolerances for `assert_frame_matches`.
+#[derive(Clone, Debug)]
+pub struct DiffOptions {
+    /// Perceptual color difference in `[0, 1]` above which a pixel counts as mismatched.
+    /// `0.1` ignores anti-aliasing and driver rounding noise while catching visible changes.
+    pub threshold: f32,
+    /// Fraction of mismatched pixels allowed before the comparison fails.
+    pub max_mismatch_ratio: f32,
+    /// Where `<name>.actual.png` and `<name>.diff.png` are written when a comparison fails.
+    pub artifact_dir: PathBuf,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 0.1,
+            max_mismatch_ratio: 0.0,
+            artifact_dir: PathBuf::from("target/libforge-diffs"),
+        }
+    }
+}
+
+/// Error from comparing two images.
+#[derive(Debug, thiserror::Error)]
+pub enum DiffError {
+    #[error("size mismatch: actual {actual:?}, expected {expected:?}")]
+    SizeMismatch {
+        actual: (u32, u32),
+        expected: (u32, u32),
+    },
+}
+
+/// Result of comparing two images of the same size.
+pub struct DiffReport {
+    /// Pixels whose perceptual difference exceeds the threshold.
+    pub mismatched: usize,
+    /// Largest perceptual difference found, in `[0, 1]`.
+    pub max_delta: f32,
+    /// Mismatched pixels in red over a faded copy of the expected image.
+    pub diff_image: RgbaImage,
+}
+
+impl DiffReport {
+    pub fn mismatch_ratio(&self) -> f32 {
+        let total = self.diff_image.width() as usize * self.diff_image.height() as usize;
+        if total == 0 {
+            0.0
+        } else {
+            self.mismatched as f32 / total as f32
+        }
+    }
+}
+
+/// Perceptual distance between two pixels in `[0, 1]`, measured in YIQ space (weights luma
+/// above chroma, like the eye) after blending both over white.
+pub fn pixel_delta(a: Rgba<u8>, b: Rgba<u8>) -> f32 {
+    let blend = |p: Rgba<u8>| {
+        let alpha = p[3] as f32 / 255.0;
+        [0, 1, 2].map(|i| 255.0 + (p[i] as f32 - 255.0) * alpha)
+    };
+    let yiq = |[r, g, b]: [f32; 3]| {
+        (
+            0.298_895_3 * r + 0.586_622_5 * g + 0.114_482_2 * b,
+            0.595_977_9 * r - 0.274_176_3 * g - 0.321_801_6 * b,
+            0.211_470_4 * r - 0.522_617_9 * g + 0.311_147_5 * b,
+        )
+    };
+    let (y1, i1, q1) = yiq(blend(a));
+    let (y2, i2, q2) = yiq(blend(b));
+    let (dy, di, dq) = (y1 - y2, i1 - i2, q1 - q2);
+    let delta = 0.5053 * dy * dy + 0.299 * di * di + 0.1957 * dq * dq;
+    // 35215 is the largest possible delta between two colors.
+    (delta / 35215.0).sqrt().min(1.0)
+}
+
+/// Compare `actual` with `expected` pixel by pixel.
+pub fn diff_images(
+    actual: &RgbaImage,
+    expected: &RgbaImage,
+    threshold: f32,
+) -> Result<DiffReport, DiffError> {
+    if actual.dimensions() != expected.dimensions() {
+        return Err(DiffError::SizeMismatch {
+            actual: actual.dimensions(),
+            expected: expected.dimensions(),
+        });
+    }
+
+    let mut mismatched = 0;
+    let mut max_delta = 0.0f32;
+    let mut diff_image = RgbaImage::new(actual.width(), actual.height());
+    for ((a, e), out) in actual
+        .pixels()
+        .zip(expected.pixels())
+        .zip(diff_image.pixels_mut())
+    {
+        let delta = pixel_delta(*a, *e);
+        max_delta = max_delta.max(delta);
+        *out = if delta > threshold {
+            mismatched += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let gray = 255 - (255 - e.0[0].max(e.0[1]).max(e.0[2])) / 4;
+            Rgba([gray, gray, gray, 255])
+        };
+    }
+
+    Ok(DiffReport {
+        mismatched,
+        max_delta,
+        diff_image,
+    })
+}
+
+/// Assert that `actual` matches the golden PNG at `golden`, within `options`.
+///
+/// On failure, writes `<name>.actual.png` and `<name>.diff.png` to `options.artifact_dir`
+/// and panics with their paths. A missing golden image fails too, unless `LIBFORGE_BLESS`
+/// is set, in which case `actual` is saved as the new golden image.
+pub fn assert_frame_matches(
+    name: &str,
+    actual: &RgbaImage,
+    golden: impl AsRef<Path>,
+    options: &DiffOptions,
+) {
+    let golden = golden.as_ref();
+    if std::env::var_os(BLESS_ENV).is_some() {
+        if let Some(dir) = golden.parent() {
+            std::fs::create_dir_all(dir).expect("create golden image directory");
+        }
+        actual.save(golden).expect("write golden image");
+        return;
+    }
+
+    let expected = match image::open(golden) {
+        Ok(img) => img.to_rgba8(),
+        Err(e) => {
+            let actual_path = save_artifact(options, name, "actual", actual);
+            panic!(
+                "{name}: cannot read golden image {} ({e}); actual frame written to {}. \
+                 Run with {BLESS_ENV}=1 to record it.",
+                golden.display(),
+                actual_path.display()
+            );
+        }
+    };
+
+    let report = match diff_images(actual, &expected, options.threshold) {
+        Ok(report) => report,
+        Err(e) => {
+            let actual_path = save_artifact(options, name, "actual", actual);
+            panic!(
+                "{name}: {e}; actual frame written to {}",
+                actual_path.display()
+            );
+        }
+    };
+
+    if report.mismatch_ratio() > options.max_mismatch_ratio {
+        let actual_path = save_artifact(options, name, "actual", actual);
+        let diff_path = save_artifact(options, name, "diff", &report.diff_image);
+        panic!(
+            "{name}: {} pixels ({:.3}%) differ from {} (max delta {:.3}, threshold {}); \
+             see {} and {}",
+            report.mismatched,
+            report.mismatch_ratio() * 100.0,
+            golden.display(),
+            report.max_delta,
+            options.threshold,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+fn save_artifact(options: &DiffOptions, name: &str, kind: &str, img: &RgbaImage) -> PathBuf {
+    let path = options.artifact_dir.join(format!("{name}.{kind}.png"));
+    let _ = std::fs::create_dir_all(&options.artifact_dir);
+    let _ = img.save(&path);
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_differences_pass_and_large_ones_are_reported() {
+        let expected = RgbaImage::from_pixel(4, 4, Rgba([100, 150, 200, 255]));
+        let mut actual = expected.clone();
+        actual.put_pixel(0, 0, Rgba([101, 150, 199, 255]));
+        actual.put_pixel(3, 3, Rgba([0, 0, 0, 255]));
+
+        let report = diff_images(&actual, &expected, 0.1).unwrap();
+        assert_eq!(report.mismatched, 1);
+        assert_eq!(report.diff_image.get_pixel(3, 3), &Rgba([255, 0, 0, 255]));
+        assert!((report.mismatch_ratio() - 1.0 / 16.0).abs() < 1e-6);
+
+        assert!(pixel_delta(Rgba([0, 0, 0, 255]), Rgba([255; 4])) > 0.9);
+        assert!(diff_images(&RgbaImage::new(2, 2), &expected, 0.1).is_err());
+    }
+
+    #[test]
+    fn mismatching_frame_writes_artifacts() {
+        let dir = std::env::temp_dir().join("libforge-testing-artifacts");
+        let golden = dir.join("golden.png");
+        std::fs::create_dir_all(&dir).unwrap();
+        RgbaImage::from_pixel(2, 2, Rgba([255; 4]))
+            .save(&golden)
+            .unwrap();
+        let options = DiffOptions {
+            artifact_dir: dir.clone(),
+            ..DiffOptions::default()
+        };
+
+        assert_frame_matches(
+            "same",
+            &RgbaImage::from_pixel(2, 2, Rgba([255; 4])),
+            &golden,
+            &options,
+        );
+        let black = RgbaImage::from_pixel(2, 2, Rgba([0, 0, 0, 255]));
+        let result =
+            std::panic::catch_unwind(|| assert_frame_matches("changed", &black, &golden, &options));
+        assert!(result.is_err());
+        assert!(dir.join("changed.diff.png").exists());
+    }
+}
@@ -0,0 +1,944 @@
+//! TrueType/OpenType text drawn from a persistent glyph atlas.
+//!
+//! Glyphs are rasterized once per (font, glyph, size) into a single atlas texture that lives
+//! for the whole run, so every `draw_text` call is a batch of quads from one texture. Laid
+//! out strings are cached by (font, size, string) and kept while they are drawn every
+//! frame, so static labels skip layout and rasterization entirely.
+//!
+//! A [`FontFamily`] chains fonts so characters missing from the first (e.g. Japanese in a
+//! Latin font) are drawn from the next that has them. When the atlas fills up (large CJK
+//! glyph sets), its least recently used rows are evicted and re-rasterized on demand.
+//!
+//! With the `shaping` feature, text is shaped with rustybuzz (Arabic joining, ligatures,
+//! mark positioning, kerning from GPOS) and reordered with unicode-bidi, so right-to-left
+//! and mixed-direction lines display correctly. Without it, each character maps straight
+//! to its glyph, left to right.
+
+use crate::renderer::{Renderer, TextureId};
+use crate::{Color, Rect};
+use ab_glyph::{Font, FontArc, GlyphId, PxScale, ScaleFont};
+use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
+use std::collections::HashMap;
+#[cfg(feature = "shaping")]
+mod shaping;
+
+/// Handle to a font loaded with `ctx.load_font_from_bytes`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FontId(pub(crate) u32);
+
+/// Fonts tried in order for each character, registered with `ctx.create_font_family`.
+///
+/// ```ignore
+/// let ui = ctx.create_font_family(FontFamily::new([latin, japanese, symbols]))?;
+/// ctx.draw_text(ui, "Score: 100 点", 10.0, 10.0, 24.0, Color::WHITE);
+/// ```
+///
+/// Line height comes from the first font. Only outline glyphs are drawn, so color bitmap
+/// emoji fonts show nothing; use an outline (monochrome) emoji font.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FontFamily {
+    fonts: Vec<FontId>,
+}
+
+impl FontFamily {
+    pub fn new(fonts: impl IntoIterator<Item = FontId>) -> Self {
+        Self {
+            fonts: fonts.into_iter().collect(),
+        }
+    }
+
+    /// Append a fallback font.
+    pub fn with(mut self, font: FontId) -> Self {
+        self.fonts.push(font);
+        self
+    }
+
+    pub fn fonts(&self) -> &[FontId] {
+        &self.fonts
+    }
+}
+
+/// Fonts searched for the characters of a run in `font`: its family members (or `font`
+/// itself), then those of `base`, so a bold variant without CJK glyphs still falls back
+/// through the base family.
+fn fallback_chain(
+    families: &HashMap<FontId, Vec<FontId>>,
+    font: FontId,
+    base: FontId,
+) -> Vec<FontId> {
+    let mut chain = Vec::new();
+    for font in [font, base] {
+        let members = match families.get(&font) {
+            Some(members) => members.as_slice(),
+            None => std::slice::from_ref(&font),
+        };
+        for &member in members {
+            if !chain.contains(&member) {
+                chain.push(member);
+            }
+        }
+    }
+    chain
+}
+
+// Side of the square glyph atlas texture, in pixels.
+const ATLAS_SIZE: u32 = 1024;
+
+// Transparent gap between glyphs so filtering never samples a neighbour.
+const GLYPH_PADDING: u32 = 1;
+
+/// A glyph placed relative to the top-left of its laid-out string.
+#[derive(Clone, Copy, Debug)]
+struct LaidGlyph {
+    id: GlyphId,
+    font: FontId,
+    // Index into `TextLayout::styles`.
+    style: u16,
+    // Pen position on the baseline.
+    x: f32,
+    y: f32,
+}
+
+/// Per-span drawing style of a laid-out string.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Style {
+    // Replaces the draw color's RGB; `None` uses the draw color.
+    color: Option<[f32; 4]>,
+    // Bold without a bold font: each glyph is drawn twice, offset horizontally.
+    faux_bold: bool,
+}
+
+/// A string broken into positioned glyphs, plus its measured size.
+#[derive(Debug, Default)]
+struct TextLayout {
+    glyphs: Vec<LaidGlyph>,
+    styles: Vec<Style>,
+    width: f32,
+    height: f32,
+}
+
+/// A piece of text laid out in one font and style.
+struct Run<'a> {
+    text: &'a str,
+    font: FontId,
+    style: u16,
+}
+
+/// Decimal form of `value` written into `buf`, without allocating.
+pub(crate) fn format_int(value: i64, buf: &mut [u8; 20]) -> &str {
+    // Filled from the end, least-significant digit first.
+    let mut start = buf.len();
+    let mut n = value.unsigned_abs();
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    if value < 0 {
+        start -= 1;
+        buf[start] = b'-';
+    }
+    std::str::from_utf8(&buf[start..]).unwrap()
+}
+
+/// Horizontal offset of the second pass of faux-bold glyphs.
+fn faux_bold_offset(size: f32) -> f32 {
+    (size / 24.0).round().max(1.0)
+}
+
+/// Lay out `runs` at `size` pixels, using `base` for line metrics. Lines break at `\n` and,
+/// with `wrap`, at the last space before a glyph would cross that width (a single word
+/// wider than `wrap` overflows).
+///
+/// Each character comes from the first font in its run's fallback chain that has a glyph
+/// for it. This is the layout used without the `shaping` feature.
+#[cfg_attr(feature = "shaping", allow(dead_code))]
+fn layout(
+    fonts: &[FontArc],
+    families: &HashMap<FontId, Vec<FontId>>,
+    base: FontId,
+    runs: &[Run<'_>],
+    styles: Vec<Style>,
+    size: f32,
+    wrap: Option<f32>,
+) -> TextLayout {
+    let scale = PxScale::from(size);
+    let metrics = fonts[base.0 as usize].as_scaled(scale);
+    let line_height = metrics.height() + metrics.line_gap();
+    let mut out = TextLayout {
+        styles,
+        ..TextLayout::default()
+    };
+    let mut lines = 1;
+    let (mut x, mut y) = (0.0f32, metrics.ascent());
+    let mut prev: Option<(FontId, GlyphId)> = None;
+    // After the last space on this line: index of the next glyph, its x, and the line's
+    // width without the space.
+    let mut break_point: Option<(usize, f32, f32)> = None;
+    for run in runs {
+        let chain = fallback_chain(families, run.font, base);
+        let bold = out.styles[run.style as usize].faux_bold;
+        for c in run.text.chars() {
+            if c == '\n' {
+                out.width = out.width.max(x);
+                x = 0.0;
+                y += line_height;
+                lines += 1;
+                prev = None;
+                break_point = None;
+                continue;
+            }
+            if c.is_control() {
+                continue;
+            }
+            // Without any font that has `c`, the first one's missing-glyph box is drawn.
+            let font = chain
+                .iter()
+                .copied()
+                .find(|f| fonts[f.0 as usize].glyph_id(c).0 != 0)
+                .unwrap_or(chain[0]);
+            let scaled = fonts[font.0 as usize].as_scaled(scale);
+            let id = scaled.glyph_id(c);
+            if let Some((prev_font, prev)) = prev
+                && prev_font == font
+            {
+                x += scaled.kern(prev, id);
+            }
+            prev = Some((font, id));
+            let advance = scaled.h_advance(id) + if bold { faux_bold_offset(size) } else { 0.0 };
+            if let (Some(max), Some((start, bx, line_width))) = (wrap, break_point)
+                && c != ' '
+                && x + advance > max
+            {
+                for glyph in &mut out.glyphs[start..] {
+                    glyph.x -= bx;
+                    glyph.y += line_height;
+                }
+                out.width = out.width.max(line_width);
+                x -= bx;
+                y += line_height;
+                lines += 1;
+                break_point = None;
+            }
+            out.glyphs.push(LaidGlyph {
+                id,
+                font,
+                style: run.style,
+                x,
+                y,
+            });
+            if c == ' ' {
+                break_point = Some((out.glyphs.len(), x + advance, x));
+            }
+            x += advance;
+        }
+    }
+    out.width = out.width.max(x);
+    out.height = metrics.height() + line_height * (lines - 1) as f32;
+    out
+}
+
+/// A markup span: text plus the tags open around it.
+#[derive(Clone, Debug, PartialEq)]
+struct Span {
+    text: String,
+    color: Option<[f32; 4]>,
+    bold: bool,
+}
+
+/// `#rrggbb` or `#rrggbbaa` (the `#` is optional).
+fn parse_hex_color(hex: &str) -> Option<[f32; 4]> {
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    let rgba = match hex.len() {
+        6 => (value << 8) | 0xff,
+        8 => value,
+        _ => return None,
+    };
+    Some(rgba.to_be_bytes().map(|c| c as f32 / 255.0))
+}
+
+/// Split markup into styled spans.
+///
+/// Supports `[color=#rrggbb]` / `[color=#rrggbbaa]` ... `[/color]` and `[b]` ... `[/b]`,
+/// both nestable. `[[` is a literal `[`; unknown or malformed tags are kept as text.
+fn parse_markup(markup: &str) -> Vec<Span> {
+    let mut spans: Vec<Span> = Vec::new();
+    let mut colors: Vec<[f32; 4]> = Vec::new();
+    let mut bold = 0u32;
+    let mut text = String::new();
+
+    let flush = |spans: &mut Vec<Span>, text: &mut String, color, bold| {
+        if text.is_empty() {
+            return;
+        }
+        match spans.last_mut() {
+            Some(last) if last.color == color && last.bold == bold => last.text.push_str(text),
+            _ => spans.push(Span {
+                text: text.clone(),
+                color,
+                bold,
+            }),
+        }
+        text.clear();
+    };
+
+    let mut rest = markup;
+    while let Some(open) = rest.find('[') {
+        text.push_str(&rest[..open]);
+        rest = &rest[open..];
+        if let Some(after) = rest.strip_prefix("[[") {
+            text.push('[');
+            rest = after;
+            continue;
+        }
+        let Some(close) = rest.find(']') else {
+            break;
+        };
+        let tag = &rest[1..close];
+        let (color, is_bold) = (colors.last().copied(), bold > 0);
+        match tag {
+            "b" => {
+                flush(&mut spans, &mut text, color, is_bold);
+                bold += 1;
+            }
+            "/b" if bold > 0 => {
+                flush(&mut spans, &mut text, color, is_bold);
+                bold -= 1;
+            }
+            "/color" if !colors.is_empty() => {
+                flush(&mut spans, &mut text, color, is_bold);
+                colors.pop();
+            }
+            _ => match tag.strip_prefix("color=").and_then(parse_hex_color) {
+                Some(new) => {
+                    flush(&mut spans, &mut text, color, is_bold);
+                    colors.push(new);
+                }
+                None => {
+                    // Not a tag: keep the `[` and scan on from the next character.
+                    text.push('[');
+                    rest = &rest[1..];
+                    continue;
+                }
+            },
+        }
+        rest = &rest[close + 1..];
+    }
+    text.push_str(rest);
+    flush(&mut spans, &mut text, colors.last().copied(), bold > 0);
+    spans
+}
+
+/// What a cached layout depends on besides the string itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct LayoutKey {
+    font: FontId,
+    // `f32` bits of the size and wrap width (infinite when not wrapping).
+    size: u32,
+    wrap: u32,
+    // The string is markup for `parse_markup`.
+    markup: bool,
+}
+
+impl LayoutKey {
+    pub fn plain(font: FontId, size: f32) -> Self {
+        Self {
+            font,
+            size: size.to_bits(),
+            wrap: f32::INFINITY.to_bits(),
+            markup: false,
+        }
+    }
+
+    pub fn markup(font: FontId, size: f32, wrap: Option<f32>) -> Self {
+        Self {
+            wrap: wrap.unwrap_or(f32::INFINITY).to_bits(),
+            markup: true,
+            ..Self::plain(font, size)
+        }
+    }
+
+    fn size(&self) -> f32 {
+        f32::from_bits(self.size)
+    }
+
+    fn wrap(&self) -> Option<f32> {
+        Some(f32::from_bits(self.wrap)).filter(|w| w.is_finite())
+    }
+}
+
+/// Cached layouts per key and string, tagged with the frame they were last used.
+///
+/// Strings not drawn during the previous frame are dropped at the start of the next, so
+/// static labels stay cached while per-frame strings (timers, scores) don't accumulate.
+struct LayoutCache<T> {
+    entries: HashMap<LayoutKey, HashMap<String, (T, u64)>>,
+    frame: u64,
+}
+
+impl<T> Default for LayoutCache<T> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            frame: 0,
+        }
+    }
+}
+
+impl<T> LayoutCache<T> {
+    fn get_or_insert_with(&mut self, key: LayoutKey, text: &str, create: impl FnOnce() -> T) -> &T {
+        let strings = self.entries.entry(key).or_default();
+        // Look up by `&str` first so hits don't allocate a key.
+        if !strings.contains_key(text) {
+            strings.insert(text.to_string(), (create(), self.frame));
+        }
+        let entry = strings.get_mut(text).unwrap();
+        entry.1 = self.frame;
+        &entry.0
+    }
+
+    /// Start a new frame, dropping entries unused since the one before.
+    fn next_frame(&mut self) {
+        let frame = self.frame;
+        self.entries.retain(|_, strings| {
+            strings.retain(|_, (_, used)| *used == frame);
+            !strings.is_empty()
+        });
+        self.frame += 1;
+    }
+}
+
+/// A row of the glyph atlas.
+#[derive(Clone, Copy, Debug)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    next_x: u32,
+    // Last frame a glyph on this shelf was drawn or added.
+    used: u64,
+}
+
+/// Shelf allocator for the glyph atlas: rows of glyphs, each as tall as its tallest glyph.
+///
+/// Shelves are the unit of eviction: when the atlas is full, the least recently used shelf
+/// that fits is emptied and refilled.
+#[derive(Debug)]
+struct ShelfPacker {
+    size: u32,
+    // Top to bottom.
+    shelves: Vec<Shelf>,
+}
+
+impl ShelfPacker {
+    fn new(size: u32) -> Self {
+        Self {
+            size,
+            shelves: Vec::new(),
+        }
+    }
+
+    /// Top-left and shelf index of a free `w x h` region (padding included), or `None` if
+    /// the atlas is full.
+    fn allocate(&mut self, w: u32, h: u32, frame: u64) -> Option<(u32, u32, usize)> {
+        let (w, h) = (w + GLYPH_PADDING, h + GLYPH_PADDING);
+        // Reuse the first shelf that is tall enough without wasting more than half of it.
+        for (index, shelf) in self.shelves.iter_mut().enumerate() {
+            if h <= shelf.height && h * 2 >= shelf.height && shelf.next_x + w <= self.size {
+                let x = shelf.next_x;
+                shelf.next_x += w;
+                shelf.used = frame;
+                return Some((x, shelf.y, index));
+            }
+        }
+        let y = self.shelves.last().map_or(0, |s| s.y + s.height);
+        if y + h > self.size || w > self.size {
+            return None;
+        }
+        self.shelves.push(Shelf {
+            y,
+            height: h,
+            next_x: w,
+            used: frame,
+        });
+        Some((0, y, self.shelves.len() - 1))
+    }
+
+    /// Empty the least recently used shelf that is tall enough for `w x h` and wasn't used
+    /// in `frame`, and allocate the region at its start.
+    fn evict(&mut self, w: u32, h: u32, frame: u64) -> Option<(u32, u32, usize)> {
+        let (w, h) = (w + GLYPH_PADDING, h + GLYPH_PADDING);
+        if w > self.size {
+            return None;
+        }
+        let (index, shelf) = self
+            .shelves
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, s)| s.used < frame && h <= s.height)
+            .min_by_key(|(_, s)| s.used)?;
+        shelf.next_x = w;
+        shelf.used = frame;
+        Some((0, shelf.y, index))
+    }
+
+    fn touch(&mut self, shelf: usize, frame: u64) {
+        self.shelves[shelf].used = frame;
+    }
+
+    fn clear(&mut self) {
+        self.shelves.clear();
+    }
+}
+
+/// Where a rasterized glyph sits in the atlas.
+#[derive(Clone, Copy, Debug)]
+struct AtlasGlyph {
+    // Pixel region in the atlas texture.
+    src: Rect,
+    // Offset of the region's top-left from the pen position on the baseline.
+    offset: (f32, f32),
+    shelf: usize,
+}
+
+/// CPU copy of the glyph atlas (alpha coverage) and its GPU texture.
+struct GlyphAtlas {
+    texture: TextureId,
+    coverage: Vec<u8>,
+    packer: ShelfPacker,
+    // `None` for glyphs with no outline (spaces).
+    glyphs: HashMap<(FontId, GlyphId, u32), Option<AtlasGlyph>>,
+    // Region changed since the last upload, as `(x0, y0, x1, y1)`.
+    dirty: Option<(u32, u32, u32, u32)>,
+    // Counts `flush` calls, for least-recently-used eviction.
+    frame: u64,
+}
+
+impl GlyphAtlas {
+    fn new(texture: TextureId) -> Self {
+        Self {
+            texture,
+            coverage: vec![0; (ATLAS_SIZE * ATLAS_SIZE) as usize],
+            packer: ShelfPacker::new(ATLAS_SIZE),
+            glyphs: HashMap::new(),
+            dirty: None,
+            frame: 0,
+        }
+    }
+
+    /// The atlas entry for `id`, rasterizing it on first use.
+    fn glyph(
+        &mut self,
+        font_id: FontId,
+        font: &FontArc,
+        id: GlyphId,
+        size: f32,
+    ) -> Option<AtlasGlyph> {
+        let key = (font_id, id, size.to_bits());
+        if let Some(&glyph) = self.glyphs.get(&key) {
+            if let Some(entry) = glyph {
+                self.packer.touch(entry.shelf, self.frame);
+            }
+            return glyph;
+        }
+        let glyph = self.rasterize(font, id, size);
+        self.glyphs.insert(key, glyph);
+        glyph
+    }
+
+    fn rasterize(&mut self, font: &FontArc, id: GlyphId, size: f32) -> Option<AtlasGlyph> {
+        let outlined = font.outline_glyph(
+            id.with_scale_and_position(PxScale::from(size), ab_glyph::point(0.0, 0.0)),
+        )?;
+        let bounds = outlined.px_bounds();
+        let (w, h) = (bounds.width() as u32, bounds.height() as u32);
+        if w == 0 || h == 0 {
+            return None;
+        }
+        let frame = self.frame;
+        let allocated = self.packer.allocate(w, h, frame).or_else(|| {
+            let (x, y, shelf) = self.packer.evict(w, h, frame)?;
+            self.evict_shelf(shelf);
+            Some((x, y, shelf))
+        });
+        let (x, y, shelf) = match allocated {
+            Some(pos) => pos,
+            None => {
+                // Every shelf was drawn from this frame; start over, so strings already
+                // drawn this frame may show wrong glyphs once.
+                log_warn!("glyph atlas full, clearing it");
+                self.clear();
+                self.packer.allocate(w, h, frame)?
+            }
+        };
+        let stride = ATLAS_SIZE as usize;
+        outlined.draw(|gx, gy, c| {
+            let i = (y + gy) as usize * stride + (x + gx) as usize;
+            self.coverage[i] = (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        });
+        self.mark_dirty(x, y, x + w, y + h);
+        Some(AtlasGlyph {
+            src: Rect {
+                x: x as f32,
+                y: y as f32,
+                w: w as f32,
+                h: h as f32,
+            },
+            offset: (bounds.min.x, bounds.min.y),
+            shelf,
+        })
+    }
+
+    /// Forget the glyphs on `shelf` and blank its pixels before it is refilled.
+    fn evict_shelf(&mut self, shelf: usize) {
+        let Shelf { y, height, .. } = self.packer.shelves[shelf];
+        log_debug!("evicting glyph atlas row at y = {y}");
+        self.glyphs
+            .retain(|_, glyph| glyph.is_none_or(|glyph| glyph.shelf != shelf));
+        let stride = ATLAS_SIZE as usize;
+        self.coverage[y as usize * stride..(y + height) as usize * stride].fill(0);
+        self.mark_dirty(0, y, ATLAS_SIZE, y + height);
+    }
+
+    fn clear(&mut self) {
+        self.coverage.fill(0);
+        self.packer.clear();
+        self.glyphs.clear();
+        self.mark_dirty(0, 0, ATLAS_SIZE, ATLAS_SIZE);
+    }
+
+    fn mark_dirty(&mut self, x0: u32, y0: u32, x1: u32, y1: u32) {
+        self.dirty = Some(match self.dirty {
+            Some((a, b, c, d)) => (a.min(x0), b.min(y0), c.max(x1), d.max(y1)),
+            None => (x0, y0, x1, y1),
+        });
+    }
+
+    /// Upload the region rasterized since the last flush as white texels with alpha coverage.
+    fn flush<W>(&mut self, renderer: &mut Renderer<W>)
+    where
+        W: HasWindowHandle + HasDisplayHandle + wgpu::WasmNotSendSync + Sync + Clone + 'static,
+    {
+        self.frame += 1;
+        let Some((x0, y0, x1, y1)) = self.dirty.take() else {
+            return;
+        };
+        let mut rgba = Vec::with_capacity(((x1 - x0) * (y1 - y0) * 4) as usize);
+        for row in y0..y1 {
+            let start = (row * ATLAS_SIZE + x0) as usize;
+            for &a in &self.coverage[start..start + (x1 - x0) as usize] {
+                rgba.extend_from_slice(&[255, 255, 255, a]);
+            }
+        }
+        renderer.write_texture_region(self.texture, (x0, y0), (x1 - x0, y1 - y0), &rgba);
+    }
+}
+
+/// Loaded fonts, the glyph atlas and the layout cache behind `ctx.draw_text`.
+#[derive(Default)]
+pub(crate) struct TextSystem {
+    // Indexed by `FontId`; a family's entry is its first member, for line metrics.
+    fonts: Vec<FontArc>,
+    // Font files for rustybuzz, parallel to `fonts`.
+    #[cfg(feature = "shaping")]
+    data: Vec<std::sync::Arc<[u8]>>,
+    // Member fonts of each `FontFamily`, in fallback order.
+    families: HashMap<FontId, Vec<FontId>>,
+    // Bold variant of a font, used by `[b]` markup.
+    bold: HashMap<FontId, FontId>,
+    // Families created by `family_for`, by member list.
+    #[cfg(feature = "i18n")]
+    fallback_families: HashMap<Vec<FontId>, FontId>,
+    // Created with the first text draw.
+    atlas: Option<GlyphAtlas>,
+    layouts: LayoutCache<TextLayout>,
+}
+
+impl TextSystem {
+    pub fn load_font(&mut self, bytes: &[u8]) -> Result<FontId, ab_glyph::InvalidFont> {
+        let font = FontArc::try_from_vec(bytes.to_vec())?;
+        self.fonts.push(font);
+        #[cfg(feature = "shaping")]
+        self.data.push(bytes.into());
+        Ok(FontId(self.fonts.len() as u32 - 1))
+    }
+
+    /// Register `family` as a new font, or `None` if it is empty or names an unknown font.
+    /// Families nested in `family` are flattened.
+    pub fn create_family(&mut self, family: &FontFamily) -> Option<FontId> {
+        if family
+            .fonts
+            .iter()
+            .any(|f| self.fonts.get(f.0 as usize).is_none())
+        {
+            return None;
+        }
+        let mut members = Vec::new();
+        for &font in &family.fonts {
+            for member in fallback_chain(&self.families, font, font) {
+                if !members.contains(&member) {
+                    members.push(member);
+                }
+            }
+        }
+        let first = members.first()?.0 as usize;
+        self.fonts.push(self.fonts[first].clone());
+        #[cfg(feature = "shaping")]
+        self.data.push(self.data[first].clone());
+        let id = FontId(self.fonts.len() as u32 - 1);
+        self.families.insert(id, members);
+        Some(id)
+    }
+
+    /// `fonts` as one font: a family registered on first use, or the font itself when
+    /// there is only one. `None` if `fonts` is empty or names an unknown font.
+    #[cfg(feature = "i18n")]
+    pub fn family_for(&mut self, fonts: &[FontId]) -> Option<FontId> {
+        if let [font] = fonts {
+            return self.fonts.get(font.0 as usize).map(|_| *font);
+        }
+        if let Some(&id) = self.fallback_families.get(fonts) {
+            return Some(id);
+        }
+        let id = self.create_family(&FontFamily::new(fonts.iter().copied()))?;
+        self.fallback_families.insert(fonts.to_vec(), id);
+        Some(id)
+    }
+
+    pub fn set_bold_variant(&mut self, regular: FontId, bold: FontId) {
+        self.bold.insert(regular, bold);
+    }
+
+    /// Width and height of `text`; zero for an unknown font.
+    pub fn measure(&mut self, key: LayoutKey, text: &str) -> (f32, f32) {
+        match self.layout(key, text) {
+            Some(layout) => (layout.width, layout.height),
+            None => (0.0, 0.0),
+        }
+    }
+
+    /// The cached layout of `text`, laid out on a miss. `None` for an unknown font.
+    fn layout(&mut self, key: LayoutKey, text: &str) -> Option<&TextLayout> {
+        if self.fonts.get(key.font.0 as usize).is_none() {
+            log_warn!("unknown {:?}", key.font);
+            return None;
+        }
+        let (fonts, families, bold) = (&self.fonts, &self.families, &self.bold);
+        #[cfg(feature = "shaping")]
+        let data = &self.data;
+        Some(self.layouts.get_or_insert_with(key, text, || {
+            let (size, wrap) = (key.size(), key.wrap());
+            let layout = |runs: &[Run<'_>], styles| {
+                #[cfg(feature = "shaping")]
+                return shaping::layout(fonts, data, families, key.font, runs, styles, size, wrap);
+                #[cfg(not(feature = "shaping"))]
+                layout(fonts, families, key.font, runs, styles, size, wrap)
+            };
+            if !key.markup {
+                let run = Run {
+                    text,
+                    font: key.font,
+                    style: 0,
+                };
+                let styles = vec![Style::default()];
+                return layout(&[run], styles);
+            }
+            let spans = parse_markup(text);
+            let mut styles = Vec::new();
+            let runs: Vec<Run<'_>> = spans
+                .iter()
+                .map(|span| {
+                    let variant = bold.get(&key.font).filter(|_| span.bold);
+                    let style = Style {
+                        color: span.color,
+                        faux_bold: span.bold && variant.is_none(),
+                    };
+                    let index = styles.iter().position(|s| *s == style).unwrap_or_else(|| {
+                        styles.push(style);
+                        styles.len() - 1
+                    });
+                    Run {
+                        text: &span.text,
+                        font: variant.copied().unwrap_or(key.font),
+                        style: index as u16,
+                    }
+                })
+                .collect();
+            layout(&runs, styles)
+        }))
+    }
+
+    /// Draw `text` with its top-left corner at `(x, y)`.
+    pub fn draw<W>(
+        &mut self,
+        renderer: &mut Renderer<W>,
+        key: LayoutKey,
+        text: &str,
+        (x, y): (f32, f32),
+        color: Color,
+    ) where
+        W: HasWindowHandle + HasDisplayHandle + wgpu::WasmNotSendSync + Sync + Clone + 'static,
+    {
+        let size = key.size();
+        if size <= 0.0 {
+            return;
+        }
+        if self.atlas.is_none() {
+            match renderer.create_dynamic_texture("libforge_glyph_atlas", ATLAS_SIZE, ATLAS_SIZE) {
+                Ok(texture) => self.atlas = Some(GlyphAtlas::new(texture)),
+                Err(e) => {
+                    log_warn!("cannot create glyph atlas: {e}");
+                    return;
+                }
+            }
+        }
+        if self.layout(key, text).is_none() {
+            return;
+        }
+        // Looked up again so the borrow is split from `fonts` and `atlas`.
+        let layout = &self.layouts.entries[&key][text].0;
+        let atlas = self.atlas.as_mut().unwrap();
+        // Snap the pen to whole pixels so glyph texels map 1:1 to screen pixels.
+        let (x, y) = (x.round(), y.round());
+        for glyph in &layout.glyphs {
+            let font = &self.fonts[glyph.font.0 as usize];
+            let Some(entry) = atlas.glyph(glyph.font, font, glyph.id, size) else {
+                continue;
+            };
+            let style = layout.styles[glyph.style as usize];
+            let tint = match style.color {
+                // Span colors keep the draw color's alpha so fading text fades every span.
+                Some([r, g, b, a]) => [r, g, b, a * color.0[3]],
+                None => color.0,
+            };
+            let mut dst = Rect {
+                x: x + glyph.x.round() + entry.offset.0,
+                y: y + glyph.y.round() + entry.offset.1,
+                w: entry.src.w,
+                h: entry.src.h,
+            };
+            renderer.draw_subtexture(atlas.texture, entry.src, dst, tint);
+            if style.faux_bold {
+                dst.x += faux_bold_offset(size);
+                renderer.draw_subtexture(atlas.texture, entry.src, dst, tint);
+            }
+        }
+    }
+
+    /// Upload newly rasterized glyphs and age the layout cache; call once per frame before
+    /// the frame is submitted.
+    pub fn end_frame<W>(&mut self, renderer: &mut Renderer<W>)
+    where
+        W: HasWindowHandle + HasDisplayHandle + wgpu::WasmNotSendSync + Sync + Clone + 'static,
+    {
+        if let Some(atlas) = &mut self.atlas {
+            atlas.flush(renderer);
+        }
+        self.layouts.next_frame();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers_format_into_the_stack_buffer() {
+        let mut buf = [0; 20];
+        assert_eq!(format_int(0, &mut buf), "0");
+        assert_eq!(format_int(-305, &mut buf), "-305");
+        assert_eq!(format_int(i64::MIN, &mut buf), i64::MIN.to_string());
+        assert_eq!(format_int(i64::MAX, &mut buf), i64::MAX.to_string());
+    }
+
+    fn cached<T>(cache: &LayoutCache<T>) -> usize {
+        cache.entries.values().map(HashMap::len).sum()
+    }
+
+    #[test]
+    fn layouts_survive_while_drawn_every_frame() {
+        let mut cache = LayoutCache::<u32>::default();
+        let key = LayoutKey::plain(FontId(0), 16.0);
+        let mut created = 0;
+        for frame in 0..3 {
+            cache.get_or_insert_with(key, "Start", || {
+                created += 1;
+                0
+            });
+            cache.get_or_insert_with(key, &format!("Time {frame}"), || 0);
+            cache.next_frame();
+        }
+        assert_eq!(created, 1);
+        // "Start" and the last timer string.
+        assert_eq!(cached(&cache), 2);
+        cache.next_frame();
+        assert_eq!(cached(&cache), 0);
+    }
+
+    #[test]
+    fn markup_splits_nested_spans_and_keeps_unknown_tags() {
+        let red = Some([1.0, 0.0, 0.0, 1.0]);
+        let span = |text: &str, color, bold| Span {
+            text: text.to_string(),
+            color,
+            bold,
+        };
+        assert_eq!(
+            parse_markup("Hit for [color=#ff0000][b]12[/b] damage[/color] [[x] [i]"),
+            [
+                span("Hit for ", None, false),
+                span("12", red, true),
+                span(" damage", red, false),
+                span(" [x] [i]", None, false),
+            ]
+        );
+        assert_eq!(
+            parse_hex_color("#00ff0080"),
+            Some([0.0, 1.0, 0.0, 128.0 / 255.0])
+        );
+        assert_eq!(parse_hex_color("red"), None);
+    }
+
+    #[test]
+    fn shelf_packer_reuses_rows_and_reports_full() {
+        let mut packer = ShelfPacker::new(32);
+        assert_eq!(packer.allocate(10, 10, 0), Some((0, 0, 0)));
+        assert_eq!(packer.allocate(10, 8, 0), Some((11, 0, 0)));
+        // Too short for the first shelf's height budget: opens a new shelf.
+        assert_eq!(packer.allocate(4, 4, 0), Some((0, 11, 1)));
+        assert_eq!(packer.allocate(40, 4, 0), None);
+        assert_eq!(packer.allocate(10, 20, 0), None);
+    }
+
+    #[test]
+    fn full_atlas_evicts_least_recently_used_shelf() {
+        let mut packer = ShelfPacker::new(32);
+        for frame in 0..3 {
+            assert_eq!(
+                packer.allocate(30, 9, frame),
+                Some((0, frame as u32 * 10, frame as usize))
+            );
+        }
+        assert_eq!(packer.allocate(10, 9, 3), None);
+        // Shelf 0 was drawn from again, so shelf 1 is the oldest.
+        packer.touch(0, 3);
+        assert_eq!(packer.evict(10, 9, 3), Some((0, 10, 1)));
+        assert_eq!(packer.allocate(10, 9, 3), Some((11, 10, 1)));
+        // Shelves used this frame are never evicted, nor ones too short.
+        assert_eq!(packer.evict(10, 9, 2), None);
+        assert_eq!(packer.evict(10, 12, 4), None);
+    }
+
+    #[test]
+    fn fallback_chain_flattens_families_behind_the_run_font() {
+        let (latin, bold, jp) = (FontId(0), FontId(1), FontId(2));
+        let family = FontId(3);
+        let families = HashMap::from([(family, vec![latin, jp])]);
+        assert_eq!(fallback_chain(&families, family, family), [latin, jp]);
+        assert_eq!(fallback_chain(&families, bold, family), [bold, latin, jp]);
+        assert_eq!(fallback_chain(&families, latin, latin), [latin]);
+    }
+}
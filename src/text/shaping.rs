@@ -0,0 +1,339 @@
+//! Complex-script layout for the `shaping` feature: OpenType shaping with rustybuzz and
+//! bidirectional reordering with unicode-bidi.
+//!
+//! Each paragraph is split into pieces of one run, one embedding level and one font, which
+//! are shaped separately. Lines are wrapped in logical order between shaped clusters, then
+//! every line is put into visual order with the Unicode Bidirectional Algorithm.
+
+use super::{FontId, LaidGlyph, Run, Style, TextLayout, fallback_chain, faux_bold_offset};
+use ab_glyph::{Font, FontArc, GlyphId, PxScale, ScaleFont};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+use unicode_bidi::{BidiInfo, Level};
+
+/// Characters drawn as a unit; wrapping never splits one.
+#[derive(Clone, Debug, PartialEq)]
+struct Cluster {
+    // Byte offset of its first character in the paragraph.
+    start: usize,
+    // Glyphs in visual order, positioned relative to the cluster's pen position.
+    glyphs: Vec<(FontId, GlyphId, f32, f32)>,
+    advance: f32,
+    style: u16,
+    space: bool,
+}
+
+/// Lay out `runs` like `super::layout`, shaping them with the fonts' OpenType tables.
+/// `data` holds the font file of each `FontId`.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn layout(
+    fonts: &[FontArc],
+    data: &[Arc<[u8]>],
+    families: &HashMap<FontId, Vec<FontId>>,
+    base: FontId,
+    runs: &[Run<'_>],
+    styles: Vec<Style>,
+    size: f32,
+    wrap: Option<f32>,
+) -> TextLayout {
+    let scale = PxScale::from(size);
+    let metrics = fonts[base.0 as usize].as_scaled(scale);
+    let line_height = metrics.height() + metrics.line_gap();
+    let mut out = TextLayout {
+        styles,
+        ..TextLayout::default()
+    };
+
+    // The runs joined into one string without control characters (line breaks aside), and
+    // the byte range each run covers.
+    let mut text = String::new();
+    let mut run_ranges = Vec::with_capacity(runs.len());
+    for run in runs {
+        let start = text.len();
+        text.extend(run.text.chars().filter(|&c| c == '\n' || !c.is_control()));
+        run_ranges.push(start..text.len());
+    }
+
+    let mut y = metrics.ascent();
+    let mut line_count = 0;
+    let mut offset = 0;
+    for paragraph in text.split('\n') {
+        let shaper = Shaper {
+            fonts,
+            data,
+            families,
+            base,
+            styles: &out.styles,
+            scale,
+            size,
+        };
+        let bidi = BidiInfo::new(paragraph, None);
+        let clusters = shaper.shape_paragraph(paragraph, offset, runs, &run_ranges, &bidi.levels);
+        offset += paragraph.len() + 1;
+        let lines = break_lines(&clusters, wrap);
+        line_count += lines.len();
+        let rtl = bidi.paragraphs.first().is_some_and(|p| p.level.is_rtl());
+
+        for line in lines {
+            let width: f32 = clusters[line.clone()].iter().map(|c| c.advance).sum();
+            let mut pen = match wrap {
+                Some(max) if rtl => {
+                    out.width = out.width.max(max);
+                    (max - width).max(0.0)
+                }
+                _ => 0.0,
+            };
+            out.width = out.width.max(width);
+            let order = match bidi.paragraphs.first() {
+                Some(para) => visual_order(&clusters, line, paragraph.len(), |range| {
+                    bidi.visual_runs(para, range)
+                }),
+                None => Vec::new(),
+            };
+            for index in order {
+                let cluster = &clusters[index];
+                for &(font, id, gx, gy) in &cluster.glyphs {
+                    out.glyphs.push(LaidGlyph {
+                        id,
+                        font,
+                        style: cluster.style,
+                        x: pen + gx,
+                        y: y + gy,
+                    });
+                }
+                pen += cluster.advance;
+            }
+            y += line_height;
+        }
+    }
+    out.height = metrics.height() + line_height * line_count.saturating_sub(1) as f32;
+    out
+}
+
+/// Fonts and scale shared by every piece of a layout.
+struct Shaper<'a> {
+    fonts: &'a [FontArc],
+    data: &'a [Arc<[u8]>],
+    families: &'a HashMap<FontId, Vec<FontId>>,
+    base: FontId,
+    styles: &'a [Style],
+    scale: PxScale,
+    size: f32,
+}
+
+impl Shaper<'_> {
+    /// Shaped clusters of `paragraph` in logical order. `offset` is the paragraph's position
+    /// in the text that `run_ranges` index.
+    fn shape_paragraph(
+        &self,
+        paragraph: &str,
+        offset: usize,
+        runs: &[Run<'_>],
+        run_ranges: &[Range<usize>],
+        levels: &[Level],
+    ) -> Vec<Cluster> {
+        // Pieces of one run, level and font, as (byte range, run, level, font).
+        let mut pieces: Vec<(Range<usize>, usize, Level, FontId)> = Vec::new();
+        for (i, c) in paragraph.char_indices() {
+            let run = run_ranges
+                .iter()
+                .position(|r| r.contains(&(offset + i)))
+                .unwrap_or(0);
+            let level = levels[i];
+            let font = self.font_for(c, runs[run].font, pieces.last().map(|p| p.3));
+            match pieces.last_mut() {
+                Some(last) if last.1 == run && last.2 == level && last.3 == font => {
+                    last.0.end = i + c.len_utf8();
+                }
+                _ => pieces.push((i..i + c.len_utf8(), run, level, font)),
+            }
+        }
+
+        let mut clusters = Vec::new();
+        for (range, run, level, font) in pieces {
+            let style = runs[run].style;
+            let bold = self.styles[style as usize].faux_bold;
+            let mut shaped = self.shape(&paragraph[range.clone()], font, level.is_rtl());
+            for cluster in &mut shaped {
+                cluster.start += range.start;
+                cluster.style = style;
+                if bold && cluster.advance > 0.0 {
+                    cluster.advance += faux_bold_offset(self.size);
+                }
+            }
+            clusters.extend(shaped);
+        }
+        clusters
+    }
+
+    /// Font in `run_font`'s fallback chain drawing `c`. Spaces and zero-width glyphs
+    /// (combining marks) stay in the previous character's font when it has them, so they
+    /// shape together with it.
+    fn font_for(&self, c: char, run_font: FontId, prev: Option<FontId>) -> FontId {
+        let has = |f: FontId| self.fonts[f.0 as usize].glyph_id(c).0 != 0;
+        let chain = fallback_chain(self.families, run_font, self.base);
+        // Without any font that has `c`, the first one's missing-glyph box is drawn.
+        let font = chain.iter().copied().find(|&f| has(f)).unwrap_or(chain[0]);
+        let zero_width = {
+            let font = &self.fonts[font.0 as usize];
+            font.h_advance_unscaled(font.glyph_id(c)) == 0.0
+        };
+        match prev {
+            Some(prev)
+                if (c.is_whitespace() || zero_width) && chain.contains(&prev) && has(prev) =>
+            {
+                prev
+            }
+            _ => font,
+        }
+    }
+
+    /// Shape one piece in one font and direction into clusters in logical order.
+    fn shape(&self, text: &str, font: FontId, rtl: bool) -> Vec<Cluster> {
+        let Some(face) = rustybuzz::Face::from_slice(&self.data[font.0 as usize], 0) else {
+            return Vec::new();
+        };
+        let mut buffer = rustybuzz::UnicodeBuffer::new();
+        buffer.push_str(text);
+        buffer.set_direction(if rtl {
+            rustybuzz::Direction::RightToLeft
+        } else {
+            rustybuzz::Direction::LeftToRight
+        });
+        buffer.guess_segment_properties();
+        let shaped = rustybuzz::shape(&face, &[], buffer);
+
+        let px = self.fonts[font.0 as usize]
+            .as_scaled(self.scale)
+            .h_scale_factor();
+        let mut clusters: Vec<Cluster> = Vec::new();
+        for (info, pos) in shaped.glyph_infos().iter().zip(shaped.glyph_positions()) {
+            let start = info.cluster as usize;
+            let cluster = match clusters.last_mut() {
+                Some(last) if last.start == start => last,
+                _ => {
+                    clusters.push(Cluster {
+                        start,
+                        glyphs: Vec::new(),
+                        advance: 0.0,
+                        style: 0,
+                        space: text[start..].starts_with(' '),
+                    });
+                    clusters.last_mut().unwrap()
+                }
+            };
+            let id = GlyphId(info.glyph_id as u16);
+            cluster.glyphs.push((
+                font,
+                id,
+                cluster.advance + pos.x_offset as f32 * px,
+                -pos.y_offset as f32 * px,
+            ));
+            cluster.advance += pos.x_advance as f32 * px;
+        }
+        if rtl {
+            // Glyphs come back in visual order; clusters are kept in logical order.
+            clusters.reverse();
+        }
+        clusters
+    }
+}
+
+/// Cluster ranges of each line, wrapping after the last space before a cluster would cross
+/// `wrap`. The space a line breaks at is dropped.
+fn break_lines(clusters: &[Cluster], wrap: Option<f32>) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let (mut start, mut x) = (0, 0.0f32);
+    let mut break_point: Option<usize> = None;
+    for (i, cluster) in clusters.iter().enumerate() {
+        if let (Some(max), Some(at)) = (wrap, break_point)
+            && !cluster.space
+            && x + cluster.advance > max
+        {
+            lines.push(start..at - 1);
+            start = at;
+            x = clusters[at..i].iter().map(|c| c.advance).sum();
+            break_point = None;
+        }
+        x += cluster.advance;
+        if cluster.space {
+            break_point = Some(i + 1);
+        }
+    }
+    lines.push(start..clusters.len());
+    lines
+}
+
+/// Cluster indices of `line` in display order. `visual_runs` maps the line's byte range to
+/// its levels and level runs in visual order, as `BidiInfo::visual_runs` does.
+fn visual_order(
+    clusters: &[Cluster],
+    line: Range<usize>,
+    text_len: usize,
+    visual_runs: impl FnOnce(Range<usize>) -> (Vec<Level>, Vec<Range<usize>>),
+) -> Vec<usize> {
+    if line.is_empty() {
+        return Vec::new();
+    }
+    let end = clusters.get(line.end).map_or(text_len, |c| c.start);
+    let (levels, runs) = visual_runs(clusters[line.start].start..end);
+    let mut order = Vec::with_capacity(line.len());
+    for run in runs {
+        let first = order.len();
+        order.extend(line.clone().filter(|&i| run.contains(&clusters[i].start)));
+        if levels[run.start].is_rtl() {
+            order[first..].reverse();
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clusters(text: &str) -> Vec<Cluster> {
+        text.char_indices()
+            .map(|(start, c)| Cluster {
+                start,
+                glyphs: Vec::new(),
+                advance: 10.0,
+                style: 0,
+                space: c == ' ',
+            })
+            .collect()
+    }
+
+    fn visual(text: &str) -> String {
+        let bidi = BidiInfo::new(text, None);
+        let clusters = clusters(text);
+        let para = &bidi.paragraphs[0];
+        visual_order(&clusters, 0..clusters.len(), text.len(), |range| {
+            bidi.visual_runs(para, range)
+        })
+        .into_iter()
+        .map(|i| text[clusters[i].start..].chars().next().unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn lines_follow_the_bidi_algorithm() {
+        assert_eq!(visual("abc def"), "abc def");
+        // Hebrew runs reverse; numbers inside them keep their order.
+        assert_eq!(visual("אבג 123 דה"), "הד 123 גבא");
+        assert_eq!(visual("abc אבג def"), "abc גבא def");
+        // Neutrals between an RTL run and the paragraph end take the paragraph direction.
+        // Brackets are mirrored by shaping, not here.
+        assert_eq!(visual("אב (ג)!"), "!)ג( בא");
+    }
+
+    #[test]
+    fn wrapping_breaks_at_spaces_and_drops_them() {
+        let text = "aaa bb cccc";
+        let lines = break_lines(&clusters(text), Some(45.0));
+        assert_eq!(lines, [0..3, 4..6, 7..11]);
+        assert_eq!(break_lines(&clusters(text), None), vec![0..11]);
+        assert_eq!(break_lines(&[], Some(10.0)), vec![0..0]);
+    }
+}
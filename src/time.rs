@@ -0,0 +1,195 @@
+use std::time::Instant;
+
+/// Where `begin_drawing` gets each frame's delta time from.
+pub enum TimeSource {
+    /// Wall-clock time between `begin_drawing` calls (default).
+    RealTime,
+    /// Every frame advances by exactly this many seconds, however long it really took.
+    Fixed(f32),
+    /// Frames advance only by the time passed to `ctx.tick_manual(dt)` since the last frame.
+    Manual,
+    /// Called once per frame for the delta in seconds (e.g. a replay file or a JS clock).
+    Custom(Box<dyn FnMut() -> f32>),
+}
+
+impl std::fmt::Debug for TimeSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeSource::RealTime => f.write_str("RealTime"),
+            TimeSource::Fixed(dt) => f.debug_tuple("Fixed").field(dt).finish(),
+            TimeSource::Manual => f.write_str("Manual"),
+            TimeSource::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Produces frame deltas from a `TimeSource` and tracks total elapsed time.
+pub(crate) struct FrameClock {
+    source: TimeSource,
+    // Only touched by `RealTime`, so other sources never read `Instant` (unreliable on wasm).
+    last_instant: Option<Instant>,
+    // Time queued by `tick_manual` for the next frame.
+    manual: f32,
+    elapsed: f64,
+}
+
+// Delta reported for the first real-time frame, before there is a previous frame.
+const FIRST_FRAME_DT: f32 = 1.0 / 60.0;
+
+impl Default for FrameClock {
+    fn default() -> Self {
+        Self {
+            source: TimeSource::RealTime,
+            last_instant: None,
+            manual: 0.0,
+            elapsed: 0.0,
+        }
+    }
+}
+
+impl FrameClock {
+    pub fn set_source(&mut self, source: TimeSource) {
+        self.source = source;
+        self.last_instant = None;
+        self.manual = 0.0;
+    }
+
+    pub fn source(&self) -> &TimeSource {
+        &self.source
+    }
+
+    /// Queue `dt` seconds for the next frame, switching to `TimeSource::Manual` if needed.
+    pub fn tick_manual(&mut self, dt: f32) {
+        if !matches!(self.source, TimeSource::Manual) {
+            self.set_source(TimeSource::Manual);
+        }
+        self.manual += dt.max(0.0);
+    }
+
+    /// Advance to the next frame and return its delta in seconds.
+    pub fn next_frame(&mut self) -> f32 {
+        let dt = match &mut self.source {
+            TimeSource::RealTime => {
+                let now = Instant::now();
+                let dt = self
+                    .last_instant
+                    .map_or(FIRST_FRAME_DT, |last| (now - last).as_secs_f32());
+                self.last_instant = Some(now);
+                dt
+            }
+            TimeSource::Fixed(dt) => *dt,
+            TimeSource::Manual => std::mem::take(&mut self.manual),
+            TimeSource::Custom(next) => next(),
+        };
+        let dt = dt.max(0.0);
+        self.elapsed += dt as f64;
+        dt
+    }
+
+    /// Sum of all frame deltas so far, in seconds.
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+}
+
+// Never run more than this many fixed steps per frame, so a long hitch can't snowball
+// into ever-longer frames.
+const MAX_FIXED_STEPS: u32 = 5;
+
+/// Splits frame time into fixed simulation ticks for `ctx.fixed_steps()`.
+#[derive(Debug)]
+pub(crate) struct FixedTimestep {
+    step: f32,
+    accumulator: f32,
+    steps: u32,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self {
+            step: 1.0 / 60.0,
+            accumulator: 0.0,
+            steps: 0,
+        }
+    }
+}
+
+impl FixedTimestep {
+    pub fn set_step(&mut self, step: f32) {
+        self.step = step.max(f32::EPSILON);
+        self.accumulator = self.accumulator.min(self.step);
+    }
+
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// Add a frame's `dt` and work out how many ticks fit.
+    pub fn advance(&mut self, dt: f32) {
+        self.accumulator += dt.max(0.0);
+        self.steps = 0;
+        while self.accumulator >= self.step && self.steps < MAX_FIXED_STEPS {
+            self.accumulator -= self.step;
+            self.steps += 1;
+        }
+        if self.steps == MAX_FIXED_STEPS {
+            self.accumulator = self.accumulator.min(self.step);
+        }
+    }
+
+    /// Ticks to simulate this frame.
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// How far the frame is between the latest tick and the next, in `[0, 1)`.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_manual_and_custom_sources_are_deterministic() {
+        let mut clock = FrameClock::default();
+        clock.set_source(TimeSource::Fixed(0.25));
+        assert_eq!(clock.next_frame(), 0.25);
+        assert_eq!(clock.next_frame(), 0.25);
+
+        clock.tick_manual(0.1);
+        clock.tick_manual(0.2);
+        assert!(matches!(clock.source(), TimeSource::Manual));
+        assert!((clock.next_frame() - 0.3).abs() < 1e-6);
+        // Nothing ticked since the last frame: time stands still.
+        assert_eq!(clock.next_frame(), 0.0);
+
+        let mut steps = [0.5f32, 1.0].into_iter();
+        clock.set_source(TimeSource::Custom(Box::new(move || {
+            steps.next().unwrap_or(0.0)
+        })));
+        assert_eq!(clock.next_frame(), 0.5);
+        assert_eq!(clock.next_frame(), 1.0);
+        assert!((clock.elapsed() - 2.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn fixed_timestep_counts_ticks_and_keeps_the_remainder() {
+        let mut fixed = FixedTimestep::default();
+        fixed.set_step(0.1);
+        fixed.advance(0.25);
+        assert_eq!(fixed.steps(), 2);
+        assert!((fixed.alpha() - 0.5).abs() < 1e-4);
+
+        fixed.advance(0.02);
+        assert_eq!(fixed.steps(), 0);
+        assert!((fixed.alpha() - 0.7).abs() < 1e-4);
+
+        // A long hitch is capped instead of replayed.
+        fixed.advance(10.0);
+        assert_eq!(fixed.steps(), MAX_FIXED_STEPS);
+        assert!(fixed.alpha() <= 1.0);
+    }
+}
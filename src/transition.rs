@@ -0,0 +1,131 @@
+//! Full-screen scene transitions drawn over the finished frame.
+//!
+//! A transition covers the screen during the first half of its duration and reveals it
+//! again during the second; swap scenes on the frame it reports `Covered`:
+//!
+//! ```ignore
+//! if ctx.is_key_pressed(Key::Enter) {
+//!     ctx.start_transition(Transition::CircleWipe, 0.8);
+//! }
+//! if ctx.transition_event() == Some(TransitionEvent::Covered) {
+//!     scene = Scene::Level2;
+//! }
+//! ```
+
+/// How a transition hides the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transition {
+    /// Fade to black and back.
+    Fade,
+    /// A black iris closing on the center of the screen, then opening again.
+    CircleWipe,
+    /// The picture breaks into ever larger blocks and fades out, then sharpens back.
+    Pixelate,
+}
+
+/// Milestones of a running transition, reported for one frame by `ctx.transition_event()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionEvent {
+    /// The screen is fully hidden; changes made now aren't seen.
+    Covered,
+    /// The transition is over and no longer drawn.
+    Finished,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TransitionState {
+    kind: Transition,
+    duration: f32,
+    elapsed: f32,
+    // Milestones already reported.
+    covered: bool,
+    finished: bool,
+}
+
+impl TransitionState {
+    pub(crate) fn new(kind: Transition, duration: f32) -> Self {
+        Self {
+            kind,
+            duration: duration.max(0.0),
+            elapsed: 0.0,
+            covered: false,
+            finished: false,
+        }
+    }
+
+    pub(crate) fn kind(&self) -> Transition {
+        self.kind
+    }
+
+    /// Age the transition by `dt` seconds and report the milestone reached, if any.
+    ///
+    /// Time stops at the midpoint for the `Covered` step, so even a long frame shows one
+    /// fully covered frame before the reveal starts.
+    pub(crate) fn advance(&mut self, dt: f32) -> Option<TransitionEvent> {
+        if self.finished {
+            return None;
+        }
+        self.elapsed += dt;
+        let half = self.duration / 2.0;
+        if !self.covered && self.elapsed >= half {
+            self.covered = true;
+            self.elapsed = half;
+            return Some(TransitionEvent::Covered);
+        }
+        if self.covered && self.elapsed >= self.duration {
+            self.finished = true;
+            return Some(TransitionEvent::Finished);
+        }
+        None
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// How much of the screen is hidden, from 0 at the start to 1 at the midpoint and back.
+    pub(crate) fn coverage(&self) -> f32 {
+        if self.finished {
+            return 0.0;
+        }
+        if self.duration <= 0.0 {
+            return if self.covered { 1.0 } else { 0.0 };
+        }
+        let t = (self.elapsed / self.duration).clamp(0.0, 1.0);
+        1.0 - (2.0 * t - 1.0).abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn covers_then_reveals_with_one_event_each() {
+        let mut t = TransitionState::new(Transition::Fade, 1.0);
+        assert_eq!(t.coverage(), 0.0);
+        assert_eq!(t.advance(0.25), None);
+        assert_eq!(t.coverage(), 0.5);
+
+        // A long frame stops at the midpoint so the swap happens fully covered.
+        assert_eq!(t.advance(0.6), Some(TransitionEvent::Covered));
+        assert_eq!(t.coverage(), 1.0);
+        assert_eq!(t.advance(0.25), None);
+        assert_eq!(t.coverage(), 0.5);
+        assert!(!t.is_finished());
+
+        assert_eq!(t.advance(0.5), Some(TransitionEvent::Finished));
+        assert!(t.is_finished());
+        assert_eq!(t.coverage(), 0.0);
+        assert_eq!(t.advance(0.1), None);
+    }
+
+    #[test]
+    fn zero_duration_still_reports_both_events() {
+        let mut t = TransitionState::new(Transition::Pixelate, 0.0);
+        assert_eq!(t.advance(0.016), Some(TransitionEvent::Covered));
+        assert_eq!(t.coverage(), 1.0);
+        assert_eq!(t.advance(0.016), Some(TransitionEvent::Finished));
+        assert!(t.is_finished());
+    }
+}
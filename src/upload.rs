@@ -0,0 +1,104 @@
+use crate::error::RendererError;
+use crate::renderer::TextureId;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Called once a queued texture is on the GPU (or failed to upload).
+pub(crate) type UploadCallback = Box<dyn FnOnce(Result<TextureId, RendererError>)>;
+
+/// Decoded RGBA8 image waiting for its GPU upload.
+pub(crate) struct PendingUpload {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+    pub on_done: UploadCallback,
+}
+
+/// Texture uploads staggered across frames under a time budget.
+pub(crate) struct UploadQueue {
+    pending: VecDeque<PendingUpload>,
+    /// Upload time allowed per frame. At least one texture is uploaded per frame so the
+    /// queue always drains, even if a single upload exceeds the budget.
+    pub budget: Duration,
+}
+
+impl Default for UploadQueue {
+    fn default() -> Self {
+        Self {
+            pending: VecDeque::new(),
+            budget: Duration::from_millis(2),
+        }
+    }
+}
+
+impl UploadQueue {
+    pub fn push(&mut self, upload: PendingUpload) {
+        self.pending.push_back(upload);
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Upload queued textures in order until this frame's budget is spent, running each
+    /// completion callback. Returns the number uploaded.
+    pub fn process(
+        &mut self,
+        mut upload: impl FnMut(&PendingUpload) -> Result<TextureId, RendererError>,
+    ) -> usize {
+        let start = Instant::now();
+        let mut done = 0;
+        while let Some(item) = self.pending.pop_front() {
+            let result = upload(&item);
+            (item.on_done)(result);
+            done += 1;
+            if start.elapsed() >= self.budget {
+                break;
+            }
+        }
+        done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn pending(name: &str, log: &Rc<RefCell<Vec<u32>>>) -> PendingUpload {
+        let log = log.clone();
+        PendingUpload {
+            name: name.to_string(),
+            width: 1,
+            height: 1,
+            rgba: vec![0; 4],
+            on_done: Box::new(move |r| log.borrow_mut().push(r.unwrap().0)),
+        }
+    }
+
+    #[test]
+    fn budget_limits_uploads_per_frame_but_always_makes_progress() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut queue = UploadQueue {
+            budget: Duration::ZERO,
+            ..UploadQueue::default()
+        };
+        for name in ["a", "b", "c"] {
+            queue.push(pending(name, &log));
+        }
+
+        let mut next_id = 0;
+        let mut upload = |_: &PendingUpload| {
+            next_id += 1;
+            Ok(TextureId(next_id))
+        };
+        assert_eq!(queue.process(&mut upload), 1);
+        assert_eq!(queue.len(), 2);
+
+        queue.budget = Duration::from_secs(60);
+        assert_eq!(queue.process(&mut upload), 2);
+        assert_eq!(*log.borrow(), [1, 2, 3]);
+    }
+}
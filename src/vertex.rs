@@ -23,3 +23,32 @@ impl Vertex {
         }
     }
 }
+
+/// Vertex of an SDF shape quad; the fragment shader evaluates a rounded box per pixel.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct ShapeVertex {
+    pub pos: [f32; 2],
+    /// Position relative to the shape center, in shape units.
+    pub local: [f32; 2],
+    /// Half width, half height, corner radius.
+    pub shape: [f32; 3],
+    pub color: [f32; 4],
+}
+
+impl ShapeVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+        0 => Float32x2, // position
+        1 => Float32x2, // local
+        2 => Float32x3, // half extents + radius
+        3 => Float32x4, // color
+    ];
+
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ShapeVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use crate::Rect;
+
+/// How a virtual resolution is scaled to fit the window.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ScalingMode {
+    /// Fill the whole window, ignoring aspect ratio.
+    Stretch,
+    /// Scale uniformly to the largest size that fits, centered with bars on the sides.
+    Letterbox,
+    /// Like `Letterbox`, but only by whole-number factors (crisp pixel art).
+    IntegerScale,
+}
+
+/// How an aspect-locked canvas (see `ctx.set_aspect_ratio`) fills the window.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum FillMode {
+    /// Keep the aspect ratio, centered with black bars on the sides or top and bottom.
+    Letterbox,
+    /// Stretch the canvas over the whole window, distorting it when the shapes differ.
+    Stretch,
+}
+
+impl FillMode {
+    pub(crate) fn scaling_mode(self) -> ScalingMode {
+        match self {
+            FillMode::Letterbox => ScalingMode::Letterbox,
+            FillMode::Stretch => ScalingMode::Stretch,
+        }
+    }
+}
+
+/// Largest `width / height == aspect` canvas, in whole pixels, that fits the surface.
+pub fn fit_aspect(surface_w: u32, surface_h: u32, aspect: f32) -> (u32, u32) {
+    if !(aspect.is_finite() && aspect > 0.0) {
+        return (surface_w, surface_h);
+    }
+    let (sw, sh) = (surface_w as f32, surface_h as f32);
+    let (w, h) = if sw / sh > aspect {
+        (sh * aspect, sh)
+    } else {
+        (sw, sw / aspect)
+    };
+    ((w.round() as u32).max(1), (h.round() as u32).max(1))
+}
+
+/// Viewport in surface pixels: `(x, y, width, height)`.
+pub type Viewport = (f32, f32, f32, f32);
+
+/// Compute the surface viewport a `virtual_w x virtual_h` canvas occupies.
+pub fn fit_viewport(
+    surface_w: u32,
+    surface_h: u32,
+    virtual_w: u32,
+    virtual_h: u32,
+    mode: ScalingMode,
+) -> Viewport {
+    let sw = surface_w as f32;
+    let sh = surface_h as f32;
+    if virtual_w == 0 || virtual_h == 0 {
+        return (0.0, 0.0, sw, sh);
+    }
+    let vw = virtual_w as f32;
+    let vh = virtual_h as f32;
+
+    let scale = match mode {
+        ScalingMode::Stretch => return (0.0, 0.0, sw, sh),
+        ScalingMode::Letterbox => (sw / vw).min(sh / vh),
+        // Never go below 1x, even if the window is smaller than the canvas.
+        ScalingMode::IntegerScale => (sw / vw).min(sh / vh).floor().max(1.0),
+    };
+
+    let w = vw * scale;
+    let h = vh * scale;
+    (((sw - w) * 0.5).floor(), ((sh - h) * 0.5).floor(), w, h)
+}
+
+/// Map a point in surface pixels into the canvas shown inside `viewport`.
+pub fn surface_to_canvas(
+    viewport: Viewport,
+    canvas_w: f32,
+    canvas_h: f32,
+    x: f32,
+    y: f32,
+) -> (f32, f32) {
+    let (vx, vy, vw, vh) = viewport;
+    if vw <= 0.0 || vh <= 0.0 {
+        return (x, y);
+    }
+    ((x - vx) * canvas_w / vw, (y - vy) * canvas_h / vh)
+}
+
+/// A point on the canvas edge or center that HUD elements are placed relative to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Where along each axis the anchor sits: 0 = left/top, 0.5 = center, 1 = right/bottom.
+    fn factors(self) -> (f32, f32) {
+        match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Center => (0.5, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+/// A `size` rect placed at `anchor` of a `canvas`-sized area.
+///
+/// `offset` points inward from the anchored edges, so `(16, 16)` is a 16 pixel margin at
+/// every corner; on centered axes it shifts right or down.
+pub fn anchored_rect(
+    canvas: (f32, f32),
+    anchor: Anchor,
+    offset: (f32, f32),
+    size: (f32, f32),
+) -> Rect {
+    let (fx, fy) = anchor.factors();
+    // Right/bottom anchors measure their offset from the far edge.
+    let inward = |f: f32, offset: f32| if f == 1.0 { -offset } else { offset };
+    Rect {
+        x: (canvas.0 - size.0) * fx + inward(fx, offset.0),
+        y: (canvas.1 - size.1) * fy + inward(fy, offset.1),
+        w: size.0,
+        h: size.1,
+    }
+}
+
+/// A `size` rect `offset` from `cursor` (down-right for positive offsets), flipped to the
+/// other side of the cursor on any axis where it would leave the canvas, like a tooltip.
+pub fn cursor_rect(
+    canvas: (f32, f32),
+    cursor: (f32, f32),
+    offset: (f32, f32),
+    size: (f32, f32),
+) -> Rect {
+    let place = |cursor: f32, offset: f32, size: f32, limit: f32| {
+        let start = cursor + offset;
+        if start + size > limit {
+            // Mirror the offset; the rect ends that far before the cursor instead.
+            (cursor - offset - size).max(0.0)
+        } else {
+            start.max(0.0)
+        }
+    };
+    Rect {
+        x: place(cursor.0, offset.0, size.0, canvas.0),
+        y: place(cursor.1, offset.1, size.1, canvas.1),
+        w: size.0,
+        h: size.1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_viewport_modes() {
+        assert_eq!(
+            fit_viewport(1000, 600, 320, 180, ScalingMode::Stretch),
+            (0.0, 0.0, 1000.0, 600.0)
+        );
+
+        // 1000x600 fits 320x180 at 3.125x: width-limited, bars top and bottom.
+        let (x, y, w, h) = fit_viewport(1000, 600, 320, 180, ScalingMode::Letterbox);
+        assert_eq!((x, w), (0.0, 1000.0));
+        assert!((h - 562.5).abs() < 1e-3);
+        assert_eq!(y, 18.0);
+
+        assert_eq!(
+            fit_viewport(1000, 600, 320, 180, ScalingMode::IntegerScale),
+            (20.0, 30.0, 960.0, 540.0)
+        );
+
+        let mapped = surface_to_canvas((20.0, 30.0, 960.0, 540.0), 320.0, 180.0, 500.0, 300.0);
+        assert_eq!(mapped, (160.0, 90.0));
+    }
+
+    #[test]
+    fn aspect_lock_fits_the_largest_canvas() {
+        // Wider than 16:9: pillarbox, full height.
+        assert_eq!(fit_aspect(2000, 900, 16.0 / 9.0), (1600, 900));
+        // Taller: letterbox, full width.
+        assert_eq!(fit_aspect(800, 800, 16.0 / 9.0), (800, 450));
+        assert_eq!(fit_aspect(800, 600, 0.0), (800, 600));
+
+        let (w, h) = fit_aspect(2000, 900, 16.0 / 9.0);
+        assert_eq!(
+            fit_viewport(2000, 900, w, h, FillMode::Letterbox.scaling_mode()),
+            (200.0, 0.0, 1600.0, 900.0)
+        );
+    }
+
+    #[test]
+    fn anchored_rects_keep_their_margin_from_each_edge() {
+        let canvas = (800.0, 600.0);
+        let size = (100.0, 50.0);
+        let at = |anchor| {
+            let r = anchored_rect(canvas, anchor, (10.0, 20.0), size);
+            (r.x, r.y)
+        };
+        assert_eq!(at(Anchor::TopLeft), (10.0, 20.0));
+        assert_eq!(at(Anchor::TopRight), (690.0, 20.0));
+        assert_eq!(at(Anchor::BottomRight), (690.0, 530.0));
+        assert_eq!(at(Anchor::Center), (360.0, 295.0));
+        assert_eq!(at(Anchor::Bottom), (360.0, 530.0));
+    }
+
+    #[test]
+    fn cursor_rect_flips_at_the_canvas_edge() {
+        let canvas = (800.0, 600.0);
+        let r = cursor_rect(canvas, (100.0, 100.0), (12.0, 12.0), (200.0, 40.0));
+        assert_eq!((r.x, r.y), (112.0, 112.0));
+        let r = cursor_rect(canvas, (700.0, 590.0), (12.0, 12.0), (200.0, 40.0));
+        assert_eq!((r.x, r.y), (488.0, 538.0));
+    }
+}
@@ -0,0 +1,133 @@
+use crate::Rect;
+
+/// Mouse cursor driven by a gamepad analog stick.
+///
+/// libforge has no gamepad backend, so feed stick axes from any gamepad crate into
+/// `ctx.update_virtual_cursor(x, y)` each frame. The result moves `mouse_position()` and
+/// `ctx.set_virtual_cursor_button` injects clicks, so mouse-driven UI works unchanged.
+#[derive(Clone, Debug)]
+pub struct VirtualCursor {
+    /// Speed at full stick deflection, in pixels per second.
+    pub speed: f32,
+    /// Stick magnitude below which input is ignored.
+    pub deadzone: f32,
+    /// Response curve exponent (`1.0` = linear, higher = finer control near center).
+    pub exponent: f32,
+    /// Distance (pixels) within which a released stick pulls the cursor to a target's center.
+    pub snap_radius: f32,
+    /// Speed multiplier while the cursor is over a snap target (`1.0` = no slowdown).
+    pub target_slowdown: f32,
+    targets: Vec<Rect>,
+}
+
+impl Default for VirtualCursor {
+    fn default() -> Self {
+        Self {
+            speed: 900.0,
+            deadzone: 0.15,
+            exponent: 2.0,
+            snap_radius: 48.0,
+            target_slowdown: 0.5,
+            targets: Vec::new(),
+        }
+    }
+}
+
+// How quickly a snapping cursor closes the gap to the target center (per second).
+const SNAP_RATE: f32 = 20.0;
+
+fn contains(r: &Rect, (x, y): (f32, f32)) -> bool {
+    x >= r.x && x <= r.x + r.w && y >= r.y && y <= r.y + r.h
+}
+
+impl VirtualCursor {
+    /// Replace the focusable rectangles the cursor snaps to.
+    pub fn set_targets(&mut self, targets: &[Rect]) {
+        self.targets.clear();
+        self.targets.extend_from_slice(targets);
+    }
+
+    /// Stick deflection after the deadzone and response curve, in `[0, 1]`.
+    fn response(&self, magnitude: f32) -> f32 {
+        let dz = self.deadzone.clamp(0.0, 0.99);
+        let t = ((magnitude - dz) / (1.0 - dz)).clamp(0.0, 1.0);
+        t.powf(self.exponent.max(0.1))
+    }
+
+    fn nearest_target_center(&self, pos: (f32, f32)) -> Option<(f32, f32)> {
+        self.targets
+            .iter()
+            .map(|r| (r.x + r.w * 0.5, r.y + r.h * 0.5))
+            .map(|c| (c, (c.0 - pos.0).hypot(c.1 - pos.1)))
+            .filter(|(_, d)| *d <= self.snap_radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(c, _)| c)
+    }
+
+    /// Advance the cursor at `pos` by one frame of stick input, clamped to `bounds`.
+    pub fn step(&self, pos: (f32, f32), stick: (f32, f32), dt: f32, bounds: Rect) -> (f32, f32) {
+        let magnitude = stick.0.hypot(stick.1);
+        let amount = self.response(magnitude);
+
+        let (x, y) = if amount > 0.0 {
+            let over_target = self.targets.iter().any(|r| contains(r, pos));
+            let slow = if over_target {
+                self.target_slowdown
+            } else {
+                1.0
+            };
+            let v = self.speed * amount * slow * dt / magnitude;
+            (pos.0 + stick.0 * v, pos.1 + stick.1 * v)
+        } else if let Some(c) = self.nearest_target_center(pos) {
+            let k = (SNAP_RATE * dt).min(1.0);
+            (pos.0 + (c.0 - pos.0) * k, pos.1 + (c.1 - pos.1) * k)
+        } else {
+            pos
+        };
+
+        (
+            x.clamp(bounds.x, bounds.x + bounds.w),
+            y.clamp(bounds.y, bounds.y + bounds.h),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Rect = Rect {
+        x: 0.0,
+        y: 0.0,
+        w: 800.0,
+        h: 600.0,
+    };
+
+    #[test]
+    fn stick_moves_with_curve_and_release_snaps_to_target() {
+        let mut cursor = VirtualCursor::default();
+
+        // Inside the deadzone nothing moves; full tilt moves `speed * dt`.
+        assert_eq!(
+            cursor.step((100.0, 100.0), (0.1, 0.0), 0.1, SCREEN),
+            (100.0, 100.0)
+        );
+        let (x, _) = cursor.step((100.0, 100.0), (1.0, 0.0), 0.1, SCREEN);
+        assert!((x - 190.0).abs() < 1e-3);
+
+        // Clamped to the screen.
+        assert_eq!(
+            cursor.step((790.0, 0.0), (1.0, -1.0), 1.0, SCREEN),
+            (800.0, 0.0)
+        );
+
+        cursor.set_targets(&[Rect {
+            x: 200.0,
+            y: 200.0,
+            w: 40.0,
+            h: 20.0,
+        }]);
+        let snapped = cursor.step((230.0, 200.0), (0.0, 0.0), 1.0, SCREEN);
+        assert_eq!(snapped, (220.0, 210.0));
+    }
+}
@@ -0,0 +1,121 @@
+//! Geometry for `draw_waveform` and `draw_spectrum`.
+//!
+//! Both reduce their input to at most one bar per pixel column and emit plain colored
+//! quads, so a visualizer costs one batch no matter how many samples are passed in.
+
+use crate::vertex::Vertex;
+use crate::{Color, Rect};
+
+fn push_quad(out: &mut Vec<Vertex>, x0: f32, y0: f32, x1: f32, y1: f32, color: Color) {
+    let v = |x, y| Vertex {
+        pos: [x, y],
+        uv: [0.0, 0.0],
+        color: color.0,
+    };
+    out.extend_from_slice(&[
+        v(x0, y0),
+        v(x1, y0),
+        v(x1, y1),
+        v(x0, y0),
+        v(x1, y1),
+        v(x0, y1),
+    ]);
+}
+
+/// Number of columns `len` values are reduced to across `width` pixels.
+fn columns(len: usize, width: f32) -> usize {
+    len.min(width.max(1.0) as usize)
+}
+
+/// Min/max envelope of `samples` (in `[-1, 1]`), one vertical bar per column, centered
+/// vertically in `rect`. Bars are at least `thickness` tall so silence still shows a line.
+pub(crate) fn waveform(
+    out: &mut Vec<Vertex>,
+    samples: &[f32],
+    rect: Rect,
+    thickness: f32,
+    color: Color,
+) {
+    let cols = columns(samples.len(), rect.w);
+    if cols == 0 {
+        return;
+    }
+    let col_w = rect.w / cols as f32;
+    let mid = rect.y + rect.h * 0.5;
+    let half = rect.h * 0.5;
+    out.reserve(cols * 6);
+    for c in 0..cols {
+        let bucket = &samples[c * samples.len() / cols..(c + 1) * samples.len() / cols];
+        let (lo, hi) = bucket
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(lo, hi), &s| (lo.min(s), hi.max(s)));
+        let (mut top, mut bottom) = (
+            mid - hi.clamp(-1.0, 1.0) * half,
+            mid - lo.clamp(-1.0, 1.0) * half,
+        );
+        if bottom - top < thickness {
+            let center = (top + bottom) * 0.5;
+            top = center - thickness * 0.5;
+            bottom = center + thickness * 0.5;
+        }
+        let x = rect.x + c as f32 * col_w;
+        push_quad(out, x, top, x + col_w, bottom, color);
+    }
+}
+
+/// Bottom-aligned bars for `bins` (heights in `[0, 1]` of `rect.h`), merging bins by their
+/// maximum when there are more than pixel columns. `gap` is the space between bars.
+pub(crate) fn spectrum(out: &mut Vec<Vertex>, bins: &[f32], rect: Rect, gap: f32, color: Color) {
+    let cols = columns(bins.len(), rect.w);
+    if cols == 0 {
+        return;
+    }
+    let col_w = rect.w / cols as f32;
+    let bar_w = (col_w - gap).max(1.0_f32.min(col_w));
+    let bottom = rect.y + rect.h;
+    out.reserve(cols * 6);
+    for c in 0..cols {
+        let bucket = &bins[c * bins.len() / cols..(c + 1) * bins.len() / cols];
+        let value = bucket
+            .iter()
+            .copied()
+            .fold(0.0f32, f32::max)
+            .clamp(0.0, 1.0);
+        if value <= 0.0 {
+            continue;
+        }
+        let x = rect.x + c as f32 * col_w;
+        push_quad(out, x, bottom - value * rect.h, x + bar_w, bottom, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect {
+        x: 0.0,
+        y: 0.0,
+        w: 2.0,
+        h: 100.0,
+    };
+
+    #[test]
+    fn waveform_reduces_to_one_bar_per_column() {
+        let mut out = Vec::new();
+        waveform(&mut out, &[0.5, -0.5, 0.0, 0.0], AREA, 2.0, Color::WHITE);
+        assert_eq!(out.len(), 12);
+        // First column spans the envelope of [0.5, -0.5]; the silent one is `thickness` tall.
+        assert_eq!((out[0].pos[1], out[2].pos[1]), (25.0, 75.0));
+        assert_eq!((out[6].pos[1], out[8].pos[1]), (49.0, 51.0));
+    }
+
+    #[test]
+    fn spectrum_bars_merge_by_max_and_skip_silence() {
+        let mut out = Vec::new();
+        spectrum(&mut out, &[0.2, 0.5, 0.0, 0.0], AREA, 0.0, Color::WHITE);
+        assert_eq!(out.len(), 6);
+        assert_eq!(out[0].pos[1], 50.0);
+        assert_eq!(out[2].pos, [1.0, 100.0]);
+    }
+}
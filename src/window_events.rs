@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+use winit::dpi::PhysicalSize;
+
+/// A window-level event that isn't input: focus, minimization, or drag-and-drop.
+///
+/// Read this frame's events with `ctx.window_events()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WindowSignal {
+    FocusGained,
+    FocusLost,
+    /// The window was minimized or is otherwise fully hidden; skip rendering until `Restored`.
+    Minimized,
+    Restored,
+    /// A file is being dragged over the window. One per file for multi-file drags.
+    FileHovered(PathBuf),
+    /// The drag left the window without dropping.
+    FileHoverCancelled,
+    FileDropped(PathBuf),
+}
+
+/// Window events collected between frames, exposed for one frame at a time.
+#[derive(Debug)]
+pub(crate) struct WindowEvents {
+    pending: Vec<WindowSignal>,
+    frame: Vec<WindowSignal>,
+    focused: bool,
+    // Minimized is reported as a zero-size resize on some platforms and as occlusion on
+    // others; the window counts as minimized while either applies.
+    zero_size: bool,
+    occluded: bool,
+}
+
+impl Default for WindowEvents {
+    fn default() -> Self {
+        Self {
+            pending: Vec::new(),
+            frame: Vec::new(),
+            focused: true,
+            zero_size: false,
+            occluded: false,
+        }
+    }
+}
+
+impl WindowEvents {
+    /// Make events received since the last frame visible to this frame.
+    pub fn begin_frame(&mut self) {
+        self.frame.clear();
+        std::mem::swap(&mut self.frame, &mut self.pending);
+    }
+
+    pub fn handle_focus(&mut self, focused: bool) {
+        if focused != self.focused {
+            self.focused = focused;
+            self.pending.push(if focused {
+                WindowSignal::FocusGained
+            } else {
+                WindowSignal::FocusLost
+            });
+        }
+    }
+
+    pub fn handle_resize(&mut self, size: PhysicalSize<u32>) {
+        let was = self.is_minimized();
+        self.zero_size = size.width == 0 || size.height == 0;
+        self.push_minimized_change(was);
+    }
+
+    pub fn handle_occluded(&mut self, occluded: bool) {
+        let was = self.is_minimized();
+        self.occluded = occluded;
+        self.push_minimized_change(was);
+    }
+
+    fn push_minimized_change(&mut self, was: bool) {
+        match (was, self.is_minimized()) {
+            (false, true) => self.pending.push(WindowSignal::Minimized),
+            (true, false) => self.pending.push(WindowSignal::Restored),
+            _ => {}
+        }
+    }
+
+    pub fn push(&mut self, signal: WindowSignal) {
+        self.pending.push(signal);
+    }
+
+    pub fn events(&self) -> &[WindowSignal] {
+        &self.frame
+    }
+
+    pub fn dropped_files(&self) -> impl Iterator<Item = &Path> {
+        self.frame.iter().filter_map(|e| match e {
+            WindowSignal::FileDropped(path) => Some(path.as_path()),
+            _ => None,
+        })
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        self.zero_size || self.occluded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_visible_for_exactly_one_frame() {
+        let mut events = WindowEvents::default();
+        events.handle_focus(true);
+        events.handle_focus(false);
+        events.push(WindowSignal::FileDropped("a.png".into()));
+        assert!(events.events().is_empty());
+
+        events.begin_frame();
+        assert_eq!(events.events()[0], WindowSignal::FocusLost);
+        assert_eq!(
+            events.dropped_files().collect::<Vec<_>>(),
+            [Path::new("a.png")]
+        );
+        assert!(!events.is_focused());
+
+        events.begin_frame();
+        assert!(events.events().is_empty());
+    }
+
+    #[test]
+    fn minimized_by_zero_size_or_occlusion() {
+        let mut events = WindowEvents::default();
+        events.handle_resize(PhysicalSize::new(0, 0));
+        events.handle_occluded(true);
+        assert!(events.is_minimized());
+        events.handle_resize(PhysicalSize::new(800, 600));
+        assert!(events.is_minimized());
+        events.handle_occluded(false);
+        events.begin_frame();
+        assert_eq!(
+            events.events(),
+            [WindowSignal::Minimized, WindowSignal::Restored]
+        );
+    }
+}
@@ -0,0 +1,173 @@
+#[cfg(feature = "serde")]
+use crate::storage::{Storage, StorageError};
+use std::path::Path;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::window::Window;
+
+/// Saved window geometry: monitor, outer position, inner size, and maximized state.
+///
+/// Capture with `WindowPlacement::capture(&window)` before exit and restore with
+/// `placement.apply(&window)` after creating the window on the next run. Persist it with
+/// `save` / `load`, or as JSON in a `Storage` slot with `save_to` / `load_from` (`serde`
+/// feature).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WindowPlacement {
+    /// Name of the monitor the window was on, if the platform reports one.
+    pub monitor: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+}
+
+impl WindowPlacement {
+    /// Read the current placement of `window`.
+    pub fn capture(window: &Window) -> Self {
+        let pos = window
+            .outer_position()
+            .unwrap_or(PhysicalPosition::new(0, 0));
+        let size = window.inner_size();
+        Self {
+            monitor: window.current_monitor().and_then(|m| m.name()),
+            x: pos.x,
+            y: pos.y,
+            width: size.width,
+            height: size.height,
+            maximized: window.is_maximized(),
+        }
+    }
+
+    /// Move and resize `window` to this placement.
+    ///
+    /// If the saved monitor is gone (e.g. unplugged) the window is placed on the primary
+    /// monitor instead, clamped so it stays visible.
+    pub fn apply(&self, window: &Window) {
+        let saved = self.monitor.as_ref().and_then(|name| {
+            window
+                .available_monitors()
+                .find(|m| m.name().as_deref() == Some(name.as_str()))
+        });
+
+        let (x, y) = match saved {
+            Some(_) => (self.x, self.y),
+            None => match window.primary_monitor() {
+                Some(m) => {
+                    let origin = m.position();
+                    let size = m.size();
+                    let max_x = origin.x + (size.width as i32 - self.width as i32).max(0);
+                    let max_y = origin.y + (size.height as i32 - self.height as i32).max(0);
+                    (self.x.clamp(origin.x, max_x), self.y.clamp(origin.y, max_y))
+                }
+                None => (self.x, self.y),
+            },
+        };
+
+        let _ = window.request_inner_size(PhysicalSize::new(self.width, self.height));
+        window.set_outer_position(PhysicalPosition::new(x, y));
+        window.set_maximized(self.maximized);
+    }
+
+    /// Serialize to a simple `key=value` text form.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        if let Some(monitor) = &self.monitor {
+            out.push_str(&format!("monitor={monitor}\n"));
+        }
+        out.push_str(&format!(
+            "x={}\ny={}\nwidth={}\nheight={}\nmaximized={}\n",
+            self.x, self.y, self.width, self.height, self.maximized
+        ));
+        out
+    }
+
+    /// Parse the text form produced by `to_text`. Returns `None` if a required key is missing.
+    pub fn from_text(text: &str) -> Option<Self> {
+        let mut placement = Self {
+            monitor: None,
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+            maximized: false,
+        };
+        let mut seen = 0;
+
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "monitor" => placement.monitor = Some(value.to_string()),
+                "x" => placement.x = value.parse().ok()?,
+                "y" => placement.y = value.parse().ok()?,
+                "width" => placement.width = value.parse().ok()?,
+                "height" => placement.height = value.parse().ok()?,
+                "maximized" => placement.maximized = value.parse().ok()?,
+                _ => continue,
+            }
+            seen += 1;
+        }
+
+        let required = 5 + placement.monitor.is_some() as usize;
+        (seen >= required && placement.width > 0 && placement.height > 0).then_some(placement)
+    }
+
+    /// Write the placement to `path` in the text form, atomically (see
+    /// `storage::write_atomic`).
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        crate::storage::write_atomic(path.as_ref(), self.to_text().as_bytes())
+    }
+
+    /// Load a placement from `path`. Returns `None` if the file is missing or malformed.
+    pub fn load(path: impl AsRef<Path>) -> Option<Self> {
+        Self::from_text(&std::fs::read_to_string(path).ok()?)
+    }
+
+    /// Save the placement as JSON in `storage`'s `slot`.
+    #[cfg(feature = "serde")]
+    pub fn save_to(&self, storage: &Storage, slot: &str) -> Result<(), StorageError> {
+        storage.save_json(slot, self)
+    }
+
+    /// Load a placement saved with `save_to`, or `None` if the slot was never saved.
+    #[cfg(feature = "serde")]
+    pub fn load_from(storage: &Storage, slot: &str) -> Result<Option<Self>, StorageError> {
+        storage.load_json(slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placement_text_round_trip() {
+        let placement = WindowPlacement {
+            monitor: Some("DELL U2720Q".into()),
+            x: -1200,
+            y: 40,
+            width: 1280,
+            height: 720,
+            maximized: true,
+        };
+        assert_eq!(
+            WindowPlacement::from_text(&placement.to_text()),
+            Some(placement.clone())
+        );
+        assert_eq!(WindowPlacement::from_text("x=1\ny=2"), None);
+
+        #[cfg(feature = "serde")]
+        {
+            let storage = Storage::with_dir(std::env::temp_dir().join("libforge-placement-test"));
+            placement.save_to(&storage, "window").unwrap();
+            assert_eq!(
+                WindowPlacement::load_from(&storage, "window").unwrap(),
+                Some(placement)
+            );
+            storage.delete("window").unwrap();
+        }
+    }
+}
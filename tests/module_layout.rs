@@ -0,0 +1,35 @@
+// Guards against a module living in both `src/foo.rs` and `src/foo/mod.rs`.
+//
+// rustc only compiles whichever file the `mod` declaration resolves to, so a stale copy
+// (as `src/renderer.rs` once was next to `src/renderer/mod.rs`) drifts silently.
+use std::path::Path;
+
+#[test]
+fn no_module_has_both_a_file_and_a_directory() {
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let mut duplicates = Vec::new();
+    let mut dirs = vec![src];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                let file = path.with_extension("rs");
+                if path.join("mod.rs").is_file() && file.is_file() {
+                    duplicates.push(file);
+                }
+                dirs.push(path);
+            }
+        }
+    }
+    assert!(
+        duplicates.is_empty(),
+        "modules with both foo.rs and foo/mod.rs: {duplicates:?}"
+    );
+}
+
+#[test]
+fn renderer_is_a_module_directory() {
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    assert!(src.join("renderer/mod.rs").is_file());
+    assert!(!src.join("renderer.rs").exists());
+}